@@ -1,20 +1,107 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use simple_easing::{
+    back_in_out, back_out, bounce_in_out, bounce_out, circ_in_out, circ_out, cubic_in_out,
+    cubic_out, elastic_in_out, elastic_out, expo_in_out, expo_out, linear, quad_in_out, quad_out,
+    quart_in_out, quart_out, quint_in_out, quint_out, sine_in_out, sine_out,
+};
 use xilem::WidgetView;
 use xilem::core::Edit;
 use xilem::core::one_of::Either;
-use xilem::view::text_button;
+use xilem::style::Style;
+use xilem::view::{CrossAxisAlignment, button, checkbox, flex_col, flex_row, label, text_button};
+
+use crate::thing::Thing;
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    Sine,
+    Quad,
+    #[default]
+    Cubic,
+    Quart,
+    Quint,
+    Expo,
+    Circ,
+    Back,
+    Elastic,
+    Bounce,
+}
+
+impl std::fmt::Display for Easing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl Easing {
+    pub const ALL: [Easing; 11] = [
+        Easing::Linear,
+        Easing::Sine,
+        Easing::Quad,
+        Easing::Cubic,
+        Easing::Quart,
+        Easing::Quint,
+        Easing::Expo,
+        Easing::Circ,
+        Easing::Back,
+        Easing::Elastic,
+        Easing::Bounce,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&easing| easing == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn in_out(self) -> fn(f32) -> f32 {
+        match self {
+            Easing::Linear => linear,
+            Easing::Sine => sine_in_out,
+            Easing::Quad => quad_in_out,
+            Easing::Cubic => cubic_in_out,
+            Easing::Quart => quart_in_out,
+            Easing::Quint => quint_in_out,
+            Easing::Expo => expo_in_out,
+            Easing::Circ => circ_in_out,
+            Easing::Back => back_in_out,
+            Easing::Elastic => elastic_in_out,
+            Easing::Bounce => bounce_in_out,
+        }
+    }
+
+    pub fn out(self) -> fn(f32) -> f32 {
+        match self {
+            Easing::Linear => linear,
+            Easing::Sine => sine_out,
+            Easing::Quad => quad_out,
+            Easing::Cubic => cubic_out,
+            Easing::Quart => quart_out,
+            Easing::Quint => quint_out,
+            Easing::Expo => expo_out,
+            Easing::Circ => circ_out,
+            Easing::Back => back_out,
+            Easing::Elastic => elastic_out,
+            Easing::Bounce => bounce_out,
+        }
+    }
+}
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AnimStep {
-    Idle(u64),
+    Idle(f64),
     Scaling,
-    Slowing(u64),
-    Pausing(u64),
-    Shifting(u64),
+    Slowing(f64),
+    Pausing(f64),
+    Shifting(f64),
+    Finished,
 }
 
 impl Default for AnimStep {
     fn default() -> Self {
-        Self::Shifting(Self::SHIFTING_FRAMES)
+        Self::Shifting(Self::SHIFTING_TIME)
     }
 }
 
@@ -24,64 +111,391 @@ impl AnimStep {
     pub const SLOWING_TIME: f64 = 0.1;
     pub const SHIFTING_TIME: f64 = 2.;
 
-    pub const IDLE_FRAMES: u64 = (Self::IDLE_TIME * Animation::FPS) as u64;
-    pub const PAUSING_FRAMES: u64 = (Self::PAUSING_TIME * Animation::FPS) as u64;
-    pub const SLOWING_FRAMES: u64 = (Self::SLOWING_TIME * Animation::FPS) as u64;
-    pub const SHIFTING_FRAMES: u64 = (Self::SHIFTING_TIME * Animation::FPS) as u64;
-
-    fn next(&self) -> AnimStep {
+    fn next(&self, at_end: bool, pacing: &Pacing) -> AnimStep {
         match self {
             AnimStep::Idle(_) => AnimStep::Scaling,
-            AnimStep::Scaling => AnimStep::Slowing(Self::SLOWING_FRAMES),
-            AnimStep::Slowing(_) => AnimStep::Pausing(Self::PAUSING_FRAMES),
-            AnimStep::Pausing(_) => AnimStep::Shifting(Self::SHIFTING_FRAMES),
-            AnimStep::Shifting(_) => AnimStep::Idle(Self::IDLE_FRAMES),
+            AnimStep::Scaling => AnimStep::Slowing(pacing.slowing_time),
+            AnimStep::Slowing(_) => AnimStep::Pausing(pacing.pausing_time),
+            AnimStep::Pausing(_) if at_end => AnimStep::Finished,
+            AnimStep::Pausing(_) => AnimStep::Shifting(pacing.shifting_time),
+            AnimStep::Shifting(_) => AnimStep::Idle(pacing.idle_time),
+            AnimStep::Finished => AnimStep::Finished,
+        }
+    }
+
+    pub fn progress(&self, pacing: &Pacing) -> Option<f32> {
+        let (remaining, duration) = match *self {
+            AnimStep::Idle(t) => (t, pacing.idle_time),
+            AnimStep::Slowing(t) => (t, pacing.slowing_time),
+            AnimStep::Pausing(t) => (t, pacing.pausing_time),
+            AnimStep::Shifting(t) => (t, pacing.shifting_time),
+            AnimStep::Scaling | AnimStep::Finished => return None,
+        };
+        match duration {
+            0. => Some(1.),
+            duration => Some((1. - remaining / duration).clamp(0., 1.) as f32),
+        }
+    }
+
+    fn count_down(remaining: f64, dt: f64) -> f64 {
+        let remaining = remaining - dt;
+        if remaining < dt - Animation::EPSILON {
+            0.
+        } else {
+            remaining
         }
     }
 
-    fn advance(&mut self, scaling_done: bool, slowing_done: bool) {
+    fn advance(
+        &mut self,
+        dt: f64,
+        pacing: &Pacing,
+        scaling_done: bool,
+        slowing_done: bool,
+        at_end: bool,
+        hold: bool,
+    ) {
         match self {
-            AnimStep::Idle(i) | AnimStep::Pausing(i) | AnimStep::Shifting(i) => {
-                if *i > 0 {
-                    *i -= 1;
+            // a finished pause waits for the presenter before shifting on
+            AnimStep::Pausing(t) if *t <= 0. && hold && !at_end => (),
+            AnimStep::Idle(t) | AnimStep::Pausing(t) | AnimStep::Shifting(t) => {
+                if *t > 0. {
+                    *t = Self::count_down(*t, dt);
                 } else {
-                    *self = self.next();
+                    *self = self.next(at_end, pacing);
                 }
             }
             AnimStep::Scaling => {
                 if scaling_done {
-                    *self = self.next();
+                    *self = self.next(at_end, pacing);
                 }
             }
-            AnimStep::Slowing(i) => {
-                if slowing_done || *i == 0 {
-                    *self = self.next();
+            AnimStep::Slowing(t) => {
+                if slowing_done || *t == 0. {
+                    *self = self.next(at_end, pacing);
                 } else {
-                    *i -= 1;
+                    *t = Self::count_down(*t, dt);
                 }
             }
+            AnimStep::Finished => (),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pacing {
+    pub idle_time: f64,
+    pub pausing_time: f64,
+    pub slowing_time: f64,
+    pub shifting_time: f64,
+    pub acceleration: f64,
+    pub speed: f64,
+}
+
+impl Pacing {
+    pub const CALM: Self = Self {
+        idle_time: 1.5,
+        pausing_time: 6.,
+        slowing_time: 0.2,
+        shifting_time: 3.,
+        acceleration: 0.15,
+        speed: 0.75,
+    };
+    pub const STANDARD: Self = Self {
+        idle_time: AnimStep::IDLE_TIME,
+        pausing_time: AnimStep::PAUSING_TIME,
+        slowing_time: AnimStep::SLOWING_TIME,
+        shifting_time: AnimStep::SHIFTING_TIME,
+        acceleration: 0.25,
+        speed: 1.,
+    };
+    pub const FAST: Self = Self {
+        idle_time: 0.5,
+        pausing_time: 1.5,
+        slowing_time: 0.1,
+        shifting_time: 1.2,
+        acceleration: 0.5,
+        speed: 1.5,
+    };
+}
+
+impl Default for Pacing {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+impl std::fmt::Display for Pacing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "idle {} s, pause {} s, slow {} s, shift {} s, acceleration {}, speed ×{}",
+            self.idle_time,
+            self.pausing_time,
+            self.slowing_time,
+            self.shifting_time,
+            self.acceleration,
+            self.speed
+        )
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ProfileBase {
+    Calm,
+    #[default]
+    Standard,
+    Fast,
+}
+
+impl std::fmt::Display for ProfileBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl ProfileBase {
+    pub fn next(self) -> Self {
+        match self {
+            ProfileBase::Calm => ProfileBase::Standard,
+            ProfileBase::Standard => ProfileBase::Fast,
+            ProfileBase::Fast => ProfileBase::Calm,
+        }
+    }
+
+    pub fn pacing(self) -> Pacing {
+        match self {
+            ProfileBase::Calm => Pacing::CALM,
+            ProfileBase::Standard => Pacing::STANDARD,
+            ProfileBase::Fast => Pacing::FAST,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pausing_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slowing_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shifting_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acceleration: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f64>,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimationProfile {
+    pub base: ProfileBase,
+    pub custom: ProfileOverrides,
+}
+
+impl std::fmt::Display for AnimationProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.is_custom() {
+            true => write!(f, "Custom ({})", self.base),
+            false => write!(f, "{}", self.base),
+        }
+    }
+}
+
+impl AnimationProfile {
+    pub const TIME_STEP: f64 = 0.5;
+    pub const SPEED_STEP: f64 = 0.25;
+
+    pub fn is_custom(&self) -> bool {
+        self.custom != ProfileOverrides::default()
+    }
+
+    pub fn resolve(&self) -> Pacing {
+        let base = self.base.pacing();
+        let custom = self.custom;
+        Pacing {
+            idle_time: custom.idle_time.unwrap_or(base.idle_time).max(0.),
+            pausing_time: custom.pausing_time.unwrap_or(base.pausing_time).max(0.),
+            slowing_time: custom.slowing_time.unwrap_or(base.slowing_time).max(0.),
+            shifting_time: custom
+                .shifting_time
+                .unwrap_or(base.shifting_time)
+                .max(Animation::FRAME_SECS),
+            acceleration: custom.acceleration.unwrap_or(base.acceleration).max(0.),
+            speed: custom.speed.unwrap_or(base.speed).max(Self::SPEED_STEP),
         }
     }
+
+    pub fn with_pausing_time(self, pausing_time: f64) -> Self {
+        let custom = ProfileOverrides {
+            pausing_time: Some(pausing_time.max(0.)),
+            ..self.custom
+        };
+        Self { custom, ..self }
+    }
+
+    pub fn with_speed(self, speed: f64) -> Self {
+        let custom = ProfileOverrides {
+            speed: Some(speed.max(Self::SPEED_STEP)),
+            ..self.custom
+        };
+        Self { custom, ..self }
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let pacing = self.resolve();
+        flex_col((
+            flex_row((
+                label("Animation profile:").color(Thing::NAME_COLOR),
+                text_button(self.to_string(), |state: &mut Self| {
+                    state.base = state.base.next();
+                }),
+                self.is_custom().then(|| {
+                    text_button("Reset", |state: &mut Self| {
+                        state.custom = ProfileOverrides::default();
+                    })
+                }),
+            )),
+            flex_row((
+                label("Pause:").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state =
+                        state.with_pausing_time(state.resolve().pausing_time - Self::TIME_STEP);
+                }),
+                text_button("+", |state: &mut Self| {
+                    *state =
+                        state.with_pausing_time(state.resolve().pausing_time + Self::TIME_STEP);
+                }),
+                label("Speed:").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state = state.with_speed(state.resolve().speed - Self::SPEED_STEP);
+                }),
+                text_button("+", |state: &mut Self| {
+                    *state = state.with_speed(state.resolve().speed + Self::SPEED_STEP);
+                }),
+            )),
+            label(pacing.to_string()).color(Thing::VALUE_COLOR),
+        ))
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+    }
+}
+
+// Emitted by `Animation::tick` for whoever wants to react to playback, like
+// sound cues. The queue is drained by the app every frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimEvent {
+    ShiftStarted,
+    Reached,
 }
 
-#[derive(Default)]
 pub struct Animation {
     pub active: bool,
     pub frame: u64,
+    pub elapsed: f64,
     pub step: AnimStep,
+    pub shift_easing: Easing,
+    pub slow_easing: Easing,
+    pub pacing: Pacing,
+    pub next_pacing: Pacing,
+    pub manual_advance: bool,
+    pub advance_requested: bool,
+    pub events: Vec<AnimEvent>,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self::with_pacing(Pacing::default())
+    }
 }
 
 impl Animation {
     pub const FRAME_DURATION: u64 = 16;
     pub const FPS: f64 = 1000. / Self::FRAME_DURATION as f64;
+    pub const FRAME_SECS: f64 = 1. / Self::FPS;
+    pub const MAX_DT: f64 = 0.1;
+    pub const EPSILON: f64 = 1e-9;
+    // The refresh rates offered in the settings. Xilem gives the app neither
+    // the window nor its monitor, and `WindowOptions` only configures the
+    // window, so the display rate cannot be queried and the user picks it.
+    // Animation speed does not depend on it, since frames advance by the
+    // measured time.
+    pub const FRAME_RATES: [u32; 6] = [60, 90, 120, 144, 165, 240];
+
+    pub fn frame_interval(refresh_rate: u32) -> Duration {
+        match refresh_rate {
+            0 => Duration::from_millis(Self::FRAME_DURATION),
+            rate => Duration::from_secs_f64(1. / rate as f64),
+        }
+    }
+
+    pub fn next_frame_rate(refresh_rate: u32) -> u32 {
+        Self::FRAME_RATES
+            .iter()
+            .copied()
+            .find(|&rate| rate > refresh_rate)
+            .unwrap_or(Self::FRAME_RATES[0])
+    }
 
-    pub fn tick(&mut self, scaling_done: bool, slowing_done: bool) {
+    pub fn with_pacing(pacing: Pacing) -> Self {
+        Self {
+            active: false,
+            frame: 0,
+            elapsed: 0.,
+            step: AnimStep::Shifting(pacing.shifting_time),
+            shift_easing: Easing::default(),
+            slow_easing: Easing::default(),
+            pacing,
+            next_pacing: pacing,
+            manual_advance: false,
+            advance_requested: false,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn tick(&mut self, dt: f64, scaling_done: bool, slowing_done: bool, at_end: bool) {
         self.frame += 1;
-        self.step.advance(scaling_done, slowing_done);
+        self.elapsed += dt;
+        let step = std::mem::discriminant(&self.step);
+        let hold = self.manual_advance && !self.advance_requested;
+        self.step.advance(
+            dt,
+            &self.next_pacing,
+            scaling_done,
+            slowing_done,
+            at_end,
+            hold,
+        );
+        if std::mem::discriminant(&self.step) != step {
+            self.pacing = self.next_pacing;
+            self.advance_requested = false;
+            match self.step {
+                AnimStep::Shifting(_) => self.events.push(AnimEvent::ShiftStarted),
+                AnimStep::Pausing(_) => self.events.push(AnimEvent::Reached),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn waiting(&self) -> bool {
+        self.manual_advance && matches!(self.step, AnimStep::Pausing(t) if t <= 0.)
+    }
+
+    // Lets the current pause end; only takes effect once its time is up.
+    pub fn request_advance(&mut self) {
+        if matches!(self.step, AnimStep::Pausing(_)) {
+            self.advance_requested = true;
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        matches!(self.step, AnimStep::Finished)
     }
 
     pub fn secs(&self) -> f64 {
-        self.frame as f64 / Self::FPS
+        self.elapsed
     }
 
     pub fn info(&self) -> String {
@@ -97,15 +511,208 @@ impl Animation {
         }
     }
 
-    pub fn playback_button(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+    pub fn playback_button(&mut self, text_size: f32) -> impl WidgetView<Edit<Self>> + use<> {
         if self.active {
-            Either::A(text_button("Pause", |state: &mut Self| {
-                state.active = false;
-            }))
+            Either::A(button(
+                label("Pause").text_size(text_size),
+                |state: &mut Self| {
+                    state.active = false;
+                },
+            ))
         } else {
-            Either::B(text_button("Play", |state: &mut Self| {
-                state.active = true;
-            }))
+            Either::B(button(
+                label("Play").text_size(text_size),
+                |state: &mut Self| {
+                    state.active = true;
+                },
+            ))
         }
     }
+
+    pub fn advance_buttons(&mut self, text_size: f32) -> impl WidgetView<Edit<Self>> + use<> {
+        let next = self.manual_advance.then(|| {
+            button(
+                label(if self.waiting() { "Next ▶" } else { "Next" }).text_size(text_size),
+                |state: &mut Self| state.request_advance(),
+            )
+        });
+        flex_row((
+            checkbox(
+                "Manual advance",
+                self.manual_advance,
+                |state: &mut Self, checked| {
+                    state.manual_advance = checked;
+                    state.advance_requested = false;
+                },
+            ),
+            next,
+        ))
+    }
+
+    pub fn easing_buttons(&mut self, text_size: f32) -> impl WidgetView<Edit<Self>> + use<> {
+        flex_row((
+            button(
+                label(format!("Shift: {}", self.shift_easing)).text_size(text_size),
+                |state: &mut Self| {
+                    state.shift_easing = state.shift_easing.next();
+                },
+            ),
+            button(
+                label(format!("Slow: {}", self.slow_easing)).text_size(text_size),
+                |state: &mut Self| {
+                    state.slow_easing = state.slow_easing.next();
+                },
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing_endpoints() {
+        for easing in Easing::ALL {
+            for curve in [easing.in_out(), easing.out()] {
+                assert!(curve(0.).abs() < 1e-6, "{easing} at 0");
+                assert!((curve(1.) - 1.).abs() < 1e-6, "{easing} at 1");
+            }
+        }
+        assert_eq!(Easing::default().in_out()(0.25), cubic_in_out(0.25));
+        assert_eq!(Easing::default().out()(0.25), cubic_out(0.25));
+        assert_eq!(Easing::Bounce.next(), Easing::Linear);
+    }
+
+    #[test]
+    fn test_manual_advance_holds_the_pause() {
+        let mut animation = Animation {
+            step: AnimStep::Pausing(AnimStep::PAUSING_TIME),
+            manual_advance: true,
+            ..Animation::default()
+        };
+        for _ in 0..1000 {
+            animation.tick(Animation::FRAME_SECS, true, true, false);
+        }
+        assert_eq!(animation.step, AnimStep::Pausing(0.));
+        assert!(animation.waiting());
+
+        animation.request_advance();
+        animation.tick(Animation::FRAME_SECS, true, true, false);
+        assert!(matches!(animation.step, AnimStep::Shifting(_)));
+        assert!(!animation.advance_requested);
+
+        let mut animation = Animation {
+            step: AnimStep::Pausing(0.),
+            manual_advance: true,
+            ..Animation::default()
+        };
+        animation.tick(Animation::FRAME_SECS, true, true, true);
+        assert_eq!(animation.step, AnimStep::Finished);
+
+        let mut animation = Animation {
+            step: AnimStep::Pausing(0.),
+            ..Animation::default()
+        };
+        animation.tick(Animation::FRAME_SECS, true, true, false);
+        assert!(matches!(animation.step, AnimStep::Shifting(_)));
+    }
+
+    #[test]
+    fn test_step_durations_match_frame_counts() {
+        let frames = |mut step: AnimStep, dt: f64| {
+            let kind = std::mem::discriminant(&step);
+            let mut count = 0;
+            while std::mem::discriminant(&step) == kind {
+                step.advance(dt, &Pacing::STANDARD, true, false, false, false);
+                count += 1;
+            }
+            count
+        };
+        let dt = Animation::FRAME_SECS;
+        assert_eq!(frames(AnimStep::default(), dt), 126);
+        assert_eq!(frames(AnimStep::Idle(AnimStep::IDLE_TIME), dt), 63);
+        assert_eq!(frames(AnimStep::Pausing(AnimStep::PAUSING_TIME), dt), 188);
+        assert_eq!(frames(AnimStep::default(), 1. / 144.), 289);
+        assert_eq!(frames(AnimStep::default(), 1. / 30.), 61);
+    }
+
+    #[test]
+    fn test_profile_resolution() {
+        assert_eq!(AnimationProfile::default().resolve(), Pacing::STANDARD);
+        for base in [ProfileBase::Calm, ProfileBase::Standard, ProfileBase::Fast] {
+            let profile = AnimationProfile {
+                base,
+                ..AnimationProfile::default()
+            };
+            assert_eq!(profile.resolve(), base.pacing());
+            assert_eq!(profile.to_string(), base.to_string());
+        }
+        assert_eq!(ProfileBase::Fast.next(), ProfileBase::Calm);
+    }
+
+    #[test]
+    fn test_custom_overrides_merge_onto_base() {
+        let profile = AnimationProfile {
+            base: ProfileBase::Fast,
+            custom: ProfileOverrides {
+                pausing_time: Some(10.),
+                ..ProfileOverrides::default()
+            },
+        };
+        let pacing = profile.resolve();
+        assert_eq!(pacing.pausing_time, 10.);
+        assert_eq!(pacing.shifting_time, Pacing::FAST.shifting_time);
+        assert_eq!(pacing.speed, Pacing::FAST.speed);
+        assert_eq!(profile.to_string(), "Custom (Fast)");
+
+        let calm = AnimationProfile {
+            base: ProfileBase::Calm,
+            ..profile
+        };
+        assert_eq!(calm.resolve().pausing_time, 10.);
+        assert_eq!(calm.resolve().idle_time, Pacing::CALM.idle_time);
+
+        let clamped = profile.with_pausing_time(-3.).with_speed(0.);
+        assert_eq!(clamped.resolve().pausing_time, 0.);
+        assert_eq!(clamped.resolve().speed, AnimationProfile::SPEED_STEP);
+        let json = serde_json::to_string(&clamped).unwrap();
+        assert!(!json.contains("idle_time"));
+        let loaded: AnimationProfile = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, clamped);
+        let legacy: AnimationProfile = serde_json::from_str("{}").unwrap();
+        assert_eq!(legacy, AnimationProfile::default());
+    }
+
+    #[test]
+    fn test_profile_switch_applies_at_next_transition() {
+        let mut animation = Animation {
+            step: AnimStep::Pausing(1.),
+            next_pacing: Pacing::FAST,
+            ..Animation::default()
+        };
+        animation.tick(0.5, false, false, false);
+        assert_eq!(animation.step, AnimStep::Pausing(0.5));
+        assert_eq!(animation.pacing, Pacing::STANDARD);
+        while matches!(animation.step, AnimStep::Pausing(_)) {
+            animation.tick(0.5, false, false, false);
+        }
+        assert_eq!(
+            animation.step,
+            AnimStep::Shifting(Pacing::FAST.shifting_time)
+        );
+        assert_eq!(animation.pacing, Pacing::FAST);
+    }
+
+    #[test]
+    fn test_frame_interval() {
+        assert_eq!(Animation::frame_interval(0), Duration::from_millis(16));
+        assert_eq!(
+            Animation::frame_interval(120),
+            Duration::from_secs_f64(1. / 120.)
+        );
+        assert_eq!(Animation::next_frame_rate(60), 90);
+        assert_eq!(Animation::next_frame_rate(240), 60);
+        assert_eq!(Animation::next_frame_rate(0), 60);
+    }
 }
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use xilem::winit::error::EventLoopError;
+use xilem::{EventLoop, Xilem};
+
+use crate::diagnostics::Diagnostics;
+use crate::error::StateError;
+use crate::notice::Notice;
+use crate::settings::Settings;
+use crate::sound::SoundFiles;
+use crate::thing::Thing;
+use crate::{PROJECT_DIRS, State};
+
+/// Builder for the comparison app.
+///
+/// Without any options the app opens the dataset and settings saved in the
+/// user's preference folder, like the `scale-comparison` binary does. Things
+/// passed with [`App::with_things`] use default settings and are only saved
+/// when a data file is given.
+///
+/// ```
+/// use scale_comparison::{App, Thing};
+///
+/// let state = App::new()
+///     .with_things(vec![Thing::new("Blink", 0.3), Thing::new("Week", 604800.)])
+///     .state();
+/// assert_eq!(state.things().len(), 2);
+/// assert_eq!(state.things()[1].name, "Week");
+/// ```
+#[derive(Debug, Default)]
+pub struct App {
+    things: Option<Vec<Thing>>,
+    data_file: Option<PathBuf>,
+    url: Option<String>,
+    settings: Option<Settings>,
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts with these things instead of loading a dataset.
+    pub fn with_things(mut self, things: Vec<Thing>) -> Self {
+        self.things = Some(things);
+        self
+    }
+
+    /// Loads from and saves to `path` instead of the default data file.
+    pub fn with_data_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.data_file = Some(path.into());
+        self
+    }
+
+    /// Uses these settings instead of the saved ones.
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Downloads the dataset at `url` once the window is open.
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Builds the app state without opening a window.
+    pub fn state(self) -> State {
+        let settings = match (self.settings, &self.things) {
+            (Some(settings), _) => settings,
+            (None, Some(_)) => Settings::default(),
+            (None, None) => Settings::load(),
+        };
+        let mut state = match (self.url, self.things) {
+            (Some(url), _) => State::from_url(url, settings),
+            (None, Some(things)) => State::new(things, settings),
+            (None, None) => {
+                let data_file = self.data_file.unwrap_or_else(State::default_data_file);
+                return Self::open(data_file, settings);
+            }
+        };
+        if let Some(data_file) = self.data_file {
+            state.set_data_file(data_file);
+        }
+        state
+    }
+
+    fn open(data_file: PathBuf, settings: Settings) -> State {
+        match State::open(&data_file, settings) {
+            Ok(state) => state,
+            Err(StateError::NotFound(_)) => {
+                let mut state = State::new(Vec::new(), settings);
+                state.set_data_file(data_file);
+                state
+            }
+            // the empty fallback must never be saved over a file that failed to load
+            Err(err) => {
+                let mut state = State::new(Vec::new(), settings);
+                state.notices.push(err.notice());
+                state.notices.push(Notice::warning(format!(
+                    "{} was left untouched and edits will not be saved",
+                    data_file.display()
+                )));
+                state
+            }
+        }
+    }
+
+    pub fn run(self) -> Result<(), EventLoopError> {
+        let mut state = self.state();
+        state.notices.log_to(Diagnostics::log_file(&PROJECT_DIRS));
+        state.sound_files = SoundFiles::load();
+        Xilem::new(state, State::view).run_in(EventLoop::with_user_event())
+    }
+}
@@ -0,0 +1,180 @@
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use xilem::core::Edit;
+use xilem::style::Style;
+use xilem::view::{CrossAxisAlignment, flex_col, label, portal, sized_box, text_button};
+use xilem::{FontWeight, WidgetView};
+
+use crate::State;
+use crate::viewport::Viewport;
+
+pub const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+// Missing parts count as zero and pre-release or build suffixes are ignored.
+impl FromStr for Version {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let core = s.trim().split(['-', '+']).next().unwrap_or_default();
+        let mut parts = core.split('.').map(str::parse::<u32>);
+        let mut next = || parts.next().unwrap_or(Ok(0));
+        Ok(Self {
+            major: next()?,
+            minor: next()?,
+            patch: next()?,
+        })
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = ParseIntError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Version> for String {
+    fn from(version: Version) -> Self {
+        version.to_string()
+    }
+}
+
+impl Version {
+    pub fn current() -> Self {
+        env!("CARGO_PKG_VERSION").parse().unwrap()
+    }
+}
+
+// Settings written before the last seen version was recorded count as an
+// upgrade too.
+pub fn is_upgrade(last_seen: Option<Version>, current: Version) -> bool {
+    last_seen.is_none_or(|seen| seen < current)
+}
+
+// The highlights listed under every `## <version>` heading newer than `since`.
+pub fn highlights(changelog: &str, since: Option<Version>) -> Vec<(Version, Vec<&str>)> {
+    let mut sections: Vec<(Version, Vec<&str>)> = Vec::new();
+    let mut skipping = true;
+    for line in changelog.lines().map(str::trim) {
+        if let Some(heading) = line.strip_prefix("## ") {
+            let version = heading.parse::<Version>().ok();
+            skipping = version.is_none_or(|version| !is_upgrade(since, version));
+            if !skipping && let Some(version) = version {
+                sections.push((version, Vec::new()));
+            }
+        } else if let Some(item) = line.strip_prefix("- ")
+            && !skipping
+            && let Some((_, items)) = sections.last_mut()
+        {
+            items.push(item);
+        }
+    }
+    sections
+}
+
+pub fn view(since: Option<Version>) -> impl WidgetView<Edit<State>> + use<> {
+    let sections = highlights(CHANGELOG, since)
+        .into_iter()
+        .map(|(version, items)| {
+            flex_col((
+                label(format!("Version {version}")).weight(FontWeight::SEMI_BOLD),
+                items
+                    .into_iter()
+                    .map(|item| label(format!("• {item}")))
+                    .collect::<Vec<_>>(),
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+        })
+        .collect::<Vec<_>>();
+    sized_box(
+        flex_col((
+            label("What's new").weight(FontWeight::BOLD),
+            portal(flex_col(sections).cross_axis_alignment(CrossAxisAlignment::Start)),
+            text_button("Got it", |state: &mut State| state.dismiss_whats_new()),
+        ))
+        .cross_axis_alignment(CrossAxisAlignment::Start),
+    )
+    .expand()
+    .padding(15.)
+    .background_color(Viewport::FOOTER_AREA_COLOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    fn version(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_upgrade_detection() {
+        assert_eq!(
+            version("1.2.3-beta.1"),
+            Version {
+                major: 1,
+                minor: 2,
+                patch: 3
+            }
+        );
+        assert_eq!(version("0.2"), version("0.2.0"));
+        assert!("".parse::<Version>().is_err());
+        assert!("x.1".parse::<Version>().is_err());
+
+        assert!(is_upgrade(Some(version("0.1.0")), version("0.2.0")));
+        assert!(is_upgrade(Some(version("0.9.9")), version("0.10.0")));
+        assert!(!is_upgrade(Some(version("0.2.0")), version("0.2.0")));
+        assert!(!is_upgrade(Some(version("1.0.0")), version("0.2.0")));
+        assert!(is_upgrade(None, version("0.2.0")));
+
+        assert_eq!(
+            Settings::default().last_seen_version,
+            Some(Version::current())
+        );
+        let old: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(old.last_seen_version, None);
+        let json = serde_json::to_string(&Settings::default()).unwrap();
+        assert!(json.contains(&format!("\"last_seen_version\":\"{}\"", Version::current())));
+        let settings: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(settings.last_seen_version, Some(Version::current()));
+    }
+
+    #[test]
+    fn test_highlights_since_last_seen() {
+        let changelog = "# What's new\n\n## 0.3.0\n- Third\n\n## 0.2.0\n- Second\n- More\n\n## 0.1.0\n- First\n";
+        let versions = |since| {
+            highlights(changelog, since)
+                .into_iter()
+                .map(|(version, items)| (version.to_string(), items))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            versions(Some(version("0.1.0"))),
+            [
+                ("0.3.0".to_string(), vec!["Third"]),
+                ("0.2.0".to_string(), vec!["Second", "More"]),
+            ]
+        );
+        assert!(versions(Some(version("0.3.0"))).is_empty());
+        assert_eq!(versions(None).len(), 3);
+        assert!(!highlights(CHANGELOG, None).is_empty());
+    }
+}
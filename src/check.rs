@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use crate::error::StateError;
+use crate::import::{ImportReport, load_file};
+use crate::validation::{ValidationIssue, has_errors, validate};
+
+pub struct CheckReport {
+    pub import: ImportReport,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl CheckReport {
+    pub fn new(import: ImportReport) -> Self {
+        let issues = validate(&import.things);
+        Self { import, issues }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.import.errors.is_empty() && !has_errors(&self.issues)
+    }
+}
+
+impl std::fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, thing) in self.import.things.iter().enumerate() {
+            writeln!(
+                f,
+                "#{} {}: {} ({})",
+                i + 1,
+                thing.name,
+                thing.value,
+                thing.value.inner()
+            )?;
+        }
+        for err in &self.import.errors {
+            writeln!(f, "error: {err}")?;
+        }
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
+        }
+        write!(
+            f,
+            "{} things, {}",
+            self.import.things.len(),
+            if self.is_valid() { "valid" } else { "invalid" }
+        )
+    }
+}
+
+pub fn check_file(path: impl AsRef<Path>) -> Result<CheckReport, StateError> {
+    Ok(CheckReport::new(load_file(path)?))
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use xilem::Color;
+use xilem::palette::css;
+
+use crate::thing::Thing;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Side {
+    Primary,
+    Secondary,
+}
+
+impl Side {
+    pub fn bar_color(self) -> Color {
+        match self {
+            Side::Primary => Thing::BAR_COLOR,
+            Side::Secondary => css::CORNFLOWER_BLUE,
+        }
+    }
+
+    pub fn value_color(self) -> Color {
+        match self {
+            Side::Primary => Thing::VALUE_COLOR,
+            Side::Secondary => css::LIGHT_SKY_BLUE,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Bar<'a> {
+    pub index: usize,
+    pub side: Side,
+    pub thing: &'a Thing,
+    pub offset: f64,
+    pub width: f64,
+    pub row: usize,
+}
+
+pub const PAIR_GAP: f64 = 4.;
+pub const PAIR_WIDTH: f64 = (Thing::BAR_WIDTH - PAIR_GAP) / 2.;
+pub const PAIR_SHIFT: f64 = (PAIR_WIDTH + PAIR_GAP) / 2.;
+
+pub fn slot_count(things: &[Thing], secondary: Option<&[Thing]>) -> usize {
+    things.len().max(secondary.map_or(0, <[Thing]>::len))
+}
+
+pub fn slot_scale(things: &[Thing], secondary: Option<&[Thing]>, index: usize) -> Option<f64> {
+    let primary = things.get(index).and_then(Thing::finite_scale);
+    let secondary = secondary
+        .and_then(|secondary| secondary.get(index))
+        .and_then(Thing::finite_scale);
+    match (primary, secondary) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+pub fn bars<'a>(things: &'a [Thing], secondary: Option<&'a [Thing]>) -> Vec<Bar<'a>> {
+    let mut bars = Vec::new();
+    for index in 0..slot_count(things, secondary) {
+        let primary = things.get(index);
+        let secondary = secondary.and_then(|secondary| secondary.get(index));
+        match (primary, secondary) {
+            (Some(primary), Some(secondary)) => {
+                bars.push(Bar {
+                    index,
+                    side: Side::Primary,
+                    thing: primary,
+                    offset: -PAIR_SHIFT,
+                    width: PAIR_WIDTH,
+                    row: 0,
+                });
+                bars.push(Bar {
+                    index,
+                    side: Side::Secondary,
+                    thing: secondary,
+                    offset: PAIR_SHIFT,
+                    width: PAIR_WIDTH,
+                    row: 1,
+                });
+            }
+            (Some(thing), None) | (None, Some(thing)) => bars.push(Bar {
+                index,
+                side: if primary.is_some() {
+                    Side::Primary
+                } else {
+                    Side::Secondary
+                },
+                thing,
+                offset: 0.,
+                width: Thing::BAR_WIDTH,
+                row: 0,
+            }),
+            (None, None) => {}
+        }
+    }
+    bars
+}
+
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct ComparisonSession {
+    pub source: String,
+    pub things: Vec<Thing>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset(values: &[f64]) -> Vec<Thing> {
+        values
+            .iter()
+            .map(|&value| Thing::new(&value.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_pairs_share_slot() {
+        let things = dataset(&[1., 60.]);
+        let secondary = dataset(&[2., 3600.]);
+        let bars = bars(&things, Some(&secondary));
+        assert_eq!(bars.len(), 4);
+        for pair in bars.chunks(2) {
+            assert_eq!(pair[0].index, pair[1].index);
+            assert_eq!(pair[0].side, Side::Primary);
+            assert_eq!(pair[1].side, Side::Secondary);
+            assert_eq!(pair[0].offset, -pair[1].offset);
+            assert_eq!((pair[0].row, pair[1].row), (0, 1));
+            let left = pair[0].offset - pair[0].width / 2.;
+            let right = pair[1].offset + pair[1].width / 2.;
+            assert_eq!(right - left, Thing::BAR_WIDTH);
+            assert!(pair[0].offset + pair[0].width / 2. < pair[1].offset - pair[1].width / 2.);
+        }
+    }
+
+    #[test]
+    fn test_mismatched_lengths_render_singletons() {
+        let things = dataset(&[1., 60., 3600.]);
+        let secondary = dataset(&[2.]);
+        let bars = bars(&things, Some(&secondary));
+        assert_eq!(bars.len(), 4);
+        assert_eq!(bars[2].index, 1);
+        assert_eq!(bars[2].offset, 0.);
+        assert_eq!(bars[2].width, Thing::BAR_WIDTH);
+        assert_eq!(bars[3].side, Side::Primary);
+
+        let bars = super::bars(&secondary, Some(&things));
+        assert_eq!(bars[3].side, Side::Secondary);
+        assert_eq!(bars[3].offset, 0.);
+        assert_eq!(slot_count(&secondary, Some(&things)), 3);
+    }
+
+    #[test]
+    fn test_slot_scale_uses_max_of_pair() {
+        let things = dataset(&[1e3, 10.]);
+        let secondary = dataset(&[10.]);
+        assert_eq!(slot_scale(&things, Some(&secondary), 0), Some(3.));
+        assert_eq!(slot_scale(&things, Some(&secondary), 1), Some(1.));
+        assert_eq!(slot_scale(&things, None, 2), None);
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let session = ComparisonSession {
+            source: "fiction.json".to_string(),
+            things: dataset(&[1., 60.]),
+        };
+        let json = serde_json::to_string(&session).unwrap();
+        let loaded: ComparisonSession = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.source, "fiction.json");
+        assert_eq!(loaded.things.len(), 2);
+        assert_eq!(loaded.things[1].name, "60");
+        assert_eq!(loaded.things[1].scale(), session.things[1].scale());
+    }
+}
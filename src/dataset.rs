@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+use crate::animation::{Animation, AnimationProfile};
+use crate::notice::Notice;
+use crate::thing::Thing;
+use crate::utils::slugify;
+use crate::validation::{IssueKind, validate};
+use crate::viewport::PlayRange;
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+pub struct Dataset {
+    #[serde(default)]
+    pub project: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub axis_title: String,
+    #[serde(default)]
+    pub chart_title: bool,
+    #[serde(default)]
+    pub profile: AnimationProfile,
+    #[serde(default, skip_serializing_if = "PlayRange::is_all")]
+    pub play_range: PlayRange,
+    pub things: Vec<Thing>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DatasetFile {
+    Named(Box<Dataset>),
+    Legacy(Vec<Thing>),
+}
+
+impl Dataset {
+    pub const APP_NAME: &str = "Scale Comparison";
+    pub const DEFAULT_AXIS_TITLE: &str = "Duration (seconds, log scale)";
+
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        Ok(match serde_json::from_str(text)? {
+            DatasetFile::Named(dataset) => *dataset,
+            DatasetFile::Legacy(things) => Self {
+                things,
+                ..Self::default()
+            },
+        })
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn validate(&self) -> Vec<Notice> {
+        validate(&self.things)
+            .into_iter()
+            .filter(|issue| issue.kind == IssueKind::NonPositive)
+            .map(|issue| {
+                Notice::error(format!(
+                    "#{} {} and will be skipped in the preview",
+                    issue.index + 1,
+                    issue.message
+                ))
+            })
+            .collect()
+    }
+
+    pub fn title(project: &str, name: &str) -> String {
+        let title = match name.trim() {
+            "" => Self::APP_NAME.to_string(),
+            name => format!("{} — {name}", Self::APP_NAME),
+        };
+        match project.trim() {
+            "" => title,
+            project => format!("{project} — {title}"),
+        }
+    }
+
+    pub fn credit(project: &str, name: &str, author: &str) -> String {
+        let title = [project.trim(), name.trim()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" — ");
+        match (title.as_str(), author.trim()) {
+            (title, "") => title.to_string(),
+            ("", author) => format!("by {author}"),
+            (title, author) => format!("{title} · by {author}"),
+        }
+    }
+
+    pub fn window_title(project: &str, name: &str, animation: &Animation) -> String {
+        format!("{}{}", Self::title(project, name), animation.info())
+    }
+
+    pub fn axis_title(axis_title: &str) -> &str {
+        match axis_title.trim() {
+            "" => Self::DEFAULT_AXIS_TITLE,
+            axis_title => axis_title,
+        }
+    }
+
+    pub fn file_stem(name: &str) -> String {
+        match slugify(name).as_str() {
+            "" => slugify(Self::APP_NAME),
+            slug => slug.to_string(),
+        }
+    }
+
+    pub fn frame_file_name(name: &str, frame: u64) -> String {
+        format!("{}-frame-{frame:04}.png", Self::file_stem(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_flags_non_positive_values() {
+        let dataset = Dataset {
+            things: vec![
+                Thing::new("Nothing", 0.),
+                Thing::new("Blink", 0.3),
+                Thing::new("Debt", -5.),
+            ],
+            ..Dataset::default()
+        };
+        let notices = dataset.validate();
+        assert_eq!(notices.len(), 2);
+        assert_eq!(
+            notices[0].message,
+            "#1 \"Nothing\" has a non-positive value and will be skipped in the preview"
+        );
+        assert!(notices[1].message.starts_with("#3 \"Debt\""));
+        assert!(Dataset::default().validate().is_empty());
+    }
+
+    #[test]
+    fn test_named_and_legacy_files() {
+        let named = r#"{"name":"Cosmic Timescales","things":[{"name":"Blink","value":[{"significand":3.0,"exponent":-1.0}]}]}"#;
+        let dataset = Dataset::from_json(named).unwrap();
+        assert_eq!(dataset.name, "Cosmic Timescales");
+        assert_eq!(dataset.things.len(), 1);
+
+        let profiled = r#"{"profile":{"base":"Calm","custom":{"pausing_time":4.0}},"things":[]}"#;
+        let dataset = Dataset::from_json(profiled).unwrap();
+        assert_eq!(dataset.profile.resolve().pausing_time, 4.);
+        assert_eq!(dataset.profile.resolve().shifting_time, 3.);
+        let saved = serde_json::to_string(&dataset).unwrap();
+        assert_eq!(Dataset::from_json(&saved).unwrap().profile, dataset.profile);
+
+        let legacy = r#"[{"name":"Blink","value":[{"significand":3.0,"exponent":-1.0}]}]"#;
+        let dataset = Dataset::from_json(legacy).unwrap();
+        assert_eq!(dataset.name, "");
+        assert_eq!(dataset.axis_title, "");
+        assert!(!dataset.chart_title);
+        assert_eq!(dataset.profile, AnimationProfile::default());
+        assert_eq!(dataset.things[0].name, "Blink");
+    }
+
+    #[test]
+    fn test_credit() {
+        assert_eq!(Dataset::credit("", "", ""), "");
+        assert_eq!(Dataset::credit(" ", "Timescales", ""), "Timescales");
+        assert_eq!(Dataset::credit("", "", "Ada"), "by Ada");
+        assert_eq!(
+            Dataset::credit("Cosmos", "Timescales", "Ada"),
+            "Cosmos — Timescales · by Ada"
+        );
+    }
+
+    #[test]
+    fn test_window_title() {
+        let mut animation = Animation::default();
+        assert_eq!(
+            Dataset::window_title("", "", &animation),
+            "Scale Comparison"
+        );
+        animation.frame = 125;
+        animation.elapsed = 2.;
+        animation.active = true;
+        assert_eq!(
+            Dataset::window_title("Lecture 3", "", &animation),
+            "Lecture 3 — Scale Comparison | frame: 125, time: 2.0 s"
+        );
+        animation.active = false;
+        assert_eq!(
+            Dataset::window_title("Lecture 3", "Cosmic Timescales", &animation),
+            "Lecture 3 — Scale Comparison — Cosmic Timescales | frame: 125, time: 2.0 s [paused]"
+        );
+    }
+
+    #[test]
+    fn test_axis_title() {
+        assert_eq!(Dataset::axis_title(""), "Duration (seconds, log scale)");
+        assert_eq!(Dataset::axis_title(" Age (s) "), "Age (s)");
+    }
+
+    #[test]
+    fn test_title_and_file_names() {
+        assert_eq!(
+            Dataset::title("", "Cosmic Timescales"),
+            "Scale Comparison — Cosmic Timescales"
+        );
+        assert_eq!(Dataset::title("", "  "), "Scale Comparison");
+        assert_eq!(
+            Dataset::frame_file_name("Cosmic Timescales", 1),
+            "cosmic-timescales-frame-0001.png"
+        );
+        assert_eq!(
+            Dataset::frame_file_name("", 12),
+            "scale-comparison-frame-0012.png"
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::LazyLock;
+use std::task::{Context, Poll, Waker};
+
+use directories::ProjectDirs;
+use xilem::core::Edit;
+use xilem::style::Style;
+use xilem::vello::wgpu;
+use xilem::view::{CrossAxisAlignment, flex_col, flex_row, label, sized_box, text_button};
+use xilem::{FontWeight, WidgetView};
+
+use crate::State;
+use crate::utils::{copy_to_clipboard, open_in_file_manager};
+use crate::viewport::Viewport;
+
+// Asking wgpu for an adapter is slow, so it happens once per run.
+static RENDERER: LazyLock<String> = LazyLock::new(|| {
+    let instance = wgpu::Instance::default();
+    let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()));
+    adapter.into_iter().next().map_or_else(
+        || "vello, no GPU adapter found".to_string(),
+        |adapter| {
+            let info = adapter.get_info();
+            format!("vello on {} ({})", info.name, info.backend.to_str())
+        },
+    )
+});
+
+// wgpu resolves adapter requests right away on native backends, so polling
+// without a real waker is enough.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostics {
+    pub version: String,
+    pub renderer: String,
+    pub data_dir: PathBuf,
+    pub config_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub log_file: PathBuf,
+    pub data_file: Option<PathBuf>,
+}
+
+impl Diagnostics {
+    pub fn collect(dirs: &ProjectDirs, data_file: Option<&Path>) -> Self {
+        Self::with_renderer(dirs, data_file, &RENDERER)
+    }
+
+    fn with_renderer(dirs: &ProjectDirs, data_file: Option<&Path>, renderer: &str) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            renderer: renderer.to_string(),
+            data_dir: dirs.data_dir().to_path_buf(),
+            config_dir: dirs.preference_dir().to_path_buf(),
+            cache_dir: dirs.cache_dir().to_path_buf(),
+            log_file: Self::log_file(dirs),
+            data_file: data_file.map(Path::to_path_buf),
+        }
+    }
+
+    pub fn log_file(dirs: &ProjectDirs) -> PathBuf {
+        dirs.data_dir().join("scale-comparison.log")
+    }
+
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Version", self.version.clone()),
+            ("Renderer", self.renderer.clone()),
+            ("Data folder", self.data_dir.display().to_string()),
+            ("Settings folder", self.config_dir.display().to_string()),
+            ("Cache folder", self.cache_dir.display().to_string()),
+            ("Log file", self.log_file.display().to_string()),
+            (
+                "Dataset file",
+                self.data_file
+                    .as_ref()
+                    .map_or("none, changes are not saved".to_string(), |path| {
+                        path.display().to_string()
+                    }),
+            ),
+        ]
+    }
+
+    pub fn view(&self) -> impl WidgetView<Edit<State>> + use<> {
+        let entries = self
+            .entries()
+            .into_iter()
+            .map(|(key, value)| {
+                flex_row((
+                    label(format!("{key}:")).weight(FontWeight::SEMI_BOLD),
+                    label(value),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let report = self.to_string();
+        let folder = self.data_dir.clone();
+        sized_box(
+            flex_col((
+                label("Scale Comparison").weight(FontWeight::BOLD),
+                entries,
+                flex_row((
+                    text_button("Open data folder", move |_: &mut State| {
+                        let _ = open_in_file_manager(&folder);
+                    }),
+                    text_button("Copy diagnostics", move |_: &mut State| {
+                        let _ = copy_to_clipboard(&report);
+                    }),
+                )),
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Start),
+        )
+        .expand()
+        .padding(15.)
+        .background_color(Viewport::FOOTER_AREA_COLOR)
+    }
+}
+
+impl std::fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (key, value) in self.entries() {
+            writeln!(f, "{key}: {value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics_report() {
+        let dirs = ProjectDirs::from("org", "darajkong", "scale-comparison").unwrap();
+        let data_file = dirs.preference_dir().join("data.json");
+        let diagnostics = Diagnostics::with_renderer(&dirs, Some(&data_file), "vello on test");
+        assert_eq!(diagnostics.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            diagnostics.log_file,
+            dirs.data_dir().join("scale-comparison.log")
+        );
+        assert_eq!(diagnostics.data_file.as_ref(), Some(&data_file));
+
+        let report = diagnostics.to_string();
+        assert_eq!(report.lines().count(), diagnostics.entries().len());
+        assert!(report.starts_with(&format!("Version: {}\n", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains(&format!("Dataset file: {}", data_file.display())));
+        assert!(report.contains(&format!("Cache folder: {}", dirs.cache_dir().display())));
+        assert!(report.contains("Renderer: vello on test\n"));
+        assert!(report.contains(&format!("Log file: {}", diagnostics.log_file.display())));
+    }
+}
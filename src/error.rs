@@ -0,0 +1,236 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::notice::{Level, Notice};
+use crate::validation::ValidationIssue;
+
+#[derive(Debug)]
+pub enum StateError {
+    Io(io::Error),
+    NotFound(PathBuf),
+    Unwritable {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse(serde_json::Error),
+    // a file whose extension names a format its contents are not in
+    Format {
+        which: &'static str,
+        source: serde_json::Error,
+    },
+    // the unreadable file was copied to `backup` before anything could overwrite it
+    Corrupt {
+        backup: PathBuf,
+        source: serde_json::Error,
+    },
+    #[cfg(feature = "xlsx")]
+    Spreadsheet(calamine::Error),
+    UnsupportedFormat(PathBuf),
+    // only the issues severe enough to refuse the data
+    Validation(Vec<ValidationIssue>),
+    NoDataFile,
+}
+
+impl std::fmt::Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StateError::Io(err) => write!(f, "{err}"),
+            StateError::NotFound(path) => write!(f, "No file at {}", path.display()),
+            StateError::Unwritable { path, source } => {
+                write!(f, "Could not write {}: {source}", path.display())
+            }
+            StateError::Parse(err) => write!(f, "Invalid JSON: {err}"),
+            StateError::Format { which, source } => write!(f, "Not a valid {which} file: {source}"),
+            StateError::Corrupt { backup, source } => write!(
+                f,
+                "The data file is damaged ({source}); a copy was kept at {}",
+                backup.display()
+            ),
+            #[cfg(feature = "xlsx")]
+            StateError::Spreadsheet(err) => write!(f, "Could not read spreadsheet: {err}"),
+            StateError::UnsupportedFormat(path) => {
+                write!(f, "Unsupported file format: {}", path.display())
+            }
+            StateError::Validation(issues) => {
+                let issues = issues
+                    .iter()
+                    .map(|issue| format!("#{} {}", issue.index + 1, issue.message))
+                    .collect::<Vec<_>>();
+                write!(f, "Invalid data: {}", issues.join("; "))
+            }
+            StateError::NoDataFile => write!(f, "This dataset has no data file to save to"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StateError::Io(err) => Some(err),
+            StateError::NotFound(_) => None,
+            StateError::Unwritable { source, .. } => Some(source),
+            StateError::Parse(err) => Some(err),
+            StateError::Format { source, .. } => Some(source),
+            StateError::Corrupt { source, .. } => Some(source),
+            #[cfg(feature = "xlsx")]
+            StateError::Spreadsheet(err) => Some(err),
+            StateError::UnsupportedFormat(_)
+            | StateError::Validation(_)
+            | StateError::NoDataFile => None,
+        }
+    }
+}
+
+impl StateError {
+    pub fn level(&self) -> Level {
+        match self {
+            StateError::NotFound(_) => Level::Info,
+            StateError::Parse(_)
+            | StateError::Format { .. }
+            | StateError::UnsupportedFormat(_)
+            | StateError::Validation(_)
+            | StateError::NoDataFile => Level::Warning,
+            #[cfg(feature = "xlsx")]
+            StateError::Spreadsheet(_) => Level::Warning,
+            StateError::Io(_) | StateError::Unwritable { .. } | StateError::Corrupt { .. } => {
+                Level::Error
+            }
+        }
+    }
+
+    pub fn notice(&self) -> Notice {
+        Notice::new(self.level(), self.to_string())
+    }
+
+    // Tells a missing file apart from other read errors.
+    pub fn reading(path: &Path, err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => StateError::NotFound(path.to_path_buf()),
+            _ => StateError::Io(err),
+        }
+    }
+}
+
+impl From<io::Error> for StateError {
+    fn from(err: io::Error) -> Self {
+        StateError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for StateError {
+    fn from(err: serde_json::Error) -> Self {
+        StateError::Parse(err)
+    }
+}
+
+#[cfg(feature = "xlsx")]
+impl From<calamine::Error> for StateError {
+    fn from(err: calamine::Error) -> Self {
+        StateError::Spreadsheet(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::State;
+    use crate::import::load_file;
+    use crate::settings::Settings;
+    use crate::thing::Thing;
+
+    #[test]
+    fn test_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.json");
+        match State::load_from(&path, Settings::default()) {
+            Err(StateError::NotFound(missing)) => assert_eq!(missing, path),
+            other => panic!("expected NotFound, got {:?}", other.err()),
+        }
+        let err = load_file(&path).unwrap_err();
+        assert!(matches!(err, StateError::NotFound(_)));
+        assert_eq!(err.notice().level, Level::Info);
+    }
+
+    #[test]
+    fn test_malformed_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let text = "[{\"name\": \"Blink\", \"value\": ";
+        fs::write(&path, text).unwrap();
+        match load_file(&path) {
+            Err(err @ StateError::Format { which: "JSON", .. }) => {
+                assert_eq!(err.level(), Level::Warning);
+                assert!(err.to_string().starts_with("Not a valid JSON file"));
+            }
+            other => panic!("expected Format, got {:?}", other.err()),
+        }
+        match State::load_from(&path, Settings::default()) {
+            Err(err @ StateError::Corrupt { .. }) => {
+                assert_eq!(err.level(), Level::Error);
+                let StateError::Corrupt { backup, .. } = err else {
+                    unreachable!()
+                };
+                assert_eq!(backup, dir.path().join("data.corrupt.json"));
+                assert_eq!(fs::read_to_string(backup).unwrap(), text);
+            }
+            other => panic!("expected Corrupt, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_corrupt_file_without_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        fs::write(&path, "{").unwrap();
+        // a directory in the way of the copy
+        fs::create_dir(dir.path().join("data.corrupt.json")).unwrap();
+        match State::load_from(&path, Settings::default()) {
+            Err(StateError::Parse(err)) => assert!(err.is_eof()),
+            other => panic!("expected Parse, got {:?}", other.err()),
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{");
+    }
+
+    #[test]
+    fn test_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("things.json");
+        let things = [Thing::new("Blink", 0.1), Thing::new("Never", -1.)];
+        fs::write(&path, serde_json::to_string(&things).unwrap()).unwrap();
+        let mut state = State::new(Vec::new(), Settings::default());
+        state.compare_path = path.to_string_lossy().into_owned();
+        state.compare_with();
+        assert!(state.secondary.is_none());
+        let notice = state.notices.iter().last().unwrap();
+        assert_eq!(notice.level, Level::Warning);
+        assert_eq!(
+            notice.message,
+            "Invalid data: #2 \"Never\" has a non-positive value"
+        );
+    }
+
+    #[test]
+    fn test_unwritable_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocker = dir.path().join("blocker");
+        fs::write(&blocker, "").unwrap();
+        let mut state = State::new(Vec::new(), Settings::default());
+        state.set_data_file(blocker.join("data.json"));
+        match state.save() {
+            Err(err @ StateError::Unwritable { .. }) => {
+                assert_eq!(err.notice().level, Level::Error);
+                assert!(err.to_string().contains("blocker"));
+            }
+            other => panic!("expected Unwritable, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unsupported_format() {
+        let err = load_file("things.txt").unwrap_err();
+        assert!(matches!(err, StateError::UnsupportedFormat(_)));
+        assert_eq!(err.to_string(), "Unsupported file format: things.txt");
+    }
+}
@@ -0,0 +1,80 @@
+use crate::thing::Thing;
+
+// Which thing the compact data list shows as a full card. At most one row is
+// expanded, so opening a row collapses the previous one.
+#[derive(Debug, Default)]
+pub struct Expansion {
+    expanded: Option<u64>,
+}
+
+impl Expansion {
+    pub fn expanded(&self) -> Option<u64> {
+        self.expanded
+    }
+
+    pub fn is_expanded(&self, id: u64) -> bool {
+        self.expanded == Some(id)
+    }
+
+    pub fn set(&mut self, id: u64, expanded: bool) {
+        if expanded {
+            self.expanded = Some(id);
+        } else if self.is_expanded(id) {
+            self.expanded = None;
+        }
+    }
+
+    // Keeps an unapplied value edit on screen when the list turns compact.
+    pub fn enter_compact(&mut self, things: &[Thing]) {
+        if let Some(thing) = things.iter().find(|thing| thing.value.is_editing()) {
+            self.expanded = Some(thing.id);
+        }
+    }
+
+    pub fn retain(&mut self, things: &[Thing]) {
+        if self
+            .expanded
+            .is_some_and(|id| !things.iter().any(|thing| thing.id == id))
+        {
+            self.expanded = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expansion_is_exclusive() {
+        let mut expansion = Expansion::default();
+        expansion.set(1, true);
+        expansion.set(2, true);
+        assert_eq!(expansion.expanded(), Some(2));
+        assert!(!expansion.is_expanded(1));
+
+        expansion.set(1, false);
+        assert_eq!(expansion.expanded(), Some(2));
+        expansion.set(2, false);
+        assert_eq!(expansion.expanded(), None);
+    }
+
+    #[test]
+    fn test_compact_mode_keeps_edits_and_forgets_deleted_rows() {
+        let mut things = vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)];
+        let mut expansion = Expansion::default();
+        expansion.enter_compact(&things);
+        assert_eq!(expansion.expanded(), None);
+
+        things[1].value.start_editing();
+        expansion.set(things[0].id, true);
+        expansion.enter_compact(&things);
+        assert_eq!(expansion.expanded(), Some(things[1].id));
+
+        expansion.retain(&things);
+        assert_eq!(expansion.expanded(), Some(things[1].id));
+        things.remove(1);
+        expansion.retain(&things);
+        assert_eq!(expansion.expanded(), None);
+    }
+}
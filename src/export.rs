@@ -0,0 +1,748 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use std::{fmt, fs, io};
+
+use xilem::vello::kurbo::Size;
+
+use crate::animation::{Animation, AnimationProfile};
+use crate::dataset::Dataset;
+use crate::notice::Notice;
+use crate::settings::Settings;
+use crate::thing::Thing;
+use crate::units::TimeScale;
+use crate::utils::float_to_string;
+use crate::viewport::{BarGeometry, Viewport};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportProgress {
+    pub frame: u64,
+    pub total: u64,
+}
+
+impl ExportProgress {
+    pub fn percent(&self) -> u64 {
+        (self.frame * 100).checked_div(self.total).unwrap_or(100)
+    }
+}
+
+impl fmt::Display for ExportProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Exporting… {}% (frame {}/{})",
+            self.percent(),
+            self.frame,
+            self.total
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportSummary {
+    pub frames: u64,
+    pub total: u64,
+    pub elapsed: Duration,
+    pub bytes: u64,
+    pub cancelled: bool,
+}
+
+impl ExportSummary {
+    pub const MEGABYTE: f64 = 1e6;
+
+    pub fn notice(&self) -> Notice {
+        let size = float_to_string(self.bytes as f64 / Self::MEGABYTE);
+        let elapsed = TimeScale::from(self.elapsed.as_secs_f64());
+        match self.cancelled {
+            true => Notice::warning(format!(
+                "Export cancelled after {}/{} frames ({size} MB in {elapsed})",
+                self.frames, self.total
+            )),
+            false => Notice::info(format!(
+                "Exported {} frames ({size} MB in {elapsed})",
+                self.frames
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportMessage {
+    Progress(ExportProgress),
+    Done(ExportSummary),
+    Failed(String),
+}
+
+/// Writes `total` frames with `write_frame`, which returns the number of
+/// bytes written. `cancel` is checked between frames. Every run ends with
+/// either `Done` or `Failed`.
+pub fn run_frames(
+    total: u64,
+    cancel: &AtomicBool,
+    mut write_frame: impl FnMut(u64) -> io::Result<u64>,
+    mut report: impl FnMut(ExportMessage),
+) -> io::Result<ExportSummary> {
+    let started = Instant::now();
+    let mut bytes = 0;
+    let mut frames = 0;
+    while frames < total && !cancel.load(Ordering::Relaxed) {
+        match write_frame(frames) {
+            Ok(written) => bytes += written,
+            Err(err) => {
+                report(ExportMessage::Failed(format!(
+                    "Export failed at frame {}/{total}: {err}",
+                    frames + 1
+                )));
+                return Err(err);
+            }
+        }
+        frames += 1;
+        report(ExportMessage::Progress(ExportProgress {
+            frame: frames,
+            total,
+        }));
+    }
+    let summary = ExportSummary {
+        frames,
+        total,
+        elapsed: started.elapsed(),
+        bytes,
+        cancelled: frames < total,
+    };
+    report(ExportMessage::Done(summary));
+    Ok(summary)
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Empty,
+    InvalidSize(Size),
+    InvalidFps(u32),
+    InvalidSpeed(f64),
+    MissingFramesDir,
+    Io(io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::Empty => write!(f, "Nothing to export: no thing has a positive value"),
+            ExportError::InvalidSize(size) => {
+                write!(f, "Invalid frame size {}×{}", size.width, size.height)
+            }
+            ExportError::InvalidFps(fps) => write!(
+                f,
+                "Invalid frame rate {fps}, expected 1 to {}",
+                AnimationExport::MAX_FPS
+            ),
+            ExportError::InvalidSpeed(speed) => write!(f, "Invalid speed {speed}"),
+            ExportError::MissingFramesDir => write!(f, "No frames directory was given"),
+            ExportError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        ExportError::Io(err)
+    }
+}
+
+/// Runs the animation without a window and writes one PNG per frame.
+///
+/// ```no_run
+/// use scale_comparison::Thing;
+/// use scale_comparison::export::AnimationExport;
+///
+/// let frames = AnimationExport::new(vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)])
+///     .size(1280., 720.)
+///     .fps(30)
+///     .frames_dir("frames")
+///     .render()?;
+/// # Ok::<(), scale_comparison::export::ExportError>(())
+/// ```
+pub struct AnimationExport {
+    things: Vec<Thing>,
+    secondary: Option<Vec<Thing>>,
+    profile: AnimationProfile,
+    name: String,
+    settings: Settings,
+    size: Size,
+    fps: u32,
+    speed: f64,
+    frames_dir: Option<PathBuf>,
+    cancel: Arc<AtomicBool>,
+    report: Option<Box<dyn FnMut(ExportMessage) + Send>>,
+}
+
+impl AnimationExport {
+    pub const DEFAULT_SIZE: Size = Size::new(1920., 1080.);
+    pub const DEFAULT_FPS: u32 = 60;
+    pub const MAX_FPS: u32 = 240;
+    pub const MAX_SIDE: f64 = 16384.;
+
+    pub fn new(things: Vec<Thing>) -> Self {
+        Self {
+            things,
+            secondary: None,
+            profile: AnimationProfile::default(),
+            name: String::new(),
+            settings: Settings::default(),
+            size: Self::DEFAULT_SIZE,
+            fps: Self::DEFAULT_FPS,
+            speed: 1.,
+            frames_dir: None,
+            cancel: Arc::default(),
+            report: None,
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Drawn at `settings.export_scale` rather than the screen's UI scale.
+    pub fn settings(mut self, settings: Settings) -> Self {
+        self.settings = settings.for_export();
+        self
+    }
+
+    /// A dataset compared side by side with the main one.
+    pub fn secondary(mut self, secondary: Option<Vec<Thing>>) -> Self {
+        self.secondary = secondary;
+        self
+    }
+
+    pub fn profile(mut self, profile: AnimationProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.size = Size::new(width, height);
+        self
+    }
+
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    /// Playback speed, 2 plays the animation twice as fast.
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn frames_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.frames_dir = Some(path.into());
+        self
+    }
+
+    /// Checked between frames, setting it stops the export early.
+    pub fn cancel_flag(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn on_progress(self, mut progress: impl FnMut(ExportProgress) + Send + 'static) -> Self {
+        self.on_message(move |message| {
+            if let ExportMessage::Progress(message) = message {
+                progress(message);
+            }
+        })
+    }
+
+    /// Receives progress and exactly one final `Done` or `Failed` message.
+    pub fn on_message(mut self, report: impl FnMut(ExportMessage) + Send + 'static) -> Self {
+        self.report = Some(Box::new(report));
+        self
+    }
+
+    pub fn validate(&self) -> Result<&Path, ExportError> {
+        if !self
+            .things
+            .iter()
+            .any(|thing| thing.finite_scale().is_some())
+        {
+            return Err(ExportError::Empty);
+        }
+        let Size { width, height } = self.size;
+        let side = 1.0..=Self::MAX_SIDE;
+        if !(side.contains(&width) && side.contains(&height)) {
+            return Err(ExportError::InvalidSize(self.size));
+        }
+        if !(1..=Self::MAX_FPS).contains(&self.fps) {
+            return Err(ExportError::InvalidFps(self.fps));
+        }
+        if !(self.speed.is_finite() && self.speed > 0.) {
+            return Err(ExportError::InvalidSpeed(self.speed));
+        }
+        self.frames_dir
+            .as_deref()
+            .ok_or(ExportError::MissingFramesDir)
+    }
+
+    fn viewport(&self) -> Viewport {
+        let mut viewport = Viewport::init_with(&self.things, self.settings.visible_decades);
+        viewport.apply_settings((&self.things, self.secondary.as_deref()), &self.settings);
+        viewport.animation = Animation {
+            active: true,
+            ..Animation::with_pacing(self.profile.resolve())
+        };
+        viewport
+    }
+
+    fn advance(&self, viewport: &mut Viewport) {
+        let mut remaining = self.speed / self.fps as f64;
+        while remaining > Animation::EPSILON && !viewport.animation.finished() {
+            let dt = remaining.min(Animation::FRAME_SECS);
+            viewport.update_animation(&self.things, self.secondary.as_deref(), dt);
+            remaining -= dt;
+        }
+    }
+
+    // runs the animation once, handing each frame to `visit`
+    fn simulate(&self, mut visit: impl FnMut(&Viewport)) -> u64 {
+        let mut viewport = self.viewport();
+        visit(&viewport);
+        let mut frames = 1;
+        while !viewport.animation.finished() && frames < Viewport::MAX_SIMULATED_FRAMES {
+            self.advance(&mut viewport);
+            visit(&viewport);
+            frames += 1;
+        }
+        frames
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.simulate(|_| {})
+    }
+
+    /// Writes each frame as a PNG of the bars over the chart background.
+    pub fn render(self) -> Result<u64, ExportError> {
+        self.render_with("png", encode_png)
+    }
+
+    pub fn render_with(
+        mut self,
+        extension: &str,
+        mut encode: impl FnMut(&[BarGeometry], Size) -> io::Result<Vec<u8>>,
+    ) -> Result<u64, ExportError> {
+        let mut report = self.report.take();
+        let mut report = move |message| {
+            if let Some(report) = &mut report {
+                report(message);
+            }
+        };
+        let dir = match self.validate().map(Path::to_path_buf) {
+            Ok(dir) => dir,
+            Err(err) => {
+                report(ExportMessage::Failed(err.to_string()));
+                return Err(err);
+            }
+        };
+        if let Err(err) = fs::create_dir_all(&dir) {
+            report(ExportMessage::Failed(format!(
+                "Could not create {}: {err}",
+                dir.display()
+            )));
+            return Err(err.into());
+        }
+        let mut frames = Vec::new();
+        self.simulate(|viewport| {
+            frames.push(viewport.geometry_snapshot(
+                (&self.things, self.secondary.as_deref()),
+                &self.settings,
+                self.size,
+            ));
+        });
+        let summary = run_frames(
+            frames.len() as u64,
+            &self.cancel,
+            |frame| {
+                let bytes = encode(&frames[frame as usize], self.size)?;
+                let file = Path::new(&Dataset::frame_file_name(&self.name, frame + 1))
+                    .with_extension(extension);
+                fs::write(dir.join(file), &bytes)?;
+                Ok(bytes.len() as u64)
+            },
+            report,
+        )?;
+        Ok(summary.frames)
+    }
+}
+
+/// Fills the bars of one frame over the chart background as RGBA pixels,
+/// top row first. Labels need the GPU renderer and are left out.
+pub fn rasterize(geometry: &[BarGeometry], size: Size) -> Vec<u8> {
+    let (width, height) = (size.width.round() as usize, size.height.round() as usize);
+    let background = Viewport::BACKGROUND_COLOR.to_rgba8().to_u8_array();
+    let mut pixels = background.repeat(width * height);
+    for bar in geometry.iter().filter(|bar| bar.alpha > 0.) {
+        let color = bar.side.bar_color().to_rgba8().to_u8_array();
+        let alpha = bar.alpha.clamp(0., 1.);
+        let columns = bar.left.max(0.).round() as usize..bar.right.min(size.width).round() as usize;
+        let rows = bar.top.max(0.).round() as usize..bar.bottom.min(size.height).round() as usize;
+        for row in rows {
+            for column in columns.clone() {
+                let pixel = &mut pixels[(row * width + column) * 4..][..3];
+                for (channel, &source) in pixel.iter_mut().zip(&color) {
+                    let blended = source as f32 * alpha + *channel as f32 * (1. - alpha);
+                    *channel = blended.round() as u8;
+                }
+            }
+        }
+    }
+    pixels
+}
+
+pub fn encode_png(geometry: &[BarGeometry], size: Size) -> io::Result<Vec<u8>> {
+    let pixels = rasterize(geometry, size);
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(
+        &mut bytes,
+        size.width.round() as u32,
+        size.height.round() as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&pixels).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(bytes)
+}
+
+/// An export handed to a background thread and cancelled from the app.
+#[derive(Clone)]
+pub struct ExportJob {
+    cancel: Arc<AtomicBool>,
+    export: Arc<Mutex<Option<AnimationExport>>>,
+}
+
+impl ExportJob {
+    pub fn new(export: AnimationExport) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let export = export.cancel_flag(cancel.clone());
+        Self {
+            cancel,
+            export: Arc::new(Mutex::new(Some(export))),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Starts rendering on its own thread, only the first call does anything.
+    pub fn spawn(
+        &self,
+        report: impl FnMut(ExportMessage) + Send + 'static,
+    ) -> Option<JoinHandle<Result<u64, ExportError>>> {
+        let export = self.export.lock().ok()?.take()?;
+        Some(std::thread::spawn(move || {
+            export.on_message(report).render()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::ProfileBase;
+    use crate::comparison::Side;
+    use crate::math::ENumber;
+
+    #[test]
+    fn test_progress_title() {
+        let progress = ExportProgress {
+            frame: 1032,
+            total: 2400,
+        };
+        assert_eq!(progress.percent(), 43);
+        assert_eq!(progress.to_string(), "Exporting… 43% (frame 1032/2400)");
+        assert_eq!(ExportProgress { frame: 0, total: 0 }.percent(), 100);
+    }
+
+    #[test]
+    fn test_run_frames_reports_each_frame() {
+        let mut messages = Vec::new();
+        let summary = run_frames(
+            3,
+            &AtomicBool::new(false),
+            |_| Ok(1_500_000),
+            |message| messages.push(message),
+        )
+        .unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            messages[1],
+            ExportMessage::Progress(ExportProgress { frame: 2, total: 3 })
+        );
+        assert_eq!(messages[3], ExportMessage::Done(summary));
+        assert_eq!((summary.frames, summary.bytes), (3, 4_500_000));
+        assert!(!summary.cancelled);
+        assert!(
+            summary
+                .notice()
+                .message
+                .starts_with("Exported 3 frames (4.5 MB in")
+        );
+    }
+
+    #[test]
+    fn test_run_frames_stops_when_cancelled() {
+        let cancel = AtomicBool::new(false);
+        let summary = run_frames(
+            100,
+            &cancel,
+            |frame| {
+                if frame == 4 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                Ok(10)
+            },
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(summary.frames, 5);
+        assert!(summary.cancelled);
+        assert!(
+            summary
+                .notice()
+                .message
+                .starts_with("Export cancelled after 5/100 frames")
+        );
+    }
+
+    fn export() -> AnimationExport {
+        AnimationExport::new(vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)])
+            .name("Quick look")
+            .size(320., 240.)
+            .fps(2)
+            .speed(20.)
+    }
+
+    #[test]
+    fn test_animation_export_validates_inputs() {
+        let err = |export: AnimationExport| export.frames_dir("frames").validate().unwrap_err();
+        assert!(matches!(
+            err(AnimationExport::new(vec![Thing::new("Nothing", 0.)])),
+            ExportError::Empty
+        ));
+        assert!(matches!(
+            err(export().size(0., 240.)),
+            ExportError::InvalidSize(_)
+        ));
+        assert!(matches!(err(export().fps(0)), ExportError::InvalidFps(0)));
+        assert!(matches!(
+            err(export().speed(f64::NAN)),
+            ExportError::InvalidSpeed(_)
+        ));
+        assert!(matches!(
+            export().validate(),
+            Err(ExportError::MissingFramesDir)
+        ));
+    }
+
+    #[test]
+    fn test_animation_export_writes_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let expected = export().frame_count();
+        assert!(expected > 2);
+        let reported = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let counter = reported.clone();
+        let frames = export()
+            .frames_dir(dir.path())
+            .on_progress(move |progress| {
+                assert_eq!(progress.total, expected);
+                counter.store(progress.frame, Ordering::Relaxed);
+            })
+            .render()
+            .unwrap();
+        assert_eq!(frames, expected);
+        assert_eq!(reported.load(Ordering::Relaxed), expected);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count() as u64, frames);
+        let first = dir.path().join("quick-look-frame-0001.png");
+        let last = dir.path().join(format!("quick-look-frame-{frames:04}.png"));
+        assert!(first.exists() && last.exists());
+        let decoder = png::Decoder::new(io::Cursor::new(fs::read(last).unwrap()));
+        let info = decoder.read_info().unwrap();
+        assert_eq!((info.info().width, info.info().height), (320, 240));
+
+        let frames = export()
+            .frames_dir(dir.path().join("raw"))
+            .render_with("json", |geometry, _| Ok(serde_json::to_vec(geometry)?))
+            .unwrap();
+        assert_eq!(frames, expected);
+        let first = dir.path().join("raw/quick-look-frame-0001.json");
+        let geometry: Vec<BarGeometry> = serde_json::from_slice(&fs::read(first).unwrap()).unwrap();
+        assert_eq!(geometry.len(), 2);
+    }
+
+    #[test]
+    fn test_animation_export_uses_profile_and_secondary() {
+        let calm = AnimationProfile {
+            base: ProfileBase::Calm,
+            ..AnimationProfile::default()
+        };
+        assert!(export().profile(calm).frame_count() > export().frame_count());
+
+        let dir = tempfile::tempdir().unwrap();
+        let secondary = vec![
+            Thing::new("Second", 1.),
+            Thing::new("Hour", 3600.),
+            Thing::new("Year", 31556952.),
+        ];
+        export()
+            .secondary(Some(secondary))
+            .frames_dir(dir.path())
+            .render_with("json", |geometry, _| {
+                assert_eq!(geometry.len(), 5);
+                let sides = geometry.iter().filter(|bar| bar.side == Side::Secondary);
+                assert_eq!(sides.count(), 3);
+                Ok(Vec::new())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_animation_export_ignores_the_ui_scale() {
+        let screen = Settings::default().with_ui_scale(2.);
+        assert_eq!(export().settings(screen).settings.ui_scale, 1.);
+        let export = export().settings(screen.with_export_scale(1.5));
+        assert_eq!(export.settings.ui_scale, 1.5);
+    }
+
+    #[test]
+    fn test_rasterize_blends_bars_over_the_background() {
+        let size = Size::new(4., 3.);
+        let bar = |side, alpha| BarGeometry {
+            index: 0,
+            side,
+            name: String::new(),
+            value: ENumber::from(1.),
+            x: 1.,
+            left: 0.,
+            right: 2.,
+            top: 1.,
+            bottom: 3.,
+            alpha,
+            clamped: false,
+        };
+        let background = Viewport::BACKGROUND_COLOR.to_rgba8().to_u8_array();
+        let pixel = |pixels: &[u8], x: usize, y: usize| -> [u8; 4] {
+            pixels[(y * 4 + x) * 4..][..4].try_into().unwrap()
+        };
+        let pixels = rasterize(&[bar(Side::Primary, 1.)], size);
+        assert_eq!(pixels.len(), 4 * 3 * 4);
+        assert_eq!(pixel(&pixels, 0, 0), background);
+        assert_eq!(pixel(&pixels, 2, 2), background);
+        assert_eq!(
+            pixel(&pixels, 1, 2),
+            Thing::BAR_COLOR.to_rgba8().to_u8_array()
+        );
+
+        let faded = rasterize(&[bar(Side::Secondary, 0.5)], size);
+        let blue = Side::Secondary.bar_color().to_rgba8().to_u8_array();
+        let half = (blue[2] as f32 * 0.5 + background[2] as f32 * 0.5).round() as u8;
+        assert_eq!(pixel(&faded, 0, 1)[2], half);
+        assert!(
+            rasterize(&[bar(Side::Primary, 0.)], size)
+                .chunks(4)
+                .all(|p| p == background)
+        );
+    }
+
+    #[test]
+    fn test_run_frames_stops_on_error() {
+        let mut reported = 0;
+        let result = run_frames(
+            10,
+            &AtomicBool::new(false),
+            |frame| match frame {
+                2 => Err(io::Error::other("disk full")),
+                _ => Ok(1),
+            },
+            |_| reported += 1,
+        );
+        assert_eq!(result.unwrap_err().to_string(), "disk full");
+        assert_eq!(reported, 3);
+    }
+
+    #[test]
+    fn test_run_frames_reports_failures() {
+        let mut messages = Vec::new();
+        let _ = run_frames(
+            10,
+            &AtomicBool::new(false),
+            |frame| match frame {
+                2 => Err(io::Error::other("disk full")),
+                _ => Ok(1),
+            },
+            |message| messages.push(message),
+        );
+        assert_eq!(
+            messages.last(),
+            Some(&ExportMessage::Failed(
+                "Export failed at frame 3/10: disk full".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_export_job_ends_with_one_final_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let job = ExportJob::new(export().frames_dir(dir.path()));
+        let messages = sender.clone();
+        let handle = job
+            .spawn(move |message| messages.send(message).unwrap())
+            .unwrap();
+        assert!(job.spawn(|_| {}).is_none());
+        let frames = handle.join().unwrap().unwrap();
+        let received = receiver.try_iter().collect::<Vec<_>>();
+        assert_eq!(received.len() as u64, frames + 1);
+        assert!(
+            matches!(received.last(), Some(ExportMessage::Done(summary)) if !summary.cancelled)
+        );
+
+        let job = ExportJob::new(export().frames_dir(dir.path().join("cancelled")));
+        job.cancel();
+        assert!(job.is_cancelled());
+        let messages = sender.clone();
+        let frames = job
+            .spawn(move |message| messages.send(message).unwrap())
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+        assert_eq!(frames, 0);
+        let received = receiver.try_iter().collect::<Vec<_>>();
+        assert!(matches!(received[..], [ExportMessage::Done(summary)] if summary.cancelled));
+
+        // a file where the frames directory should go
+        let blocked = dir.path().join("blocked");
+        fs::write(&blocked, "").unwrap();
+        let job = ExportJob::new(export().frames_dir(&blocked));
+        let result = job
+            .spawn(move |message| sender.send(message).unwrap())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert!(result.is_err());
+        let received = receiver.try_iter().collect::<Vec<_>>();
+        assert!(
+            matches!(&received[..], [ExportMessage::Failed(message)] if message.starts_with("Could not create"))
+        );
+    }
+}
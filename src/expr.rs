@@ -0,0 +1,298 @@
+use crate::math::ENumber;
+use crate::units::{DAY, GIGA, HOUR, KILO, MEGA, MINUTE, PETA, TERA, YEAR};
+
+pub const CONSTANTS: [(&str, f64); 10] = [
+    ("SECOND", 1.),
+    ("MINUTE", MINUTE),
+    ("HOUR", HOUR),
+    ("DAY", DAY),
+    ("YEAR", YEAR),
+    ("KILO", KILO),
+    ("MEGA", MEGA),
+    ("GIGA", GIGA),
+    ("TERA", TERA),
+    ("PETA", PETA),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ExprError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+pub fn constant(name: &str) -> Option<f64> {
+    CONSTANTS
+        .iter()
+        .find(|(constant, _)| constant.eq_ignore_ascii_case(name))
+        .map(|&(_, value)| value)
+}
+
+pub fn evaluate(text: &str) -> Result<ENumber, ExprError> {
+    let mut parser = Parser { text, pos: 0 };
+    let value = parser.expr()?;
+    match parser.peek() {
+        Some(')') => Err(parser.error(parser.pos, "unmatched ')'")),
+        Some(c) => Err(parser.error(parser.pos, format!("unexpected '{c}'"))),
+        None => Ok(value),
+    }
+}
+
+fn pow(base: ENumber, exponent: ENumber) -> Result<ENumber, &'static str> {
+    let exponent = exponent.collapse().ok_or("exponent is too large")?;
+    if let Some(result) = base.collapse().map(|base| base.powf(exponent))
+        && result.is_finite()
+        && result != 0.
+    {
+        return Ok(ENumber::from(result));
+    }
+    if base.significand() == 0. {
+        return match exponent > 0. {
+            true => Ok(base),
+            false => Err("zero cannot be raised to a non-positive power"),
+        };
+    }
+    let (sign, log) = base.erect();
+    if sign < 0. && exponent.fract() != 0. {
+        return Err("a negative base needs a whole exponent");
+    }
+    let log = log * exponent;
+    let sign = if sign < 0. && exponent % 2. != 0. {
+        -1.
+    } else {
+        1.
+    };
+    Ok(ENumber::normalize(
+        sign * 10_f64.powf(log.fract()),
+        log.trunc(),
+    ))
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, pos: usize, message: impl Into<String>) -> ExprError {
+        ExprError {
+            column: self.text[..pos].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> Option<usize> {
+        (self.peek() == Some(c)).then(|| {
+            self.pos += c.len_utf8();
+            self.pos - c.len_utf8()
+        })
+    }
+
+    fn expr(&mut self) -> Result<ENumber, ExprError> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+').is_some() {
+                value = value + self.term()?;
+            } else if self.eat('-').is_some() {
+                value = value - self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<ENumber, ExprError> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*').is_some() {
+                value = value * self.unary()?;
+            } else if let Some(op) = self.eat('/') {
+                let divisor = self.unary()?;
+                if divisor.significand() == 0. {
+                    return Err(self.error(op, "division by zero"));
+                }
+                value = value / divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<ENumber, ExprError> {
+        if self.eat('-').is_some() {
+            Ok(-self.unary()?)
+        } else if self.eat('+').is_some() {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<ENumber, ExprError> {
+        let base = self.primary()?;
+        let Some(op) = self.eat('^') else {
+            return Ok(base);
+        };
+        let exponent = self.unary()?;
+        pow(base, exponent).map_err(|message| self.error(op, message))
+    }
+
+    fn primary(&mut self) -> Result<ENumber, ExprError> {
+        let start = self.pos;
+        match self.peek() {
+            Some('(') => {
+                let open = self.eat('(').unwrap_or(start);
+                let value = self.expr()?;
+                self.eat(')')
+                    .ok_or_else(|| self.error(open, "unclosed '('"))?;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let start = self.pos;
+                let len = self.text[start..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(self.text.len() - start);
+                self.pos += len;
+                let name = &self.text[start..self.pos];
+                constant(name)
+                    .map(ENumber::from)
+                    .ok_or_else(|| self.error(start, format!("unknown name \"{name}\"")))
+            }
+            Some(c) => Err(self.error(self.pos, format!("unexpected '{c}'"))),
+            None => Err(self.error(self.pos, "expected a number")),
+        }
+    }
+
+    fn number(&mut self) -> Result<ENumber, ExprError> {
+        let start = self.pos;
+        let bytes = self.text.as_bytes();
+        let mut end = start;
+        while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+            end += 1;
+        }
+        if end < bytes.len() && matches!(bytes[end], b'e' | b'E') {
+            let mut exponent = end + 1;
+            if exponent < bytes.len() && matches!(bytes[exponent], b'+' | b'-') {
+                exponent += 1;
+            }
+            if exponent < bytes.len() && bytes[exponent].is_ascii_digit() {
+                end = exponent;
+                while end < bytes.len() && bytes[end].is_ascii_digit() {
+                    end += 1;
+                }
+            }
+        }
+        self.pos = end;
+        let number = &self.text[start..end];
+        number
+            .parse::<ENumber>()
+            .map_err(|_| self.error(start, format!("invalid number \"{number}\"")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_table() {
+        let cases: [(&str, ENumber); 24] = [
+            ("42", ENumber::from(42.)),
+            ("  7.5  ", ENumber::from(7.5)),
+            (".5", ENumber::from(0.5)),
+            ("1e3", ENumber::from(1000.)),
+            ("2.3e-23", ENumber::new(2.3, -23)),
+            ("8*MINUTE+20", ENumber::from(500.)),
+            ("8 * minute + 20", ENumber::from(500.)),
+            ("13.8e9*YEAR", ENumber::from(13.8e9) * YEAR),
+            ("2^10 * DAY", ENumber::from(1024. * DAY)),
+            (
+                "3600*24*365.25*1e9",
+                ENumber::from(3600. * 24. * 365.25 * 1e9),
+            ),
+            ("1 + 2 * 3", ENumber::from(7.)),
+            ("(1 + 2) * 3", ENumber::from(9.)),
+            ("10 - 4 - 3", ENumber::from(3.)),
+            ("100 / 10 / 5", ENumber::from(2.)),
+            ("2 ^ 3 ^ 2", ENumber::from(512.)),
+            ("-2 ^ 2", ENumber::from(-4.)),
+            ("2 ^ -1", ENumber::from(0.5)),
+            ("(-2) ^ 3", ENumber::from(-8.)),
+            ("--3", ENumber::from(3.)),
+            ("+3 - -3", ENumber::from(6.)),
+            ("((((1))))", ENumber::from(1.)),
+            ("1e500*YEAR", ENumber::new(1., 500) * YEAR),
+            ("10^400 / 10^399", ENumber::from(10.)),
+            ("KILO*MEGA*GIGA*TERA*PETA", ENumber::new(1., 45)),
+        ];
+        for (text, expected) in cases {
+            let value = evaluate(text).unwrap_or_else(|err| panic!("{text}: {err}"));
+            assert!(value.relative_eq(&expected, 1e-12), "{text}: {value}");
+        }
+    }
+
+    #[test]
+    fn test_evaluate_huge_powers() {
+        let value = evaluate("10^500").unwrap();
+        assert!(value.relative_eq(&ENumber::new(1., 500), 1e-9));
+        let value = evaluate("(-10)^401").unwrap();
+        assert!(value.relative_eq(&ENumber::new(-1., 401), 1e-9));
+        let value = evaluate("2^0.5").unwrap();
+        assert!(value.relative_eq(&ENumber::from(2_f64.sqrt()), 1e-12));
+        assert_eq!(evaluate("0^2"), Ok(ENumber::from(0.)));
+    }
+
+    #[test]
+    fn test_evaluate_error_columns() {
+        let cases = [
+            ("", 1, "expected a number"),
+            ("1 +", 4, "expected a number"),
+            ("2 * (3 + 4", 5, "unclosed '('"),
+            ("2 * 3)", 6, "unmatched ')'"),
+            ("5 / (2 - 2)", 3, "division by zero"),
+            ("3 * WEEKS", 5, "unknown name \"WEEKS\""),
+            ("1.2.3 + 1", 1, "invalid number \"1.2.3\""),
+            ("4 % 2", 3, "unexpected '%'"),
+            ("2 DAY", 3, "unexpected 'D'"),
+            ("(-8) ^ 0.5", 6, "a negative base needs a whole exponent"),
+            ("0 ^ -1", 3, "zero cannot be raised to a non-positive power"),
+            ("2 ^ 1e400", 3, "exponent is too large"),
+            ("µs", 1, "unknown name \"µs\""),
+            ("µ + ", 1, "unknown name \"µ\""),
+        ];
+        for (text, column, message) in cases {
+            let err = evaluate(text).unwrap_err();
+            assert_eq!(
+                (err.column, err.message.as_str()),
+                (column, message),
+                "{text}"
+            );
+        }
+        assert_eq!(
+            evaluate("1 +").unwrap_err().to_string(),
+            "expected a number at column 4"
+        );
+    }
+
+    #[test]
+    fn test_constants_are_case_insensitive() {
+        assert_eq!(constant("year"), Some(YEAR));
+        assert_eq!(constant("Hour"), Some(HOUR));
+        assert_eq!(constant("fortnight"), None);
+        assert_eq!(evaluate("second"), Ok(ENumber::from(1.)));
+    }
+}
@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::Path;
+
+use crate::dataset::Dataset;
+use crate::error::StateError;
+use crate::thing::Thing;
+use crate::units::{ParseTimeScaleError, TimeScale};
+
+#[cfg(feature = "xlsx")]
+pub mod spreadsheet;
+pub mod text;
+
+pub fn parse_value(text: &str) -> Result<TimeScale, ParseTimeScaleError> {
+    text.parse::<TimeScale>()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Row {}: {}", self.row, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub things: Vec<Thing>,
+    pub errors: Vec<RowError>,
+}
+
+impl ImportReport {
+    pub fn push_row(&mut self, row: usize, name: &str, value: &str) {
+        match parse_value(value) {
+            Ok(value) => self.things.push(Thing::new(name, value)),
+            Err(ParseTimeScaleError::Ambiguous(reason)) => self.errors.push(RowError {
+                row,
+                message: format!("ambiguous value \"{value}\" ({reason})"),
+            }),
+            Err(err) => self.errors.push(RowError {
+                row,
+                message: format!("invalid value \"{value}\" ({err})"),
+            }),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Imported {} rows, {} errors",
+            self.things.len(),
+            self.errors.len()
+        )
+    }
+}
+
+pub fn from_json(text: &str) -> serde_json::Result<ImportReport> {
+    Ok(ImportReport {
+        things: Dataset::from_json(text)?.things,
+        errors: Vec::new(),
+    })
+}
+
+// A first row whose value doesn't even start like a number is a header;
+// anything else is data and gets reported if it fails to parse.
+fn looks_numeric(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '.' | '-' | '+'))
+}
+
+pub fn from_csv(text: &str) -> ImportReport {
+    let mut report = ImportReport::default();
+    for (i, line) in text.lines().enumerate() {
+        let row = i + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some((name, value)) = line.split_once(',') else {
+            report.errors.push(RowError {
+                row,
+                message: "missing value".to_string(),
+            });
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+        if row == 1 && !looks_numeric(value) {
+            continue;
+        }
+        report.push_row(row, name, value);
+    }
+    report
+}
+
+fn read(path: &Path) -> Result<String, StateError> {
+    fs::read_to_string(path).map_err(|err| StateError::reading(path, err))
+}
+
+pub fn load_file(path: impl AsRef<Path>) -> Result<ImportReport, StateError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("json") => from_json(&read(path)?).map_err(|source| StateError::Format {
+            which: "JSON",
+            source,
+        }),
+        Some("csv") => Ok(from_csv(&read(path)?)),
+        #[cfg(feature = "xlsx")]
+        Some("xlsx" | "xlsm" | "xls" | "ods") => {
+            let sheet = spreadsheet::Sheet::open(path)?;
+            let (name_col, value_col) = sheet.detect_columns().unwrap_or((0, 1));
+            Ok(sheet.import(name_col, value_col))
+        }
+        _ => Err(StateError::UnsupportedFormat(path.to_path_buf())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::{DAY, YEAR};
+
+    #[test]
+    fn test_csv_mixed_units() {
+        let report = from_csv(
+            "name, value\n\
+             Blink, 0.1\n\
+             Week, 7 d\n\
+             Sun lifespan, 10 Gy\n\
+             proton decay, 1e34 y\n\
+             Gestation, 9 M\n\
+             Marathon, 1,5 h\n\
+             Nap\n",
+        );
+        let values = report
+            .things
+            .iter()
+            .map(|thing| (thing.name.as_str(), thing.value.inner()))
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[0], ("Blink", 0.1.into()));
+        assert_eq!(values[1], ("Week", (7. * DAY).into()));
+        assert_eq!(values[2].1.exponent(), (1e10 * YEAR).log10().floor());
+        assert_eq!(values[3].1.exponent(), (1e34 * YEAR).log10().floor());
+
+        let rows = report.errors.iter().map(|err| err.row).collect::<Vec<_>>();
+        assert_eq!(rows, [6, 7, 8]);
+        assert!(
+            report.errors[0]
+                .message
+                .starts_with("ambiguous value \"9 M\"")
+        );
+        assert!(
+            report.errors[1]
+                .message
+                .starts_with("ambiguous value \"1,5 h\"")
+        );
+        assert_eq!(report.errors[2].message, "missing value");
+    }
+
+    #[test]
+    fn test_csv_first_row_is_data_when_numeric() {
+        let report = from_csv("Blink, 0.1\nWeek, 7 d\n");
+        assert_eq!(report.things.len(), 2);
+        assert!(report.errors.is_empty());
+
+        let report = from_csv("Gestation, 9 M\nWeek, 7 d\n");
+        assert_eq!(report.things.len(), 1);
+        assert_eq!(report.errors[0].row, 1);
+        assert!(report.errors[0].message.starts_with("ambiguous value"));
+
+        let report = from_csv("name, duration (s)\nWeek, 7 d\n");
+        assert_eq!(report.things.len(), 1);
+        assert!(report.errors.is_empty());
+    }
+}
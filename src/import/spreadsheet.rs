@@ -0,0 +1,307 @@
+use std::path::Path;
+
+use calamine::{Data, Reader, open_workbook_auto};
+use xilem::WidgetView;
+use xilem::core::Edit;
+use xilem::palette::css;
+use xilem::style::Style;
+use xilem::view::{
+    CrossAxisAlignment, FlexExt, flex_col, flex_row, label, text_button, text_input,
+};
+
+use crate::import::{ImportReport, RowError, parse_value};
+use crate::thing::Thing;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Empty,
+    Text(String),
+    Number(f64),
+}
+
+impl From<&Data> for Cell {
+    fn from(value: &Data) -> Self {
+        match value {
+            Data::Empty => Cell::Empty,
+            Data::Int(i) => Cell::Number(*i as f64),
+            Data::Float(f) => Cell::Number(*f),
+            other => Cell::Text(other.to_string()),
+        }
+    }
+}
+
+impl Cell {
+    fn text(&self) -> String {
+        match self {
+            Cell::Empty => String::new(),
+            Cell::Text(text) => text.trim().to_string(),
+            Cell::Number(number) => number.to_string(),
+        }
+    }
+
+    fn is_value(&self) -> bool {
+        match self {
+            Cell::Empty => false,
+            Cell::Text(text) => parse_value(text).is_ok(),
+            Cell::Number(_) => true,
+        }
+    }
+
+    fn is_name(&self) -> bool {
+        matches!(self, Cell::Text(text) if !text.trim().is_empty() && parse_value(text).is_err())
+    }
+}
+
+pub fn column_label(col: usize) -> String {
+    let mut label = String::new();
+    let mut n = col + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        label.insert(0, (b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
+    }
+    label
+}
+
+pub struct Sheet {
+    first_row: usize,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Sheet {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, calamine::Error> {
+        let mut workbook = open_workbook_auto(path)?;
+        let range = workbook
+            .worksheet_range_at(0)
+            .ok_or(calamine::Error::Msg("Workbook has no sheets"))??;
+        let first_row = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+        let rows = range
+            .rows()
+            .map(|row| row.iter().map(Cell::from).collect())
+            .collect();
+        Ok(Self { first_row, rows })
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    fn cell(&self, row: usize, col: usize) -> &Cell {
+        self.rows
+            .get(row)
+            .and_then(|row| row.get(col))
+            .unwrap_or(&Cell::Empty)
+    }
+
+    pub fn detect_columns(&self) -> Option<(usize, usize)> {
+        let counts = (0..self.width())
+            .map(|col| {
+                (0..self.rows.len()).fold((0, 0), |(names, values), row| {
+                    let cell = self.cell(row, col);
+                    (
+                        names + cell.is_name() as usize,
+                        values + cell.is_value() as usize,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        let name_col = counts.iter().position(|(names, values)| names > values)?;
+        let value_col = counts
+            .iter()
+            .enumerate()
+            .position(|(col, (names, values))| col != name_col && values > names)?;
+        Some((name_col, value_col))
+    }
+
+    fn has_header(&self, value_col: usize) -> bool {
+        self.cell(0, value_col).is_name()
+    }
+
+    fn data_rows(&self, value_col: usize) -> impl Iterator<Item = usize> + use<> {
+        (self.has_header(value_col) as usize)..self.rows.len()
+    }
+
+    pub fn preview(
+        &self,
+        name_col: usize,
+        value_col: usize,
+        count: usize,
+    ) -> Vec<(String, String)> {
+        self.data_rows(value_col)
+            .take(count)
+            .map(|row| {
+                (
+                    self.cell(row, name_col).text(),
+                    self.cell(row, value_col).text(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn import(&self, name_col: usize, value_col: usize) -> ImportReport {
+        let mut report = ImportReport::default();
+        for row in self.data_rows(value_col) {
+            let number = self.first_row + row + 1;
+            let name = self.cell(row, name_col).text();
+            let value = self.cell(row, value_col).text();
+            match (name.is_empty(), value.is_empty()) {
+                (true, true) => (),
+                (true, false) => report.errors.push(RowError {
+                    row: number,
+                    message: "missing name".to_string(),
+                }),
+                (false, true) => report.errors.push(RowError {
+                    row: number,
+                    message: "missing value".to_string(),
+                }),
+                (false, false) => report.push_row(number, &name, &value),
+            }
+        }
+        report
+    }
+}
+
+#[derive(Default)]
+pub struct SpreadsheetImport {
+    pub path: String,
+    pub sheet: Option<Sheet>,
+    pub name_col: usize,
+    pub value_col: usize,
+    pub message: Option<String>,
+    pub errors: Vec<RowError>,
+}
+
+impl SpreadsheetImport {
+    pub const PREVIEW_ROWS: usize = 5;
+
+    fn open(&mut self) {
+        self.errors.clear();
+        match Sheet::open(self.path.trim()) {
+            Ok(sheet) => {
+                let (name_col, value_col) = sheet.detect_columns().unwrap_or((0, 1));
+                self.name_col = name_col;
+                self.value_col = value_col;
+                self.message = None;
+                self.sheet = Some(sheet);
+            }
+            Err(err) => {
+                self.message = Some(format!("Could not open spreadsheet: {err}"));
+                self.sheet = None;
+            }
+        }
+    }
+
+    fn append(&mut self) -> Option<Vec<Thing>> {
+        let report = self.sheet.take()?.import(self.name_col, self.value_col);
+        self.message = Some(report.summary());
+        self.errors = report.errors;
+        Some(report.things)
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>, Option<Vec<Thing>>> + use<> {
+        let open = flex_row((
+            text_input(self.path.clone(), |state: &mut Self, value| {
+                state.path = value;
+                None
+            })
+            .placeholder("path to .xlsx or .ods")
+            .flex(1.),
+            text_button("Open spreadsheet", |state: &mut Self| {
+                state.open();
+                None
+            }),
+        ));
+        let preview = self.sheet.as_ref().map(|sheet| {
+            let width = sheet.width().max(1);
+            let rows = sheet
+                .preview(self.name_col, self.value_col, Self::PREVIEW_ROWS)
+                .into_iter()
+                .map(|(name, value)| label(format!("{name} → {value}")).color(Thing::VALUE_COLOR))
+                .collect::<Vec<_>>();
+            let columns = flex_row((
+                label("Name column:").color(Thing::NAME_COLOR),
+                text_button(column_label(self.name_col), move |state: &mut Self| {
+                    state.name_col = (state.name_col + 1) % width;
+                    None
+                }),
+                label("Value column:").color(Thing::NAME_COLOR),
+                text_button(column_label(self.value_col), move |state: &mut Self| {
+                    state.value_col = (state.value_col + 1) % width;
+                    None
+                }),
+                text_button("Append rows", |state: &mut Self| state.append()),
+                text_button("Cancel", |state: &mut Self| {
+                    state.sheet = None;
+                    None
+                }),
+            ));
+            flex_col((columns, rows)).cross_axis_alignment(CrossAxisAlignment::Start)
+        });
+        let message = self
+            .message
+            .clone()
+            .map(|message| label(message).color(Thing::NAME_COLOR));
+        let errors = self
+            .errors
+            .iter()
+            .map(|err| label(err.to_string()).color(css::ORANGE))
+            .collect::<Vec<_>>();
+        flex_col((open, preview, message, errors)).cross_axis_alignment(CrossAxisAlignment::Start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/things.xlsx");
+
+    #[test]
+    fn test_column_label() {
+        assert_eq!(column_label(0), "A");
+        assert_eq!(column_label(25), "Z");
+        assert_eq!(column_label(26), "AA");
+        assert_eq!(column_label(27), "AB");
+    }
+
+    #[test]
+    fn test_sheet_detect_columns() {
+        let sheet = Sheet::open(FIXTURE).unwrap();
+        assert_eq!(sheet.detect_columns(), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_sheet_import() {
+        let sheet = Sheet::open(FIXTURE).unwrap();
+        let report = sheet.import(0, 1);
+        let names = report
+            .things
+            .iter()
+            .map(|thing| thing.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "Hydrogen-7 half-life",
+                "Blink of an eye",
+                "Week",
+                "Age of the universe",
+                "Heat death"
+            ]
+        );
+        assert_eq!(report.things[0].value.inner().exponent(), -23.);
+        assert!((report.things[0].value.inner().significand() - 2.3).abs() < 1e-9);
+        assert_eq!(report.things[2].value.to_string(), "7 d");
+        assert_eq!(report.things[4].value.inner().exponent(), 1000.);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].row, 6);
+    }
+
+    #[test]
+    fn test_sheet_import_swapped_columns() {
+        let sheet = Sheet::open(FIXTURE).unwrap();
+        let report = sheet.import(1, 0);
+        assert!(report.things.is_empty());
+        assert_eq!(report.errors.len(), 6);
+    }
+}
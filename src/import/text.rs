@@ -0,0 +1,371 @@
+use xilem::core::Edit;
+use xilem::palette::css;
+use xilem::style::Style;
+use xilem::view::{
+    CrossAxisAlignment, FlexExt, flex_col, flex_row, label, text_button, text_input,
+};
+use xilem::{Color, WidgetView};
+
+use crate::import::{RowError, parse_value};
+use crate::thing::Thing;
+use crate::units::{ParseTimeScaleError, TimeScale, split_unit};
+
+const BULLETS: [&str; 3] = ["- ", "* ", "• "];
+const DASHES: [&str; 3] = [" - ", " – ", " — "];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confidence {
+    High,
+    Low,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedLine {
+    pub name: String,
+    pub value: TimeScale,
+    pub confidence: Confidence,
+}
+
+fn separators(line: &str) -> Vec<(usize, usize)> {
+    let find = |pattern: &str| {
+        line.match_indices(pattern)
+            .map(|(i, sep)| (i, sep.len()))
+            .collect::<Vec<_>>()
+    };
+    let tabs = find("\t");
+    if !tabs.is_empty() {
+        return tabs;
+    }
+    let colons = find(":");
+    if !colons.is_empty() {
+        return colons;
+    }
+    let mut dashes = DASHES
+        .iter()
+        .flat_map(|dash| find(dash))
+        .collect::<Vec<_>>();
+    dashes.sort();
+    dashes
+}
+
+pub fn split_line(line: &str) -> Option<(&str, &str, bool)> {
+    let line = BULLETS
+        .iter()
+        .find_map(|bullet| line.trim_start().strip_prefix(bullet))
+        .unwrap_or(line);
+    let separators = separators(line);
+    let &(i, len) = separators.last()?;
+    let (name, value) = (line[..i].trim(), line[i + len..].trim());
+    Some((name, value, separators.len() > 1))
+}
+
+pub fn parse_line(line: &str) -> Result<ParsedLine, String> {
+    let Some((name, value, several)) = split_line(line) else {
+        return Err("no dash, colon or tab between name and value".to_string());
+    };
+    parse_parts(name, value, several)
+}
+
+pub fn parse_quick_add(line: &str) -> Result<ParsedLine, String> {
+    match line.rsplit_once('=') {
+        Some((name, value)) => parse_parts(name.trim(), value.trim(), false),
+        None => parse_line(line),
+    }
+}
+
+fn parse_parts(name: &str, value: &str, several: bool) -> Result<ParsedLine, String> {
+    let (name, value, swapped) = match (parse_value(value), parse_value(name)) {
+        (Err(_), Ok(_)) => (value, name, true),
+        _ => (name, value, false),
+    };
+    if name.is_empty() {
+        return Err("missing name".to_string());
+    }
+    let parsed = parse_value(value).map_err(|err| match err {
+        ParseTimeScaleError::Ambiguous(reason) => {
+            format!("ambiguous value \"{value}\" ({reason})")
+        }
+        err => format!("invalid value \"{value}\" ({err})"),
+    })?;
+    let unitless = split_unit(value).1.is_empty();
+    let confidence = if several || swapped || unitless {
+        Confidence::Low
+    } else {
+        Confidence::High
+    };
+    Ok(ParsedLine {
+        name: name.to_string(),
+        value: parsed,
+        confidence,
+    })
+}
+
+pub fn parse_text(text: &str) -> Vec<Result<ParsedLine, RowError>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            parse_line(line).map_err(|message| RowError {
+                row: i + 1,
+                message,
+            })
+        })
+        .collect()
+}
+
+#[derive(Default)]
+pub struct TextImport {
+    pub text: String,
+    pub message: Option<String>,
+}
+
+impl TextImport {
+    pub const LOW_CONFIDENCE_COLOR: Color = css::YELLOW;
+
+    fn append(&mut self) -> Option<Vec<Thing>> {
+        let lines = parse_text(&self.text);
+        let things = lines
+            .iter()
+            .flatten()
+            .map(|line| Thing::new(&line.name, line.value.clone()))
+            .collect::<Vec<_>>();
+        if things.is_empty() {
+            return None;
+        }
+        self.text = lines
+            .iter()
+            .zip(self.text.lines().filter(|line| !line.trim().is_empty()))
+            .filter(|(line, _)| line.is_err())
+            .map(|(_, text)| text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.message = Some(format!(
+            "Imported {} lines, {} flagged",
+            things.len(),
+            lines.len() - things.len()
+        ));
+        Some(things)
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>, Option<Vec<Thing>>> + use<> {
+        let lines = parse_text(&self.text);
+        let count = lines.iter().flatten().count();
+        let input = flex_row((
+            text_input(self.text.clone(), |state: &mut Self, value| {
+                state.text = value;
+                state.message = None;
+                None
+            })
+            .placeholder("Paste a list, one \"name - value\" per line")
+            .flex(1.),
+            (count > 0).then(|| {
+                text_button(format!("Append {count} lines"), |state: &mut Self| {
+                    state.append()
+                })
+            }),
+        ));
+        let preview = lines
+            .into_iter()
+            .map(|line| match line {
+                Ok(line) => {
+                    let (suffix, color) = match line.confidence {
+                        Confidence::High => ("", Thing::VALUE_COLOR),
+                        Confidence::Low => ("  (check)", Self::LOW_CONFIDENCE_COLOR),
+                    };
+                    label(format!("{} → {}{suffix}", line.name, line.value)).color(color)
+                }
+                Err(err) => label(err.to_string()).color(css::ORANGE),
+            })
+            .collect::<Vec<_>>();
+        let message = self
+            .message
+            .clone()
+            .map(|message| label(message).color(Thing::NAME_COLOR));
+        flex_col((input, preview, message)).cross_axis_alignment(CrossAxisAlignment::Start)
+    }
+}
+
+#[derive(Default)]
+pub struct QuickAdd {
+    pub text: String,
+    pub error: Option<String>,
+}
+
+impl QuickAdd {
+    fn submit(&mut self, text: String) -> Option<Thing> {
+        match parse_quick_add(&text) {
+            Ok(line) => {
+                self.text.clear();
+                self.error = None;
+                Some(Thing::new(&line.name, line.value))
+            }
+            Err(err) => {
+                self.text = text;
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>, Option<Thing>> + use<> {
+        let error = self
+            .error
+            .clone()
+            .map(|error| label(error).color(css::ORANGE));
+        flex_col((
+            text_input(self.text.clone(), |state: &mut Self, value| {
+                state.text = value;
+                state.error = None;
+                None
+            })
+            .placeholder("Quick add: name = value, then press Enter")
+            .on_enter(|state: &mut Self, value| state.submit(value)),
+            error,
+        ))
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ENumber;
+    use crate::units::{GIGA, MEGA, YEAR};
+
+    fn parsed(line: &str) -> ParsedLine {
+        parse_line(line).unwrap_or_else(|err| panic!("{line}: {err}"))
+    }
+
+    #[test]
+    fn test_split_line() {
+        assert_eq!(
+            split_line("Blink of an eye - 0.3 s"),
+            Some(("Blink of an eye", "0.3 s", false))
+        );
+        assert_eq!(
+            split_line("Age of the universe: 13.8 billion years"),
+            Some(("Age of the universe", "13.8 billion years", false))
+        );
+        assert_eq!(split_line("Week\t7 d"), Some(("Week", "7 d", false)));
+        assert_eq!(
+            split_line("Note: ratio: 1:2\t3 s"),
+            Some(("Note: ratio: 1:2", "3 s", false))
+        );
+        assert_eq!(
+            split_line("Half-life of hydrogen-7 – 2.3e-23 s"),
+            Some(("Half-life of hydrogen-7", "2.3e-23 s", false))
+        );
+        assert_eq!(
+            split_line("Sun - core - 1e5 y"),
+            Some(("Sun - core", "1e5 y", true))
+        );
+        assert_eq!(split_line("- Lunch: 1 h"), Some(("Lunch", "1 h", false)));
+        assert_eq!(split_line("Half-life 2.3e-23 s"), None);
+        assert_eq!(split_line("Blink 0.3 s"), None);
+    }
+
+    #[test]
+    fn test_parse_line_confidence() {
+        let line = parsed("Age of the universe: 13.8 billion years");
+        assert_eq!(line.name, "Age of the universe");
+        assert_eq!(line.value.inner(), ENumber::from(13.8) * GIGA * YEAR);
+        assert_eq!(line.confidence, Confidence::High);
+
+        let line = parsed("Hydrogen atom — 2 million seconds");
+        assert_eq!(line.value.inner(), ENumber::from(2. * MEGA));
+        assert_eq!(line.confidence, Confidence::High);
+
+        assert_eq!(parsed("Lunch break - 2 hours").confidence, Confidence::High);
+        assert_eq!(parsed("Blink - 0.3").confidence, Confidence::Low);
+        assert_eq!(parsed("A - B - 3 s").confidence, Confidence::Low);
+
+        let line = parsed("0.3 s - Blink of an eye");
+        assert_eq!(line.name, "Blink of an eye");
+        assert_eq!(line.confidence, Confidence::Low);
+    }
+
+    #[test]
+    fn test_parse_line_flags_instead_of_guessing() {
+        let err = |line: &str| parse_line(line).unwrap_err();
+        assert!(err("Gestation: 9 months").starts_with("ambiguous"));
+        assert!(err("Debt: 4 billion").starts_with("ambiguous"));
+        assert!(err("Someday - soon").starts_with("invalid"));
+        assert!(err("Blink 0.3 s").contains("no dash"));
+        assert!(err(": 3 s").contains("missing name"));
+        assert!(err("Half-life 2.3e-23 s").contains("no dash"));
+    }
+
+    #[test]
+    fn test_parse_text_rows() {
+        let lines = parse_text(
+            "Blink of an eye - 0.3 s\n\
+             \n\
+             Gestation: 9 months\n\
+             Week\t7 d",
+        );
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].is_ok());
+        assert_eq!(lines[1].as_ref().unwrap_err().row, 3);
+        assert!(lines[2].is_ok());
+    }
+
+    #[test]
+    fn test_parse_quick_add() {
+        let line = parse_quick_add("Blink of an eye = 0.3 s").unwrap();
+        assert_eq!(line.name, "Blink of an eye");
+        assert_eq!(line.value.inner(), ENumber::from(0.3));
+
+        let line = parse_quick_add("E=mc² demo = 2 hours").unwrap();
+        assert_eq!(line.name, "E=mc² demo");
+        assert_eq!(line.value.inner(), ENumber::from(7200.));
+
+        let line = parse_quick_add("Age of the universe = 13.8 billion years").unwrap();
+        assert_eq!(line.value.inner(), ENumber::from(13.8) * GIGA * YEAR);
+
+        let line = parse_quick_add("Week: 7 days").unwrap();
+        assert_eq!(line.name, "Week");
+        assert!(
+            parse_quick_add("Someday = soon")
+                .unwrap_err()
+                .starts_with("invalid")
+        );
+        assert!(
+            parse_quick_add(" = 3 s")
+                .unwrap_err()
+                .contains("missing name")
+        );
+    }
+
+    #[test]
+    fn test_quick_add_clears_only_on_success() {
+        let mut quick_add = QuickAdd::default();
+        assert!(
+            quick_add
+                .submit("Gestation = 9 months".to_string())
+                .is_none()
+        );
+        assert_eq!(quick_add.text, "Gestation = 9 months");
+        assert!(quick_add.error.as_deref().unwrap().starts_with("ambiguous"));
+
+        let thing = quick_add.submit("Lunch = 1 h".to_string()).unwrap();
+        assert_eq!(thing.name, "Lunch");
+        assert_eq!(quick_add.text, "");
+        assert_eq!(quick_add.error, None);
+    }
+
+    #[test]
+    fn test_append_keeps_flagged_lines() {
+        let mut import = TextImport {
+            text: "Blink - 0.3 s\nGestation: 9 months\nWeek\t7 d".to_string(),
+            message: None,
+        };
+        let things = import.append().unwrap();
+        assert_eq!(things.len(), 2);
+        assert_eq!(import.text, "Gestation: 9 months");
+        assert_eq!(
+            import.message.as_deref(),
+            Some("Imported 2 lines, 1 flagged")
+        );
+        assert!(import.append().is_none());
+    }
+}
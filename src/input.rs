@@ -0,0 +1,405 @@
+use xilem::vello::kurbo::{Point, Size};
+
+use crate::comparison::Side;
+use crate::units::TimeScale;
+use crate::viewport::Viewport;
+use crate::{State, Tab};
+
+pub mod pointer;
+
+// Pointer events in canvas coordinates, tagged with the pane they landed in,
+// and key presses with whatever had keyboard focus. Pointer events come from
+// the `pointer::PointerArea` wrapped around the chart.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    PointerMove {
+        pane: Side,
+        point: Point,
+    },
+    PointerLeave,
+    PointerDown {
+        pane: Side,
+        point: Point,
+        size: Size,
+        button: PointerButton,
+    },
+    Key {
+        key: Key,
+        modifiers: Modifiers,
+        focus: Focus,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerButton {
+    Primary,
+    Secondary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Tab,
+    Plus,
+    Minus,
+    Escape,
+    Digit(u8),
+    Z,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+}
+
+// A value editor is the only text field keys act on; typing into any other
+// input must not nudge values or rescale the UI.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Focus {
+    #[default]
+    None,
+    ValueEditor(usize),
+    TextInput,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        ctrl: false,
+    };
+    pub const SHIFT: Self = Self {
+        shift: true,
+        ctrl: false,
+    };
+    pub const CTRL: Self = Self {
+        shift: false,
+        ctrl: true,
+    };
+
+    // Shift moves by whole units, Ctrl by hundredths.
+    pub fn nudge_step(self) -> f64 {
+        match (self.shift, self.ctrl) {
+            (true, _) => TimeScale::NUDGE_STEP * 10.,
+            (false, true) => TimeScale::NUDGE_STEP / 10.,
+            (false, false) => TimeScale::NUDGE_STEP,
+        }
+    }
+}
+
+impl State {
+    pub fn handle_input(&mut self, input: Input) {
+        match input {
+            Input::PointerMove { pane, point } => {
+                // only the pane under the pointer keeps a hover
+                self.clear_hover();
+                self.pane_mut(pane).hover = Some(point);
+            }
+            Input::PointerLeave => self.clear_hover(),
+            Input::PointerDown {
+                pane,
+                point,
+                size,
+                button,
+            } => self.pointer_down(pane, point, size, button),
+            Input::Key {
+                key,
+                modifiers,
+                focus,
+            } => self.key_down(key, modifiers, focus),
+        }
+    }
+
+    fn key_down(&mut self, key: Key, modifiers: Modifiers, focus: Focus) {
+        match (key, modifiers.ctrl) {
+            (Key::Escape, _) => self.viewport.context_menu = None,
+            (Key::Z, true) => self.undo_last(),
+            (Key::Tab, true) => self.set_tab(match modifiers.shift {
+                true => self.tab.previous(),
+                false => self.tab.next(),
+            }),
+            (Key::Digit(number), true) => {
+                if let Some(tab) = Tab::from_number(number as usize) {
+                    self.set_tab(tab);
+                }
+            }
+            _ => match focus {
+                Focus::ValueEditor(index) => self.nudge_value(index, key, modifiers),
+                Focus::TextInput => {}
+                Focus::None => self.chart_key(key, modifiers),
+            },
+        }
+    }
+
+    fn nudge_value(&mut self, index: usize, key: Key, modifiers: Modifiers) {
+        let record_history = self.settings.record_history;
+        let Some(thing) = self.things.get_mut(index).filter(|thing| !thing.live) else {
+            return;
+        };
+        let previous = match key {
+            Key::Up => thing.value.nudge(modifiers.nudge_step()),
+            Key::Down => thing.value.nudge(-modifiers.nudge_step()),
+            Key::PageUp => thing.value.nudge_exponent(1),
+            Key::PageDown => thing.value.nudge_exponent(-1),
+            _ => None,
+        };
+        if let Some(previous) = previous {
+            let action = thing.committed(previous, record_history);
+            self.thing_action(index, action);
+        }
+    }
+
+    fn chart_key(&mut self, key: Key, modifiers: Modifiers) {
+        match key {
+            Key::Plus | Key::Minus => {
+                self.settings = match key {
+                    Key::Plus => self.settings.scaled_up(),
+                    _ => self.settings.scaled_down(),
+                };
+                let _ = self.settings.save();
+            }
+            _ if self.tab != Tab::Preview => {}
+            Key::Tab if modifiers.shift => {
+                self.viewport
+                    .move_focus(&self.things, self.secondary.as_deref(), -1);
+            }
+            Key::Tab | Key::Right | Key::Down => {
+                self.viewport
+                    .move_focus(&self.things, self.secondary.as_deref(), 1);
+            }
+            Key::Left | Key::Up => {
+                self.viewport
+                    .move_focus(&self.things, self.secondary.as_deref(), -1);
+            }
+            _ => {}
+        }
+    }
+
+    fn pointer_down(&mut self, pane: Side, point: Point, size: Size, button: PointerButton) {
+        if self.viewport.context_menu.is_some() {
+            // any click closes the menu, one on an item also runs it
+            if let Some((id, item)) = self.viewport.menu_click(point, self.settings.ui_scale)
+                && pane == Side::Primary
+                && button == PointerButton::Primary
+            {
+                self.context_action(id, item);
+            }
+            return;
+        }
+        if pane == Side::Primary && button == PointerButton::Secondary {
+            let secondary = self.secondary.as_deref().filter(|_| self.split.is_none());
+            let bars = self
+                .viewport
+                .frame_bars((&self.things, secondary), &self.settings, pane);
+            self.viewport.open_context_menu(&bars, point, size);
+        }
+    }
+
+    fn clear_hover(&mut self) {
+        self.viewport.hover = None;
+        if let Some(split) = &mut self.split {
+            split.hover = None;
+        }
+    }
+
+    fn pane_mut(&mut self, pane: Side) -> &mut Viewport {
+        match (pane, self.split.as_mut()) {
+            (Side::Secondary, Some(split)) => split,
+            _ => &mut self.viewport,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xilem::vello::kurbo::{Affine, Vec2};
+
+    use super::*;
+    use crate::math::ENumber;
+    use crate::settings::Settings;
+    use crate::thing::Thing;
+    use crate::viewport::MenuItem;
+
+    fn press(state: &mut State, key: Key, modifiers: Modifiers, focus: Focus) {
+        state.handle_input(Input::Key {
+            key,
+            modifiers,
+            focus,
+        });
+    }
+
+    fn right_click(state: &mut State, point: Point, size: Size) {
+        state.handle_input(Input::PointerDown {
+            pane: Side::Primary,
+            point,
+            size,
+            button: PointerButton::Secondary,
+        });
+    }
+
+    #[test]
+    fn test_pointer_moves_set_the_hover() {
+        let mut state = State::new(
+            vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)],
+            Settings::default(),
+        );
+        state.secondary = Some(vec![Thing::new("Minute", 60.)]);
+        state.set_split(true);
+        let point = Point::new(40., 60.);
+        state.handle_input(Input::PointerMove {
+            pane: Side::Primary,
+            point,
+        });
+        assert_eq!(state.viewport.hover, Some(point));
+        state.handle_input(Input::PointerMove {
+            pane: Side::Secondary,
+            point,
+        });
+        assert_eq!(state.viewport.hover, None);
+        assert_eq!(state.split.as_ref().unwrap().hover, Some(point));
+        state.handle_input(Input::PointerLeave);
+        assert_eq!(state.split.as_ref().unwrap().hover, None);
+    }
+
+    #[test]
+    fn test_right_click_opens_the_menu_and_clicks_run_items() {
+        let things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Minute", 60.),
+            Thing::new("Hour", 3600.),
+        ];
+        let ids = things.iter().map(|thing| thing.id).collect::<Vec<_>>();
+        let mut state = State::new(things, Settings::default());
+        state.viewport.shift = 2.;
+        state.viewport.update_camera();
+        let size = Size::new(800., 600.);
+        let half_size = size.to_vec2() / 2.;
+        let world_camera =
+            Affine::FLIP_Y.then_translate(half_size) * state.viewport.camera.inverse();
+        let bars = state
+            .viewport
+            .frame_bars((&state.things, None), &state.settings, Side::Primary);
+        let position = state.viewport.bar_position(&bars[1], half_size);
+        let inside = world_camera * Point::new(position.x, position.y / 2.);
+
+        right_click(&mut state, inside, size);
+        let menu = state.viewport.context_menu.unwrap();
+        assert_eq!(menu.id, ids[1]);
+        let item = menu.item_rect(1, state.settings.ui_scale).center();
+        state.handle_input(Input::PointerDown {
+            pane: Side::Primary,
+            point: item,
+            size,
+            button: PointerButton::Primary,
+        });
+        assert_eq!(menu.item_at(item, 1.), Some(MenuItem::SetReference));
+        assert_eq!(state.viewport.reference, Some(ids[1]));
+        assert_eq!(state.viewport.context_menu, None);
+
+        // a click away only dismisses
+        right_click(&mut state, inside, size);
+        state.handle_input(Input::PointerDown {
+            pane: Side::Primary,
+            point: inside - Vec2::new(10., 10.),
+            size,
+            button: PointerButton::Primary,
+        });
+        assert_eq!(state.viewport.context_menu, None);
+        assert_eq!(state.viewport.reference, Some(ids[1]));
+
+        right_click(&mut state, Point::new(-1000., 10.), size);
+        assert_eq!(state.viewport.context_menu, None);
+    }
+
+    #[test]
+    fn test_arrow_keys_nudge_the_focused_value() {
+        let mut state = State::new(vec![Thing::new("Blink", 0.3)], Settings::default());
+        let editor = Focus::ValueEditor(0);
+        let value = |state: &State| state.things[0].value.inner();
+        press(&mut state, Key::Up, Modifiers::NONE, editor);
+        assert_eq!(value(&state), ENumber::new(3.1, -1));
+        press(&mut state, Key::Up, Modifiers::SHIFT, editor);
+        assert_eq!(value(&state), ENumber::new(4., -1));
+        press(&mut state, Key::Down, Modifiers::CTRL, editor);
+        assert_eq!(value(&state), ENumber::new(3.99, -1));
+        press(&mut state, Key::PageUp, Modifiers::NONE, editor);
+        assert_eq!(value(&state), ENumber::new(3.99, 0));
+        assert_eq!(state.undo.len(), 4);
+
+        // typing into another field leaves the value alone
+        press(&mut state, Key::Up, Modifiers::NONE, Focus::TextInput);
+        press(&mut state, Key::Plus, Modifiers::NONE, Focus::TextInput);
+        assert_eq!(value(&state), ENumber::new(3.99, 0));
+        assert_eq!(state.settings.ui_scale, 1.);
+
+        press(&mut state, Key::Z, Modifiers::CTRL, Focus::TextInput);
+        assert_eq!(value(&state), ENumber::new(3.99, -1));
+        assert_eq!(state.undo.len(), 3);
+    }
+
+    #[test]
+    fn test_shortcuts() {
+        let things = vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)];
+        let mut state = State::new(things, Settings::default());
+        press(&mut state, Key::Plus, Modifiers::NONE, Focus::None);
+        assert_eq!(state.settings.ui_scale, 1.25);
+        press(&mut state, Key::Minus, Modifiers::NONE, Focus::None);
+        assert_eq!(state.settings.ui_scale, 1.);
+
+        press(&mut state, Key::Digit(2), Modifiers::CTRL, Focus::TextInput);
+        assert_eq!(state.tab, Tab::Preview);
+        press(&mut state, Key::Tab, Modifiers::CTRL, Focus::None);
+        assert_eq!(state.tab, Tab::Settings);
+        press(
+            &mut state,
+            Key::Tab,
+            Modifiers {
+                shift: true,
+                ctrl: true,
+            },
+            Focus::None,
+        );
+        assert_eq!(state.tab, Tab::Preview);
+        press(&mut state, Key::Digit(9), Modifiers::CTRL, Focus::None);
+        assert_eq!(state.tab, Tab::Preview);
+
+        press(&mut state, Key::Tab, Modifiers::NONE, Focus::None);
+        assert_eq!(state.viewport.keyboard_focus, Some(0));
+        press(&mut state, Key::Right, Modifiers::NONE, Focus::None);
+        assert_eq!(state.viewport.keyboard_focus, Some(1));
+        press(&mut state, Key::Tab, Modifiers::SHIFT, Focus::None);
+        assert_eq!(state.viewport.keyboard_focus, Some(0));
+        press(&mut state, Key::Left, Modifiers::NONE, Focus::TextInput);
+        assert_eq!(state.viewport.keyboard_focus, Some(0));
+
+        state.set_tab(Tab::Data);
+        press(&mut state, Key::Tab, Modifiers::NONE, Focus::None);
+        assert_eq!(state.viewport.keyboard_focus, Some(0));
+    }
+
+    #[test]
+    fn test_escape_closes_the_context_menu() {
+        let things = vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)];
+        let mut state = State::new(things, Settings::default());
+        state.viewport.shift = 1.;
+        state.viewport.update_camera();
+        let size = Size::new(800., 600.);
+        let half_size = size.to_vec2() / 2.;
+        let world_camera =
+            Affine::FLIP_Y.then_translate(half_size) * state.viewport.camera.inverse();
+        let bars = state
+            .viewport
+            .frame_bars((&state.things, None), &state.settings, Side::Primary);
+        let position = state.viewport.bar_position(&bars[0], half_size);
+        let inside = world_camera * Point::new(position.x, position.y / 2.);
+        right_click(&mut state, inside, size);
+        assert!(state.viewport.context_menu.is_some());
+        press(&mut state, Key::Escape, Modifiers::NONE, Focus::None);
+        assert_eq!(state.viewport.context_menu, None);
+    }
+}
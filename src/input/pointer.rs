@@ -0,0 +1,280 @@
+use xilem::core::{Edit, MessageContext, MessageResult, Mut, View, ViewId, ViewMarker};
+use xilem::masonry::accesskit::{Node, Role};
+use xilem::masonry::core::{
+    AccessCtx, BoxConstraints, ChildrenIds, EventCtx, LayoutCtx, NewWidget, PaintCtx,
+    PointerButton as MasonryButton, PointerButtonEvent, PointerEvent, PropertiesMut, PropertiesRef,
+    RegisterCtx, Update, UpdateCtx, Widget, WidgetMut, WidgetPod,
+};
+use xilem::vello::Scene;
+use xilem::vello::kurbo::{Point, Size};
+use xilem::{Pod, ViewCtx, WidgetView};
+
+use crate::State;
+use crate::input::{Input, PointerButton};
+use crate::viewport::Viewport;
+
+// What the pointer did over the chart, in the chart's own coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PointerAction {
+    Move {
+        point: Point,
+        size: Size,
+    },
+    Leave,
+    Down {
+        point: Point,
+        size: Size,
+        button: PointerButton,
+    },
+}
+
+impl PointerAction {
+    // Resolves the pane under the pointer; in split mode the chart holds two.
+    pub fn input(self, split: bool) -> Input {
+        match self {
+            PointerAction::Move { point, size } => {
+                let (pane, point, _) = Viewport::pane_at(point, size, split);
+                Input::PointerMove { pane, point }
+            }
+            PointerAction::Leave => Input::PointerLeave,
+            PointerAction::Down {
+                point,
+                size,
+                button,
+            } => {
+                let (pane, point, size) = Viewport::pane_at(point, size, split);
+                Input::PointerDown {
+                    pane,
+                    point,
+                    size,
+                    button,
+                }
+            }
+        }
+    }
+}
+
+// Wraps the chart and reports the pointer as `PointerAction`s. Events bubble
+// up here from the canvas and from the overlay drawn on top of it.
+pub struct PointerArea {
+    child: WidgetPod<dyn Widget>,
+}
+
+impl PointerArea {
+    pub fn new(child: NewWidget<impl Widget + ?Sized>) -> Self {
+        Self {
+            child: child.erased().to_pod(),
+        }
+    }
+
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, dyn Widget> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+}
+
+impl Widget for PointerArea {
+    type Action = PointerAction;
+
+    fn on_pointer_event(
+        &mut self,
+        ctx: &mut EventCtx<'_>,
+        _props: &mut PropertiesMut<'_>,
+        event: &PointerEvent,
+    ) {
+        let action = match event {
+            PointerEvent::Move(update) => PointerAction::Move {
+                point: ctx.local_position(update.current.position),
+                size: ctx.size(),
+            },
+            PointerEvent::Leave(_) | PointerEvent::Cancel(_) => PointerAction::Leave,
+            PointerEvent::Down(PointerButtonEvent {
+                button: Some(button),
+                state,
+                ..
+            }) => PointerAction::Down {
+                point: ctx.local_position(state.position),
+                size: ctx.size(),
+                button: match button {
+                    MasonryButton::Primary => PointerButton::Primary,
+                    MasonryButton::Secondary => PointerButton::Secondary,
+                    _ => return,
+                },
+            },
+            _ => return,
+        };
+        ctx.submit_action::<Self::Action>(action);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx<'_>, _props: &mut PropertiesMut<'_>, event: &Update) {
+        // the window can keep the pointer while the chart loses it, e.g. to
+        // the things panel
+        if let Update::HoveredChanged(false) = event {
+            ctx.submit_action::<Self::Action>(PointerAction::Leave);
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx<'_>) {
+        ctx.register_child(&mut self.child);
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx<'_>,
+        _props: &mut PropertiesMut<'_>,
+        bc: &BoxConstraints,
+    ) -> Size {
+        let size = ctx.run_layout(&mut self.child, bc);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx<'_>, _props: &PropertiesRef<'_>, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(
+        &mut self,
+        _ctx: &mut AccessCtx<'_>,
+        _props: &PropertiesRef<'_>,
+        _node: &mut Node,
+    ) {
+    }
+
+    fn children_ids(&self) -> ChildrenIds {
+        ChildrenIds::from_slice(&[self.child.id()])
+    }
+}
+
+// Hands what the pointer does over `chart` to `State::handle_input`.
+pub fn pointer_input<V: WidgetView<Edit<State>>>(chart: V) -> PointerInput<V> {
+    PointerInput { chart }
+}
+
+pub struct PointerInput<V> {
+    chart: V,
+}
+
+const CHART_VIEW_ID: ViewId = ViewId::new(0);
+
+impl<V> ViewMarker for PointerInput<V> {}
+
+impl<V: WidgetView<Edit<State>>> View<Edit<State>, (), ViewCtx> for PointerInput<V> {
+    type Element = Pod<PointerArea>;
+    type ViewState = V::ViewState;
+
+    fn build(&self, ctx: &mut ViewCtx, state: &mut State) -> (Self::Element, Self::ViewState) {
+        let (chart, chart_state) = ctx.with_id(CHART_VIEW_ID, |ctx| self.chart.build(ctx, state));
+        let pod = ctx.with_action_widget(|ctx| ctx.create_pod(PointerArea::new(chart.new_widget)));
+        (pod, chart_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<'_, Self::Element>,
+        state: &mut State,
+    ) {
+        ctx.with_id(CHART_VIEW_ID, |ctx| {
+            let chart = PointerArea::child_mut(&mut element);
+            self.chart
+                .rebuild(&prev.chart, view_state, ctx, chart.downcast(), state);
+        });
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<'_, Self::Element>,
+    ) {
+        ctx.with_id(CHART_VIEW_ID, |ctx| {
+            let chart = PointerArea::child_mut(&mut element);
+            self.chart.teardown(view_state, ctx, chart.downcast());
+        });
+        ctx.teardown_action_source(element);
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        message: &mut MessageContext,
+        mut element: Mut<'_, Self::Element>,
+        state: &mut State,
+    ) -> MessageResult<()> {
+        match message.take_first() {
+            Some(CHART_VIEW_ID) => {
+                let chart = PointerArea::child_mut(&mut element);
+                self.chart
+                    .message(view_state, message, chart.downcast(), state)
+            }
+            Some(_) => MessageResult::Stale,
+            None => match message.take_message::<PointerAction>() {
+                Some(action) => {
+                    state.handle_input(action.input(state.split.is_some()));
+                    MessageResult::Action(())
+                }
+                None => MessageResult::Stale,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparison::Side;
+
+    #[test]
+    fn test_pointer_actions_land_in_their_pane() {
+        let size = Size::new(802., 600.);
+        let down = |x| PointerAction::Down {
+            point: Point::new(x, 60.),
+            size,
+            button: PointerButton::Secondary,
+        };
+        assert_eq!(
+            down(600.).input(false),
+            Input::PointerDown {
+                pane: Side::Primary,
+                point: Point::new(600., 60.),
+                size,
+                button: PointerButton::Secondary,
+            }
+        );
+        let pane = Size::new(400., 600.);
+        assert_eq!(
+            PointerAction::Move {
+                point: Point::new(700., 60.),
+                size,
+            }
+            .input(true),
+            Input::PointerMove {
+                pane: Side::Secondary,
+                point: Point::new(298., 60.),
+            }
+        );
+        assert_eq!(PointerAction::Leave.input(true), Input::PointerLeave);
+        assert_eq!(
+            down(100.).input(true),
+            Input::PointerDown {
+                pane: Side::Primary,
+                point: Point::new(100., 60.),
+                size: pane,
+                button: PointerButton::Secondary,
+            }
+        );
+        assert_eq!(
+            down(600.).input(true),
+            Input::PointerDown {
+                pane: Side::Secondary,
+                point: Point::new(198., 60.),
+                size: pane,
+                button: PointerButton::Secondary,
+            }
+        );
+    }
+}
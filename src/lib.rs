@@ -1,41 +1,169 @@
-use std::fs;
-use std::path::PathBuf;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
+use std::time::Instant;
+use std::{fs, io};
 
 use directories::ProjectDirs;
-use xilem::core::{Edit, lens, map_action};
+use serde::{Deserialize, Serialize};
+use xilem::core::{Edit, fork, lens, map_action};
 use xilem::masonry::properties::types::AsUnit;
 use xilem::style::Style;
 use xilem::view::{
-    FlexExt, MainAxisAlignment, flex_col, flex_row, indexed_stack, portal, sized_box, text_button,
+    CrossAxisAlignment, FlexExt, MainAxisAlignment, button, checkbox, flex_col, flex_row,
+    indexed_stack, label, portal, sized_box, task, text_button, text_input,
 };
-use xilem::{AppState, WidgetView, WindowId, WindowView, window};
+use xilem::{AnyWidgetView, AppState, WidgetView, WindowId, WindowView, window};
 
 pub mod animation;
+pub mod app;
+pub mod changelog;
+pub mod check;
+pub mod comparison;
+pub mod dataset;
+pub mod diagnostics;
+pub mod error;
+pub mod expansion;
+pub mod export;
+pub mod expr;
+pub mod import;
+pub mod input;
 pub mod math;
+pub mod notice;
+pub mod presets;
+pub mod remote;
+pub mod selection;
+pub mod settings;
+pub mod sound;
+pub mod summary;
+pub mod theme;
 pub mod thing;
+pub mod undo;
 pub mod units;
 pub mod utils;
+pub mod validation;
 pub mod viewport;
 
-use crate::thing::Thing;
-use crate::viewport::Viewport;
+use crate::animation::{AnimEvent, Animation, AnimationProfile};
+pub use crate::app::App;
+use crate::changelog::{Version, is_upgrade};
+use crate::comparison::ComparisonSession;
+use crate::dataset::Dataset;
+use crate::diagnostics::Diagnostics;
+use crate::error::StateError;
+use crate::expansion::Expansion;
+use crate::export::{AnimationExport, ExportJob, ExportMessage, ExportProgress};
+use crate::import::load_file;
+#[cfg(feature = "xlsx")]
+use crate::import::spreadsheet::SpreadsheetImport;
+use crate::import::text::{QuickAdd, TextImport};
+pub use crate::math::ENumber;
+use crate::notice::{Level, Notice, Notices};
+use crate::presets::{Rng, random_selection};
+use crate::remote::{FetchError, RemoteDataset, cache_file, open_url};
+use crate::selection::{BulkEdit, Selection, common};
+use crate::settings::Settings;
+#[cfg(feature = "sound")]
+use crate::sound::{CuePlayer, RodioSink};
+use crate::sound::{SoundFiles, SoundSettings};
+pub use crate::thing::Thing;
+use crate::thing::{HistoryEntry, MarkerStyle, ThingAction};
+use crate::undo::{Change, UndoStack, ValueEdit};
+pub use crate::units::TimeScale;
+use crate::utils::copy_to_clipboard;
+use crate::validation::{
+    MIN_DECADE_GAP, Severity, ValidationIssue, small_gaps, smallest_not_first, validate,
+};
+use crate::viewport::MenuItem;
+pub use crate::viewport::{TimelineEvent, Viewport, simulate_timeline};
 
 pub static PROJECT_DIRS: LazyLock<ProjectDirs> =
     LazyLock::new(|| ProjectDirs::from("org", "darajkong", env!("CARGO_PKG_NAME")).unwrap());
 
-#[derive(Copy, Clone)]
-enum Tab {
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Tab {
     Data,
+    #[default]
     Preview,
+    Settings,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 3] = [Tab::Data, Tab::Preview, Tab::Settings];
+
+    pub fn index(self) -> usize {
+        match self {
+            Tab::Data => 0,
+            Tab::Preview => 1,
+            Tab::Settings => 2,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    pub fn from_number(number: usize) -> Option<Self> {
+        number
+            .checked_sub(1)
+            .and_then(|index| Self::ALL.get(index).copied())
+    }
 }
 
 pub struct State {
     running: bool,
+    launched: Instant,
     window_id: WindowId,
+    about_window_id: WindowId,
+    show_about: bool,
     tab: Tab,
+    // None when there is nowhere safe to save, see `App::state`
+    data_file: Option<PathBuf>,
+    project: String,
+    name: String,
+    author: String,
+    axis_title: String,
+    chart_title: bool,
+    profile: AnimationProfile,
     things: Vec<Thing>,
+    secondary: Option<Vec<Thing>>,
+    compare_path: String,
+    multiply_factor: String,
+    recent_first: bool,
     viewport: Viewport,
+    split: Option<Viewport>,
+    settings: Settings,
+    notices: Notices,
+    remote_url: Option<String>,
+    #[cfg(feature = "xlsx")]
+    spreadsheet: SpreadsheetImport,
+    text_import: TextImport,
+    quick_add: QuickAdd,
+    selection: Selection,
+    expansion: Expansion,
+    undo: UndoStack,
+    // bumped by every dataset edit; saved when it matches `saved_edits`
+    edits: u64,
+    saved_edits: u64,
+    confirm_close: bool,
+    // the path typed into the save-as prompt while it is open
+    save_as: Option<String>,
+    close_window_id: WindowId,
+    export: Option<ExportProgress>,
+    export_job: Option<ExportJob>,
+    whats_new_window_id: WindowId,
+    show_whats_new: bool,
+    #[cfg(feature = "sound")]
+    cues: CuePlayer,
+    sound_files: SoundFiles,
+    #[cfg(feature = "sound")]
+    sound_sink: Option<RodioSink>,
+    #[cfg(feature = "sound")]
+    sound_unavailable: bool,
 }
 
 impl AppState for State {
@@ -45,97 +173,1746 @@ impl AppState for State {
 }
 
 impl State {
-    pub fn new(things: Vec<Thing>) -> Self {
-        let viewport = Viewport::init(&things);
+    pub const SURPRISE_COUNT: usize = 6;
+    pub const EXPONENT_MARGIN: f64 = 1.;
+    pub const DEFAULT_EXPONENT_RANGE: RangeInclusive<f64> = -3.0..=20.0;
+
+    pub fn new(things: Vec<Thing>, settings: Settings) -> Self {
+        let viewport = Viewport::init_with(&things, settings.visible_decades);
+        // an empty new dataset has nothing worth asking about on close
+        let edits = u64::from(!things.is_empty());
         Self {
             running: true,
+            launched: Instant::now(),
             window_id: WindowId::next(),
-            tab: Tab::Preview,
+            about_window_id: WindowId::next(),
+            show_about: false,
+            tab: settings.last_tab,
             viewport,
+            data_file: None,
+            project: String::new(),
+            name: String::new(),
+            author: String::new(),
+            axis_title: String::new(),
+            chart_title: false,
+            profile: AnimationProfile::default(),
             things,
+            secondary: None,
+            compare_path: String::new(),
+            multiply_factor: String::new(),
+            recent_first: false,
+            split: None,
+            settings,
+            notices: Notices::default(),
+            remote_url: None,
+            #[cfg(feature = "xlsx")]
+            spreadsheet: SpreadsheetImport::default(),
+            text_import: TextImport::default(),
+            quick_add: QuickAdd::default(),
+            selection: Selection::default(),
+            expansion: Expansion::default(),
+            undo: UndoStack::default(),
+            edits,
+            saved_edits: 0,
+            confirm_close: false,
+            save_as: None,
+            close_window_id: WindowId::next(),
+            export: None,
+            export_job: None,
+            whats_new_window_id: WindowId::next(),
+            show_whats_new: is_upgrade(settings.last_seen_version, Version::current()),
+            #[cfg(feature = "sound")]
+            cues: CuePlayer::default(),
+            sound_files: SoundFiles::default(),
+            #[cfg(feature = "sound")]
+            sound_sink: None,
+            #[cfg(feature = "sound")]
+            sound_unavailable: false,
+        }
+    }
+
+    pub fn from_url(url: String, settings: Settings) -> Self {
+        let mut state = Self::new(Vec::new(), settings);
+        state
+            .notices
+            .push(Notice::info(format!("Downloading {url}…")));
+        state.remote_url = Some(url);
+        state
+    }
+
+    fn finish_remote(&mut self, result: Result<RemoteDataset, FetchError>) {
+        self.remote_url = None;
+        self.notices.clear();
+        match result {
+            Ok(remote) => {
+                self.open_dataset(remote.dataset);
+                self.mark_edited();
+                self.set_tab(Tab::Preview);
+                self.notices.extend(remote.notices);
+            }
+            Err(err) => self.notices.push(Notice::error(err.to_string())),
+        }
+    }
+
+    fn frames_dir() -> PathBuf {
+        PROJECT_DIRS.data_dir().join("frames")
+    }
+
+    fn start_export(&mut self) {
+        if self.export_job.is_some() {
+            return;
+        }
+        let dir = Self::frames_dir();
+        self.notices.push(Notice::info(format!(
+            "Exporting frames to {}",
+            dir.display()
+        )));
+        let export = AnimationExport::new(self.things.clone())
+            .secondary(self.secondary.clone())
+            .profile(self.profile)
+            .name(self.name.clone())
+            .settings(self.settings)
+            .frames_dir(dir);
+        self.export_job = Some(ExportJob::new(export));
+    }
+
+    fn cancel_export(&mut self) {
+        if let Some(job) = &self.export_job {
+            job.cancel();
+        }
+    }
+
+    pub fn on_export_message(&mut self, message: ExportMessage) {
+        match message {
+            ExportMessage::Progress(progress) => self.export = Some(progress),
+            ExportMessage::Done(summary) => {
+                self.export = None;
+                self.export_job = None;
+                self.notices.push(summary.notice());
+            }
+            ExportMessage::Failed(message) => {
+                self.export = None;
+                self.export_job = None;
+                self.notices.push(Notice::error(message));
+            }
+        }
+    }
+
+    fn window_title(&self) -> String {
+        match &self.export {
+            Some(progress) => format!("{progress} — {}", Dataset::title(&self.project, &self.name)),
+            None => Dataset::window_title(&self.project, &self.name, &self.viewport.animation),
+        }
+    }
+
+    fn update_live(&mut self) {
+        let value = Thing::live_value(self.launched.elapsed());
+        let secondary = self.secondary.iter_mut().flatten();
+        for thing in self.things.iter_mut().chain(secondary) {
+            if thing.live {
+                thing.value = value.clone();
+            }
+        }
+    }
+
+    // Brings both panes up to date with the settings, each with the lists it
+    // draws, and locks the split pane to the main one.
+    fn apply_settings(&mut self) {
+        let secondary = self.secondary.as_deref();
+        match &mut self.split {
+            Some(split) => {
+                self.viewport
+                    .apply_settings((&self.things, None), &self.settings);
+                split.apply_settings((secondary.unwrap_or_default(), None), &self.settings);
+                split.follow(&self.viewport);
+            }
+            None => {
+                self.viewport
+                    .apply_settings((&self.things, secondary), &self.settings);
+            }
         }
     }
 
-    fn data_file() -> PathBuf {
+    pub(crate) fn update_animation(&mut self, dt: f64) {
+        self.viewport.reduce_motion = self.settings.reduce_motion;
+        self.viewport.animation.next_pacing = self.profile.resolve();
+        self.apply_settings();
+        let repairs = self.viewport.repairs;
+        self.viewport.update_animation(
+            &self.things,
+            self.secondary.as_deref(),
+            dt.min(Animation::MAX_DT),
+        );
+        if repairs == 0 && self.viewport.repairs > 0 {
+            self.notices.push(Notice::warning(Viewport::REPAIR_NOTICE));
+        }
+        if let Some(split) = &mut self.split {
+            split.follow(&self.viewport);
+        }
+        let events = std::mem::take(&mut self.viewport.animation.events);
+        self.play_cues(&events);
+    }
+
+    // The output device is opened on the first cue, and only tried once.
+    #[cfg(feature = "sound")]
+    fn play_cues(&mut self, events: &[AnimEvent]) {
+        if events.is_empty() || !self.settings.sound.any() {
+            return;
+        }
+        if self.sound_sink.is_none() && !self.sound_unavailable {
+            match RodioSink::open(&self.sound_files, PROJECT_DIRS.preference_dir()) {
+                Ok((sink, notices)) => {
+                    self.notices.extend(notices);
+                    self.sound_sink = Some(sink);
+                }
+                Err(err) => {
+                    self.sound_unavailable = true;
+                    self.notices.push(Notice::warning(format!(
+                        "Sound cues are unavailable: {err}"
+                    )));
+                }
+            }
+        }
+        if let Some(sink) = &mut self.sound_sink {
+            let now = self.launched.elapsed().as_secs_f64();
+            self.cues.dispatch(events, now, self.settings.sound, sink);
+        }
+    }
+
+    #[cfg(not(feature = "sound"))]
+    fn play_cues(&mut self, _events: &[AnimEvent]) {}
+
+    // The sink is opened again on the next cue, loading the new files.
+    fn sound_files_changed(&mut self) {
+        let _ = self.sound_files.save();
+        #[cfg(feature = "sound")]
+        {
+            self.sound_sink = None;
+            self.sound_unavailable = false;
+        }
+    }
+
+    fn preview(&mut self) {
+        self.sort_things();
+        self.viewport.sync(&self.things);
+        let autosave = self.settings.autosave && self.data_file.is_some();
+        if autosave
+            && self.has_unsaved_changes()
+            && let Err(err) = self.save()
+        {
+            self.notices.push(err.notice());
+        }
+        self.set_tab(Tab::Preview);
+    }
+
+    fn sort_things(&mut self) {
+        if !self
+            .things
+            .is_sorted_by(|a, b| a.value.total_cmp(&b.value).is_le())
+        {
+            self.things.sort_by(|a, b| a.value.total_cmp(&b.value));
+            self.mark_edited();
+        }
+    }
+
+    pub(crate) fn mark_edited(&mut self) {
+        self.edits += 1;
+    }
+
+    // Closing with unsaved edits asks first instead of dropping them.
+    fn request_close(&mut self) {
+        if self.has_unsaved_changes() {
+            self.confirm_close = true;
+        } else {
+            self.running = false;
+        }
+    }
+
+    fn cancel_close(&mut self) {
+        self.confirm_close = false;
+        self.save_as = None;
+    }
+
+    fn save_and_quit(&mut self) {
+        match self.save() {
+            Ok(()) => self.running = false,
+            Err(StateError::NoDataFile) => self.ask_save_path(),
+            Err(err) => {
+                self.confirm_close = false;
+                self.notices.push(err.notice());
+            }
+        }
+    }
+
+    // A dataset without a data file, like a download, asks where to go
+    // instead of failing to save.
+    fn save_or_ask(&mut self) {
+        match self.save() {
+            Ok(()) => {}
+            Err(StateError::NoDataFile) => self.ask_save_path(),
+            Err(err) => self.notices.push(err.notice()),
+        }
+    }
+
+    fn ask_save_path(&mut self) {
+        self.save_as = Some(Self::default_data_file().display().to_string());
+    }
+
+    // Saves to `path` and keeps saving there; a failed save leaves the data
+    // file as it was.
+    pub fn save_as(&mut self, path: impl Into<PathBuf>) -> Result<(), StateError> {
+        let previous = self.data_file.replace(path.into());
+        let saved = self.save();
+        if saved.is_err() {
+            self.data_file = previous;
+        }
+        saved
+    }
+
+    fn finish_save_as(&mut self, quit: bool) {
+        let Some(path) = self.save_as.take() else {
+            return;
+        };
+        match self.save_as(&path) {
+            Ok(()) => self.running &= !quit,
+            Err(err) => {
+                self.notices.push(err.notice());
+                self.save_as = Some(path);
+            }
+        }
+    }
+
+    fn save_as_view(&self, quit: bool) -> Option<impl WidgetView<Edit<Self>> + use<>> {
+        let path = self.save_as.clone()?;
+        let palette = self.settings.palette;
+        let replaces = Path::new(&path).exists().then(|| {
+            label("A file already exists there and will be replaced").color(Level::Warning.color())
+        });
+        Some(flex_col((
+            label("Save this dataset to:").color(palette.text),
+            text_input(path, |state: &mut Self, value| state.save_as = Some(value)),
+            replaces,
+            flex_row((
+                text_button("Save", move |state: &mut Self| state.finish_save_as(quit)),
+                text_button("Cancel", |state: &mut Self| state.save_as = None),
+            )),
+        )))
+    }
+
+    fn close_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let palette = self.settings.palette;
+        sized_box(
+            flex_col((
+                label("This dataset has unsaved changes.").color(palette.text),
+                flex_row((
+                    text_button("Save and quit", |state: &mut Self| state.save_and_quit()),
+                    text_button("Quit without saving", |state: &mut Self| {
+                        state.running = false;
+                    }),
+                    text_button("Cancel", |state: &mut Self| state.cancel_close()),
+                )),
+                self.save_as_view(true),
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Start),
+        )
+        .expand()
+        .padding(15.)
+        .background_color(palette.background)
+    }
+
+    fn set_split(&mut self, enabled: bool) {
+        self.split = match (enabled, &self.secondary) {
+            (true, Some(things)) => {
+                let mut split = Viewport::init_with(things, self.viewport.decades);
+                split.follow(&self.viewport);
+                Some(split)
+            }
+            _ => None,
+        };
+    }
+
+    pub(crate) fn dismiss_whats_new(&mut self) {
+        self.show_whats_new = false;
+        self.settings.last_seen_version = Some(Version::current());
+        let _ = self.settings.save();
+    }
+
+    pub(crate) fn set_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.settings.last_tab = tab;
+        let _ = self.settings.save();
+    }
+
+    pub fn default_data_file() -> PathBuf {
         let mut path = PROJECT_DIRS.preference_dir().join("_").to_path_buf();
         path.set_file_name("data.json");
         path
     }
 
-    pub fn load() -> anyhow::Result<Self> {
-        let path = Self::data_file();
-        let string = fs::read_to_string(path)?;
-        let things = serde_json::from_str(&string)?;
-        let state = Self::new(things);
+    fn comparison_file(&self) -> Option<PathBuf> {
+        let data_file = self.data_file.as_ref()?;
+        Some(data_file.with_file_name("comparison.json"))
+    }
+
+    pub fn things(&self) -> &[Thing] {
+        &self.things
+    }
+
+    // Whole powers of ten from a margin below the smallest thing to a margin above the largest.
+    pub fn exponent_range(&self) -> RangeInclusive<f64> {
+        let (min, max) = self
+            .things
+            .iter()
+            .map(|thing| thing.value.inner().erect().1)
+            .filter(|exponent| exponent.is_finite())
+            .fold(
+                (f64::INFINITY, f64::NEG_INFINITY),
+                |(min, max), exponent| (min.min(exponent), max.max(exponent)),
+            );
+        if min > max {
+            return Self::DEFAULT_EXPONENT_RANGE;
+        }
+        (min - Self::EXPONENT_MARGIN).floor()..=(max + Self::EXPONENT_MARGIN).ceil()
+    }
+
+    pub fn data_file(&self) -> Option<&Path> {
+        self.data_file.as_deref()
+    }
+
+    pub fn set_data_file(&mut self, path: impl Into<PathBuf>) {
+        self.data_file = Some(path.into());
+    }
+
+    pub fn load_from(path: impl AsRef<Path>, settings: Settings) -> Result<Self, StateError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| StateError::reading(path, err))?;
+        let dataset = match Dataset::from_json(&text) {
+            Ok(dataset) => dataset,
+            Err(source) => {
+                return Err(match Self::back_up(path) {
+                    Ok(backup) => StateError::Corrupt { backup, source },
+                    Err(_) => StateError::Parse(source),
+                });
+            }
+        };
+        let mut state = Self::new(Vec::new(), settings);
+        state.open_dataset(dataset);
+        state.data_file = Some(path.to_path_buf());
+        state.saved_edits = state.edits;
+        Ok(state)
+    }
+
+    // Takes over everything a dataset file holds; where it is saved is up to
+    // the caller.
+    fn open_dataset(&mut self, dataset: Dataset) {
+        self.notices.extend(dataset.validate());
+        self.project = dataset.project;
+        self.name = dataset.name;
+        self.author = dataset.author;
+        self.axis_title = dataset.axis_title;
+        self.chart_title = dataset.chart_title;
+        self.profile = dataset.profile;
+        self.things = dataset.things;
+        self.viewport = Viewport::init_with(&self.things, self.settings.visible_decades);
+        self.viewport.animation = Animation::with_pacing(dataset.profile.resolve());
+        if !dataset.play_range.is_all() {
+            self.viewport
+                .set_play_range(dataset.play_range, &self.things);
+        }
+    }
+
+    pub fn load() -> Result<Self, StateError> {
+        Self::open(Self::default_data_file(), Settings::load())
+    }
+
+    pub fn open(path: impl AsRef<Path>, settings: Settings) -> Result<Self, StateError> {
+        let mut state = Self::load_from(path, settings)?;
+        state.restore_comparison();
         let _ = state.save();
         Ok(state)
     }
 
-    pub fn save(&self) -> anyhow::Result<()> {
-        let path = Self::data_file();
+    // Keeps a copy of a file that failed to parse, since the next save
+    // replaces it.
+    fn back_up(path: &Path) -> io::Result<PathBuf> {
+        let backup = path.with_extension("corrupt.json");
+        fs::copy(path, &backup)?;
+        Ok(backup)
+    }
+
+    // A damaged comparison only costs the secondary dataset, never the primary
+    // one. It is backed up first, since the next save removes the file.
+    fn restore_comparison(&mut self) {
+        let Some(path) = self.comparison_file() else {
+            return;
+        };
+        let Ok(string) = fs::read_to_string(&path) else {
+            return;
+        };
+        match serde_json::from_str::<ComparisonSession>(&string) {
+            Ok(session) => {
+                self.compare_path = session.source;
+                self.secondary = Some(session.things);
+            }
+            Err(source) => {
+                let err = match Self::back_up(&path) {
+                    Ok(backup) => StateError::Corrupt { backup, source },
+                    Err(_) => StateError::Parse(source),
+                };
+                self.notices.push(Notice::new(
+                    err.level(),
+                    format!("The comparison dataset could not be restored: {err}"),
+                ));
+            }
+        }
+    }
+
+    fn dataset(&self) -> Dataset {
+        Dataset {
+            project: self.project.clone(),
+            name: self.name.clone(),
+            author: self.author.clone(),
+            axis_title: self.axis_title.clone(),
+            chart_title: self.chart_title,
+            profile: self.profile,
+            play_range: self.viewport.play_range,
+            things: self.things.clone(),
+        }
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.edits != self.saved_edits
+    }
+
+    pub fn save(&mut self) -> Result<(), StateError> {
+        let Some(path) = &self.data_file else {
+            return Err(StateError::NoDataFile);
+        };
+        let unwritable = |source| StateError::Unwritable {
+            path: path.clone(),
+            source,
+        };
         if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
+            fs::create_dir_all(parent).map_err(unwritable)?;
+        }
+        fs::write(path, self.dataset().to_json()?).map_err(unwritable)?;
+        self.saved_edits = self.edits;
+        match &self.secondary {
+            Some(things) => {
+                let session = ComparisonSession {
+                    source: self.compare_path.clone(),
+                    things: things.clone(),
+                };
+                if let Some(path) = self.comparison_file() {
+                    fs::write(path, serde_json::to_string(&session)?)?;
+                }
+            }
+            None => {
+                if let Some(path) = self.comparison_file() {
+                    let _ = fs::remove_file(path);
+                }
+            }
         }
-        fs::write(path, serde_json::to_string(&self.things)?)?;
         Ok(())
     }
 
-    pub fn data_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
-        let things = self
+    fn compare_with(&mut self) {
+        match load_file(&self.compare_path) {
+            Ok(report) => {
+                let errors = validate(&report.things)
+                    .into_iter()
+                    .filter(|issue| issue.kind.severity() == Severity::Error)
+                    .collect::<Vec<_>>();
+                if !errors.is_empty() {
+                    self.notices.push(StateError::Validation(errors).notice());
+                    return;
+                }
+                let mut things = report.things;
+                things.sort_by(|a, b| a.value.total_cmp(&b.value));
+                self.secondary = Some(things);
+                self.set_split(self.split.is_some());
+                self.notices.extend(
+                    report
+                        .errors
+                        .iter()
+                        .map(|err| Notice::warning(err.to_string())),
+                );
+            }
+            Err(err) => self.notices.push(err.notice()),
+        }
+    }
+
+    fn comparison_label(&self) -> Option<String> {
+        self.secondary.as_ref().map(|_| {
+            Path::new(&self.compare_path)
+                .file_stem()
+                .map_or("Comparison".to_string(), |stem| {
+                    stem.to_string_lossy().into_owned()
+                })
+        })
+    }
+
+    fn titles_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        flex_col((
+            flex_row((
+                text_input(self.project.clone(), |state: &mut Self, value| {
+                    state.project = value;
+                    state.mark_edited();
+                })
+                .placeholder("Project name")
+                .flex(1.),
+                text_input(self.name.clone(), |state: &mut Self, value| {
+                    state.name = value;
+                    state.mark_edited();
+                })
+                .placeholder("Dataset name")
+                .flex(1.),
+                text_input(self.author.clone(), |state: &mut Self, value| {
+                    state.author = value;
+                    state.mark_edited();
+                })
+                .placeholder("Author")
+                .flex(1.),
+            )),
+            flex_row((
+                text_input(self.axis_title.clone(), |state: &mut Self, value| {
+                    state.axis_title = value;
+                    state.mark_edited();
+                })
+                .placeholder(Dataset::DEFAULT_AXIS_TITLE)
+                .flex(1.),
+                checkbox(
+                    "Show chart title",
+                    self.chart_title,
+                    |state: &mut Self, checked| {
+                        state.chart_title = checked;
+                        state.mark_edited();
+                    },
+                ),
+            )),
+        ))
+    }
+
+    fn compare_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let clear_btn = self.secondary.is_some().then(|| {
+            text_button("Clear comparison", |state: &mut Self| {
+                state.secondary = None;
+                state.split = None;
+            })
+        });
+        let split_checkbox = self.secondary.is_some().then(|| {
+            checkbox(
+                "Side by side",
+                self.split.is_some(),
+                |state: &mut Self, checked| state.set_split(checked),
+            )
+        });
+        flex_row((
+            text_input(self.compare_path.clone(), |state: &mut Self, value| {
+                state.compare_path = value;
+            })
+            .placeholder("Path to a dataset to compare with")
+            .flex(1.),
+            text_button("Compare with…", Self::compare_with),
+            split_checkbox,
+            clear_btn,
+        ))
+    }
+
+    pub fn surprise(&mut self, seed: u64) {
+        let mut rng = Rng::seeded(seed);
+        self.things = random_selection(&mut rng, Self::SURPRISE_COUNT, MIN_DECADE_GAP);
+        self.name = format!("Surprise #{}", rng.below(100) + 1);
+        self.mark_edited();
+        self.secondary = None;
+        self.split = None;
+        self.viewport.restart(&self.things);
+    }
+
+    pub fn context_action(&mut self, id: u64, item: MenuItem) {
+        let Some(index) = self.things.iter().position(|thing| thing.id == id) else {
+            return;
+        };
+        match item {
+            MenuItem::Edit => self.set_tab(Tab::Data),
+            MenuItem::SetReference => {
+                self.viewport.reference = (self.viewport.reference != Some(id)).then_some(id);
+            }
+            MenuItem::CopyValue => {
+                let thing = &self.things[index];
+                let text = thing
+                    .value
+                    .fmt_styled(thing.display_unit, self.settings.format_style());
+                let _ = copy_to_clipboard(&text);
+            }
+            MenuItem::Center => {
+                self.viewport
+                    .center_on(index, &self.things, self.secondary.as_deref());
+            }
+        }
+    }
+
+    pub fn validate(things: &[Thing]) -> Vec<ValidationIssue> {
+        validate(things)
+    }
+
+    fn valid_factor(factor: ENumber) -> bool {
+        let finite = factor.significand().is_finite() && factor.exponent().is_finite();
+        finite && factor.significand() > 0.
+    }
+
+    pub fn parse_factor(text: &str) -> Result<ENumber, String> {
+        let factor = text.parse::<ENumber>().map_err(|err| err.to_string())?;
+        match Self::valid_factor(factor) {
+            true => Ok(factor),
+            false => Err("the factor must be a positive, finite number".to_string()),
+        }
+    }
+
+    pub fn multiply_values(&mut self, factor: ENumber) {
+        if !Self::valid_factor(factor) {
+            return;
+        }
+        let now = HistoryEntry::now();
+        let record_history = self.settings.record_history;
+        let edits = self
             .things
-            .iter()
-            .enumerate()
-            .map(|(i, _)| {
-                map_action(
-                    lens(Thing::view, move |state: &mut Self, ()| {
-                        state.things.get_mut(i).unwrap()
-                    }),
-                    move |state: &mut Self, delete| {
-                        if delete {
-                            state.things.remove(i);
-                        }
+            .iter_mut()
+            .filter(|thing| !thing.live)
+            .map(|thing| {
+                let previous = thing.value.inner();
+                let recorded = thing.multiply(factor, record_history, now);
+                ValueEdit {
+                    id: thing.id,
+                    previous,
+                    value: thing.value.inner(),
+                    recorded,
+                }
+                .into()
+            })
+            .collect();
+        self.undo.push_all(edits);
+        self.mark_edited();
+        self.sort_things();
+        self.viewport.sync(&self.things);
+    }
+
+    fn multiply_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let factor = Self::parse_factor(&self.multiply_factor);
+        let preview = match (&factor, self.things.iter().find(|thing| !thing.live)) {
+            _ if self.multiply_factor.trim().is_empty() => None,
+            (Ok(factor), Some(thing)) => Some(
+                label(format!(
+                    "{}: {} → {}",
+                    thing.display_name(),
+                    thing.value.fmt_secs(),
+                    TimeScale::from(thing.value.inner() * *factor).fmt_secs()
+                ))
+                .color(Thing::VALUE_COLOR),
+            ),
+            (Ok(_), None) => None,
+            (Err(err), _) => {
+                Some(label(format!("Invalid factor: {err}")).color(Level::Error.color()))
+            }
+        };
+        let apply_btn = factor.ok().map(|factor| {
+            text_button("Multiply all", move |state: &mut Self| {
+                state.multiply_values(factor);
+                state.multiply_factor.clear();
+            })
+        });
+        flex_col((
+            flex_row((
+                text_input(self.multiply_factor.clone(), |state: &mut Self, value| {
+                    state.multiply_factor = value;
+                })
+                .placeholder("Multiply all values by, e.g. 3.156e7")
+                .flex(1.),
+                apply_btn,
+            )),
+            preview,
+        ))
+    }
+
+    fn quick_add_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        map_action(
+            lens(QuickAdd::view, |state: &mut Self, ()| &mut state.quick_add),
+            |state: &mut Self, thing| {
+                if let Some(thing) = thing {
+                    state.things.push(thing);
+                    state.mark_edited();
+                }
+            },
+        )
+    }
+
+    fn selection_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let selected = self.selection.selected(&self.things);
+        let editor = (selected.len() >= 2).then(|| {
+            let marker = common(selected.iter().map(|thing| thing.marker));
+            let display_unit = common(selected.iter().map(|thing| thing.display_unit));
+            flex_row((
+                label(format!("{} selected", selected.len())).color(Thing::NAME_COLOR),
+                text_button(
+                    format!(
+                        "Marker: {}",
+                        marker.map_or(Selection::MIXED.to_string(), |marker| marker.to_string())
+                    ),
+                    move |state: &mut Self| {
+                        let marker = marker.map_or(MarkerStyle::default(), MarkerStyle::next);
+                        state.bulk_edit(BulkEdit::Marker(marker));
                     },
-                )
+                ),
+                text_button(
+                    format!(
+                        "Display unit: {}",
+                        display_unit.map_or(Selection::MIXED.to_string(), Thing::display_unit_name)
+                    ),
+                    move |state: &mut Self| {
+                        let display_unit = display_unit.and_then(Thing::next_display_unit);
+                        state.bulk_edit(BulkEdit::DisplayUnit(display_unit));
+                    },
+                ),
+                text_button("Clear selection", |state: &mut Self| {
+                    state.selection.ids.clear();
+                }),
+            ))
+        });
+        flex_row(editor)
+    }
+
+    // Indices into `things` in the order the data tab lists them.
+    fn display_order(&self) -> Vec<usize> {
+        let mut order = (0..self.things.len()).collect::<Vec<_>>();
+        if self.recent_first {
+            order.sort_by_key(|&index| std::cmp::Reverse(self.things[index].modified));
+        }
+        order
+    }
+
+    fn thing_action(&mut self, index: usize, action: Option<ThingAction>) {
+        let id = self.things[index].id;
+        // the card reports every edit, even ones without an action
+        if !matches!(action, Some(ThingAction::SetExpanded(_))) {
+            self.mark_edited();
+        }
+        match action {
+            Some(ThingAction::Delete) => {
+                let thing = Box::new(self.things.remove(index));
+                self.undo.push(Change::Delete { index, thing });
+                self.expansion.retain(&self.things);
+            }
+            Some(ThingAction::CommitValue { previous, recorded }) => {
+                self.undo.push(ValueEdit {
+                    id,
+                    previous,
+                    value: self.things[index].value.inner(),
+                    recorded,
+                });
+            }
+            Some(ThingAction::SetExpanded(expanded)) => self.expansion.set(id, expanded),
+            None => {}
+        }
+    }
+
+    pub(crate) fn undo_last(&mut self) {
+        if self.undo.undo(&mut self.things).is_some() {
+            self.mark_edited();
+            self.sort_things();
+            self.viewport.sync(&self.things);
+        }
+    }
+
+    fn set_compact_data(&mut self, compact: bool) {
+        self.settings.compact_data = compact;
+        if compact {
+            self.expansion.enter_compact(&self.things);
+        }
+        let _ = self.settings.save();
+    }
+
+    fn bulk_edit(&mut self, edit: BulkEdit) {
+        let previous = selection::apply(&mut self.things, &self.selection.ids, edit);
+        self.undo
+            .push_all(previous.into_iter().map(Change::from).collect());
+        self.mark_edited();
+    }
+
+    fn text_import_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        map_action(
+            lens(TextImport::view, |state: &mut Self, ()| {
+                &mut state.text_import
+            }),
+            |state: &mut Self, things| {
+                if let Some(things) = things {
+                    state.things.extend(things);
+                    state.mark_edited();
+                }
+            },
+        )
+    }
+
+    #[cfg(feature = "xlsx")]
+    fn import_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        map_action(
+            lens(SpreadsheetImport::view, |state: &mut Self, ()| {
+                &mut state.spreadsheet
+            }),
+            |state: &mut Self, things| {
+                if let Some(things) = things {
+                    state.things.extend(things);
+                    state.mark_edited();
+                }
+            },
+        )
+    }
+
+    #[cfg(not(feature = "xlsx"))]
+    fn import_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        flex_row(())
+    }
+
+    fn card_view(i: usize, settings: Settings) -> impl WidgetView<Edit<Self>> + use<> {
+        map_action(
+            lens(
+                move |thing: &mut Thing| thing.view(i, settings),
+                move |state: &mut Self, ()| state.things.get_mut(i).unwrap(),
+            ),
+            move |state: &mut Self, action| state.thing_action(i, action),
+        )
+    }
+
+    pub fn data_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let settings = match self.settings.slider_fit_data {
+            true => {
+                let range = self.exponent_range();
+                self.settings
+                    .with_slider_range(*range.start() as i32, *range.end() as i32)
+            }
+            false => self.settings,
+        };
+        let palette = settings.palette;
+        let compact = settings.compact_data;
+        let things = self
+            .display_order()
+            .into_iter()
+            .map(|i| {
+                let thing = &self.things[i];
+                let id = thing.id;
+                let expanded = self.expansion.is_expanded(id);
+                let row = compact.then(|| {
+                    map_action(
+                        lens(
+                            move |thing: &mut Thing| thing.compact_view(i, expanded, settings),
+                            move |state: &mut Self, ()| state.things.get_mut(i).unwrap(),
+                        ),
+                        move |state: &mut Self, action| state.thing_action(i, action),
+                    )
+                });
+                let card = (!compact).then(|| Self::card_view(i, settings));
+                let select = checkbox(
+                    "Select",
+                    self.selection.ids.contains(&id),
+                    move |state: &mut Self, checked| state.selection.set(id, checked),
+                );
+                flex_col((flex_row((select, row)), card))
+                    .cross_axis_alignment(CrossAxisAlignment::Start)
             })
             .collect::<Vec<_>>();
-        let new_btn = flex_row(text_button("Add new", |state: &mut Self| {
-            state.things.push(Thing::default());
-        }))
+        let order_hint = smallest_not_first(&self.things).map(|index| {
+            label(format!(
+                "\"{}\" is the smallest value but is not first. Consider sorting so the preview opens on it.",
+                self.things[index].name
+            ))
+            .color(Level::Warning.color())
+        });
+        let scales = self.things.iter().map(Thing::scale).collect::<Vec<_>>();
+        let gap_hint = small_gaps(&scales).first().map(|&index| {
+            label(format!(
+                "\"{}\" and \"{}\" are less than a decade apart, so the preview slows down between them.",
+                self.things[index - 1].name,
+                self.things[index].name
+            ))
+            .color(Level::Warning.color())
+        });
+        let new_btn = flex_row((
+            button(label("Add new").color(palette.text), |state: &mut Self| {
+                state.things.push(Thing::default());
+                state.mark_edited();
+            })
+            .background_color(palette.button),
+            text_button("Surprise me", |state: &mut Self| {
+                let seed = state.launched.elapsed().as_nanos() as u64 ^ HistoryEntry::now();
+                state.surprise(seed);
+                state.set_tab(Tab::Preview);
+            }),
+            text_button(
+                match self.recent_first {
+                    true => "Dataset order",
+                    false => "Recently modified first",
+                },
+                |state: &mut Self| state.recent_first = !state.recent_first,
+            ),
+        ))
         .must_fill_major_axis(true)
         .main_axis_alignment(MainAxisAlignment::Center);
         let list = portal(
             flex_row(
-                sized_box(flex_col((things, new_btn)))
-                    .width(800.px())
-                    .padding(10.),
+                sized_box(flex_col((
+                    self.quick_add_view(),
+                    self.selection_view(),
+                    self.titles_view(),
+                    order_hint,
+                    gap_hint,
+                    lens(Notices::view, |state: &mut Self, ()| &mut state.notices),
+                    self.import_view(),
+                    self.text_import_view(),
+                    self.compare_view(),
+                    self.multiply_view(),
+                    things,
+                    new_btn,
+                )))
+                .width(800.px())
+                .padding(10.),
             )
             .must_fill_major_axis(true)
             .main_axis_alignment(MainAxisAlignment::Center),
         );
-        let controls = flex_row(text_button("Save and preview", |state: &mut Self| {
-            state.things.sort_by(|a, b| a.value.total_cmp(&b.value));
-            state.viewport = Viewport::init(&state.things);
-            let _ = state.save();
-            state.tab = Tab::Preview;
-        }))
+        // The portal has no way to scroll to a row, so the expanded card sits
+        // under the list where scrolling or collapsing rows can't move it away.
+        let editor = compact
+            .then(|| self.expansion.expanded())
+            .flatten()
+            .and_then(|id| self.things.iter().position(|thing| thing.id == id))
+            .map(|i| {
+                flex_row(
+                    sized_box(Self::card_view(i, settings))
+                        .width(800.px())
+                        .padding(10.),
+                )
+                .must_fill_major_axis(true)
+                .main_axis_alignment(MainAxisAlignment::Center)
+            });
+        let unsaved = self
+            .has_unsaved_changes()
+            .then(|| label("● Unsaved changes").color(Level::Warning.color()));
+        let undo = self
+            .undo
+            .label(&self.things)
+            .map(|text| text_button(text, |state: &mut Self| state.undo_last()));
+        let controls = flex_row((
+            text_button("Settings", |state: &mut Self| state.set_tab(Tab::Settings)),
+            checkbox("Compact list", compact, |state: &mut Self, checked| {
+                state.set_compact_data(checked);
+            }),
+            unsaved,
+            undo,
+            text_button("Save", |state: &mut Self| state.save_or_ask()),
+            checkbox(
+                "Save on preview",
+                self.settings.autosave,
+                |state: &mut Self, checked| {
+                    state.settings.autosave = checked;
+                    let _ = state.settings.save();
+                },
+            ),
+            text_button("Restart", |state: &mut Self| {
+                state.sort_things();
+                state.viewport.restart(&state.things);
+                state.set_tab(Tab::Preview);
+            }),
+            button(label("Preview").color(palette.text), |state: &mut Self| {
+                state.preview()
+            })
+            .background_color(palette.button),
+        ))
+        .must_fill_major_axis(true)
+        .main_axis_alignment(MainAxisAlignment::Center)
+        .background_color(palette.background);
+        let save_as = self.save_as_view(false).filter(|_| !self.confirm_close);
+        flex_col((
+            list.flex(1.),
+            editor,
+            save_as,
+            sized_box(controls).height(75.px()),
+        ))
+        .must_fill_major_axis(true)
+        .gap(0.px())
+    }
+
+    fn settings_view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let panel = map_action(
+            lens(Settings::view, |state: &mut Self, ()| &mut state.settings),
+            |state: &mut Self, ()| {
+                state.viewport.set_decades(state.settings.visible_decades);
+                if let Some(split) = &mut state.split {
+                    split.follow(&state.viewport);
+                }
+                let _ = state.settings.save();
+            },
+        );
+        let profile = map_action(
+            lens(AnimationProfile::view, |state: &mut Self, ()| {
+                &mut state.profile
+            }),
+            |state: &mut Self, ()| {
+                state.mark_edited();
+                let _ = state.save();
+            },
+        );
+        let sound = cfg!(feature = "sound").then(|| {
+            flex_col((
+                map_action(
+                    lens(SoundSettings::view, |state: &mut Self, ()| {
+                        &mut state.settings.sound
+                    }),
+                    |state: &mut Self, ()| {
+                        let _ = state.settings.save();
+                    },
+                ),
+                map_action(
+                    lens(SoundFiles::view, |state: &mut Self, ()| {
+                        &mut state.sound_files
+                    }),
+                    |state: &mut Self, ()| state.sound_files_changed(),
+                ),
+            ))
+        });
+        let export = text_button(
+            match &self.export_job {
+                Some(job) if job.is_cancelled() => "Cancelling…",
+                Some(_) => "Cancel export",
+                None => "Export frames",
+            },
+            |state: &mut Self| match state.export_job {
+                Some(_) => state.cancel_export(),
+                None => state.start_export(),
+            },
+        );
+        let controls = flex_row((
+            text_button("Edit data", |state: &mut Self| state.set_tab(Tab::Data)),
+            text_button("Back to preview", |state: &mut Self| {
+                state.set_tab(Tab::Preview)
+            }),
+            export,
+        ))
         .must_fill_major_axis(true)
         .main_axis_alignment(MainAxisAlignment::Center)
         .background_color(Viewport::FOOTER_AREA_COLOR);
-        flex_col((list.flex(1.), sized_box(controls).height(75.px())))
-            .must_fill_major_axis(true)
-            .gap(0.px())
+        flex_col((
+            portal(
+                sized_box(
+                    flex_col((panel, sound, profile))
+                        .cross_axis_alignment(CrossAxisAlignment::Start),
+                )
+                .width(800.px())
+                .padding(10.),
+            )
+            .flex(1.),
+            sized_box(controls).height(75.px()),
+        ))
+        .must_fill_major_axis(true)
+        .gap(0.px())
+    }
+
+    fn tab_view(&mut self, tab: Tab) -> Box<AnyWidgetView<Edit<Self>>> {
+        match tab {
+            Tab::Data => self.data_view().boxed(),
+            Tab::Preview => self
+                .viewport
+                .view(
+                    &self.things,
+                    self.comparison_label().as_deref(),
+                    self.split.is_some(),
+                    self.settings,
+                )
+                .boxed(),
+            Tab::Settings => self.settings_view().boxed(),
+        }
     }
 
     pub fn view(&mut self) -> impl Iterator<Item = WindowView<Self>> + use<> {
+        self.apply_settings();
+        let remote = self.remote_url.clone().map(|url| {
+            task(
+                move |proxy, _| {
+                    let url = url.clone();
+                    async move {
+                        let result = open_url(&url, &cache_file(&url)).await;
+                        let _ = proxy.message(result);
+                    }
+                },
+                |state: &mut State, result| state.finish_remote(result),
+            )
+        });
+        let export = self.export_job.clone().map(|job| {
+            task(
+                move |proxy, _| {
+                    let _ = job.spawn(move |message| {
+                        let _ = proxy.message(message);
+                    });
+                    async {}
+                },
+                |state: &mut State, message| state.on_export_message(message),
+            )
+        });
         std::iter::once(
             window(
                 self.window_id,
-                format!("Scale Comparison{}", self.viewport.animation.info()),
-                indexed_stack((self.data_view(), self.viewport.view())).active(self.tab as usize),
+                self.window_title(),
+                fork(
+                    indexed_stack(
+                        Tab::ALL
+                            .iter()
+                            .map(|&tab| self.tab_view(tab))
+                            .collect::<Vec<_>>(),
+                    )
+                    .active(self.tab.index()),
+                    (remote, export),
+                ),
             )
             .with_options(|options: xilem::WindowOptions<_>| {
-                options.on_close(|state: &mut State| state.running = false)
+                options.on_close(State::request_close)
             }),
         )
+        .chain(self.confirm_close.then(|| {
+            window(self.close_window_id, "Unsaved changes", self.close_view()).with_options(
+                |options: xilem::WindowOptions<_>| options.on_close(State::cancel_close),
+            )
+        }))
+        .chain(self.show_about.then(|| {
+            window(
+                self.about_window_id,
+                "About Scale Comparison",
+                Diagnostics::collect(&PROJECT_DIRS, self.data_file.as_deref()).view(),
+            )
+            .with_options(|options: xilem::WindowOptions<_>| {
+                options.on_close(|state: &mut State| state.show_about = false)
+            })
+        }))
+        .chain(self.show_whats_new.then(|| {
+            window(
+                self.whats_new_window_id,
+                "What's new",
+                changelog::view(self.settings.last_seen_version),
+            )
+            .with_options(|options: xilem::WindowOptions<_>| {
+                options.on_close(|state: &mut State| state.dismiss_whats_new())
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thing::Orientation;
+    use crate::viewport::PlayRange;
+
+    #[test]
+    fn test_tab_order() {
+        for (index, tab) in Tab::ALL.into_iter().enumerate() {
+            assert_eq!(tab.index(), index);
+            assert_eq!(Tab::from_number(index + 1), Some(tab));
+            assert_eq!(tab.next().previous(), tab);
+        }
+        assert_eq!(Tab::Settings.next(), Tab::Data);
+        assert_eq!(Tab::Data.previous(), Tab::Settings);
+        assert_eq!(Tab::from_number(0), None);
+        assert_eq!(Tab::from_number(Tab::ALL.len() + 1), None);
+    }
+
+    #[test]
+    fn test_multiply_values() {
+        let mut state = State::new(
+            vec![
+                Thing::new("Blink", 0.3),
+                Thing::new("Day", 86400.),
+                Thing::new("Century", (3.156, 9)),
+            ],
+            Settings::default(),
+        );
+        state.settings.record_history = true;
+        let before = state
+            .things
+            .iter()
+            .map(|thing| thing.value.inner())
+            .collect::<Vec<_>>();
+        let factor = ENumber::new(3.156, 7);
+        state.multiply_values(factor);
+        for (thing, before) in state.things.iter().zip(&before) {
+            assert_eq!(thing.value.inner(), *before * factor);
+            assert_eq!(thing.history.last().unwrap().value, *before);
+        }
+        assert_eq!(
+            state.things[2].value.inner(),
+            ENumber::new(3.156 * 3.156, 16)
+        );
+
+        // reverting from the history is a value edit like any other
+        let previous = state.things[0].revert(0).unwrap();
+        let action = state.things[0].committed(previous, true);
+        state.thing_action(0, action);
+        assert_eq!(state.things[0].value.inner(), before[0]);
+        assert_eq!(state.things[0].history.last().unwrap().value, previous);
+        state.undo_last();
+        assert_eq!(state.things[0].value.inner(), previous);
+    }
+
+    #[test]
+    fn test_recent_first_only_reorders_the_list() {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 31556952.),
+        ];
+        things[0].modified = None;
+        things[1].modified = Some(20);
+        things[2].modified = Some(10);
+        let mut state = State::new(things, Settings::default());
+        let ids = state
+            .things
+            .iter()
+            .map(|thing| thing.id)
+            .collect::<Vec<_>>();
+        let edits = state.edits;
+        assert_eq!(state.display_order(), [0, 1, 2]);
+        state.recent_first = true;
+        assert_eq!(state.display_order(), [1, 2, 0]);
+        let unchanged = state
+            .things
+            .iter()
+            .map(|thing| thing.id)
+            .collect::<Vec<_>>();
+        assert_eq!(unchanged, ids);
+        assert_eq!(state.edits, edits);
+    }
+
+    #[test]
+    fn test_multiply_rejects_bad_factors_and_undoes_in_one_step() {
+        for text in ["0", "-2", "inf", "NaN", "two"] {
+            assert!(State::parse_factor(text).is_err(), "{text}");
+        }
+        assert_eq!(State::parse_factor("2"), Ok(ENumber::from(2.)));
+
+        let mut state = State::new(
+            vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)],
+            Settings::default(),
+        );
+        state.multiply_values(ENumber::from(-1.));
+        assert_eq!(state.things[0].value.inner(), ENumber::from(0.3));
+        assert!(state.undo.is_empty());
+
+        state.things[1].live = true;
+        state.multiply_values(ENumber::from(2.));
+        assert_eq!(state.things[0].value.inner(), ENumber::from(0.6));
+        assert_eq!(state.things[1].value.inner(), ENumber::from(86400.));
+        assert_eq!(state.undo.len(), 1);
+        state.undo.undo(&mut state.things);
+        assert_eq!(state.things[0].value.inner(), ENumber::from(0.3));
+        assert!(state.undo.is_empty());
+    }
+
+    #[test]
+    fn test_one_undo_stack_for_values_styles_and_deletes() {
+        let mut state = State::new(
+            vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)],
+            Settings {
+                record_history: true,
+                ..Settings::default()
+            },
+        );
+        let ids = state
+            .things
+            .iter()
+            .map(|thing| thing.id)
+            .collect::<Vec<_>>();
+        let previous = state.things[0].value.nudge(1.).unwrap();
+        let action = state.things[0].committed(previous, true);
+        state.thing_action(0, action);
+        assert_eq!(state.things[0].history.len(), 1);
+
+        state.selection.ids.extend(ids.iter().copied());
+        state.bulk_edit(BulkEdit::Marker(MarkerStyle::Dot));
+        state.thing_action(1, Some(ThingAction::Delete));
+        assert_eq!(state.things.len(), 1);
+        assert_eq!(state.undo.len(), 3);
+
+        state.undo_last();
+        assert_eq!(
+            state
+                .things
+                .iter()
+                .map(|thing| thing.id)
+                .collect::<Vec<_>>(),
+            ids
+        );
+        state.undo_last();
+        assert!(
+            state
+                .things
+                .iter()
+                .all(|thing| thing.marker == MarkerStyle::default())
+        );
+        state.undo_last();
+        assert_eq!(state.things[0].value.inner(), ENumber::from(0.3));
+        assert!(state.things[0].history.is_empty());
+        assert!(state.undo.is_empty());
+    }
+
+    #[test]
+    fn test_context_actions() {
+        let things = vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)];
+        let ids = things.iter().map(|thing| thing.id).collect::<Vec<_>>();
+        let mut state = State::new(things, Settings::default());
+        state.context_action(ids[1], MenuItem::SetReference);
+        assert_eq!(state.viewport.reference, Some(ids[1]));
+        state.context_action(ids[0], MenuItem::SetReference);
+        assert_eq!(state.viewport.reference, Some(ids[0]));
+        state.context_action(ids[0], MenuItem::SetReference);
+        assert_eq!(state.viewport.reference, None);
+        state.context_action(ids[1], MenuItem::Center);
+        assert_eq!(state.viewport.shift, 2.);
+        state.context_action(u64::MAX, MenuItem::Center);
+        assert_eq!(state.viewport.shift, 2.);
+    }
+
+    #[test]
+    fn test_exponent_range_fits_the_data() {
+        let fixture = format!("{}/tests/fixtures/good.json", env!("CARGO_MANIFEST_DIR"));
+        let state = State::load_from(fixture, Settings::default()).unwrap();
+        let range = state.exponent_range();
+        for thing in &state.things {
+            let exponent = thing.value.inner().erect().1;
+            assert!(*range.start() < exponent && exponent < *range.end());
+        }
+        assert_eq!(range, -24.0..=7.0);
+
+        let state = State::new(Vec::new(), Settings::default());
+        assert_eq!(state.exponent_range(), State::DEFAULT_EXPONENT_RANGE);
+        let state = State::new(vec![Thing::new("Minute", 60.)], Settings::default());
+        assert_eq!(state.exponent_range(), 0.0..=3.0);
+    }
+
+    #[test]
+    fn test_surprise_is_seeded() {
+        let mut state = State::new(vec![Thing::new("Blink", 0.3)], Settings::default());
+        state.surprise(42);
+        assert_eq!(state.things.len(), State::SURPRISE_COUNT);
+        assert!(state.name.starts_with("Surprise #"));
+        let names = state
+            .things
+            .iter()
+            .map(|thing| thing.name.clone())
+            .collect::<Vec<_>>();
+        state.surprise(42);
+        assert!(
+            state
+                .things
+                .iter()
+                .map(|thing| &thing.name)
+                .eq(names.iter())
+        );
+    }
+
+    #[test]
+    fn test_play_range_is_saved_with_the_dataset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let mut state = State::new(
+            vec![
+                Thing::new("Blink", 0.3),
+                Thing::new("Day", 86400.),
+                Thing::new("Year", 31556952.),
+            ],
+            Settings::default(),
+        );
+        let range = PlayRange {
+            start: 1,
+            end: Some(2),
+        };
+        state.viewport.set_play_range(range, &state.things);
+        state.set_data_file(&path);
+        state.save().unwrap();
+
+        let state = State::load_from(&path, Settings::default()).unwrap();
+        assert_eq!(state.viewport.play_range, range);
+    }
+
+    #[test]
+    fn test_damaged_comparison_keeps_the_dataset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let mut state = State::new(
+            vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)],
+            Settings::default(),
+        );
+        state.set_data_file(&path);
+        state.save().unwrap();
+        fs::write(dir.path().join("comparison.json"), "{\"source\": ").unwrap();
+
+        let mut state = State::open(&path, Settings::default()).unwrap();
+        assert_eq!(state.things.len(), 2);
+        assert!(state.secondary.is_none());
+        assert!(
+            state
+                .notices
+                .iter()
+                .any(|notice| notice.level == Level::Error)
+        );
+        state.save().unwrap();
+        assert!(!dir.path().join("comparison.json").exists());
+        let backup = dir.path().join("comparison.corrupt.json");
+        assert_eq!(fs::read_to_string(backup).unwrap(), "{\"source\": ");
+    }
+
+    #[test]
+    fn test_preview_saves_only_with_autosave() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let mut state = State::new(
+            vec![Thing::new("Day", 86400.), Thing::new("Blink", 0.3)],
+            Settings::default(),
+        );
+        state.set_data_file(&path);
+        assert!(state.has_unsaved_changes());
+        state.save().unwrap();
+        assert!(!state.has_unsaved_changes());
+        let on_disk = fs::read_to_string(&path).unwrap();
+
+        state.settings.autosave = false;
+        state.things.push(Thing::new("Year", 3.156e7));
+        state.mark_edited();
+        state.preview();
+        assert_eq!(state.tab, Tab::Preview);
+        assert_eq!(state.things[0].name, "Blink");
+        assert_eq!(
+            state.viewport.ids,
+            state
+                .things
+                .iter()
+                .map(|thing| thing.id)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), on_disk);
+        assert!(state.has_unsaved_changes());
+
+        state.request_close();
+        assert!(state.confirm_close);
+        assert!(state.running);
+
+        state.settings.autosave = true;
+        state.preview();
+        assert!(!state.has_unsaved_changes());
+        assert_ne!(fs::read_to_string(&path).unwrap(), on_disk);
+        state.confirm_close = false;
+        state.request_close();
+        assert!(!state.running);
+    }
+
+    #[test]
+    fn test_downloaded_dataset_asks_where_to_save() {
+        let named = r#"{"project":"Cosmos","name":"Timescales","author":"A. Author","things":[{"name":"Week","value":[{"significand":6.048,"exponent":5.0}]}]}"#;
+        let mut state = State::from_url(
+            "https://example.com/things.json".into(),
+            Settings::default(),
+        );
+        state.finish_remote(Ok(RemoteDataset {
+            dataset: Dataset::from_json(named).unwrap(),
+            notices: Vec::new(),
+        }));
+        assert_eq!(state.project, "Cosmos");
+        assert_eq!(state.name, "Timescales");
+        assert_eq!(state.author, "A. Author");
+        assert_eq!(state.things[0].name, "Week");
+        assert!(state.has_unsaved_changes());
+
+        state.save_or_ask();
+        assert_eq!(
+            state.save_as,
+            Some(State::default_data_file().display().to_string())
+        );
+        assert_eq!(state.notices.iter().count(), 0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let blocked = dir.path().join("blocker");
+        fs::write(&blocked, "").unwrap();
+        let path = blocked.join("data.json");
+        state.save_as = Some(path.display().to_string());
+        state.finish_save_as(false);
+        assert_eq!(state.data_file(), None);
+        assert_eq!(state.save_as, Some(path.display().to_string()));
+
+        let path = dir.path().join("timescales.json");
+        state.save_as = Some(path.display().to_string());
+        state.finish_save_as(false);
+        assert_eq!(state.data_file(), Some(path.as_path()));
+        assert_eq!(state.save_as, None);
+        assert!(!state.has_unsaved_changes());
+        let saved = Dataset::from_json(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.name, "Timescales");
+    }
+
+    #[test]
+    fn test_save_and_quit_asks_for_a_path_first() {
+        let mut state = State::new(vec![Thing::new("Week", 604800.)], Settings::default());
+        state.request_close();
+        state.save_and_quit();
+        assert!(state.running);
+        assert!(state.save_as.is_some());
+
+        let dir = tempfile::tempdir().unwrap();
+        state.save_as = Some(dir.path().join("data.json").display().to_string());
+        state.finish_save_as(true);
+        assert!(!state.running);
+        assert!(dir.path().join("data.json").exists());
+
+        let mut state = State::new(vec![Thing::new("Week", 604800.)], Settings::default());
+        state.request_close();
+        state.save_and_quit();
+        state.cancel_close();
+        assert!(!state.confirm_close);
+        assert_eq!(state.save_as, None);
+    }
+
+    #[test]
+    fn test_live_values_do_not_count_as_changes() {
+        let mut live = Thing::new("Since launch", 1.);
+        live.live = true;
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = State::new(vec![live], Settings::default());
+        state.set_data_file(dir.path().join("data.json"));
+        state.save().unwrap();
+        state.update_live();
+        assert!(!state.has_unsaved_changes());
+        state.sort_things();
+        assert!(!state.has_unsaved_changes());
+    }
+
+    #[test]
+    fn test_export_progress_in_window_title() {
+        let mut state = State::new(Vec::new(), Settings::default());
+        state.name = "Timescales".to_string();
+        let title = state.window_title();
+        let cancel = std::sync::atomic::AtomicBool::new(false);
+        let mut titles = Vec::new();
+        export::run_frames(
+            4,
+            &cancel,
+            |_| Ok(0),
+            |message| {
+                state.on_export_message(message);
+                titles.push(state.window_title());
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            titles[1],
+            "Exporting… 50% (frame 2/4) — Scale Comparison — Timescales"
+        );
+        assert_eq!(titles.last(), Some(&title));
+        assert!(
+            state
+                .notices
+                .iter()
+                .any(|notice| notice.message.starts_with("Exported 4 frames"))
+        );
+    }
+
+    #[test]
+    fn test_export_job_clears_on_failure() {
+        let mut state = State::new(vec![Thing::new("Blink", 0.3)], Settings::default());
+        state.start_export();
+        assert!(state.export_job.is_some());
+        state.cancel_export();
+        assert!(state.export_job.as_ref().unwrap().is_cancelled());
+        state.start_export();
+        assert!(state.export_job.as_ref().unwrap().is_cancelled());
+
+        state.on_export_message(ExportMessage::Progress(ExportProgress {
+            frame: 1,
+            total: 4,
+        }));
+        state.on_export_message(ExportMessage::Failed("disk full".to_string()));
+        assert!(state.export.is_none() && state.export_job.is_none());
+        let last = state.notices.iter().last().unwrap();
+        assert_eq!(
+            (last.level, last.message.as_str()),
+            (Level::Error, "disk full")
+        );
+    }
+
+    #[test]
+    fn test_viewport_repair_pushes_one_notice() {
+        let mut state = State::new(
+            vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)],
+            Settings::default(),
+        );
+        for _ in 0..3 {
+            state.viewport.scale = f64::NAN;
+            state.update_animation(Animation::FRAME_SECS);
+            assert!(state.viewport.scale.is_finite());
+        }
+        let repairs = state
+            .notices
+            .iter()
+            .filter(|notice| notice.message == Viewport::REPAIR_NOTICE)
+            .count();
+        assert_eq!(repairs, 1);
+        assert_eq!(state.viewport.repairs, 3);
+    }
+
+    #[test]
+    fn test_split_viewports_stay_locked() {
+        let mut state = State::new(
+            vec![
+                Thing::new("Blink", 0.3),
+                Thing::new("Day", 86400.),
+                Thing::new("Century", 3.156e9),
+            ],
+            Settings::default(),
+        );
+        state.set_split(true);
+        assert!(state.split.is_none());
+
+        state.secondary = Some(vec![
+            Thing::new("Heartbeat", 0.8),
+            Thing::new("Year", 3.156e7),
+        ]);
+        state.set_split(true);
+        state.viewport.animation.active = true;
+        while !state.viewport.animation.finished() {
+            state.update_animation(Animation::FRAME_SECS);
+            let split = state.split.as_ref().unwrap();
+            assert_eq!(split.scale, state.viewport.scale);
+            assert_eq!(split.shift, state.viewport.shift);
+            assert_eq!(split.animation.step, state.viewport.animation.step);
+        }
+        assert_eq!(state.split.as_ref().unwrap().shift, 3.);
+
+        state.set_split(false);
+        assert!(state.split.is_none());
+    }
+
+    #[test]
+    fn test_settings_reach_both_panes_before_painting() {
+        let mut state = State::new(
+            vec![Thing::new("Blink", 0.3), Thing::new("Day", 86400.)],
+            Settings::default(),
+        );
+        state.secondary = Some(vec![Thing::new("Year", 3.156e7)]);
+        state.set_split(true);
+        state.settings.footer_height = Viewport::MAX_FOOTER_HEIGHT;
+        state.settings.bar_style.orientation = Orientation::Horizontal;
+        state.viewport.shift = 2.;
+        state.apply_settings();
+        let split = state.split.as_ref().unwrap();
+        for viewport in [&state.viewport, split] {
+            assert_eq!(viewport.footer_height, Viewport::MAX_FOOTER_HEIGHT);
+            assert_eq!(viewport.orientation, Orientation::Horizontal);
+        }
+        assert_eq!(state.viewport.slot_offsets.len(), 2);
+        assert_eq!(split.slot_offsets.len(), 1);
+        assert_eq!(split.shift, 2.);
     }
 }
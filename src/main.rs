@@ -1,8 +1,28 @@
-use scale_comparison::State;
+use scale_comparison::App;
+use scale_comparison::check::check_file;
 use xilem::winit::error::EventLoopError;
-use xilem::{EventLoop, Xilem};
+
+fn check(path: &str) -> i32 {
+    match check_file(path) {
+        Ok(report) => {
+            println!("{report}");
+            if report.is_valid() { 0 } else { 1 }
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            2
+        }
+    }
+}
 
 fn main() -> Result<(), EventLoopError> {
-    let app_state = State::load().unwrap_or(State::new(Vec::new()));
-    Xilem::new(app_state, State::view).run_in(EventLoop::with_user_event())
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let app = match args.as_slice() {
+        [command, path] if command == "check" || command == "--lint" => {
+            std::process::exit(check(path))
+        }
+        [flag, url] if flag == "--open-url" => App::new().with_url(url.clone()),
+        _ => App::new(),
+    };
+    app.run()
 }
@@ -1,14 +1,16 @@
 use std::cmp::Ordering;
 use std::num::ParseFloatError;
-use std::ops::{Div, Mul};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use xilem::WidgetView;
 use xilem::core::Edit;
 use xilem::view::{FlexExt, flex_row, text_button, text_input};
 
+use crate::expr::{ExprError, evaluate};
 use crate::units::{TimeUnit, UnitPrefix};
-use crate::utils::float_to_string;
+use crate::utils::{DEFAULT_SIGNIFICANT_DIGITS, float_to_string, float_to_string_with};
 
 #[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ENumber {
@@ -16,6 +18,36 @@ pub struct ENumber {
     exponent: f64,
 }
 
+impl Add<ENumber> for ENumber {
+    type Output = ENumber;
+    fn add(self, rhs: Self) -> Self::Output {
+        if rhs.significand == 0. {
+            return self;
+        }
+        let (big, small) = if self.significand == 0. || rhs.exponent > self.exponent {
+            (rhs, self)
+        } else {
+            (self, rhs)
+        };
+        let small = small.significand * 10_f64.powf(small.exponent - big.exponent);
+        Self::normalize(big.significand + small, big.exponent)
+    }
+}
+
+impl Sub<ENumber> for ENumber {
+    type Output = ENumber;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Neg for ENumber {
+    type Output = ENumber;
+    fn neg(self) -> Self::Output {
+        Self::normalize(-self.significand, self.exponent)
+    }
+}
+
 impl Mul<ENumber> for ENumber {
     type Output = ENumber;
     fn mul(self, rhs: Self) -> Self::Output {
@@ -56,6 +88,44 @@ impl std::fmt::Display for ENumber {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseENumberError {
+    Float(ParseFloatError),
+    NonFinite,
+}
+
+impl std::fmt::Display for ParseENumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseENumberError::Float(err) => write!(f, "{err}"),
+            ParseENumberError::NonFinite => write!(f, "number is not finite"),
+        }
+    }
+}
+
+impl std::error::Error for ParseENumberError {}
+
+impl From<ParseFloatError> for ParseENumberError {
+    fn from(value: ParseFloatError) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl FromStr for ENumber {
+    type Err = ParseENumberError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (significand, exponent) = match s.rfind(['e', 'E']) {
+            Some(i) => (s[..i].parse::<f64>()?, s[i + 1..].parse::<f64>()?),
+            None => (s.parse::<f64>()?, 0.),
+        };
+        if !significand.is_finite() || !exponent.is_finite() {
+            return Err(ParseENumberError::NonFinite);
+        }
+        Ok(Self::normalize(significand, exponent))
+    }
+}
+
 impl From<f64> for ENumber {
     fn from(value: f64) -> Self {
         ENumber::new(value, 0)
@@ -91,6 +161,31 @@ impl ENumber {
         Self::normalize(1., exponent)
     }
 
+    // Moves the significand to the next multiple of `step` in its direction,
+    // carrying into the exponent at 10 and borrowing from it below 1.
+    pub fn nudged(self, step: f64) -> Self {
+        const SNAP: f64 = 1e-6;
+        if self.significand <= 0. || step == 0. {
+            return self;
+        }
+        let per_unit = (1. / step.abs()).round();
+        let grid = self.significand * per_unit;
+        let snapped = match grid.round() {
+            nearest if (grid - nearest).abs() < SNAP => nearest,
+            _ if step > 0. => grid.floor(),
+            _ => grid.ceil(),
+        };
+        let next = (snapped + step.signum()) / per_unit;
+        if next < 1. {
+            return Self::normalize(10. - 1. / per_unit, self.exponent - 1.);
+        }
+        Self::normalize(next, self.exponent)
+    }
+
+    pub fn nudged_exponent(self, steps: i32) -> Self {
+        Self::normalize(self.significand, self.exponent + steps as f64)
+    }
+
     pub fn significand(&self) -> f64 {
         self.significand
     }
@@ -99,6 +194,16 @@ impl ENumber {
         self.exponent
     }
 
+    pub fn relative_eq(&self, other: &Self, epsilon: f64) -> bool {
+        let shift = self.exponent - other.exponent;
+        if shift.abs() > 1. {
+            return false;
+        }
+        let a = self.significand * 10_f64.powf(shift);
+        let b = other.significand;
+        (a - b).abs() <= epsilon * a.abs().max(b.abs())
+    }
+
     pub fn total_cmp(&self, other: &Self) -> Ordering {
         match self.exponent.total_cmp(&other.exponent) {
             std::cmp::Ordering::Equal => self.significand.total_cmp(&other.significand),
@@ -107,12 +212,22 @@ impl ENumber {
     }
 
     pub fn fmt_exp_break(&self, exp_break: u32) -> String {
+        self.fmt_exp_break_with(exp_break, DEFAULT_SIGNIFICANT_DIGITS)
+    }
+
+    pub fn fmt_exp_break_with(&self, exp_break: u32, digits: usize) -> String {
         let break_range = -(exp_break as f64)..=(exp_break as f64);
         if break_range.contains(&self.exponent) {
-            float_to_string(self.collapse().expect("Low exponents sould be collapsible"))
-                .to_string()
+            float_to_string_with(
+                self.collapse().expect("Low exponents sould be collapsible"),
+                digits,
+            )
         } else {
-            format!("{}e{}", float_to_string(self.significand), self.exponent)
+            format!(
+                "{}e{}",
+                float_to_string_with(self.significand, digits),
+                self.exponent
+            )
         }
     }
 
@@ -138,13 +253,14 @@ impl ENumber {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug)]
 pub struct ENumberEditor {
     pub editing: bool,
     pub unit_prefix: UnitPrefix,
     pub time_unit: TimeUnit,
     pub significand: String,
     pub exponent: String,
+    pub error: Option<String>,
 }
 
 impl From<ENumber> for ENumberEditor {
@@ -155,17 +271,28 @@ impl From<ENumber> for ENumberEditor {
             time_unit: TimeUnit::default(),
             significand: value.significand.to_string(),
             exponent: value.exponent.to_string(),
+            error: None,
         }
     }
 }
 
 impl TryInto<ENumber> for ENumberEditor {
-    type Error = ParseFloatError;
+    type Error = ExprError;
     fn try_into(self) -> Result<ENumber, Self::Error> {
-        let significand: f64 = self.significand.parse()?;
-        let exponent = self.exponent.parse()?;
+        let significand = evaluate(&self.significand)?;
+        let exponent = evaluate(&self.exponent)
+            .and_then(|exponent| {
+                exponent.collapse().ok_or(ExprError {
+                    column: 1,
+                    message: "too large".to_string(),
+                })
+            })
+            .map_err(|err| ExprError {
+                message: format!("exponent {}", err.message),
+                ..err
+            })?;
         let unit = self.unit_prefix.factor() * self.time_unit.factor();
-        Ok(ENumber::normalize(significand * unit, exponent))
+        Ok(ENumber::normalize(significand.significand(), significand.exponent() + exponent) * unit)
     }
 }
 
@@ -175,15 +302,18 @@ impl ENumberEditor {
             text_input(self.significand.clone(), |state: &mut Self, value| {
                 state.significand = value;
             })
-            .placeholder("significand")
+            .placeholder("Significand or expression, e.g. 8*MINUTE+20")
             .flex(1.),
             text_input(self.exponent.clone(), |state: &mut Self, value| {
                 state.exponent = value;
             })
-            .placeholder("exponent")
+            .placeholder("Exponent (power of ten)")
             .flex(1.),
             text_button(
-                self.unit_prefix.to_string(),
+                match &self.unit_prefix {
+                    UnitPrefix::None => "No prefix".to_string(),
+                    prefix => format!("Prefix: {prefix}"),
+                },
                 |state: &mut Self| match state.unit_prefix {
                     UnitPrefix::None => state.unit_prefix = UnitPrefix::Kilo,
                     UnitPrefix::Kilo => state.unit_prefix = UnitPrefix::Mega,
@@ -192,15 +322,16 @@ impl ENumberEditor {
                     UnitPrefix::Tera => state.unit_prefix = UnitPrefix::None,
                 },
             ),
-            text_button(self.time_unit.to_string(), |state: &mut Self| {
-                match state.time_unit {
+            text_button(
+                format!("Unit: {}", self.time_unit),
+                |state: &mut Self| match state.time_unit {
                     TimeUnit::Second => state.time_unit = TimeUnit::Minute,
                     TimeUnit::Minute => state.time_unit = TimeUnit::Hour,
                     TimeUnit::Hour => state.time_unit = TimeUnit::Day,
                     TimeUnit::Day => state.time_unit = TimeUnit::Year,
                     TimeUnit::Year => state.time_unit = TimeUnit::Second,
-                }
-            }),
+                },
+            ),
         ))
     }
 }
@@ -241,6 +372,88 @@ mod tests {
             .for_each(|test| assert_eq!(test.0, (test.0 * test.1) / test.1));
     }
 
+    #[test]
+    fn test_enumber_add_sub() {
+        assert_eq!(ENumber::from(8.) + ENumber::from(2.), ENumber::from(10.));
+        assert_eq!(
+            ENumber::from(480.) + ENumber::from(20.),
+            ENumber::from(500.)
+        );
+        assert_eq!(ENumber::from(2.) - ENumber::from(8.), ENumber::from(-6.));
+        assert_eq!(
+            ENumber::from(0.) + ENumber::new(3., 400),
+            ENumber::new(3., 400)
+        );
+        assert_eq!(
+            ENumber::new(3., 400) - ENumber::from(0.),
+            ENumber::new(3., 400)
+        );
+        assert_eq!(
+            ENumber::new(1., 500) + ENumber::from(1.),
+            ENumber::new(1., 500)
+        );
+        assert_eq!(
+            ENumber::new(5., 300) - ENumber::new(5., 300),
+            ENumber::from(0.)
+        );
+        assert_eq!(-ENumber::new(2., -40), ENumber::new(-2., -40));
+        assert_eq!(
+            ENumber::new(1., -400) + ENumber::from(0.),
+            ENumber::new(1., -400)
+        );
+    }
+
+    #[test]
+    fn test_nudged_carries_and_borrows() {
+        let close = |a: ENumber, b: ENumber| a.relative_eq(&b, 1e-12);
+        assert_eq!(ENumber::new(9.95, 0).nudged(0.1), ENumber::new(1., 1));
+        assert_eq!(ENumber::new(9.99, 2).nudged(0.01), ENumber::new(1., 3));
+        assert_eq!(ENumber::new(9.95, 0).nudged(1.), ENumber::new(1., 1));
+        assert!(close(
+            ENumber::new(9.95, 0).nudged(0.01),
+            ENumber::new(9.96, 0)
+        ));
+        assert!(close(
+            ENumber::new(2.34, 5).nudged(0.1),
+            ENumber::new(2.4, 5)
+        ));
+        assert!(close(
+            ENumber::new(2.34, 5).nudged(-0.1),
+            ENumber::new(2.3, 5)
+        ));
+        assert!(close(
+            ENumber::new(1.05, 0).nudged(-0.1),
+            ENumber::new(1., 0)
+        ));
+
+        assert!(close(
+            ENumber::new(1., 1).nudged(-0.1),
+            ENumber::new(9.9, 0)
+        ));
+        assert!(close(
+            ENumber::new(1., -3).nudged(-0.01),
+            ENumber::new(9.99, -4)
+        ));
+        assert!(close(ENumber::new(1., 0).nudged(-1.), ENumber::new(9., -1)));
+
+        let mut value = ENumber::new(9.8, 0);
+        for _ in 0..3 {
+            value = value.nudged(0.1);
+        }
+        assert!(close(value, ENumber::new(1.1, 1)));
+        for _ in 0..3 {
+            value = value.nudged(-0.1);
+        }
+        assert!(close(value, ENumber::new(9.8, 0)));
+
+        assert_eq!(ENumber::new(3., 2).nudged_exponent(1), ENumber::new(3., 3));
+        assert_eq!(
+            ENumber::new(3., 2).nudged_exponent(-4),
+            ENumber::new(3., -2)
+        );
+        assert_eq!(ENumber::from(0.).nudged(0.1), ENumber::from(0.));
+    }
+
     #[test]
     fn test_enumber_normalize() {
         assert_eq!(ENumber::new(12.0, 0), ENumber::new(1.2, 1));
@@ -255,4 +468,15 @@ mod tests {
         assert_eq!(ENumber::new(3.4, -76).collapse(), Some(3.4e-76));
         assert_eq!(ENumber::new(3.4, 309).collapse(), None);
     }
+
+    #[test]
+    fn test_enumber_from_str() {
+        assert_eq!("2.3e-23".parse(), Ok(ENumber::new(2.3, -23)));
+        assert_eq!(" 604800 ".parse(), Ok(ENumber::new(6.048, 5)));
+        assert_eq!("1e32000".parse(), Ok(ENumber::new(1., 32000)));
+        assert_eq!("-12E3".parse(), Ok(ENumber::new(-1.2, 4)));
+        assert_eq!("inf".parse::<ENumber>(), Err(ParseENumberError::NonFinite));
+        assert!("soon".parse::<ENumber>().is_err());
+        assert!("1e".parse::<ENumber>().is_err());
+    }
 }
@@ -0,0 +1,144 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use xilem::core::Edit;
+use xilem::palette::css;
+use xilem::style::Style;
+use xilem::view::{FlexExt, flex_col, flex_row, label, text_button};
+use xilem::{Color, WidgetView};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn color(self) -> Color {
+        match self {
+            Level::Info => css::LIGHT_GRAY,
+            Level::Warning => css::ORANGE,
+            Level::Error => css::RED,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub level: Level,
+    pub message: String,
+}
+
+impl Notice {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+        }
+    }
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(Level::Info, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Level::Warning, message)
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Level::Error, message)
+    }
+}
+
+impl std::fmt::Display for Notice {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.level, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Notices(Vec<Notice>, Option<PathBuf>);
+
+impl Notices {
+    pub fn push(&mut self, notice: Notice) {
+        if let Some(log) = &self.1 {
+            let _ = append_log(log, &notice);
+        }
+        self.0.push(notice);
+    }
+
+    // Also writes every notice to `log`, starting with the ones already shown.
+    pub fn log_to(&mut self, log: PathBuf) {
+        for notice in &self.0 {
+            let _ = append_log(&log, notice);
+        }
+        self.1 = Some(log);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Notice> {
+        self.0.iter()
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let rows = self
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, notice)| {
+                flex_row((
+                    label(notice.message.clone())
+                        .color(notice.level.color())
+                        .flex(1.),
+                    text_button("Dismiss", move |notices: &mut Self| {
+                        if i < notices.0.len() {
+                            notices.0.remove(i);
+                        }
+                    }),
+                ))
+            })
+            .collect::<Vec<_>>();
+        flex_col(rows)
+    }
+}
+
+impl Extend<Notice> for Notices {
+    fn extend<T: IntoIterator<Item = Notice>>(&mut self, iter: T) {
+        for notice in iter {
+            self.push(notice);
+        }
+    }
+}
+
+fn append_log(log: &Path, notice: &Notice) -> std::io::Result<()> {
+    if let Some(parent) = log.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(log)?;
+    writeln!(file, "{notice}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notices_are_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("logs").join("scale-comparison.log");
+        let mut notices = Notices::default();
+        notices.push(Notice::error("Could not read data.json"));
+        notices.log_to(log.clone());
+        notices.extend([Notice::info("Saved")]);
+        assert_eq!(notices.iter().count(), 2);
+        assert_eq!(
+            fs::read_to_string(&log).unwrap(),
+            "Error: Could not read data.json\nInfo: Saved\n"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::thing::Thing;
+
+pub const PRESETS: [(&str, f64, i32); 29] = [
+    ("Planck time", 5.391, -44),
+    ("Light crossing a proton", 3.3, -24),
+    ("Half-life of hydrogen-7", 2.3, -23),
+    ("Light crossing an atom", 3.3, -19),
+    ("Attosecond laser pulse", 6.7, -17),
+    ("One CPU clock cycle", 3.3, -10),
+    ("Light travelling one metre", 3.336, -9),
+    ("Camera flash", 1., -3),
+    ("Hummingbird wing beat", 1.5, -2),
+    ("Blink of an eye", 3., -1),
+    ("Heartbeat", 8., -1),
+    ("Light from the Moon", 1.28, 0),
+    ("Light from the Sun", 4.99, 2),
+    ("Lunch break", 3.6, 3),
+    ("Day", 8.64, 4),
+    ("Week", 6.048, 5),
+    ("Lunar month", 2.551, 6),
+    ("Year", 3.156, 7),
+    ("Human lifetime", 2.524, 9),
+    ("Recorded history", 1.578, 11),
+    ("Age of Homo sapiens", 9.467, 12),
+    ("Since the dinosaurs died out", 2.082, 15),
+    ("Age of the Earth", 1.434, 17),
+    ("Age of the universe", 4.35, 17),
+    ("Lifetime of a red dwarf", 3.156, 20),
+    ("End of the stellar era", 3.156, 21),
+    ("Proton decay lower bound", 5.2, 41),
+    ("Supermassive black hole evaporates", 3.156, 107),
+    ("Heat death of the universe", 3.156, 113),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0.max(1);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+}
+
+pub fn random_selection(rng: &mut Rng, n: usize, min_gap: f64) -> Vec<Thing> {
+    let mut candidates = PRESETS
+        .iter()
+        .map(|&(name, significand, exponent)| Thing::new(name, (significand, exponent)))
+        .collect::<Vec<_>>();
+    for i in (1..candidates.len()).rev() {
+        candidates.swap(i, rng.below(i + 1));
+    }
+    let mut selection: Vec<Thing> = Vec::new();
+    for thing in candidates {
+        if selection.len() == n {
+            break;
+        }
+        let scale = thing.scale();
+        if selection
+            .iter()
+            .all(|other| (other.scale() - scale).abs() >= min_gap)
+        {
+            selection.push(thing);
+        }
+    }
+    selection.sort_by(|a, b| a.value.total_cmp(&b.value));
+    selection
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::{MIN_DECADE_GAP, small_gaps};
+
+    fn scales(things: &[Thing]) -> Vec<f64> {
+        things.iter().map(Thing::scale).collect()
+    }
+
+    #[test]
+    fn test_random_selection_respects_constraints() {
+        for seed in [1, 7, 42, 2024] {
+            for (n, min_gap) in [(5, MIN_DECADE_GAP), (8, 3.), (3, 10.)] {
+                let selection = random_selection(&mut Rng::seeded(seed), n, min_gap);
+                assert_eq!(selection.len(), n, "seed {seed}");
+                let scales = scales(&selection);
+                assert!(scales.windows(2).all(|pair| pair[1] - pair[0] >= min_gap));
+                assert!(small_gaps(&scales).is_empty(), "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_selection_is_seeded() {
+        let names = |seed| {
+            random_selection(&mut Rng::seeded(seed), 6, MIN_DECADE_GAP)
+                .into_iter()
+                .map(|thing| thing.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(42), names(42));
+        assert_ne!(names(42), names(43));
+        assert!(random_selection(&mut Rng::seeded(1), 0, 1.).is_empty());
+        assert!(random_selection(&mut Rng::seeded(1), 100, 1.).len() < PRESETS.len());
+    }
+}
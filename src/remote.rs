@@ -0,0 +1,249 @@
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::{StatusCode, Url, redirect};
+
+use crate::PROJECT_DIRS;
+use crate::dataset::Dataset;
+use crate::notice::Notice;
+
+pub const MAX_DOWNLOAD_SIZE: usize = 8 * 1024 * 1024;
+pub const MAX_REDIRECTS: usize = 5;
+pub const TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug)]
+pub enum FetchError {
+    InvalidUrl(String),
+    Request(reqwest::Error),
+    Status(StatusCode),
+    TooLarge,
+    NotJson(serde_json::Error),
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FetchError::InvalidUrl(url) => write!(f, "Invalid URL: {url}"),
+            FetchError::Request(err) if err.is_redirect() => write!(f, "Too many redirects"),
+            FetchError::Request(err) if err.is_timeout() => write!(f, "Request timed out"),
+            FetchError::Request(err) => write!(f, "Request failed: {err}"),
+            FetchError::Status(status) => write!(f, "Server responded with {status}"),
+            FetchError::TooLarge => write!(
+                f,
+                "File exceeds the {} MB download limit",
+                MAX_DOWNLOAD_SIZE / (1024 * 1024)
+            ),
+            FetchError::NotJson(err) => write!(f, "File is not a valid dataset: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+#[derive(Debug)]
+pub struct Download {
+    pub final_url: Url,
+    pub redirected: bool,
+    pub body: String,
+}
+
+pub async fn fetch(url: &str, max_size: usize) -> Result<Download, FetchError> {
+    let url = Url::parse(url).map_err(|_| FetchError::InvalidUrl(url.to_string()))?;
+    let client = reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        .redirect(redirect::Policy::limited(MAX_REDIRECTS))
+        .build()
+        .map_err(FetchError::Request)?;
+    let mut response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(FetchError::Request)?;
+    if !response.status().is_success() {
+        return Err(FetchError::Status(response.status()));
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len > max_size as u64)
+    {
+        return Err(FetchError::TooLarge);
+    }
+    let final_url = response.url().clone();
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(FetchError::Request)? {
+        if body.len() + chunk.len() > max_size {
+            return Err(FetchError::TooLarge);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(Download {
+        redirected: final_url != url,
+        final_url,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+pub fn cache_file(url: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    PROJECT_DIRS
+        .cache_dir()
+        .join("remote")
+        .join(format!("{:016x}.json", hasher.finish()))
+}
+
+#[derive(Debug)]
+pub struct RemoteDataset {
+    pub dataset: Dataset,
+    pub notices: Vec<Notice>,
+}
+
+pub async fn open_url(url: &str, cache: &Path) -> Result<RemoteDataset, FetchError> {
+    match fetch(url, MAX_DOWNLOAD_SIZE).await {
+        Ok(download) => {
+            let dataset = Dataset::from_json(&download.body).map_err(FetchError::NotJson)?;
+            let mut notices = Vec::new();
+            if download.redirected {
+                notices.push(Notice::info(format!(
+                    "Redirected to {}",
+                    download.final_url
+                )));
+            }
+            let cached = cache
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::write(cache, &download.body));
+            if let Err(err) = cached {
+                notices.push(Notice::warning(format!(
+                    "Could not cache the download: {err}"
+                )));
+            }
+            Ok(RemoteDataset { dataset, notices })
+        }
+        Err(err) => {
+            let cached = fs::read_to_string(cache)
+                .ok()
+                .and_then(|text| Dataset::from_json(&text).ok());
+            match cached {
+                Some(dataset) => Ok(RemoteDataset {
+                    dataset,
+                    notices: vec![Notice::warning(format!("{err}, using the cached copy"))],
+                }),
+                None => Err(err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    const DATASET: &str = r#"[{"name":"Week","value":[{"significand":6.048,"exponent":5.0}]}]"#;
+
+    fn response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n{headers}\r\n{body}",
+            body.len()
+        )
+    }
+
+    fn serve(responses: Vec<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{address}")
+    }
+
+    #[tokio::test]
+    async fn test_open_url_caches_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("remote").join("things.json");
+        let url = serve(vec![response("200 OK", "", DATASET)]);
+        let dataset = open_url(&format!("{url}/things.json"), &cache)
+            .await
+            .unwrap();
+        assert_eq!(dataset.dataset.things.len(), 1);
+        assert!(dataset.notices.is_empty());
+        assert_eq!(fs::read_to_string(&cache).unwrap(), DATASET);
+    }
+
+    #[tokio::test]
+    async fn test_open_url_http_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = serve(vec![response("404 Not Found", "", "")]);
+        let result = open_url(&format!("{url}/things.json"), &dir.path().join("c.json")).await;
+        assert!(matches!(
+            result,
+            Err(FetchError::Status(StatusCode::NOT_FOUND))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_open_url_not_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = serve(vec![response("200 OK", "", "<html></html>")]);
+        let result = open_url(&format!("{url}/things.json"), &dir.path().join("c.json")).await;
+        assert!(matches!(result, Err(FetchError::NotJson(_))));
+    }
+
+    #[tokio::test]
+    async fn test_open_url_redirect() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = serve(vec![
+            response("302 Found", "Location: /moved.json\r\n", ""),
+            response("200 OK", "", DATASET),
+        ]);
+        let dataset = open_url(&format!("{url}/things.json"), &dir.path().join("c.json"))
+            .await
+            .unwrap();
+        assert_eq!(dataset.dataset.things.len(), 1);
+        assert_eq!(
+            dataset.notices,
+            [Notice::info(format!("Redirected to {url}/moved.json"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_size_limit() {
+        let url = serve(vec![response("200 OK", "", DATASET)]);
+        let result = fetch(&format!("{url}/things.json"), 16).await;
+        assert!(matches!(result, Err(FetchError::TooLarge)));
+    }
+
+    #[tokio::test]
+    async fn test_open_url_offline_uses_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("c.json");
+        fs::write(&cache, DATASET).unwrap();
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let dataset = open_url(&format!("http://{address}/things.json"), &cache)
+            .await
+            .unwrap();
+        assert_eq!(dataset.dataset.things.len(), 1);
+        assert_eq!(dataset.notices.len(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use crate::thing::{HistoryEntry, MarkerStyle, Thing};
+use crate::undo::StyleEdit;
+use crate::units::TimeUnit;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BulkEdit {
+    Marker(MarkerStyle),
+    DisplayUnit(Option<TimeUnit>),
+}
+
+// Returns what the things looked like before, for the undo stack.
+pub fn apply(things: &mut [Thing], ids: &HashSet<u64>, edit: BulkEdit) -> Vec<StyleEdit> {
+    let now = HistoryEntry::now();
+    things
+        .iter_mut()
+        .filter(|thing| ids.contains(&thing.id))
+        .map(|thing| {
+            let previous = StyleEdit {
+                id: thing.id,
+                marker: thing.marker,
+                display_unit: thing.display_unit,
+            };
+            match edit {
+                BulkEdit::Marker(marker) => thing.marker = marker,
+                BulkEdit::DisplayUnit(display_unit) => thing.display_unit = display_unit,
+            }
+            thing.touch(now);
+            previous
+        })
+        .collect()
+}
+
+// The shared value of the selected things, or None when they differ.
+pub fn common<T: PartialEq>(mut values: impl Iterator<Item = T>) -> Option<T> {
+    let first = values.next()?;
+    values.all(|value| value == first).then_some(first)
+}
+
+#[derive(Default)]
+pub struct Selection {
+    pub ids: HashSet<u64>,
+}
+
+impl Selection {
+    pub const MIXED: &str = "mixed";
+
+    pub fn set(&mut self, id: u64, selected: bool) {
+        if selected {
+            self.ids.insert(id);
+        } else {
+            self.ids.remove(&id);
+        }
+    }
+
+    pub fn selected<'a>(&self, things: &'a [Thing]) -> Vec<&'a Thing> {
+        things
+            .iter()
+            .filter(|thing| self.ids.contains(&thing.id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::undo::{Change, UndoStack};
+
+    fn things() -> Vec<Thing> {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 3.156e7),
+        ];
+        things[1].marker = MarkerStyle::Dot;
+        things[2].display_unit = Some(TimeUnit::Year);
+        things
+    }
+
+    #[test]
+    fn test_apply_only_touches_selected_ids() {
+        let mut things = things();
+        let ids = HashSet::from([things[0].id, things[1].id]);
+        let undo = apply(&mut things, &ids, BulkEdit::Marker(MarkerStyle::Tick));
+        assert_eq!(undo.len(), 2);
+        let markers = things.iter().map(|thing| thing.marker).collect::<Vec<_>>();
+        assert_eq!(
+            markers,
+            [MarkerStyle::Tick, MarkerStyle::Tick, MarkerStyle::Bar]
+        );
+
+        let ids = HashSet::from([things[1].id, things[2].id]);
+        apply(
+            &mut things,
+            &ids,
+            BulkEdit::DisplayUnit(Some(TimeUnit::Day)),
+        );
+        assert_eq!(things[0].display_unit, None);
+        assert_eq!(things[1].display_unit, Some(TimeUnit::Day));
+        assert_eq!(things[2].display_unit, Some(TimeUnit::Day));
+    }
+
+    #[test]
+    fn test_undo_reverts_the_whole_batch() {
+        let mut things = things();
+        let before = things
+            .iter()
+            .map(|thing| (thing.marker, thing.display_unit))
+            .collect::<Vec<_>>();
+        let mut selection = Selection::default();
+        for thing in &things {
+            selection.set(thing.id, true);
+        }
+        let mut stack = UndoStack::default();
+        let previous = apply(
+            &mut things,
+            &selection.ids,
+            BulkEdit::DisplayUnit(Some(TimeUnit::Hour)),
+        );
+        stack.push_all(previous.into_iter().map(Change::from).collect());
+        assert!(
+            things
+                .iter()
+                .all(|thing| thing.display_unit == Some(TimeUnit::Hour))
+        );
+        things.remove(0);
+        stack.undo(&mut things);
+        assert!(stack.is_empty());
+        let after = things
+            .iter()
+            .map(|thing| (thing.marker, thing.display_unit))
+            .collect::<Vec<_>>();
+        assert_eq!(after, before[1..]);
+    }
+
+    #[test]
+    fn test_common_value_or_mixed() {
+        let things = things();
+        assert_eq!(common(things.iter().map(|thing| thing.marker)), None);
+        assert_eq!(
+            common(things[..1].iter().map(|thing| thing.marker)),
+            Some(MarkerStyle::Bar)
+        );
+        assert_eq!(
+            common(things[..2].iter().map(|thing| thing.display_unit)),
+            Some(None)
+        );
+        assert_eq!(common(std::iter::empty::<MarkerStyle>()), None);
+    }
+}
@@ -0,0 +1,583 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use xilem::core::{Edit, lens};
+use xilem::style::Style;
+use xilem::view::{CrossAxisAlignment, checkbox, flex_col, flex_row, label, text_button};
+use xilem::{FontWeight, WidgetView};
+
+use crate::animation::Animation;
+use crate::changelog::Version;
+use crate::error::StateError;
+use crate::sound::SoundSettings;
+use crate::theme::Palette;
+use crate::thing::{BarStyle, Thing};
+use crate::units::{FormatStyle, TimeDisplay};
+use crate::utils::{DEFAULT_SIGNIFICANT_DIGITS, MAX_SIGNIFICANT_DIGITS};
+use crate::viewport::{AxisLabels, Corner, MinorLabels, Spacing, Viewport};
+use crate::{PROJECT_DIRS, Tab};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub ui_scale: f64,
+    pub export_scale: f64,
+    pub minor_lines: bool,
+    pub minor_hover_labels: bool,
+    pub minor_labels: MinorLabels,
+    pub axis_labels: AxisLabels,
+    pub opaque_axis_labels: bool,
+    pub bar_style: BarStyle,
+    pub value_plates: bool,
+    pub scale_bar: bool,
+    pub margin_values: bool,
+    pub tiny_markers: bool,
+    pub tiny_threshold: f64,
+    pub reduce_motion: bool,
+    pub explain_transitions: bool,
+    // the display refresh rate, as picked by the user
+    pub frame_rate: u32,
+    pub time_display: TimeDisplay,
+    pub significant_digits: usize,
+    pub epoch: Option<i32>,
+    pub record_history: bool,
+    pub slider_min: i32,
+    pub slider_max: i32,
+    pub slider_fit_data: bool,
+    pub visible_decades: u32,
+    pub spacing: Spacing,
+    pub max_bars: usize,
+    pub footer_height: f64,
+    pub footer_credit: bool,
+    pub footer_caption: bool,
+    pub watermark_corner: Corner,
+    pub watermark_margin: f64,
+    pub watermark_opacity: f32,
+    pub last_tab: Tab,
+    pub things_panel: bool,
+    pub compact_data: bool,
+    pub autosave: bool,
+    pub sound: SoundSettings,
+    #[serde(default)]
+    pub last_seen_version: Option<Version>,
+    #[serde(skip)]
+    pub palette: Palette,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.,
+            export_scale: 1.,
+            minor_lines: true,
+            minor_hover_labels: false,
+            minor_labels: MinorLabels::Off,
+            axis_labels: AxisLabels::default(),
+            opaque_axis_labels: false,
+            bar_style: BarStyle::default(),
+            value_plates: false,
+            scale_bar: false,
+            margin_values: false,
+            tiny_markers: false,
+            tiny_threshold: 1.,
+            reduce_motion: false,
+            explain_transitions: false,
+            frame_rate: Animation::FRAME_RATES[0],
+            time_display: TimeDisplay::Duration,
+            significant_digits: DEFAULT_SIGNIFICANT_DIGITS,
+            epoch: None,
+            record_history: true,
+            slider_min: -3,
+            slider_max: 20,
+            slider_fit_data: false,
+            visible_decades: Viewport::DEFAULT_DECADES,
+            spacing: Spacing::default(),
+            max_bars: Viewport::DEFAULT_MAX_BARS,
+            footer_height: Viewport::DEFAULT_FOOTER_HEIGHT,
+            footer_credit: false,
+            footer_caption: false,
+            watermark_corner: Corner::default(),
+            watermark_margin: 16.,
+            watermark_opacity: 0.6,
+            last_tab: Tab::default(),
+            things_panel: false,
+            compact_data: false,
+            autosave: true,
+            sound: SoundSettings::default(),
+            last_seen_version: Some(Version::current()),
+            palette: Palette::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub const MIN_UI_SCALE: f64 = 0.75;
+    pub const MAX_UI_SCALE: f64 = 2.;
+    pub const UI_SCALE_STEP: f64 = 0.25;
+    pub const EPOCHS: [Option<i32>; 3] = [None, Some(2000), Some(1950)];
+    pub const SLIDER_LIMIT: i32 = 300;
+    pub const TINY_THRESHOLDS: [f64; 4] = [0.5, 1., 2., 5.];
+
+    pub fn with_ui_scale(self, ui_scale: f64) -> Self {
+        Self {
+            ui_scale: ui_scale.clamp(Self::MIN_UI_SCALE, Self::MAX_UI_SCALE),
+            ..self
+        }
+    }
+
+    pub fn scaled_up(self) -> Self {
+        self.with_ui_scale(self.ui_scale + Self::UI_SCALE_STEP)
+    }
+
+    pub fn scaled_down(self) -> Self {
+        self.with_ui_scale(self.ui_scale - Self::UI_SCALE_STEP)
+    }
+
+    pub fn with_export_scale(self, export_scale: f64) -> Self {
+        Self {
+            export_scale: export_scale.clamp(Self::MIN_UI_SCALE, Self::MAX_UI_SCALE),
+            ..self
+        }
+    }
+
+    // Exports are drawn at their own scale, whatever the screen uses.
+    pub fn for_export(self) -> Self {
+        Self {
+            ui_scale: self.export_scale,
+            ..self
+        }
+    }
+
+    pub fn with_slider_range(self, min: i32, max: i32) -> Self {
+        let min = min.clamp(-Self::SLIDER_LIMIT, Self::SLIDER_LIMIT - 1);
+        Self {
+            slider_min: min,
+            slider_max: max.clamp(min + 1, Self::SLIDER_LIMIT),
+            ..self
+        }
+    }
+
+    pub fn with_visible_decades(self, decades: u32) -> Self {
+        Self {
+            visible_decades: decades.clamp(Viewport::MIN_DECADES, Viewport::MAX_DECADES),
+            ..self
+        }
+    }
+
+    pub fn with_footer_height(self, footer_height: f64) -> Self {
+        Self {
+            footer_height: footer_height
+                .clamp(Viewport::MIN_FOOTER_HEIGHT, Viewport::MAX_FOOTER_HEIGHT),
+            ..self
+        }
+    }
+
+    pub fn with_max_bars(self, max_bars: usize) -> Self {
+        Self {
+            max_bars: max_bars.clamp(Viewport::MIN_MAX_BARS, Viewport::MAX_MAX_BARS),
+            ..self
+        }
+    }
+
+    pub fn with_significant_digits(self, digits: usize) -> Self {
+        Self {
+            significant_digits: digits.clamp(1, MAX_SIGNIFICANT_DIGITS),
+            ..self
+        }
+    }
+
+    pub fn format_style(self) -> FormatStyle {
+        FormatStyle {
+            digits: self.significant_digits,
+            ..FormatStyle::default()
+        }
+    }
+
+    pub fn slider_range(self) -> (f64, f64) {
+        (self.slider_min as f64, self.slider_max as f64)
+    }
+
+    pub fn next_epoch(self) -> Self {
+        let index = Self::EPOCHS
+            .iter()
+            .position(|&epoch| epoch == self.epoch)
+            .map_or(0, |index| index + 1);
+        Self {
+            epoch: Self::EPOCHS[index % Self::EPOCHS.len()],
+            ..self
+        }
+    }
+
+    pub fn next_tiny_threshold(self) -> Self {
+        let index = Self::TINY_THRESHOLDS
+            .iter()
+            .position(|&threshold| threshold == self.tiny_threshold)
+            .map_or(0, |index| index + 1);
+        Self {
+            tiny_threshold: Self::TINY_THRESHOLDS[index % Self::TINY_THRESHOLDS.len()],
+            ..self
+        }
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        flex_col((
+            label("Settings")
+                .weight(FontWeight::BOLD)
+                .color(Thing::NAME_COLOR),
+            flex_row((
+                label("UI scale:").color(Thing::NAME_COLOR),
+                text_button("A−", |state: &mut Self| *state = state.scaled_down()),
+                label(format!("{:.0}%", self.ui_scale * 100.)).color(Thing::NAME_COLOR),
+                text_button("A+", |state: &mut Self| *state = state.scaled_up()),
+                label("Export scale:").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state = state.with_export_scale(state.export_scale - Self::UI_SCALE_STEP);
+                }),
+                label(format!("{:.0}%", self.export_scale * 100.)).color(Thing::NAME_COLOR),
+                text_button("+", |state: &mut Self| {
+                    *state = state.with_export_scale(state.export_scale + Self::UI_SCALE_STEP);
+                }),
+            )),
+            flex_row((
+                checkbox(
+                    "Minor gridlines",
+                    self.minor_lines,
+                    |state: &mut Self, checked| {
+                        state.minor_lines = checked;
+                    },
+                ),
+                checkbox(
+                    "Label on hover",
+                    self.minor_hover_labels,
+                    |state: &mut Self, checked| {
+                        state.minor_hover_labels = checked;
+                    },
+                ),
+            )),
+            flex_row((
+                label("Axis labels:").color(Thing::NAME_COLOR),
+                text_button(self.axis_labels.to_string(), |state: &mut Self| {
+                    state.axis_labels = state.axis_labels.next();
+                }),
+                checkbox(
+                    "Always opaque",
+                    self.opaque_axis_labels,
+                    |state: &mut Self, checked| {
+                        state.opaque_axis_labels = checked;
+                    },
+                ),
+            )),
+            flex_row((
+                label("Minor labels:").color(Thing::NAME_COLOR),
+                text_button(self.minor_labels.to_string(), |state: &mut Self| {
+                    state.minor_labels = state.minor_labels.next();
+                }),
+            )),
+            flex_row((
+                label("Visible decades:").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state = state.with_visible_decades(state.visible_decades.saturating_sub(1));
+                }),
+                label(self.visible_decades.to_string()).color(Thing::NAME_COLOR),
+                text_button("+", |state: &mut Self| {
+                    *state = state.with_visible_decades(state.visible_decades + 1);
+                }),
+            )),
+            flex_row((
+                label("Bar spacing:").color(Thing::NAME_COLOR),
+                text_button(self.spacing.to_string(), |state: &mut Self| {
+                    state.spacing = state.spacing.next();
+                }),
+                label("Max bars on screen:").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state =
+                        state.with_max_bars(state.max_bars.saturating_sub(Viewport::MAX_BARS_STEP));
+                }),
+                label(self.max_bars.to_string()).color(Thing::NAME_COLOR),
+                text_button("+", |state: &mut Self| {
+                    *state = state.with_max_bars(state.max_bars + Viewport::MAX_BARS_STEP);
+                }),
+            )),
+            lens(BarStyle::view, |state: &mut Self, ()| &mut state.bar_style),
+            flex_row((
+                label("Values:").color(Thing::NAME_COLOR),
+                text_button(self.time_display.to_string(), |state: &mut Self| {
+                    state.time_display = state.time_display.next();
+                }),
+                text_button(
+                    self.epoch.map_or("Epoch: present".to_string(), |year| {
+                        format!("Epoch: {year}")
+                    }),
+                    |state: &mut Self| *state = state.next_epoch(),
+                ),
+            )),
+            flex_row((
+                label("Significant digits:").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state =
+                        state.with_significant_digits(state.significant_digits.saturating_sub(1));
+                }),
+                label(self.significant_digits.to_string()).color(Thing::NAME_COLOR),
+                text_button("+", |state: &mut Self| {
+                    *state = state.with_significant_digits(state.significant_digits + 1);
+                }),
+            )),
+            flex_row((
+                label("Value slider:").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state = state.with_slider_range(state.slider_min - 1, state.slider_max);
+                }),
+                label(format!("1e{} s", self.slider_min)).color(Thing::NAME_COLOR),
+                text_button("+", |state: &mut Self| {
+                    *state = state.with_slider_range(state.slider_min + 1, state.slider_max);
+                }),
+                label("to").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state = state.with_slider_range(state.slider_min, state.slider_max - 1);
+                }),
+                label(format!("1e{} s", self.slider_max)).color(Thing::NAME_COLOR),
+                text_button("+", |state: &mut Self| {
+                    *state = state.with_slider_range(state.slider_min, state.slider_max + 1);
+                }),
+                checkbox(
+                    "Fit to data",
+                    self.slider_fit_data,
+                    |state: &mut Self, checked| {
+                        state.slider_fit_data = checked;
+                    },
+                ),
+            )),
+            checkbox(
+                "Keep a history of edited values",
+                self.record_history,
+                |state: &mut Self, checked| {
+                    state.record_history = checked;
+                },
+            ),
+            flex_row((
+                checkbox(
+                    "Value label backgrounds",
+                    self.value_plates,
+                    |state: &mut Self, checked| {
+                        state.value_plates = checked;
+                    },
+                ),
+                checkbox("Scale bar", self.scale_bar, |state: &mut Self, checked| {
+                    state.scale_bar = checked;
+                }),
+                checkbox(
+                    "Values in margin",
+                    self.margin_values,
+                    |state: &mut Self, checked| {
+                        state.margin_values = checked;
+                    },
+                ),
+                checkbox(
+                    "Mark tiny bars",
+                    self.tiny_markers,
+                    |state: &mut Self, checked| {
+                        state.tiny_markers = checked;
+                    },
+                ),
+                text_button(
+                    format!("below {} px", self.tiny_threshold),
+                    |state: &mut Self| *state = state.next_tiny_threshold(),
+                ),
+            )),
+            flex_row((
+                label("Footer:").color(Thing::NAME_COLOR),
+                text_button("−", |state: &mut Self| {
+                    *state = state
+                        .with_footer_height(state.footer_height - Viewport::FOOTER_HEIGHT_STEP);
+                }),
+                label(format!("{:.0} px", self.footer_height)).color(Thing::NAME_COLOR),
+                text_button("+", |state: &mut Self| {
+                    *state = state
+                        .with_footer_height(state.footer_height + Viewport::FOOTER_HEIGHT_STEP);
+                }),
+                checkbox(
+                    "Credit line",
+                    self.footer_credit,
+                    |state: &mut Self, checked| {
+                        state.footer_credit = checked;
+                    },
+                ),
+                checkbox(
+                    "Live caption",
+                    self.footer_caption,
+                    |state: &mut Self, checked| {
+                        state.footer_caption = checked;
+                    },
+                ),
+            )),
+            flex_row((
+                checkbox(
+                    "Reduce motion",
+                    self.reduce_motion,
+                    |state: &mut Self, checked| {
+                        state.reduce_motion = checked;
+                    },
+                ),
+                checkbox(
+                    "Explain transitions",
+                    self.explain_transitions,
+                    |state: &mut Self, checked| {
+                        state.explain_transitions = checked;
+                    },
+                ),
+            )),
+            flex_row((
+                label("Frame rate:").color(Thing::NAME_COLOR),
+                text_button(format!("{} Hz", self.frame_rate), |state: &mut Self| {
+                    state.frame_rate = Animation::next_frame_rate(state.frame_rate);
+                }),
+            )),
+        ))
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+    }
+
+    pub fn file() -> PathBuf {
+        PROJECT_DIRS.preference_dir().join("settings.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::file())
+            .ok()
+            .and_then(|string| serde_json::from_str(&string).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), StateError> {
+        let path = Self::file();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::{TimeScale, TimeUnit, YEAR};
+
+    #[test]
+    fn test_ui_scale_is_clamped() {
+        let settings = Settings::default();
+        assert_eq!(settings.scaled_up().ui_scale, 1.25);
+        assert_eq!(settings.scaled_down().ui_scale, 0.75);
+        assert_eq!(settings.scaled_down().scaled_down().ui_scale, 0.75);
+        assert_eq!(settings.with_ui_scale(5.).ui_scale, 2.);
+        assert_eq!(settings.with_ui_scale(2.).scaled_up().ui_scale, 2.);
+        assert_eq!(settings.with_export_scale(5.).export_scale, 2.);
+        assert_eq!(settings.with_export_scale(0.).export_scale, 0.75);
+    }
+
+    #[test]
+    fn test_export_scale_is_independent() {
+        let settings = Settings::default().with_ui_scale(2.).with_export_scale(1.5);
+        assert_eq!(settings.scaled_down().export_scale, 1.5);
+        assert_eq!(settings.for_export().ui_scale, 1.5);
+        assert_eq!(settings.for_export().export_scale, 1.5);
+    }
+
+    #[test]
+    fn test_slider_range_stays_ordered() {
+        let settings = Settings::default();
+        assert_eq!(settings.slider_range(), (-3., 20.));
+        let settings = settings.with_slider_range(5, 5);
+        assert_eq!((settings.slider_min, settings.slider_max), (5, 6));
+        let settings = settings.with_slider_range(-1000, 1000);
+        assert_eq!(
+            settings.slider_range(),
+            (
+                -Settings::SLIDER_LIMIT as f64,
+                Settings::SLIDER_LIMIT as f64
+            )
+        );
+    }
+
+    #[test]
+    fn test_visible_decades_are_clamped() {
+        let settings = Settings::default();
+        assert_eq!(settings.visible_decades, 3);
+        assert_eq!(settings.with_visible_decades(5).visible_decades, 5);
+        assert_eq!(settings.with_visible_decades(0).visible_decades, 3);
+        assert_eq!(settings.with_visible_decades(9).visible_decades, 6);
+    }
+
+    #[test]
+    fn test_footer_height_is_clamped() {
+        let settings = Settings::default();
+        assert_eq!(settings.footer_height, Viewport::DEFAULT_FOOTER_HEIGHT);
+        assert_eq!(
+            settings.with_footer_height(0.).footer_height,
+            Viewport::MIN_FOOTER_HEIGHT
+        );
+        assert_eq!(settings.with_footer_height(180.).footer_height, 180.);
+        assert_eq!(
+            settings.with_footer_height(1e4).footer_height,
+            Viewport::MAX_FOOTER_HEIGHT
+        );
+    }
+
+    #[test]
+    fn test_max_bars_are_clamped() {
+        let settings = Settings::default();
+        assert_eq!(settings.max_bars, Viewport::DEFAULT_MAX_BARS);
+        assert_eq!(settings.with_max_bars(0).max_bars, Viewport::MIN_MAX_BARS);
+        assert_eq!(
+            settings.with_max_bars(10_000).max_bars,
+            Viewport::MAX_MAX_BARS
+        );
+    }
+
+    #[test]
+    fn test_significant_digits_reach_the_format_style() {
+        let settings = Settings::default();
+        let year = TimeScale::from(YEAR);
+        assert_eq!(year.fmt_styled(None, settings.format_style()), "365.2 d");
+        let coarse = settings.with_significant_digits(3).format_style();
+        assert_eq!(coarse.digits, 3);
+        assert_eq!(year.fmt_styled(None, coarse), "365 d");
+        assert_eq!(year.fmt_styled(Some(TimeUnit::Year), coarse), "1 y");
+        assert_eq!(settings.with_significant_digits(0).significant_digits, 1);
+    }
+
+    #[test]
+    fn test_epoch_cycles() {
+        let settings = Settings::default();
+        assert_eq!(settings.epoch, None);
+        assert_eq!(settings.next_epoch().epoch, Some(2000));
+        assert_eq!(settings.next_epoch().next_epoch().epoch, Some(1950));
+        assert_eq!(settings.next_epoch().next_epoch().next_epoch().epoch, None);
+    }
+
+    #[test]
+    fn test_tiny_threshold_cycles() {
+        let settings = Settings::default();
+        assert_eq!(settings.next_tiny_threshold().tiny_threshold, 2.);
+        let settings = Settings {
+            tiny_threshold: 5.,
+            ..settings
+        };
+        assert_eq!(settings.next_tiny_threshold().tiny_threshold, 0.5);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                last_seen_version: None,
+                ..Settings::default()
+            }
+        );
+        let settings: Settings = serde_json::from_str(r#"{"ui_scale":1.5}"#).unwrap();
+        assert_eq!(settings.ui_scale, 1.5);
+        assert!(settings.minor_lines);
+    }
+}
@@ -0,0 +1,429 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use xilem::WidgetView;
+use xilem::core::Edit;
+use xilem::style::Style;
+use xilem::view::{FlexExt, checkbox, flex_col, flex_row, label, text_button, text_input};
+
+use crate::PROJECT_DIRS;
+use crate::animation::AnimEvent;
+use crate::error::StateError;
+use crate::thing::Thing;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cue {
+    Tick,
+    Chime,
+}
+
+impl Cue {
+    pub const ALL: [Cue; 2] = [Cue::Tick, Cue::Chime];
+
+    pub fn for_event(event: AnimEvent) -> Self {
+        match event {
+            AnimEvent::ShiftStarted => Cue::Tick,
+            AnimEvent::Reached => Cue::Chime,
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Cue::Tick => "tick.wav",
+            Cue::Chime => "chime.wav",
+        }
+    }
+
+    pub fn bundled(self) -> &'static [u8] {
+        match self {
+            Cue::Tick => include_bytes!("../assets/sounds/tick.wav"),
+            Cue::Chime => include_bytes!("../assets/sounds/chime.wav"),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Cue::Tick => "Tick sound",
+            Cue::Chime => "Chime sound",
+        }
+    }
+
+    // a wav file with this name in the preference folder replaces the bundled sound
+    pub fn override_path(self, dir: &Path) -> PathBuf {
+        dir.join(self.file_name())
+    }
+}
+
+// User-chosen .wav files, kept apart from the `Copy` settings in their own file.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SoundFiles {
+    pub tick: Option<PathBuf>,
+    pub chime: Option<PathBuf>,
+}
+
+impl SoundFiles {
+    pub fn get(&self, cue: Cue) -> Option<&Path> {
+        match cue {
+            Cue::Tick => self.tick.as_deref(),
+            Cue::Chime => self.chime.as_deref(),
+        }
+    }
+
+    pub fn set(&mut self, cue: Cue, path: &str) {
+        let path = (!path.trim().is_empty()).then(|| PathBuf::from(path));
+        match cue {
+            Cue::Tick => self.tick = path,
+            Cue::Chime => self.chime = path,
+        }
+    }
+
+    // a chosen file wins over the one named after the cue in `dir`
+    pub fn resolve(&self, cue: Cue, dir: &Path) -> PathBuf {
+        self.get(cue)
+            .map_or_else(|| cue.override_path(dir), Path::to_path_buf)
+    }
+
+    pub fn file() -> PathBuf {
+        PROJECT_DIRS.preference_dir().join("sounds.json")
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::file())
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|string| serde_json::from_str(&string).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), StateError> {
+        self.save_to(&Self::file())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), StateError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        let rows = Cue::ALL.into_iter().map(|cue| {
+            let path = self
+                .get(cue)
+                .map_or(String::new(), |path| path.display().to_string());
+            flex_row((
+                label(format!("{}:", cue.label())).color(Thing::NAME_COLOR),
+                text_input(path, move |state: &mut Self, value| state.set(cue, &value))
+                    .placeholder(format!(
+                        "Bundled, or a .wav path (default {})",
+                        cue.file_name()
+                    ))
+                    .flex(1.),
+            ))
+        });
+        flex_col(rows.collect::<Vec<_>>())
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub tick: bool,
+    pub chime: bool,
+    pub volume: f32,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            tick: false,
+            chime: false,
+            volume: 0.5,
+        }
+    }
+}
+
+impl SoundSettings {
+    pub const VOLUME_STEP: f32 = 0.1;
+
+    pub fn enabled(self, cue: Cue) -> bool {
+        let toggle = match cue {
+            Cue::Tick => self.tick,
+            Cue::Chime => self.chime,
+        };
+        toggle && self.volume > 0.
+    }
+
+    pub fn any(self) -> bool {
+        Cue::ALL.into_iter().any(|cue| self.enabled(cue))
+    }
+
+    pub fn with_volume(self, volume: f32) -> Self {
+        Self {
+            volume: ((volume / Self::VOLUME_STEP).round() * Self::VOLUME_STEP).clamp(0., 1.),
+            ..self
+        }
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        flex_row((
+            label("Sound cues:").color(Thing::NAME_COLOR),
+            checkbox("Tick on shift", self.tick, |state: &mut Self, checked| {
+                state.tick = checked;
+            }),
+            checkbox(
+                "Chime on arrival",
+                self.chime,
+                |state: &mut Self, checked| {
+                    state.chime = checked;
+                },
+            ),
+            label("Volume:").color(Thing::NAME_COLOR),
+            text_button("−", |state: &mut Self| {
+                *state = state.with_volume(state.volume - Self::VOLUME_STEP);
+            }),
+            label(format!("{:.0}%", self.volume * 100.)).color(Thing::NAME_COLOR),
+            text_button("+", |state: &mut Self| {
+                *state = state.with_volume(state.volume + Self::VOLUME_STEP);
+            }),
+        ))
+    }
+}
+
+pub trait SoundSink {
+    fn play(&mut self, cue: Cue, volume: f32);
+}
+
+#[derive(Debug, Default)]
+pub struct CuePlayer {
+    last_played: [Option<f64>; 2],
+}
+
+impl CuePlayer {
+    pub const MIN_INTERVAL: f64 = 0.15;
+
+    // Plays each cue at most once per call and never twice within
+    // `MIN_INTERVAL` seconds of `now`, so a burst of events makes one sound.
+    pub fn dispatch(
+        &mut self,
+        events: &[AnimEvent],
+        now: f64,
+        settings: SoundSettings,
+        sink: &mut impl SoundSink,
+    ) {
+        for cue in Cue::ALL {
+            let wanted =
+                settings.enabled(cue) && events.iter().any(|&event| Cue::for_event(event) == cue);
+            let last = &mut self.last_played[cue as usize];
+            if !wanted || last.is_some_and(|last| now - last < Self::MIN_INTERVAL) {
+                continue;
+            }
+            *last = Some(now);
+            sink.play(cue, settings.volume);
+        }
+    }
+}
+
+#[cfg(feature = "sound")]
+pub use output::{LoadError, RodioSink};
+
+#[cfg(feature = "sound")]
+mod output {
+    use std::fs;
+    use std::io::{self, Cursor};
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use rodio::decoder::DecoderError;
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Source, StreamError};
+
+    use super::{Cue, SoundFiles, SoundSink};
+    use crate::notice::Notice;
+
+    #[derive(Debug)]
+    pub enum LoadError {
+        Read(io::Error),
+        Decode(DecoderError),
+    }
+
+    impl std::fmt::Display for LoadError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                LoadError::Read(err) => write!(f, "could not read it: {err}"),
+                LoadError::Decode(err) => write!(f, "not a playable wav file: {err}"),
+            }
+        }
+    }
+
+    fn load(path: &Path) -> Result<Option<Arc<[u8]>>, LoadError> {
+        let bytes: Arc<[u8]> = match fs::read(path) {
+            Ok(bytes) => bytes.into(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(LoadError::Read(err)),
+        };
+        Decoder::new_wav(Cursor::new(bytes.clone())).map_err(LoadError::Decode)?;
+        Ok(Some(bytes))
+    }
+
+    pub struct RodioSink {
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        sounds: [Arc<[u8]>; 2],
+    }
+
+    impl RodioSink {
+        // Overrides that fail to load fall back to the bundled sound with a notice.
+        pub fn open(files: &SoundFiles, dir: &Path) -> Result<(Self, Vec<Notice>), StreamError> {
+            let (stream, handle) = OutputStream::try_default()?;
+            let mut notices = Vec::new();
+            let sounds = Cue::ALL.map(|cue| {
+                let path = files.resolve(cue, dir);
+                match load(&path) {
+                    Ok(Some(bytes)) => bytes,
+                    Ok(None) if files.get(cue).is_some() => {
+                        notices.push(Notice::warning(format!(
+                            "Using the default sound instead of {}: the file does not exist",
+                            path.display()
+                        )));
+                        cue.bundled().into()
+                    }
+                    Ok(None) => cue.bundled().into(),
+                    Err(err) => {
+                        notices.push(Notice::warning(format!(
+                            "Using the default sound instead of {}: {err}",
+                            path.display()
+                        )));
+                        cue.bundled().into()
+                    }
+                }
+            });
+            let sink = Self {
+                _stream: stream,
+                handle,
+                sounds,
+            };
+            Ok((sink, notices))
+        }
+    }
+
+    impl SoundSink for RodioSink {
+        fn play(&mut self, cue: Cue, volume: f32) {
+            let sound = self.sounds[cue as usize].clone();
+            if let Ok(source) = Decoder::new_wav(Cursor::new(sound)) {
+                let _ = self
+                    .handle
+                    .play_raw(source.convert_samples::<f32>().amplify(volume));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Vec<(Cue, f32)>);
+
+    impl SoundSink for Recorder {
+        fn play(&mut self, cue: Cue, volume: f32) {
+            self.0.push((cue, volume));
+        }
+    }
+
+    #[test]
+    fn test_events_play_their_enabled_cues() {
+        let mut player = CuePlayer::default();
+        let mut sink = Recorder::default();
+        let settings = SoundSettings {
+            tick: true,
+            chime: true,
+            volume: 0.8,
+        };
+        player.dispatch(&[AnimEvent::Reached], 0., settings, &mut sink);
+        player.dispatch(&[AnimEvent::ShiftStarted], 1., settings, &mut sink);
+        assert_eq!(sink.0, [(Cue::Chime, 0.8), (Cue::Tick, 0.8)]);
+
+        let chime_only = SoundSettings {
+            tick: false,
+            ..settings
+        };
+        let events = [AnimEvent::ShiftStarted, AnimEvent::Reached];
+        player.dispatch(&events, 2., chime_only, &mut sink);
+        assert_eq!(sink.0[2..], [(Cue::Chime, 0.8)]);
+
+        let muted = settings.with_volume(0.);
+        assert!(!muted.any());
+        player.dispatch(&events, 3., muted, &mut sink);
+        assert_eq!(sink.0.len(), 3);
+        player.dispatch(&[], 4., settings, &mut sink);
+        assert_eq!(sink.0.len(), 3);
+    }
+
+    #[test]
+    fn test_bursts_are_debounced() {
+        let mut player = CuePlayer::default();
+        let mut sink = Recorder::default();
+        let settings = SoundSettings {
+            tick: true,
+            chime: true,
+            ..SoundSettings::default()
+        };
+        let burst = [AnimEvent::ShiftStarted, AnimEvent::Reached].repeat(20);
+        player.dispatch(&burst, 10., settings, &mut sink);
+        assert_eq!(sink.0.len(), 2);
+
+        player.dispatch(&burst, 10.05, settings, &mut sink);
+        player.dispatch(&[AnimEvent::Reached], 10.1, settings, &mut sink);
+        assert_eq!(sink.0.len(), 2);
+        player.dispatch(&[AnimEvent::Reached], 10.2, settings, &mut sink);
+        assert_eq!(sink.0[2..], [(Cue::Chime, 0.5)]);
+        player.dispatch(&[AnimEvent::ShiftStarted], 10.21, settings, &mut sink);
+        assert_eq!(sink.0[3..], [(Cue::Tick, 0.5)]);
+    }
+
+    #[test]
+    fn test_volume_steps_and_bundled_sounds() {
+        let settings = SoundSettings::default();
+        assert_eq!(settings.with_volume(1.7).volume, 1.);
+        assert_eq!(settings.with_volume(-0.2).volume, 0.);
+        assert!((settings.with_volume(0.64).volume - 0.6).abs() < 1e-6);
+        for cue in Cue::ALL {
+            let bytes = cue.bundled();
+            assert_eq!(&bytes[..4], b"RIFF");
+            assert_eq!(&bytes[8..12], b"WAVE");
+        }
+        assert_eq!(
+            Cue::Chime.override_path(Path::new("prefs")),
+            Path::new("prefs").join("chime.wav")
+        );
+    }
+
+    #[test]
+    fn test_sound_files_are_saved_apart_from_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sounds.json");
+        assert_eq!(SoundFiles::load_from(&path), SoundFiles::default());
+
+        let mut files = SoundFiles::default();
+        files.set(Cue::Chime, "/sounds/bell.wav");
+        files.set(Cue::Tick, "  ");
+        assert_eq!(files.get(Cue::Tick), None);
+        assert_eq!(
+            files.resolve(Cue::Tick, dir.path()),
+            dir.path().join("tick.wav")
+        );
+        assert_eq!(
+            files.resolve(Cue::Chime, dir.path()),
+            Path::new("/sounds/bell.wav")
+        );
+        files.save_to(&path).unwrap();
+        assert_eq!(SoundFiles::load_from(&path), files);
+    }
+}
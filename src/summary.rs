@@ -0,0 +1,158 @@
+use crate::math::ENumber;
+use crate::thing::Thing;
+use crate::units::TimeScale;
+use crate::utils::{float_to_string_with, group_thousands, superscript_exponents};
+
+pub const JUMP_DIGITS: usize = 2;
+pub const MAX_GROUPED_JUMP: f64 = 15.;
+
+pub fn ratio(from: &TimeScale, to: &TimeScale) -> ENumber {
+    to.inner() / from.inner()
+}
+
+pub fn describe_gap(ratio: ENumber) -> String {
+    let orders = ratio.erect().1.abs().round();
+    match orders {
+        0. => "less than one order of magnitude".to_string(),
+        1. => "1 order of magnitude".to_string(),
+        _ => format!("{} orders of magnitude", group_thousands(orders as u64)),
+    }
+}
+
+pub fn describe_jump(from: &TimeScale, to: &TimeScale) -> Option<String> {
+    let ratio = ratio(from, to);
+    let (sign, log) = ratio.erect();
+    if sign <= 0. || !log.is_finite() {
+        return None;
+    }
+    let (log, direction) = match log < 0. {
+        true => (-log, "smaller"),
+        false => (log, "larger"),
+    };
+    let factor = if log < MAX_GROUPED_JUMP {
+        let rounded = float_to_string_with(10_f64.powf(log), JUMP_DIGITS);
+        match rounded.parse::<u64>() {
+            Ok(whole) => group_thousands(whole),
+            Err(_) => rounded,
+        }
+    } else {
+        let exponent = log.floor();
+        let significand = float_to_string_with(10_f64.powf(log - exponent), JUMP_DIGITS);
+        superscript_exponents(&format!("{significand}e{exponent}"))
+    };
+    Some(format!("{factor}× {direction}"))
+}
+
+fn describe_thing(thing: &Thing) -> String {
+    format!(
+        "{} ({})",
+        thing.name,
+        superscript_exponents(&thing.value.to_string())
+    )
+}
+
+pub fn summarize(things: &[Thing]) -> String {
+    let mut sorted = things.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.value.total_cmp(&b.value));
+    match sorted.as_slice() {
+        [] => "This comparison is empty.".to_string(),
+        [thing] => format!(
+            "This comparison contains a single thing: {}.",
+            describe_thing(thing)
+        ),
+        [first, .., last] => {
+            let mut summary = format!(
+                "This comparison spans {}, from {} to {}.",
+                describe_gap(ratio(&first.value, &last.value)),
+                describe_thing(first),
+                describe_thing(last)
+            );
+            if sorted.len() > 2 {
+                let (a, b) = sorted
+                    .windows(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .max_by(|(a0, b0), (a1, b1)| {
+                        ratio(&a0.value, &b0.value).total_cmp(&ratio(&a1.value, &b1.value))
+                    })
+                    .expect("At least two things should form a pair");
+                summary.push_str(&format!(
+                    " The largest jump is between {} and {} ({}).",
+                    a.name,
+                    b.name,
+                    describe_gap(ratio(&a.value, &b.value))
+                ));
+            }
+            summary
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_gap_pluralization() {
+        assert_eq!(
+            describe_gap(ENumber::new(3., 0)),
+            "less than one order of magnitude"
+        );
+        assert_eq!(describe_gap(ENumber::new(1., 1)), "1 order of magnitude");
+        assert_eq!(describe_gap(ENumber::new(1., 2)), "2 orders of magnitude");
+        assert_eq!(
+            describe_gap(ENumber::new(1., -11)),
+            "11 orders of magnitude"
+        );
+    }
+
+    #[test]
+    fn test_describe_jump() {
+        let jump = |from: f64, to: f64| describe_jump(&from.into(), &to.into());
+        assert_eq!(jump(0.3, 3600.).as_deref(), Some("12,000× larger"));
+        assert_eq!(jump(86400., 604800.).as_deref(), Some("7× larger"));
+        assert_eq!(jump(1., 2.5).as_deref(), Some("2.5× larger"));
+        assert_eq!(jump(3600., 0.3).as_deref(), Some("12,000× smaller"));
+        assert_eq!(jump(1., 0.).as_deref(), None);
+        assert_eq!(
+            describe_jump(&(5.39, -44).into(), &(4.35, 17).into()).as_deref(),
+            Some("8.1×10⁶⁰× larger")
+        );
+    }
+
+    #[test]
+    fn test_summarize_empty_and_single() {
+        assert_eq!(summarize(&[]), "This comparison is empty.");
+        assert_eq!(
+            summarize(&[Thing::new("Week", 604800.)]),
+            "This comparison contains a single thing: Week (7 d)."
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let things = [
+            Thing::new("Hydrogen-7 half-life", (2.3, -23)),
+            Thing::new("Week", 604800.),
+            Thing::new("Sun's lifespan", TimeScale::from_years((1., 10))),
+        ];
+        assert_eq!(
+            summarize(&things),
+            "This comparison spans 40 orders of magnitude, \
+             from Hydrogen-7 half-life (2.3×10⁻²³ s) to Sun's lifespan (10 Gy). \
+             The largest jump is between Hydrogen-7 half-life and Week (28 orders of magnitude)."
+        );
+    }
+
+    #[test]
+    fn test_summarize_huge_exponents() {
+        let things = [
+            Thing::new("Planck time", (5.39, -44)),
+            Thing::new("Far future", TimeScale::from_years((1., 32000))),
+        ];
+        assert_eq!(
+            summarize(&things),
+            "This comparison spans 32,051 orders of magnitude, \
+             from Planck time (5.39×10⁻⁴⁴ s) to Far future (1×10³²⁰⁰⁰ y)."
+        );
+    }
+}
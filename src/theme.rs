@@ -0,0 +1,89 @@
+use xilem::Color;
+
+use crate::thing::Thing;
+use crate::viewport::Viewport;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    pub background: Color,
+    pub border: Color,
+    pub text: Color,
+    pub muted_text: Color,
+    pub accent: Color,
+    pub button: Color,
+}
+
+impl Palette {
+    pub const DARK: Self = Self {
+        background: Viewport::FOOTER_AREA_COLOR,
+        border: Viewport::MINOR_LINE_COLOR,
+        text: Thing::NAME_COLOR,
+        muted_text: Viewport::MINOR_LABEL_COLOR,
+        accent: Thing::VALUE_COLOR,
+        button: Color::from_rgb8(39, 39, 42),
+    };
+
+    pub fn card_colors(self) -> (Color, Color) {
+        (self.background, self.border)
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xilem::palette::css;
+
+    use super::*;
+    use crate::settings::Settings;
+    use crate::utils::{MIN_CONTRAST, contrast_ratio, readable_color};
+
+    #[test]
+    fn test_card_colors_follow_palette() {
+        let settings = Settings::default();
+        assert_eq!(
+            settings.palette.card_colors(),
+            (Viewport::FOOTER_AREA_COLOR, Viewport::MINOR_LINE_COLOR)
+        );
+        let light = Palette {
+            background: css::WHITE_SMOKE,
+            border: css::LIGHT_GRAY,
+            text: css::BLACK,
+            ..Palette::DARK
+        };
+        let settings = Settings {
+            palette: light,
+            ..settings
+        };
+        assert_eq!(
+            settings.palette.card_colors(),
+            (css::WHITE_SMOKE, css::LIGHT_GRAY)
+        );
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(!json.contains("palette"));
+        let loaded: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.palette, Palette::DARK);
+    }
+
+    #[test]
+    fn test_palette_text_is_readable() {
+        let palette = Palette::DARK;
+        for (background, foreground) in [
+            (palette.background, palette.text),
+            (palette.background, palette.muted_text),
+            (palette.background, palette.accent),
+            (palette.button, palette.text),
+        ] {
+            assert!(contrast_ratio(background, foreground) >= MIN_CONTRAST);
+            assert_eq!(readable_color(background, foreground), foreground);
+        }
+        assert_eq!(
+            readable_color(css::WHITE_SMOKE, palette.accent),
+            Color::BLACK
+        );
+    }
+}
@@ -1,26 +1,377 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use serde::{Deserialize, Serialize};
 use simple_easing::cubic_in;
+use xilem::core::one_of::Either;
 use xilem::core::{Edit, View, lens};
 use xilem::masonry::core::{BrushIndex, render_text};
 use xilem::masonry::parley::{FontContext, GenericFamily, LayoutContext};
 use xilem::palette::css;
 use xilem::style::Style;
 use xilem::vello::Scene;
-use xilem::vello::kurbo::{Affine, Rect, Vec2};
-use xilem::vello::peniko::Fill;
+use xilem::vello::kurbo::{
+    Affine, Circle, Line, Point, Rect, RoundedRect, RoundedRectRadii, Size, Stroke, Vec2,
+};
+use xilem::vello::peniko::{Fill, Gradient};
 use xilem::view::{
-    CrossAxisAlignment, MainAxisAlignment, button, flex_col, flex_row, label, sized_box, text_input,
+    CrossAxisAlignment, FlexExt, MainAxisAlignment, button, checkbox, flex_col, flex_row, label,
+    sized_box, slider, text_button, text_input,
 };
 use xilem::{Color, FontWeight, TextAlign, WidgetView};
 
-use crate::units::TimeScale;
-use crate::utils::{text_layout, y_flipped_translate};
-use crate::viewport::Viewport;
+use crate::math::ENumber;
+use crate::notice::Level;
+use crate::settings::Settings;
+use crate::theme::Palette;
+use crate::units::{FormatStyle, TimeDisplay, TimeScale, TimeUnit};
+use crate::utils::{TextParams, layout_text, readable_color, upright, y_flipped_translate};
+use crate::viewport::{Direction, Viewport};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct BarStyle {
+    pub corner_radius: f64,
+    pub outline: Option<[u8; 4]>,
+    pub gradient: bool,
+    pub baseline: Baseline,
+    pub orientation: Orientation,
+}
+
+impl Default for BarStyle {
+    fn default() -> Self {
+        Self {
+            corner_radius: 0.,
+            outline: None,
+            gradient: false,
+            baseline: Baseline::default(),
+            orientation: Orientation::default(),
+        }
+    }
+}
+
+impl BarStyle {
+    pub const CORNER_RADII: [f64; 4] = [0., 4., 8., 20.];
+    pub const OUTLINE_COLOR: [u8; 4] = [255, 255, 255, 160];
+    pub const GRADIENT_DARKENING: f32 = 0.55;
+
+    pub fn clamped_radius(&self, width: f64, height: f64) -> f64 {
+        self.corner_radius.min(width / 2.).min(height.abs()).max(0.)
+    }
+
+    pub fn view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+        flex_row((
+            label("Bar style:").color(Thing::NAME_COLOR),
+            text_button(
+                format!("Corner radius: {}", self.corner_radius),
+                |state: &mut Self| {
+                    let index = Self::CORNER_RADII
+                        .iter()
+                        .position(|&radius| radius == state.corner_radius)
+                        .map_or(0, |index| index + 1);
+                    state.corner_radius = Self::CORNER_RADII[index % Self::CORNER_RADII.len()];
+                },
+            ),
+            checkbox(
+                "Outline",
+                self.outline.is_some(),
+                |state: &mut Self, checked| {
+                    state.outline = checked.then_some(Self::OUTLINE_COLOR);
+                },
+            ),
+            checkbox("Gradient", self.gradient, |state: &mut Self, checked| {
+                state.gradient = checked;
+            }),
+            text_button(
+                format!("Baseline: {}", self.baseline),
+                |state: &mut Self| {
+                    state.baseline = state.baseline.next();
+                },
+            ),
+            text_button(
+                format!("Orientation: {}", self.orientation),
+                |state: &mut Self| {
+                    state.orientation = state.orientation.next();
+                },
+            ),
+        ))
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug, PartialEq)]
+pub enum Baseline {
+    #[default]
+    Bottom,
+    Top,
+    Center,
+}
+
+impl std::fmt::Display for Baseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Baseline::Bottom => write!(f, "Bottom"),
+            Baseline::Top => write!(f, "Top"),
+            Baseline::Center => write!(f, "Center"),
+        }
+    }
+}
+
+impl Baseline {
+    pub fn next(self) -> Self {
+        match self {
+            Baseline::Bottom => Baseline::Top,
+            Baseline::Top => Baseline::Center,
+            Baseline::Center => Baseline::Bottom,
+        }
+    }
+
+    pub fn transform(self, height: f64) -> Affine {
+        match self {
+            Baseline::Bottom => Affine::IDENTITY,
+            Baseline::Top => Affine::FLIP_Y.then_translate((0., Viewport::MAX_HEIGHT).into()),
+            Baseline::Center => Affine::translate((0., (Viewport::MAX_HEIGHT - height) / 2.)),
+        }
+    }
+
+    pub fn gridline(self, position: f64) -> Option<f64> {
+        match self {
+            Baseline::Bottom => Some(position),
+            Baseline::Top => Some(Viewport::MAX_HEIGHT - position),
+            Baseline::Center => None,
+        }
+    }
+}
+
+// Bars always grow along world y and things follow each other along world x;
+// horizontal orientation swaps the two axes on screen.
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+impl std::fmt::Display for Orientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Orientation::Vertical => write!(f, "Vertical"),
+            Orientation::Horizontal => write!(f, "Horizontal"),
+        }
+    }
+}
+
+impl Orientation {
+    pub const TRANSPOSE: Affine = Affine::new([0., 1., 1., 0., 0., 0.]);
+
+    pub fn next(self) -> Self {
+        match self {
+            Orientation::Vertical => Orientation::Horizontal,
+            Orientation::Horizontal => Orientation::Vertical,
+        }
+    }
+
+    pub fn transpose(self, size: Size) -> Size {
+        match self {
+            Orientation::Vertical => size,
+            Orientation::Horizontal => Size::new(size.height, size.width),
+        }
+    }
+
+    pub fn world_trans(self, half_size: Vec2) -> Affine {
+        match self {
+            Orientation::Vertical => Affine::FLIP_Y.then_translate(half_size),
+            Orientation::Horizontal => {
+                Self::TRANSPOSE.then_translate(Vec2::new(half_size.y, half_size.x))
+            }
+        }
+    }
+
+    // how far a text box reaches along the scale
+    pub fn extent(self, size: Size) -> f64 {
+        match self {
+            Orientation::Vertical => size.height,
+            Orientation::Horizontal => size.width,
+        }
+    }
+
+    // lays a text box out between `low` and `high` along the scale, centered on `x`
+    pub fn place_text(
+        self,
+        text_camera: Affine,
+        x: f64,
+        (low, high): (f64, f64),
+        size: Size,
+    ) -> Affine {
+        match self {
+            Orientation::Vertical => text_camera * y_flipped_translate((x - size.width / 2., high)),
+            Orientation::Horizontal => {
+                upright(text_camera * y_flipped_translate((x, low)))
+                    * Affine::translate((0., -size.height / 2.))
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug, PartialEq)]
+pub enum MarkerStyle {
+    #[default]
+    Bar,
+    Dot,
+    Tick,
+}
+
+impl std::fmt::Display for MarkerStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MarkerStyle::Bar => write!(f, "Bar"),
+            MarkerStyle::Dot => write!(f, "Dot"),
+            MarkerStyle::Tick => write!(f, "Tick"),
+        }
+    }
+}
 
-#[derive(Deserialize, Serialize, Default)]
+impl MarkerStyle {
+    pub const TICK_THICKNESS: f64 = 3.;
+
+    pub fn next(self) -> Self {
+        match self {
+            MarkerStyle::Bar => MarkerStyle::Dot,
+            MarkerStyle::Dot => MarkerStyle::Tick,
+            MarkerStyle::Tick => MarkerStyle::Bar,
+        }
+    }
+
+    pub fn bounds(self, position: Vec2, width: f64) -> Rect {
+        let half_width = width / 2.;
+        let half_height = match self {
+            MarkerStyle::Bar => {
+                return Rect::new(
+                    position.x - half_width,
+                    0.,
+                    position.x + half_width,
+                    position.y,
+                )
+                .abs();
+            }
+            MarkerStyle::Dot => half_width,
+            MarkerStyle::Tick => Self::TICK_THICKNESS / 2.,
+        };
+        Rect::new(
+            position.x - half_width,
+            position.y - half_height,
+            position.x + half_width,
+            position.y + half_height,
+        )
+    }
+
+    pub fn name_anchor(self, position: Vec2, width: f64) -> f64 {
+        match self {
+            MarkerStyle::Bar => position.y,
+            _ => self.bounds(position, width).y1,
+        }
+    }
+
+    pub fn value_anchor(self, position: Vec2, width: f64) -> f64 {
+        match self {
+            MarkerStyle::Bar => 0.,
+            _ => self.bounds(position, width).y0,
+        }
+    }
+}
+
+// How one bar is drawn this frame.
+#[derive(Clone, Copy, Debug)]
+pub struct BarDraw {
+    pub color: Color,
+    pub width: f64,
+    pub alpha: f32,
+    pub style: BarStyle,
+}
+
+// How a name is drawn this frame; `row` stacks paired names.
+#[derive(Clone, Copy, Debug)]
+pub struct NameDraw {
+    pub color: Color,
+    pub width: f64,
+    pub alpha: f32,
+    pub row: usize,
+    pub ui_scale: f64,
+    pub style: BarStyle,
+}
+
+// How a value is drawn this frame; the text comes from `settings`.
+#[derive(Clone, Copy, Debug)]
+pub struct ValueDraw {
+    pub color: Color,
+    pub width: f64,
+    pub alpha: f32,
+    pub row: usize,
+    pub settings: Settings,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThingAction {
+    Delete,
+    // carries the value from before the commit, and whether it went into
+    // the history
+    CommitValue { previous: ENumber, recorded: bool },
+    // asks the compact data list to open or close this row
+    SetExpanded(bool),
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub value: ENumber,
+    pub recorded: u64,
+}
+
+impl HistoryEntry {
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+
+    pub fn age(&self, now: u64) -> String {
+        format!(
+            "{} ago",
+            TimeScale::from(now.saturating_sub(self.recorded) as f64)
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Thing {
+    #[serde(skip, default = "Thing::next_id")]
+    pub id: u64,
     pub name: String,
     pub value: TimeScale,
+    #[serde(default)]
+    pub display_unit: Option<TimeUnit>,
+    #[serde(default)]
+    pub live: bool,
+    #[serde(default)]
+    pub marker: MarkerStyle,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reveal: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<HistoryEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<u64>,
+    #[serde(skip)]
+    pub history_open: bool,
+    #[serde(skip)]
+    pub confirm_delete: bool,
+}
+
+impl Default for Thing {
+    fn default() -> Self {
+        Self::new("", TimeScale::default())
+    }
 }
 
 impl Thing {
@@ -32,75 +383,312 @@ impl Thing {
     pub const BAR_HALF: f64 = Self::BAR_WIDTH / 2.;
     pub const BAR_GAP: f64 = 100.0;
     pub const BAR_OFFSET: f64 = Self::BAR_WIDTH + Self::BAR_GAP;
+    // between a marker and its name or value
+    pub const LABEL_GAP: f64 = 10.;
+
+    pub const HISTORY_CAP: usize = 10;
+    pub const HISTORY_EPSILON: f64 = 1e-9;
+
+    pub const PLATE_ALPHA: f32 = 0.7;
+    pub const PLATE_PADDING: f64 = 4.;
+    pub const PLATE_RADIUS: f64 = 4.;
+
+    pub fn label_plate(width: f64, height: f64, ui_scale: f64) -> RoundedRect {
+        let padding = Self::PLATE_PADDING * ui_scale;
+        Rect::new(0., 0., width, height)
+            .inflate(padding, padding)
+            .to_rounded_rect(Self::PLATE_RADIUS * ui_scale)
+    }
 
     pub fn new(name: &str, value: impl Into<TimeScale>) -> Self {
         Self {
+            id: Self::next_id(),
             name: name.to_string(),
             value: value.into(),
+            display_unit: None,
+            live: false,
+            marker: MarkerStyle::default(),
+            reveal: String::new(),
+            history: Vec::new(),
+            created: Some(HistoryEntry::now()),
+            modified: Some(HistoryEntry::now()),
+            history_open: false,
+            confirm_delete: false,
+        }
+    }
+
+    pub fn display_unit_name(unit: Option<TimeUnit>) -> String {
+        unit.map_or("Auto".to_string(), |unit| unit.to_string())
+    }
+
+    pub fn next_display_unit(unit: Option<TimeUnit>) -> Option<TimeUnit> {
+        match unit {
+            None => Some(TimeUnit::Second),
+            Some(TimeUnit::Second) => Some(TimeUnit::Minute),
+            Some(TimeUnit::Minute) => Some(TimeUnit::Hour),
+            Some(TimeUnit::Hour) => Some(TimeUnit::Day),
+            Some(TimeUnit::Day) => Some(TimeUnit::Year),
+            Some(TimeUnit::Year) => None,
+        }
+    }
+
+    pub fn touch(&mut self, now: u64) {
+        self.modified = Some(now);
+    }
+
+    pub fn display_name(&self) -> &str {
+        if self.name.trim().is_empty() {
+            "unnamed thing"
+        } else {
+            &self.name
+        }
+    }
+
+    pub fn row_label(&self, index: usize) -> String {
+        format!("Thing {}: {}", index + 1, self.display_name())
+    }
+
+    pub fn delete_prompt(&self) -> String {
+        format!("Delete \"{}\"?", self.display_name())
+    }
+
+    pub fn next_id() -> u64 {
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub fn record_value(&mut self, previous: ENumber, recorded: u64) -> bool {
+        if previous.relative_eq(&self.value.inner(), Self::HISTORY_EPSILON) {
+            return false;
+        }
+        self.history.push(HistoryEntry {
+            value: previous,
+            recorded,
+        });
+        if self.history.len() > Self::HISTORY_CAP {
+            self.history.remove(0);
+        }
+        true
+    }
+
+    // Puts a value from the history back and hands back the replaced one,
+    // like `TimeScale::commit`; `committed` records it.
+    pub fn revert(&mut self, index: usize) -> Option<ENumber> {
+        if index >= self.history.len() {
+            return None;
+        }
+        let entry = self.history.remove(index);
+        let previous = self.value.inner();
+        self.value = entry.value.into();
+        Some(previous)
+    }
+
+    // Returns whether the old value went into the history.
+    pub fn multiply(&mut self, factor: ENumber, record_history: bool, recorded: u64) -> bool {
+        if self.live {
+            return false;
         }
+        let previous = self.value.inner();
+        self.value = (previous * factor).into();
+        self.touch(recorded);
+        record_history && self.record_value(previous, recorded)
+    }
+
+    pub fn live_value(elapsed: Duration) -> TimeScale {
+        elapsed.as_secs_f64().into()
     }
 
     pub fn scale(&self) -> f64 {
         self.value.inner().erect().1
     }
 
+    pub fn finite_scale(&self) -> Option<f64> {
+        let scale = self.scale();
+        (self.value.inner().significand() > 0. && scale.is_finite()).then_some(scale)
+    }
+
+    pub fn value_text(
+        &self,
+        display: TimeDisplay,
+        epoch: Option<i32>,
+        style: FormatStyle,
+    ) -> String {
+        match (display, self.display_unit) {
+            (TimeDisplay::Historical, None) => self.value.fmt_ago(epoch, style),
+            (_, unit) => self.value.fmt_styled(unit, style),
+        }
+    }
+
+    pub fn tooltip_text(&self, style: FormatStyle) -> String {
+        let value = self.value.inner();
+        format!(
+            "{}\n{}\n{}\nexponent: {}",
+            self.name,
+            value,
+            self.value.fmt_styled(self.display_unit, style),
+            value.exponent()
+        )
+    }
+
     pub fn alpha(index: usize, shift: f64) -> f32 {
         cubic_in((shift - index as f64).clamp(0., 1.) as f32)
     }
 
-    fn x_position(index: usize, half_size: Vec2) -> f64 {
-        -half_size.x - Self::BAR_OFFSET * index as f64
+    fn x_position(offset: f64, half_size: Vec2, direction: Direction) -> f64 {
+        direction.sign() * (half_size.x + offset)
     }
 
     fn y_position(&self, scale: f64) -> f64 {
         self.value.inner().to_scale(scale, Viewport::MAX_HEIGHT)
     }
 
-    pub fn position(&self, index: usize, scale: f64, half_size: Vec2) -> Vec2 {
-        Vec2::new(Self::x_position(index, half_size), self.y_position(scale))
+    pub fn position(
+        &self,
+        index: usize,
+        scale: f64,
+        half_size: Vec2,
+        direction: Direction,
+    ) -> Vec2 {
+        self.position_at(Self::BAR_OFFSET * index as f64, scale, half_size, direction)
     }
 
-    pub fn render_bar(&self, position: Vec2, alpha: f32, scene: &mut Scene, world_camera: Affine) {
-        let rect = Rect::from_origin_size(
-            (position.x - Self::BAR_HALF, 0.),
-            (Self::BAR_WIDTH, position.y),
-        );
-        scene.fill(
-            Fill::NonZero,
-            world_camera,
-            Self::BAR_COLOR.with_alpha(alpha),
-            None,
-            &rect,
-        );
+    pub fn position_at(
+        &self,
+        offset: f64,
+        scale: f64,
+        half_size: Vec2,
+        direction: Direction,
+    ) -> Vec2 {
+        Vec2::new(
+            Self::x_position(offset, half_size, direction),
+            self.y_position(scale),
+        )
+    }
+
+    pub fn render_bar(
+        &self,
+        position: Vec2,
+        BarDraw {
+            color,
+            width,
+            alpha,
+            style,
+        }: BarDraw,
+        scene: &mut Scene,
+        world_camera: Affine,
+    ) {
+        let color = color.with_alpha(alpha);
+        let world_camera = world_camera * style.baseline.transform(position.y);
+        let outline = style
+            .outline
+            .map(|[r, g, b, a]| Color::from_rgba8(r, g, b, a).multiply_alpha(alpha));
+        match self.marker {
+            MarkerStyle::Bar => {}
+            MarkerStyle::Dot => {
+                let dot = Circle::new(position.to_point(), width / 2.);
+                scene.fill(Fill::NonZero, world_camera, color, None, &dot);
+                if let Some(outline) = outline {
+                    scene.stroke(&Stroke::new(1.), world_camera, outline, None, &dot);
+                }
+                return;
+            }
+            MarkerStyle::Tick => {
+                let tick = Line::new(
+                    (position.x - width / 2., position.y),
+                    (position.x + width / 2., position.y),
+                );
+                let stroke = Stroke::new(MarkerStyle::TICK_THICKNESS);
+                scene.stroke(&stroke, world_camera, color, None, &tick);
+                return;
+            }
+        }
+        let rect = Rect::from_origin_size((position.x - width / 2., 0.), (width, position.y));
+        let radius = style.clamped_radius(width, position.y);
+        let shape = RoundedRect::from_rect(rect, RoundedRectRadii::new(0., 0., radius, radius));
+        if style.gradient {
+            let darker = color.map_lightness(|lightness| lightness * BarStyle::GRADIENT_DARKENING);
+            let gradient = Gradient::new_linear((position.x, 0.), (position.x, position.y))
+                .with_stops([darker, color]);
+            scene.fill(Fill::NonZero, world_camera, &gradient, None, &shape);
+        } else {
+            scene.fill(Fill::NonZero, world_camera, color, None, &shape);
+        }
+        if let Some(outline) = outline {
+            scene.stroke(&Stroke::new(1.), world_camera, outline, None, &shape);
+        }
+    }
+
+    // Checks each label against what is behind where it starts: the bar if
+    // it reaches that far, otherwise the canvas for names and the footer for
+    // values under bars. The canvas itself is not themed.
+    pub fn label_colors(
+        &self,
+        position: Vec2,
+        bar: BarDraw,
+        value_color: Color,
+        ui_scale: f64,
+        palette: &Palette,
+    ) -> (Color, Color) {
+        let baseline = bar.style.baseline.transform(position.y);
+        let bounds = baseline.transform_rect_bbox(self.marker.bounds(position, bar.width));
+        let gap = Self::LABEL_GAP * ui_scale;
+        let behind = |y: f64, background: Color| match bounds.y0 <= y && y <= bounds.y1 {
+            true => bar.color,
+            false => background,
+        };
+        let name_y =
+            (baseline * Point::new(0., self.marker.name_anchor(position, bar.width) + gap)).y;
+        let value_y = self.marker.value_anchor(position, bar.width) - gap;
+        let value_background = match self.marker {
+            MarkerStyle::Bar => palette.background,
+            _ => Viewport::BACKGROUND_COLOR,
+        };
+        (
+            readable_color(behind(name_y, Viewport::BACKGROUND_COLOR), palette.text),
+            readable_color(behind(value_y, value_background), value_color),
+        )
     }
 
     pub fn render_name(
         &self,
         position: Vec2,
-        alpha: f32,
+        NameDraw {
+            color,
+            width,
+            alpha,
+            row,
+            ui_scale,
+            style,
+        }: NameDraw,
         fcx: &mut FontContext,
         lcx: &mut LayoutContext<BrushIndex>,
         scene: &mut Scene,
         text_camera: Affine,
     ) {
-        let name_params = (
-            self.name.as_str(),
-            16.,
-            GenericFamily::Serif,
-            None,
-            Some(Self::BAR_HALF as f32 + Self::BAR_GAP as f32),
-            TextAlign::Center,
-        );
-        let text_layout = text_layout(fcx, lcx, name_params);
+        let name_params = TextParams::new(&self.name, 16. * ui_scale as f32)
+            .family(GenericFamily::Serif)
+            .max_advance((Self::BAR_HALF + Self::BAR_GAP) as f32 * ui_scale as f32)
+            .alignment(TextAlign::Center);
+        let text_layout = layout_text(fcx, lcx, &name_params);
+        let text_size = Size::new(text_layout.width() as f64, text_layout.height() as f64);
+        let extent = style.orientation.extent(text_size);
+        // paired names stack like their values so they do not overlap
+        let bottom = self.marker.name_anchor(position, width)
+            + Self::LABEL_GAP * ui_scale
+            + row as f64 * extent;
+        let text_box = style
+            .baseline
+            .transform(position.y)
+            .transform_rect_bbox(Rect::new(0., bottom, 0., bottom + extent));
         render_text(
             scene,
-            text_camera
-                * y_flipped_translate((
-                    position.x - text_layout.width() as f64 / 2.,
-                    position.y + text_layout.height() as f64 + 10.,
-                )),
+            style.orientation.place_text(
+                text_camera,
+                position.x,
+                (text_box.y0, text_box.y1),
+                text_size,
+            ),
             &text_layout,
-            &[Self::NAME_COLOR.with_alpha(alpha).into()],
+            &[color.with_alpha(alpha).into()],
             true,
         );
     }
@@ -108,49 +696,279 @@ impl Thing {
     pub fn render_value(
         &self,
         position: Vec2,
-        alpha: f32,
+        ValueDraw {
+            color,
+            width,
+            alpha,
+            row,
+            settings,
+        }: ValueDraw,
         fcx: &mut FontContext,
         lcx: &mut LayoutContext<BrushIndex>,
         scene: &mut Scene,
         text_camera: Affine,
     ) {
-        let value = format!("{}", self.value);
-        let name_params = (
-            value.as_str(),
-            18.,
-            GenericFamily::Monospace,
-            Some(500.),
-            Some(Self::BAR_OFFSET as f32),
-            TextAlign::Center,
-        );
-        let text_layout = text_layout(fcx, lcx, name_params);
+        let ui_scale = settings.ui_scale;
+        let value = self.value_text(
+            settings.time_display,
+            settings.epoch,
+            settings.format_style(),
+        );
+        let value_params = TextParams::new(&value, 18. * ui_scale as f32)
+            .family(GenericFamily::Monospace)
+            .weight(500.)
+            .max_advance(Self::BAR_OFFSET as f32 * ui_scale as f32)
+            .alignment(TextAlign::Center);
+        let text_layout = layout_text(fcx, lcx, &value_params);
+        let text_size = Size::new(text_layout.width() as f64, text_layout.height() as f64);
+        let orientation = settings.bar_style.orientation;
+        let extent = orientation.extent(text_size);
+        let top = self.marker.value_anchor(position, width)
+            - Self::LABEL_GAP * ui_scale
+            - row as f64 * extent;
+        let transform =
+            orientation.place_text(text_camera, position.x, (top - extent, top), text_size);
+        if settings.value_plates {
+            let rect = Self::label_plate(
+                text_layout.width() as f64,
+                text_layout.height() as f64,
+                ui_scale,
+            );
+            let plate_color = Viewport::FOOTER_AREA_COLOR.with_alpha(Self::PLATE_ALPHA * alpha);
+            scene.fill(Fill::NonZero, transform, plate_color, None, &rect);
+        }
         render_text(
             scene,
-            text_camera * y_flipped_translate((position.x - text_layout.width() as f64 / 2., -10.)),
+            transform,
             &text_layout,
-            &[Self::VALUE_COLOR.with_alpha(alpha).into()],
+            &[color.with_alpha(alpha).into()],
             true,
         );
     }
 
-    pub fn view(&mut self) -> impl WidgetView<Edit<Self>, bool> + use<> {
+    fn history_view(
+        &mut self,
+        palette: Palette,
+        style: FormatStyle,
+        record_history: bool,
+    ) -> impl WidgetView<Edit<Self>, Option<ThingAction>> + use<> {
+        let toggle = (!self.history.is_empty()).then(|| {
+            text_button(
+                format!(
+                    "{} history ({})",
+                    if self.history_open { "Hide" } else { "Show" },
+                    self.history.len()
+                ),
+                |state: &mut Self| {
+                    state.history_open = !state.history_open;
+                    None
+                },
+            )
+        });
+        let now = HistoryEntry::now();
+        let entries = self.history_open.then(|| {
+            self.history
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(index, entry)| {
+                    flex_row((
+                        label(TimeScale::from(entry.value).fmt_styled(self.display_unit, style))
+                            .color(palette.accent),
+                        label(entry.age(now)).color(palette.muted_text),
+                        text_button("Revert to this", move |state: &mut Self| {
+                            let previous = state.revert(index)?;
+                            state.committed(previous, record_history)
+                        }),
+                    ))
+                })
+                .collect::<Vec<_>>()
+        });
+        flex_col((toggle, entries))
+    }
+
+    fn delete_view(&mut self) -> impl WidgetView<Edit<Self>, Option<ThingAction>> + use<> {
+        if self.confirm_delete {
+            Either::A(flex_row((
+                label(self.delete_prompt()).color(css::RED),
+                text_button("Cancel", |state: &mut Self| {
+                    state.confirm_delete = false;
+                    None
+                }),
+                button(label("Confirm delete").color(css::RED), |_| {
+                    Some(ThingAction::Delete)
+                }),
+            )))
+        } else {
+            Either::B(button(
+                label("Delete").color(css::RED),
+                |state: &mut Self| {
+                    state.confirm_delete = true;
+                    None
+                },
+            ))
+        }
+    }
+
+    pub(crate) fn committed(
+        &mut self,
+        previous: ENumber,
+        record_history: bool,
+    ) -> Option<ThingAction> {
+        let now = HistoryEntry::now();
+        self.touch(now);
+        let recorded = record_history && self.record_value(previous, now);
+        Some(ThingAction::CommitValue { previous, recorded })
+    }
+
+    // A single line for the compact data list. An edit left open in a
+    // collapsed card can still be applied or cancelled from here.
+    pub fn compact_view(
+        &mut self,
+        index: usize,
+        expanded: bool,
+        settings: Settings,
+    ) -> impl WidgetView<Edit<Self>, Option<ThingAction>> + use<> {
+        let record_history = settings.record_history;
+        let palette = settings.palette;
+        let pending = (!expanded && self.value.is_editing()).then(|| {
+            flex_row((
+                label("Unapplied edit").color(Level::Warning.color()),
+                text_button("Apply", move |state: &mut Self| {
+                    // a rejected draft opens the card so the error is visible
+                    match state.value.commit() {
+                        Some(previous) => state.committed(previous, record_history),
+                        None => Some(ThingAction::SetExpanded(true)),
+                    }
+                }),
+                text_button("Cancel", |state: &mut Self| {
+                    state.value.cancel();
+                    None
+                }),
+            ))
+        });
+        // the expanded card has its own name input and delete button
+        let name = if expanded {
+            Either::A(label(self.display_name().to_string()).color(palette.text))
+        } else {
+            Either::B(
+                text_input(self.name.clone(), |state: &mut Self, value| {
+                    state.name = value;
+                    state.touch(HistoryEntry::now());
+                    None
+                })
+                .placeholder("Name or description"),
+            )
+        };
+        let delete = (!expanded).then(|| self.delete_view());
+        flex_row((
+            label(format!("{}.", index + 1)).color(palette.muted_text),
+            name.flex(1.),
+            label(
+                self.value
+                    .fmt_styled(self.display_unit, settings.format_style()),
+            )
+            .color(palette.accent),
+            pending,
+            text_button(if expanded { "Done" } else { "✎ Edit" }, move |_| {
+                Some(ThingAction::SetExpanded(!expanded))
+            }),
+            delete,
+        ))
+    }
+
+    pub fn view(
+        &mut self,
+        index: usize,
+        settings: Settings,
+    ) -> impl WidgetView<Edit<Self>, Option<ThingAction>> + use<> {
+        let display_unit = self.display_unit;
+        let record_history = settings.record_history;
+        let (slider_min, slider_max) = settings.slider_range();
+        let palette = settings.palette;
+        let (card_background, card_border) = palette.card_colors();
+        let value_editor = if self.live {
+            Either::A(label("Grows with the time since launch").color(palette.accent))
+        } else {
+            Either::B(
+                lens(
+                    move |value: &mut TimeScale| value.view(display_unit),
+                    move |state: &mut Self, ()| &mut state.value,
+                )
+                .map_action(move |state: &mut Self, previous| {
+                    state.committed(previous?, record_history)
+                }),
+            )
+        };
+        let value_slider = (!self.live).then(|| {
+            flex_row((
+                label(format!("1e{slider_min} s")).color(palette.muted_text),
+                slider(
+                    slider_min,
+                    slider_max,
+                    self.value.slider_position(slider_min, slider_max),
+                    |state: &mut Self, position| {
+                        state.value = TimeScale::from_slider(position);
+                        state.touch(HistoryEntry::now());
+                        None
+                    },
+                )
+                .flex(1.),
+                label(format!("1e{slider_max} s")).color(palette.muted_text),
+            ))
+        });
         sized_box(
             flex_col((
+                label(self.row_label(index))
+                    .weight(FontWeight::BOLD)
+                    .color(palette.text),
                 label("Name or description:")
                     .weight(FontWeight::SEMI_BOLD)
-                    .color(Self::NAME_COLOR),
+                    .color(palette.text),
                 text_input(self.name.clone(), |state: &mut Self, value| {
                     state.name = value;
-                    false
-                }),
+                    state.touch(HistoryEntry::now());
+                    None
+                })
+                .placeholder("Name or description"),
                 label("Value:")
                     .weight(FontWeight::SEMI_BOLD)
-                    .color(Self::NAME_COLOR),
-                lens(TimeScale::view, move |state: &mut Self, ()| {
-                    &mut state.value
+                    .color(palette.text),
+                value_editor,
+                value_slider,
+                self.history_view(palette, settings.format_style(), record_history),
+                checkbox(
+                    "Live (time since launch)",
+                    self.live,
+                    |state: &mut Self, checked| {
+                        state.live = checked;
+                        state.touch(HistoryEntry::now());
+                        None
+                    },
+                ),
+                flex_row(text_button(
+                    format!("Display unit: {}", Self::display_unit_name(display_unit)),
+                    |state: &mut Self| {
+                        state.display_unit = Self::next_display_unit(state.display_unit);
+                        state.touch(HistoryEntry::now());
+                        None
+                    },
+                )),
+                flex_row(text_button(
+                    format!("Marker: {}", self.marker),
+                    |state: &mut Self| {
+                        state.marker = state.marker.next();
+                        state.touch(HistoryEntry::now());
+                        None
+                    },
+                )),
+                text_input(self.reveal.clone(), |state: &mut Self, value| {
+                    state.reveal = value;
+                    state.touch(HistoryEntry::now());
+                    None
                 })
-                .map_action(|_, _| false),
-                flex_row(button(label("Delete").color(css::RED), |_| true))
+                .placeholder("Reveal text, shown while the tour pauses here"),
+                flex_row(self.delete_view())
                     .must_fill_major_axis(true)
                     .main_axis_alignment(MainAxisAlignment::End),
             ))
@@ -159,7 +977,295 @@ impl Thing {
         .expand_width()
         .corner_radius(10.)
         .padding(10.)
-        .border(Viewport::MINOR_LINE_COLOR, 1.)
-        .background_color(Viewport::FOOTER_AREA_COLOR)
+        .border(card_border, 1.)
+        .background_color(card_background)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar_radius_is_clamped() {
+        let style = BarStyle {
+            corner_radius: 8.,
+            ..BarStyle::default()
+        };
+        assert_eq!(style.clamped_radius(40., 500.), 8.);
+        assert_eq!(style.clamped_radius(40., 3.), 3.);
+        assert_eq!(style.clamped_radius(10., 500.), 5.);
+        assert_eq!(style.clamped_radius(40., -2.), 2.);
+        assert_eq!(style.clamped_radius(40., 0.), 0.);
+        assert_eq!(BarStyle::default().clamped_radius(40., 500.), 0.);
+    }
+
+    #[test]
+    fn test_baseline_rect_origin() {
+        let rect = Rect::new(120., 0., 160., 300.);
+        let placed = |baseline: Baseline| baseline.transform(300.).transform_rect_bbox(rect);
+        assert_eq!(placed(Baseline::Bottom), rect);
+        assert_eq!(
+            placed(Baseline::Top),
+            Rect::new(
+                120.,
+                Viewport::MAX_HEIGHT - 300.,
+                160.,
+                Viewport::MAX_HEIGHT
+            )
+        );
+        assert_eq!(placed(Baseline::Center), Rect::new(120., 350., 160., 650.));
+        for baseline in [Baseline::Bottom, Baseline::Top, Baseline::Center] {
+            assert_eq!(placed(baseline).size(), rect.size());
+        }
+
+        assert_eq!(Baseline::Bottom.gridline(200.), Some(200.));
+        assert_eq!(
+            Baseline::Top.gridline(200.),
+            Some(Viewport::MAX_HEIGHT - 200.)
+        );
+        assert_eq!(Baseline::Center.gridline(200.), None);
+    }
+
+    #[test]
+    fn test_marker_label_anchors() {
+        let position = Vec2::new(140., 300.);
+        let width = Thing::BAR_WIDTH;
+        let anchors = |marker: MarkerStyle| {
+            (
+                marker.name_anchor(position, width),
+                marker.value_anchor(position, width),
+            )
+        };
+        assert_eq!(anchors(MarkerStyle::Bar), (300., 0.));
+        assert_eq!(anchors(MarkerStyle::Dot), (320., 280.));
+        assert_eq!(anchors(MarkerStyle::Tick), (301.5, 298.5));
+
+        let bounds = MarkerStyle::Dot.bounds(position, width);
+        assert_eq!(bounds.center(), position.to_point());
+        assert_eq!(bounds.width(), bounds.height());
+        let bounds = MarkerStyle::Bar.bounds(Vec2::new(0., -50.), width);
+        assert_eq!((bounds.y0, bounds.y1), (-50., 0.));
+    }
+
+    #[test]
+    fn test_label_plate_matches_bounds() {
+        let plate = Thing::label_plate(120., 22., 1.);
+        let padding = Thing::PLATE_PADDING;
+        assert_eq!(
+            plate.rect(),
+            Rect::new(-padding, -padding, 120. + padding, 22. + padding)
+        );
+        assert_eq!(plate.radii(), RoundedRectRadii::from_single_radius(4.));
+
+        let plate = Thing::label_plate(60., 11., 2.);
+        assert_eq!(
+            plate.rect().size(),
+            (60. + 4. * padding, 11. + 4. * padding).into()
+        );
+        assert!(plate.rect().contains_rect(Rect::new(0., 0., 60., 11.)));
+    }
+
+    #[test]
+    fn test_label_colors() {
+        let mut thing = Thing::new("Blink", 0.1);
+        let position = Vec2::new(0., 300.);
+        let dark = Palette::DARK;
+        let draw = |color| BarDraw {
+            color,
+            width: Thing::BAR_WIDTH,
+            alpha: 1.,
+            style: BarStyle::default(),
+        };
+        let colors = |thing: &Thing, position, bar, value_color, palette| {
+            thing.label_colors(position, bar, value_color, 1., palette)
+        };
+        let bar = draw(Thing::BAR_COLOR);
+        assert_eq!(
+            colors(&thing, position, bar, Thing::VALUE_COLOR, &dark),
+            (Thing::NAME_COLOR, Thing::VALUE_COLOR)
+        );
+        assert_eq!(
+            colors(&thing, position, bar, css::LIGHT_SKY_BLUE, &dark),
+            (Thing::NAME_COLOR, css::LIGHT_SKY_BLUE)
+        );
+        let dim = Color::from_rgb8(40, 40, 60);
+        assert_eq!(colors(&thing, position, bar, dim, &dark).1, Color::WHITE);
+
+        let light = Palette {
+            background: css::WHITE_SMOKE,
+            text: css::BLACK,
+            ..Palette::DARK
+        };
+        let pale = draw(css::LEMON_CHIFFON);
+        // names stay on the dark canvas, values sit on the light footer
+        assert_eq!(
+            colors(&thing, position, pale, Thing::VALUE_COLOR, &light),
+            (Color::WHITE, Color::BLACK)
+        );
+        // a bar hanging from the top past the axis runs behind the value
+        let hanging = BarDraw {
+            style: BarStyle {
+                baseline: Baseline::Top,
+                ..BarStyle::default()
+            },
+            ..pale
+        };
+        let tall = Vec2::new(0., Viewport::MAX_HEIGHT + 50.);
+        assert_eq!(
+            colors(&thing, tall, hanging, css::WHITE, &dark),
+            (Thing::NAME_COLOR, Color::BLACK)
+        );
+        let navy = BarDraw {
+            color: css::NAVY,
+            ..hanging
+        };
+        assert_eq!(colors(&thing, tall, navy, css::WHITE, &light).1, css::WHITE);
+        assert_eq!(
+            colors(&thing, position, hanging, css::WHITE, &light).1,
+            Color::BLACK
+        );
+
+        thing.marker = MarkerStyle::Dot;
+        assert_eq!(colors(&thing, position, pale, dim, &light).1, Color::WHITE);
+        assert_eq!(
+            colors(&thing, position, pale, Thing::BAR_COLOR, &light).1,
+            Thing::BAR_COLOR
+        );
+    }
+
+    #[test]
+    fn test_accessible_labels() {
+        let mut thing = Thing::new("Blink", 0.1);
+        assert_eq!(thing.row_label(0), "Thing 1: Blink");
+        assert_eq!(thing.delete_prompt(), "Delete \"Blink\"?");
+        thing.name = "  ".to_string();
+        assert_eq!(thing.row_label(2), "Thing 3: unnamed thing");
+        assert!(!thing.confirm_delete);
+        assert!(
+            !serde_json::to_string(&thing)
+                .unwrap()
+                .contains("confirm_delete")
+        );
+    }
+
+    #[test]
+    fn test_history_records_changes() {
+        let mut thing = Thing::new("Proton decay", (1., 34));
+        assert!(!thing.record_value(ENumber::new(1., 34), 10));
+        assert!(!thing.record_value(ENumber::new(1. + 1e-12, 34), 10));
+        assert!(thing.record_value(ENumber::new(2., 34), 10));
+        assert_eq!(thing.history.len(), 1);
+
+        let mut thing = Thing::new("Heat death", (1., 100000));
+        assert!(!thing.record_value(ENumber::new(1. + 1e-12, 100000), 10));
+        assert!(!thing.record_value(ENumber::new(10., 99999), 10));
+        assert!(thing.record_value(ENumber::new(1.0001, 100000), 10));
+        assert!(thing.record_value(ENumber::new(1., 100001), 10));
+        assert!(thing.record_value(ENumber::new(1., -100000), 10));
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut thing = Thing::new("Blink", 0.3);
+        for i in 0..15 {
+            thing.record_value(ENumber::from(i as f64 + 1.), i);
+        }
+        assert_eq!(thing.history.len(), Thing::HISTORY_CAP);
+        assert_eq!(thing.history[0].recorded, 5);
+        assert_eq!(thing.history[0].value, ENumber::from(6.));
+    }
+
+    #[test]
+    fn test_history_revert() {
+        let mut thing = Thing::new("Blink", 0.3);
+        thing.record_value(ENumber::from(0.1), 10);
+        thing.record_value(ENumber::from(0.2), 20);
+        assert_eq!(thing.revert(0), Some(ENumber::from(0.3)));
+        assert_eq!(thing.value.inner(), ENumber::from(0.1));
+        assert_eq!(thing.history.len(), 1);
+        assert_eq!(
+            thing.committed(ENumber::from(0.3), true),
+            Some(ThingAction::CommitValue {
+                previous: ENumber::from(0.3),
+                recorded: true,
+            })
+        );
+        assert_eq!(thing.history.len(), 2);
+        assert_eq!(thing.history[1].value, ENumber::from(0.3));
+        assert_eq!(thing.revert(5), None);
+        assert_eq!(thing.history.len(), 2);
+
+        thing.revert(0);
+        let action = thing.committed(ENumber::from(0.1), false);
+        assert_eq!(
+            action,
+            Some(ThingAction::CommitValue {
+                previous: ENumber::from(0.1),
+                recorded: false,
+            })
+        );
+        assert_eq!(thing.history.len(), 1);
+    }
+
+    #[test]
+    fn test_edits_update_modified() {
+        let mut thing = Thing::new("Blink", 0.3);
+        let created = thing.created;
+        assert!(created.is_some());
+        assert_eq!(thing.modified, created);
+
+        thing.record_value(ENumber::from(0.1), 10);
+        thing.touch(4_000_000_000);
+        assert_eq!(thing.modified, Some(4_000_000_000));
+        assert_eq!(thing.created, created);
+
+        thing.touch(4_000_000_100);
+        assert_eq!(thing.modified, Some(4_000_000_100));
+        assert_eq!(thing.created, created);
+
+        let loaded: Thing = serde_json::from_str(&serde_json::to_string(&thing).unwrap()).unwrap();
+        assert_eq!(
+            (loaded.created, loaded.modified),
+            (created, Some(4_000_000_100))
+        );
+
+        let old: Thing = serde_json::from_str(
+            r#"{"name":"Blink","value":[{"significand":3.0,"exponent":-1.0}]}"#,
+        )
+        .unwrap();
+        assert_eq!((old.created, old.modified), (None, None));
+    }
+
+    #[test]
+    fn test_history_serialization() {
+        let thing = Thing::new("Blink", 0.3);
+        let json = serde_json::to_string(&thing).unwrap();
+        assert!(!json.contains("history"));
+
+        let mut thing = thing;
+        thing.record_value(ENumber::from(0.1), 10);
+        let json = serde_json::to_string(&thing).unwrap();
+        let loaded: Thing = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.history, thing.history);
+    }
+
+    #[test]
+    fn test_tooltip_text() {
+        let thing = Thing::new("Hydrogen-7 half-life", (2.3, -23));
+        assert_eq!(
+            thing.tooltip_text(FormatStyle::default()),
+            "Hydrogen-7 half-life\n2.3e-23\n2.3e-23 s\nexponent: -23"
+        );
+    }
+
+    #[test]
+    fn test_live_value() {
+        let value = Thing::live_value(Duration::from_secs(90));
+        assert_eq!(value.inner().collapse(), Some(90.));
+        assert_eq!(value.fmt_secs(), "90 s");
+
+        let value = Thing::live_value(Duration::from_secs(3 * 3600));
+        assert_eq!(value.fmt_in(TimeUnit::Hour), "3 h");
     }
 }
@@ -0,0 +1,279 @@
+use crate::math::ENumber;
+use crate::thing::{HistoryEntry, MarkerStyle, Thing};
+use crate::units::{TimeScale, TimeUnit};
+
+// A committed value change. Undoing it puts `previous` back, and drops the
+// history entry the commit recorded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValueEdit {
+    pub id: u64,
+    pub previous: ENumber,
+    pub value: ENumber,
+    pub recorded: bool,
+}
+
+// The look of a thing before a bulk edit changed it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StyleEdit {
+    pub id: u64,
+    pub marker: MarkerStyle,
+    pub display_unit: Option<TimeUnit>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Change {
+    Value(ValueEdit),
+    Style(StyleEdit),
+    Delete { index: usize, thing: Box<Thing> },
+}
+
+impl Change {
+    fn is_noop(&self) -> bool {
+        matches!(self, Change::Value(edit) if edit.previous == edit.value)
+    }
+
+    // Puts things back as they were; false when the thing is gone.
+    fn revert(&self, things: &mut Vec<Thing>, now: u64) -> bool {
+        let id = match self {
+            Change::Value(ValueEdit { id, .. }) | Change::Style(StyleEdit { id, .. }) => *id,
+            Change::Delete { index, thing } => {
+                things.insert((*index).min(things.len()), thing.as_ref().clone());
+                return true;
+            }
+        };
+        let Some(thing) = things.iter_mut().find(|thing| thing.id == id) else {
+            return false;
+        };
+        match self {
+            Change::Value(edit) => {
+                thing.value = TimeScale::from(edit.previous);
+                if edit.recorded
+                    && thing
+                        .history
+                        .last()
+                        .is_some_and(|entry| entry.value == edit.previous)
+                {
+                    thing.history.pop();
+                }
+            }
+            Change::Style(edit) => {
+                thing.marker = edit.marker;
+                thing.display_unit = edit.display_unit;
+            }
+            Change::Delete { .. } => {}
+        }
+        thing.touch(now);
+        true
+    }
+}
+
+impl From<ValueEdit> for Change {
+    fn from(edit: ValueEdit) -> Self {
+        Change::Value(edit)
+    }
+}
+
+impl From<StyleEdit> for Change {
+    fn from(edit: StyleEdit) -> Self {
+        Change::Style(edit)
+    }
+}
+
+// Each step holds the changes one action made, undone together.
+#[derive(Default)]
+pub struct UndoStack {
+    steps: Vec<Vec<Change>>,
+}
+
+impl UndoStack {
+    pub const CAP: usize = 100;
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn last(&self) -> Option<&[Change]> {
+        self.steps.last().map(Vec::as_slice)
+    }
+
+    pub fn push(&mut self, change: impl Into<Change>) {
+        self.push_all(vec![change.into()]);
+    }
+
+    pub fn push_all(&mut self, mut changes: Vec<Change>) {
+        changes.retain(|change| !change.is_noop());
+        if changes.is_empty() {
+            return;
+        }
+        self.steps.push(changes);
+        if self.steps.len() > Self::CAP {
+            self.steps.remove(0);
+        }
+    }
+
+    // What the undo button offers for the last step.
+    pub fn label(&self, things: &[Thing]) -> Option<String> {
+        let name = |id: u64| {
+            things
+                .iter()
+                .find(|thing| thing.id == id)
+                .map(|thing| format!(" ({})", thing.display_name()))
+                .unwrap_or_default()
+        };
+        Some(match self.last()? {
+            [Change::Value(edit)] => format!("Undo value edit{}", name(edit.id)),
+            [Change::Delete { thing, .. }] => format!("Undo delete ({})", thing.display_name()),
+            [Change::Style(edit)] => format!("Undo bulk edit{}", name(edit.id)),
+            changes @ [Change::Style(_), ..] => {
+                format!("Undo bulk edit ({} things)", changes.len())
+            }
+            changes => format!("Undo value edits ({} things)", changes.len()),
+        })
+    }
+
+    // Changes to things that were deleted since are dropped on the way.
+    pub fn undo(&mut self, things: &mut Vec<Thing>) -> Option<Vec<Change>> {
+        let now = HistoryEntry::now();
+        while let Some(changes) = self.steps.pop() {
+            let mut undone = changes
+                .into_iter()
+                .rev()
+                .filter(|change| change.revert(things, now))
+                .collect::<Vec<_>>();
+            undone.reverse();
+            if !undone.is_empty() {
+                return Some(undone);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(thing: &Thing, previous: f64) -> ValueEdit {
+        ValueEdit {
+            id: thing.id,
+            previous: ENumber::from(previous),
+            value: thing.value.inner(),
+            recorded: false,
+        }
+    }
+
+    #[test]
+    fn test_undo_skips_deleted_things_and_no_ops() {
+        let mut things = vec![Thing::new("Blink", 0.3), Thing::new("Minute", 60.)];
+        let mut stack = UndoStack::default();
+        stack.push(edit(&things[0], 0.2));
+        stack.push(edit(&things[1], 30.));
+        stack.push(edit(&things[1], 60.));
+        assert_eq!(stack.len(), 2);
+
+        things.remove(1);
+        let undone = stack.undo(&mut things).unwrap();
+        assert!(matches!(undone[..], [Change::Value(edit)] if edit.id == things[0].id));
+        assert_eq!(things[0].value.inner(), ENumber::from(0.2));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_batch_is_undone_in_one_step() {
+        let mut things = vec![Thing::new("Blink", 0.3), Thing::new("Minute", 60.)];
+        let mut stack = UndoStack::default();
+        stack.push_all(
+            things
+                .iter()
+                .map(|thing| {
+                    ValueEdit {
+                        previous: thing.value.inner() * ENumber::from(0.5),
+                        ..edit(thing, 0.)
+                    }
+                    .into()
+                })
+                .collect(),
+        );
+        stack.push_all(Vec::new());
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.last().map(<[_]>::len), Some(2));
+        assert_eq!(
+            stack.label(&things).as_deref(),
+            Some("Undo value edits (2 things)")
+        );
+        assert_eq!(
+            stack.undo(&mut things).map(|changes| changes.len()),
+            Some(2)
+        );
+        assert_eq!(things[0].value.inner(), ENumber::from(0.15));
+        assert_eq!(things[1].value.inner(), ENumber::from(30.));
+        assert!(stack.undo(&mut things).is_none());
+    }
+
+    #[test]
+    fn test_undo_drops_the_recorded_history_entry() {
+        let mut things = vec![Thing::new("Blink", 0.3)];
+        let previous = things[0].value.inner();
+        things[0].value = TimeScale::from(0.5);
+        assert!(things[0].record_value(previous, 1));
+        let mut stack = UndoStack::default();
+        stack.push(ValueEdit {
+            recorded: true,
+            ..edit(&things[0], 0.3)
+        });
+        stack.undo(&mut things);
+        assert_eq!(things[0].value.inner(), previous);
+        assert!(things[0].history.is_empty());
+    }
+
+    #[test]
+    fn test_deletes_and_bulk_edits_share_the_stack() {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Minute", 60.),
+            Thing::new("Day", 86400.),
+        ];
+        let mut stack = UndoStack::default();
+        let styles = things
+            .iter_mut()
+            .map(|thing| {
+                let previous = StyleEdit {
+                    id: thing.id,
+                    marker: thing.marker,
+                    display_unit: thing.display_unit,
+                };
+                thing.marker = MarkerStyle::Dot;
+                previous.into()
+            })
+            .collect();
+        stack.push_all(styles);
+        let thing = things.remove(1);
+        let id = thing.id;
+        stack.push(Change::Delete {
+            index: 1,
+            thing: Box::new(thing),
+        });
+        assert_eq!(
+            stack.label(&things).as_deref(),
+            Some("Undo delete (Minute)")
+        );
+
+        stack.undo(&mut things);
+        assert_eq!(things[1].id, id);
+        assert_eq!(
+            stack.label(&things).as_deref(),
+            Some("Undo bulk edit (3 things)")
+        );
+        stack.undo(&mut things);
+        assert!(
+            things
+                .iter()
+                .all(|thing| thing.marker == MarkerStyle::default())
+        );
+        assert!(stack.is_empty());
+    }
+}
@@ -1,15 +1,17 @@
 use std::cmp::Ordering;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use xilem::WidgetView;
 use xilem::core::one_of::Either;
-use xilem::core::{Edit, lens};
+use xilem::core::{Edit, View, lens};
 use xilem::style::Style;
 use xilem::view::{FlexExt, button, flex_row, label, text_button, text_input};
 
-use crate::math::{ENumber, ENumberEditor};
+use crate::math::{ENumber, ENumberEditor, ParseENumberError};
+use crate::notice::Level;
 use crate::thing::Thing;
-use crate::utils::float_to_string;
+use crate::utils::{DEFAULT_SIGNIFICANT_DIGITS, float_to_string_with};
 
 pub const MINUTE: f64 = 60_f64;
 pub const HOUR: f64 = 3600_f64;
@@ -22,7 +24,7 @@ pub const GIGA: f64 = 1_000_000_000_f64;
 pub const TERA: f64 = 1_000_000_000_000_f64;
 pub const PETA: f64 = 1_000_000_000_000_000_f64;
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug)]
 pub enum UnitPrefix {
     #[default]
     None,
@@ -35,7 +37,7 @@ pub enum UnitPrefix {
 impl std::fmt::Display for UnitPrefix {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            UnitPrefix::None => write!(f, "-"),
+            UnitPrefix::None => Ok(()),
             UnitPrefix::Kilo => write!(f, "Kilo"),
             UnitPrefix::Mega => write!(f, "Mega"),
             UnitPrefix::Giga => write!(f, "Giga"),
@@ -56,7 +58,7 @@ impl UnitPrefix {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TimeUnit {
     #[default]
     Second,
@@ -88,56 +90,332 @@ impl TimeUnit {
             TimeUnit::Year => YEAR,
         }
     }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TimeUnit::Second => "s",
+            TimeUnit::Minute => "m",
+            TimeUnit::Hour => "h",
+            TimeUnit::Day => "d",
+            TimeUnit::Year => "y",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TimeDisplay {
+    #[default]
+    Duration,
+    Historical,
+}
+
+impl std::fmt::Display for TimeDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TimeDisplay::Duration => write!(f, "Duration"),
+            TimeDisplay::Historical => write!(f, "Years ago"),
+        }
+    }
+}
+
+impl TimeDisplay {
+    pub fn next(self) -> Self {
+        match self {
+            TimeDisplay::Duration => TimeDisplay::Historical,
+            TimeDisplay::Historical => TimeDisplay::Duration,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseTimeScaleError {
+    Number(ParseENumberError),
+    UnknownUnit(String),
+    Ambiguous(String),
+}
+
+impl std::fmt::Display for ParseTimeScaleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseTimeScaleError::Number(err) => write!(f, "{err}"),
+            ParseTimeScaleError::UnknownUnit(unit) => write!(f, "unknown unit \"{unit}\""),
+            ParseTimeScaleError::Ambiguous(reason) => write!(f, "ambiguous: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseTimeScaleError {}
+
+impl From<ParseENumberError> for ParseTimeScaleError {
+    fn from(value: ParseENumberError) -> Self {
+        ParseTimeScaleError::Number(value)
+    }
+}
+
+pub fn split_unit(text: &str) -> (&str, &str) {
+    let chars = text.char_indices().collect::<Vec<_>>();
+    for (pos, &(i, c)) in chars.iter().enumerate() {
+        if !c.is_alphabetic() {
+            continue;
+        }
+        let after_digit = pos > 0 && matches!(chars[pos - 1].1, '0'..='9' | '.');
+        let before_exponent = matches!(chars.get(pos + 1), Some((_, '0'..='9' | '-' | '+')));
+        if !(matches!(c, 'e' | 'E') && after_digit && before_exponent) {
+            return (text[..i].trim(), text[i..].trim());
+        }
+    }
+    (text.trim(), "")
+}
+
+pub fn word_multiplier(word: &str) -> Option<f64> {
+    Some(match word.to_lowercase().as_str() {
+        "thousand" => KILO,
+        "million" => MEGA,
+        "billion" => GIGA,
+        "trillion" => TERA,
+        "quadrillion" => PETA,
+        _ => return None,
+    })
+}
+
+fn split_multiplier(unit: &str) -> Result<(f64, &str), ParseTimeScaleError> {
+    let (word, rest) = unit
+        .split_once(char::is_whitespace)
+        .map_or((unit, ""), |(word, rest)| (word, rest.trim()));
+    match word_multiplier(word) {
+        Some(_) if rest.is_empty() => Err(ParseTimeScaleError::Ambiguous(format!(
+            "\"{word}\" is not followed by a unit"
+        ))),
+        Some(multiplier) => Ok((multiplier, rest)),
+        None => Ok((1., unit)),
+    }
+}
+
+fn unit_factor(unit: &str) -> Result<f64, ParseTimeScaleError> {
+    Ok(match unit {
+        "" | "s" | "sec" | "secs" | "second" | "seconds" => 1.,
+        "ms" => 1e-3,
+        "us" | "µs" | "μs" => 1e-6,
+        "ns" => 1e-9,
+        "ps" => 1e-12,
+        "fs" => 1e-15,
+        "m" | "min" | "mins" | "minute" | "minutes" => MINUTE,
+        "h" | "hr" | "hrs" | "hour" | "hours" => HOUR,
+        "d" | "day" | "days" => DAY,
+        "w" | "wk" | "week" | "weeks" => 7. * DAY,
+        "y" | "yr" | "yrs" | "year" | "years" => YEAR,
+        "ky" | "kyr" => KILO * YEAR,
+        "My" | "Myr" => MEGA * YEAR,
+        "Gy" | "Gyr" => GIGA * YEAR,
+        "Ty" | "Tyr" => TERA * YEAR,
+        "M" | "mo" | "month" | "months" => {
+            return Err(ParseTimeScaleError::Ambiguous(format!(
+                "\"{unit}\" could be months or mega, which have no fixed length"
+            )));
+        }
+        _ if unit.len() > 2 && unit.chars().any(char::is_uppercase) => {
+            return unit_factor(&unit.to_lowercase());
+        }
+        _ => return Err(ParseTimeScaleError::UnknownUnit(unit.to_string())),
+    })
 }
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct TimeScale(ENumber, #[serde(skip)] ENumberEditor);
 
 impl std::fmt::Display for TimeScale {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.pad(&self.fmt_duration(DEFAULT_SIGNIFICANT_DIGITS, FormatStyle::DEFAULT_EXP_BREAK))
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Notation {
+    // the largest fitting unit, as `Display` picks it
+    #[default]
+    Duration,
+    Seconds,
+    Scientific,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Locale {
+    #[default]
+    DecimalPoint,
+    DecimalComma,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FormatStyle {
+    pub digits: usize,
+    pub notation: Notation,
+    pub locale: Locale,
+    pub exp_break: u32,
+}
+
+impl Default for FormatStyle {
+    fn default() -> Self {
+        Self {
+            digits: DEFAULT_SIGNIFICANT_DIGITS,
+            notation: Notation::default(),
+            locale: Locale::default(),
+            exp_break: Self::DEFAULT_EXP_BREAK,
+        }
+    }
+}
+
+impl FormatStyle {
+    pub const DEFAULT_EXP_BREAK: u32 = 6;
+}
+
+/// Formats a [`TimeScale`] with a [`FormatStyle`], honoring width and
+/// alignment flags.
+///
+/// ```
+/// use scale_comparison::TimeScale;
+/// use scale_comparison::units::{FormatStyle, Locale, Notation};
+///
+/// let style = FormatStyle {
+///     digits: 3,
+///     notation: Notation::Seconds,
+///     locale: Locale::DecimalComma,
+///     ..FormatStyle::default()
+/// };
+/// let value = TimeScale::from(1234.5678);
+/// assert_eq!(format!("[{:>10}]", value.display_with(style)), "[    1230 s]");
+/// assert_eq!(format!("{}", TimeScale::from(0.25).display_with(style)), "0,25 s");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct TimeScaleFmt<'a> {
+    value: &'a TimeScale,
+    style: FormatStyle,
+}
+
+impl std::fmt::Display for TimeScaleFmt<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let FormatStyle {
+            digits,
+            notation,
+            locale,
+            exp_break,
+        } = self.style;
+        let value = self.value.0;
+        let text = match notation {
+            Notation::Duration => self.value.fmt_duration(digits, exp_break),
+            Notation::Seconds => format!("{} s", value.fmt_exp_break_with(exp_break, digits)),
+            Notation::Scientific => format!(
+                "{}e{} s",
+                float_to_string_with(value.significand(), digits),
+                value.exponent()
+            ),
+        };
+        match locale {
+            Locale::DecimalPoint => f.pad(&text),
+            Locale::DecimalComma => f.pad(&text.replace('.', ",")),
+        }
+    }
+}
+
+/// Formats a duration in seconds the way the chart labels it.
+///
+/// Durations up to a minute stay in seconds, then the largest fitting unit is
+/// picked: minutes, hours, days, years, and mega/giga/tera years. Anything
+/// beyond that falls back to years (or seconds below one) in scientific notation.
+///
+/// ```
+/// use scale_comparison::units::{DAY, HOUR, MINUTE, format_time};
+///
+/// assert_eq!(format_time(0.00086), "0.00086 s");
+/// assert_eq!(format_time((5.39, -44)), "5.39e-44 s");
+/// assert_eq!(format_time(8. * MINUTE + 20.), "8 m 20 s");
+/// assert_eq!(format_time(HOUR + 32. * MINUTE), "1 h 32 m");
+/// assert_eq!(format_time(30.4 * DAY), "30.4 d");
+/// ```
+pub fn format_time(seconds: impl Into<ENumber>) -> String {
+    TimeScale::from(seconds).to_string()
+}
+
+/// Same as [`format_time`], but takes the duration in years.
+///
+/// ```
+/// use scale_comparison::units::format_time_years;
+///
+/// assert_eq!(format_time_years(9.5), "9.5 y");
+/// assert_eq!(format_time_years(540e6), "540 My");
+/// assert_eq!(format_time_years(2.5e9), "2.5 Gy");
+/// assert_eq!(format_time_years((1., 32000)), "1e32000 y");
+/// ```
+pub fn format_time_years(years: impl Into<ENumber>) -> String {
+    TimeScale::from_years(years).to_string()
+}
+
+impl FromStr for TimeScale {
+    type Err = ParseTimeScaleError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (number, unit) = split_unit(s.trim());
+        if number.contains(',') {
+            return Err(ParseTimeScaleError::Ambiguous(format!(
+                "\"{number}\" uses a comma as a decimal or thousands separator"
+            )));
+        }
+        let (multiplier, unit) = split_multiplier(unit)?;
+        let factor = unit_factor(unit)?;
+        Ok(Self::from(number.parse::<ENumber>()? * multiplier * factor))
+    }
+}
+
+impl<T: Into<ENumber>> From<T> for TimeScale {
+    fn from(value: T) -> Self {
+        Self(value.into(), ENumberEditor::default())
+    }
+}
+
+impl TimeScale {
+    pub const CALENDAR_YEARS: f64 = 10_000.;
+    pub const SLIDER_PRECISION: f64 = 100.;
+    pub const NUDGE_STEP: f64 = 0.1;
+
+    pub fn from_years(years: impl Into<ENumber>) -> Self {
+        Self(years.into() * YEAR, ENumberEditor::default())
+    }
+
+    pub fn inner(&self) -> ENumber {
+        self.0
+    }
+
+    pub fn display_with(&self, style: FormatStyle) -> TimeScaleFmt<'_> {
+        TimeScaleFmt { value: self, style }
+    }
+
+    fn fmt_duration(&self, digits: usize, exp_break: u32) -> String {
+        let fmt = |value: f64| float_to_string_with(value, digits);
         if let Some(collapsed) = self.0.collapse() {
             match collapsed {
-                ..=MINUTE => return write!(f, "{} s", self.0.fmt_exp_break(6)),
+                ..=MINUTE => return format!("{} s", self.0.fmt_exp_break_with(exp_break, digits)),
                 ..=HOUR => {
                     let mins = collapsed.div_euclid(MINUTE);
                     let secs = collapsed.rem_euclid(MINUTE);
-                    write!(f, "{:.0} m", mins)?;
-                    if secs != 0. {
-                        write!(f, " {:.0} s", secs)?;
-                    }
-                    return Ok(());
+                    return match secs {
+                        0. => format!("{mins:.0} m"),
+                        secs => format!("{mins:.0} m {secs:.0} s"),
+                    };
                 }
                 ..=DAY => {
                     let hrs = collapsed.div_euclid(HOUR);
                     let mins = collapsed.rem_euclid(HOUR) / MINUTE;
-                    write!(f, "{:.0} h", hrs)?;
-                    if mins != 0. {
-                        write!(f, " {:.0} m", mins)?;
-                    }
-                    return Ok(());
-                }
-                ..=YEAR => {
-                    let days = collapsed / DAY;
-                    return write!(f, "{} d", float_to_string(days));
+                    return match mins {
+                        0. => format!("{hrs:.0} h"),
+                        mins => format!("{hrs:.0} h {mins:.0} m"),
+                    };
                 }
+                ..=YEAR => return format!("{} d", fmt(collapsed / DAY)),
                 _ => {
                     let yrs = collapsed / YEAR;
                     match yrs {
-                        ..MEGA => {
-                            return write!(f, "{} y", float_to_string(yrs));
-                        }
-                        ..GIGA => {
-                            let mega = yrs / MEGA;
-                            return write!(f, "{} My", float_to_string(mega));
-                        }
-                        ..TERA => {
-                            let giga = yrs / GIGA;
-                            return write!(f, "{} Gy", float_to_string(giga));
-                        }
-                        ..PETA => {
-                            let tera = yrs / TERA;
-                            return write!(f, "{} Ty", float_to_string(tera));
-                        }
+                        ..MEGA => return format!("{} y", fmt(yrs)),
+                        ..GIGA => return format!("{} My", fmt(yrs / MEGA)),
+                        ..TERA => return format!("{} Gy", fmt(yrs / GIGA)),
+                        ..PETA => return format!("{} Ty", fmt(yrs / TERA)),
                         _ => (),
                     }
                 }
@@ -145,26 +423,72 @@ impl std::fmt::Display for TimeScale {
         }
         if self.0.exponent().signum() == 1. {
             let yrs = self.0 / YEAR;
-            write!(f, "{} y", yrs.fmt_exp_break(6))
+            format!("{} y", yrs.fmt_exp_break_with(exp_break, digits))
         } else {
-            write!(f, "{} s", self.0.fmt_exp_break(6))
+            format!("{} s", self.0.fmt_exp_break_with(exp_break, digits))
         }
     }
-}
 
-impl<T: Into<ENumber>> From<T> for TimeScale {
-    fn from(value: T) -> Self {
-        Self(value.into(), ENumberEditor::default())
+    pub fn slider_position(&self, min: f64, max: f64) -> f64 {
+        let exponent = self.0.erect().1;
+        if exponent.is_nan() {
+            min
+        } else {
+            exponent.clamp(min, max)
+        }
     }
-}
 
-impl TimeScale {
-    pub fn from_years(years: impl Into<ENumber>) -> Self {
-        Self(years.into() * YEAR, ENumberEditor::default())
+    pub fn from_slider(position: f64) -> Self {
+        let exponent = position.floor();
+        let significand = 10_f64.powf(position - exponent);
+        let significand = (significand * Self::SLIDER_PRECISION).round() / Self::SLIDER_PRECISION;
+        ENumber::normalize(significand, exponent).into()
     }
 
-    pub fn inner(&self) -> ENumber {
-        self.0
+    pub fn is_editing(&self) -> bool {
+        self.1.editing
+    }
+
+    pub fn start_editing(&mut self) {
+        self.1 = self.0.into();
+        self.1.editing = true;
+    }
+
+    // Leaves the value as it was; nothing is committed, so there is nothing to undo.
+    pub fn cancel(&mut self) {
+        self.1.editing = false;
+        self.1.error = None;
+    }
+
+    // Applies right away and hands back the previous value, like `commit`.
+    pub fn nudge(&mut self, step: f64) -> Option<ENumber> {
+        let previous = self.0;
+        self.0 = self.0.nudged(step);
+        Some(previous)
+    }
+
+    pub fn nudge_exponent(&mut self, steps: i32) -> Option<ENumber> {
+        let previous = self.0;
+        self.0 = self.0.nudged_exponent(steps);
+        Some(previous)
+    }
+
+    pub fn commit(&mut self) -> Option<ENumber> {
+        let previous = self.0;
+        match TryInto::<ENumber>::try_into(self.1.clone()) {
+            Ok(enumber) if enumber.significand() <= 0. => {
+                self.1.error = Some("Value must be greater than zero".to_string());
+                return None;
+            }
+            Ok(enumber) => self.0 = enumber,
+            Err(err) => {
+                self.1.error = Some(format!("Invalid value: {err}"));
+                return None;
+            }
+        }
+        self.1.editing = false;
+        self.1.error = None;
+        Some(previous)
     }
 
     pub fn total_cmp(&self, other: &Self) -> Ordering {
@@ -175,29 +499,87 @@ impl TimeScale {
         format!("{} s", self.0.fmt_exp_break(3))
     }
 
-    pub fn view(&mut self) -> impl WidgetView<Edit<Self>> + use<> {
+    pub fn fmt_in(&self, unit: TimeUnit) -> String {
+        format!(
+            "{} {}",
+            (self.0 / unit.factor()).fmt_exp_break(6),
+            unit.symbol()
+        )
+    }
+
+    pub fn fmt_ago(&self, epoch: Option<i32>, style: FormatStyle) -> String {
+        let years = self.0.collapse().map(|secs| secs / YEAR);
+        match (years, epoch) {
+            (Some(years), Some(epoch)) if years > 1. && years < Self::CALENDAR_YEARS => {
+                let year = epoch as f64 - years.round();
+                if year > 0. {
+                    format!("{year} CE")
+                } else {
+                    format!("{} BCE", 1. - year)
+                }
+            }
+            _ => format!("{} ago", self.display_with(style)),
+        }
+    }
+
+    pub fn fmt_with(&self, unit: Option<TimeUnit>) -> String {
+        self.fmt_styled(unit, FormatStyle::default())
+    }
+
+    pub fn fmt_styled(&self, unit: Option<TimeUnit>, style: FormatStyle) -> String {
+        match unit {
+            Some(unit) => format!(
+                "{} {}",
+                (self.0 / unit.factor()).fmt_exp_break_with(style.exp_break, style.digits),
+                unit.symbol()
+            ),
+            None => self.display_with(style).to_string(),
+        }
+    }
+
+    pub fn view(
+        &mut self,
+        display_unit: Option<TimeUnit>,
+    ) -> impl WidgetView<Edit<Self>, Option<ENumber>> + use<> {
         if self.1.editing {
             Either::A(flex_row((
-                button(label("Ok").color(Thing::VALUE_COLOR), |state: &mut Self| {
-                    if let Ok(enumber) = state.1.clone().try_into() {
-                        state.0 = enumber;
-                    }
-                    state.1.editing = false;
+                button(label("Apply value").color(Thing::VALUE_COLOR), Self::commit),
+                text_button("Cancel", |state: &mut Self| {
+                    state.cancel();
+                    None
                 }),
                 lens(ENumberEditor::view, move |state: &mut Self, ()| {
                     &mut state.1
                 })
+                .map_action(|_, ()| None)
                 .flex(1.),
+                self.1
+                    .error
+                    .clone()
+                    .map(|error| label(error).color(Level::Error.color())),
             )))
         } else {
             Either::B(flex_row((
-                text_button("Edit", |state: &mut Self| {
-                    state.1 = state.0.into();
-                    state.1.editing = true;
+                text_button("Edit value", |state: &mut Self| {
+                    state.start_editing();
+                    None
                 }),
-                text_input(self.to_string(), |_, _| {})
+                text_input(self.fmt_with(display_unit), |_, _| None)
+                    .placeholder("Value")
                     .disabled(true)
                     .flex(1.),
+                text_button("▼ Decrease", |state: &mut Self| {
+                    state.nudge(-Self::NUDGE_STEP)
+                }),
+                text_button("▲ Increase", |state: &mut Self| {
+                    state.nudge(Self::NUDGE_STEP)
+                }),
+                text_button("÷10 Divide by ten", |state: &mut Self| {
+                    state.nudge_exponent(-1)
+                }),
+                text_button("×10 Multiply by ten", |state: &mut Self| {
+                    state.nudge_exponent(1)
+                }),
             )))
         }
     }
@@ -206,6 +588,306 @@ impl TimeScale {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::undo::{Change, UndoStack, ValueEdit};
+
+    #[test]
+    fn test_commit_rejects_non_positive() {
+        let mut value = TimeScale::from(60.);
+        for input in ["0", "-3"] {
+            value.1 = value.0.into();
+            value.1.significand = input.to_string();
+            assert_eq!(value.commit(), None);
+            assert!(value.1.editing);
+            assert!(value.1.error.is_some());
+            assert_eq!(value.inner(), ENumber::from(60.));
+        }
+
+        value.1.significand = "2".to_string();
+        assert_eq!(value.commit(), Some(ENumber::from(60.)));
+        assert!(!value.1.editing);
+        assert_eq!(value.1.error, None);
+        assert_eq!(value.inner(), ENumber::new(2., 1));
+    }
+
+    #[test]
+    fn test_committed_value_is_undoable() {
+        let mut things = vec![Thing::new("Blink", 0.3), Thing::new("Minute", 60.)];
+        let mut undo = UndoStack::default();
+        let value = &mut things[1].value;
+        value.start_editing();
+        value.1.significand = "5".to_string();
+        value.1.exponent = "0".to_string();
+        let previous = value.commit().unwrap();
+        undo.push(ValueEdit {
+            id: things[1].id,
+            previous,
+            value: things[1].value.inner(),
+            recorded: false,
+        });
+        assert_eq!(things[1].value.inner(), ENumber::from(5.));
+
+        let changes = undo.undo(&mut things).unwrap();
+        assert!(matches!(changes[..], [Change::Value(edit)] if edit.value == ENumber::from(5.)));
+        assert_eq!(things[1].value.inner(), ENumber::from(60.));
+        assert!(undo.is_empty());
+    }
+
+    #[test]
+    fn test_nudge_commits_immediately() {
+        let mut value = TimeScale::from(ENumber::new(9.95, 0));
+        assert_eq!(
+            value.nudge(TimeScale::NUDGE_STEP),
+            Some(ENumber::new(9.95, 0))
+        );
+        assert_eq!(value.inner(), ENumber::new(1., 1));
+        assert_eq!(value.fmt_secs(), "10 s");
+        assert!(!value.1.editing);
+        assert_eq!(value.nudge_exponent(-1), Some(ENumber::new(1., 1)));
+        assert_eq!(value.inner(), ENumber::new(1., 0));
+
+        let mut undo = UndoStack::default();
+        let mut things = vec![Thing::new("Blink", 0.3)];
+        let previous = things[0].value.nudge(-TimeScale::NUDGE_STEP).unwrap();
+        undo.push(ValueEdit {
+            id: things[0].id,
+            previous,
+            value: things[0].value.inner(),
+            recorded: false,
+        });
+        assert!(
+            things[0]
+                .value
+                .inner()
+                .relative_eq(&ENumber::from(0.29), 1e-12)
+        );
+        undo.undo(&mut things);
+        assert_eq!(things[0].value.inner(), ENumber::from(0.3));
+    }
+
+    #[test]
+    fn test_unit_prefix_display() {
+        assert_eq!(UnitPrefix::None.to_string(), "");
+        assert_eq!(UnitPrefix::Kilo.to_string(), "Kilo");
+    }
+
+    #[test]
+    fn test_display_with_styles() {
+        let value = TimeScale::from(HOUR + 32. * MINUTE + 0.5);
+        let style = FormatStyle::default();
+        assert_eq!(value.display_with(style).to_string(), "1 h 32 m");
+        let seconds = FormatStyle {
+            notation: Notation::Seconds,
+            ..style
+        };
+        assert_eq!(value.display_with(seconds).to_string(), "5520 s");
+        let coarse = FormatStyle {
+            digits: 2,
+            ..seconds
+        };
+        assert_eq!(value.display_with(coarse).to_string(), "5500 s");
+        let precise = FormatStyle {
+            digits: 6,
+            ..seconds
+        };
+        assert_eq!(value.display_with(precise).to_string(), "5520.5 s");
+        let comma = FormatStyle {
+            locale: Locale::DecimalComma,
+            ..precise
+        };
+        assert_eq!(value.display_with(comma).to_string(), "5520,5 s");
+        let scientific = FormatStyle {
+            notation: Notation::Scientific,
+            ..style
+        };
+        assert_eq!(value.display_with(scientific).to_string(), "5.521e3 s");
+        let early = FormatStyle {
+            exp_break: 2,
+            ..seconds
+        };
+        assert_eq!(value.display_with(early).to_string(), "5.521e3 s");
+
+        let tiny = TimeScale::from(ENumber::new(5.391, -44));
+        assert_eq!(tiny.display_with(style).to_string(), "5.391e-44 s");
+        assert_eq!(
+            tiny.display_with(FormatStyle { digits: 2, ..style })
+                .to_string(),
+            "5.4e-44 s"
+        );
+        let day = TimeScale::from(30.4 * DAY);
+        assert_eq!(day.display_with(comma).to_string(), "2626560 s");
+        assert_eq!(
+            day.display_with(FormatStyle {
+                locale: Locale::DecimalComma,
+                ..style
+            })
+            .to_string(),
+            "30,4 d"
+        );
+    }
+
+    #[test]
+    fn test_display_with_pads_to_width() {
+        let value = TimeScale::from(30.4 * DAY);
+        let style = FormatStyle::default();
+        assert_eq!(format!("{:>8}|", value.display_with(style)), "  30.4 d|");
+        assert_eq!(format!("{:<8}|", value.display_with(style)), "30.4 d  |");
+        assert_eq!(format!("{:^8}|", value.display_with(style)), " 30.4 d |");
+        assert_eq!(format!("{:*>10}", value.display_with(style)), "****30.4 d");
+        assert_eq!(format!("{:3}", value.display_with(style)), "30.4 d");
+        let rows = [0.3, 86400., 3.156e16]
+            .map(|secs| format!("{:>10}", TimeScale::from(secs).display_with(style)));
+        assert!(rows.iter().all(|row| row.chars().count() == 10));
+    }
+
+    #[test]
+    fn test_cancel_discards_the_edit() {
+        let mut value = TimeScale::from(60.);
+        value.start_editing();
+        value.1.significand = "-1".to_string();
+        assert_eq!(value.commit(), None);
+        value.cancel();
+        assert!(!value.1.editing);
+        assert_eq!(value.1.error, None);
+        assert_eq!(value.inner(), ENumber::from(60.));
+    }
+
+    #[test]
+    fn test_commit_evaluates_expressions() {
+        let mut value = TimeScale::from(60.);
+        value.1 = value.0.into();
+        value.1.significand = "8*MINUTE+20".to_string();
+        value.1.exponent = "0".to_string();
+        assert_eq!(value.commit(), Some(ENumber::from(60.)));
+        assert_eq!(value.inner(), ENumber::from(500.));
+
+        value.1 = value.0.into();
+        value.1.significand = "13.8e9 * YEAR".to_string();
+        value.1.exponent = "2 - 2".to_string();
+        value.1.time_unit = TimeUnit::Minute;
+        value.commit();
+        assert!(
+            value
+                .inner()
+                .relative_eq(&(ENumber::from(13.8e9 * MINUTE) * YEAR), 1e-12)
+        );
+
+        value.1 = value.0.into();
+        value.1.significand = "2 * (3 + DAY".to_string();
+        let before = value.inner();
+        assert_eq!(value.commit(), None);
+        assert!(value.1.editing);
+        assert_eq!(
+            value.1.error.as_deref(),
+            Some("Invalid value: unclosed '(' at column 5")
+        );
+        assert_eq!(value.inner(), before);
+
+        value.1.significand = "1".to_string();
+        value.1.exponent = "3 +".to_string();
+        assert_eq!(value.commit(), None);
+        assert_eq!(
+            value.1.error.as_deref(),
+            Some("Invalid value: exponent expected a number at column 4")
+        );
+    }
+
+    #[test]
+    fn test_slider_round_trip() {
+        let (min, max) = (-3., 20.);
+        assert_eq!(TimeScale::from_slider(0.).inner(), ENumber::new(1., 0));
+        assert_eq!(TimeScale::from_slider(5.).inner(), ENumber::new(1., 5));
+        assert_eq!(TimeScale::from_slider(5.5).inner(), ENumber::new(3.16, 5));
+        assert_eq!(TimeScale::from_slider(4.9999).inner(), ENumber::new(1., 5));
+        for position in [-3., -1.25, 0., 2.7, 9.99, 20.] {
+            let value = TimeScale::from_slider(position);
+            let back = value.slider_position(min, max);
+            assert!((back - position).abs() < 0.01, "{position} -> {back}");
+        }
+
+        assert_eq!(
+            TimeScale::from(604800.).slider_position(min, max),
+            604800_f64.log10()
+        );
+        assert_eq!(TimeScale::from((1., 100)).slider_position(min, max), max);
+        assert_eq!(TimeScale::from(1e-9).slider_position(min, max), min);
+        assert_eq!(TimeScale::from(0.).slider_position(min, max), min);
+    }
+
+    #[test]
+    fn test_time_scale_fmt_ago() {
+        let tests = [
+            (TimeScale::from_years(13.8e9), None, "13.8 Gy ago"),
+            (TimeScale::from_years(66e6), Some(2000), "66 My ago"),
+            (TimeScale::from_years(12000.), None, "12000 y ago"),
+            (TimeScale::from_years(12000.), Some(1950), "12000 y ago"),
+            (TimeScale::from_years(500.), None, "500 y ago"),
+            (TimeScale::from_years(500.), Some(2000), "1500 CE"),
+            (TimeScale::from_years(4500.), Some(2000), "2501 BCE"),
+            (TimeScale::from_years(2000.), Some(2000), "1 BCE"),
+            (TimeScale::from_years((1., 100)), Some(2000), "1e100 y ago"),
+            ((7. * DAY).into(), Some(2000), "7 d ago"),
+            ((7. * DAY).into(), None, "7 d ago"),
+            (TimeScale::from_years(1.), Some(2000), "365.2 d ago"),
+        ];
+        for (value, epoch, expected) in tests {
+            assert_eq!(value.fmt_ago(epoch, FormatStyle::default()), expected);
+        }
+    }
+
+    #[test]
+    fn test_time_scale_from_str() {
+        let secs = |text: &str| text.parse::<TimeScale>().unwrap().inner();
+        assert_eq!(secs("90"), ENumber::from(90.));
+        assert_eq!(secs("1.5e3"), ENumber::from(1500.));
+        assert_eq!(secs("7 d"), ENumber::from(7. * DAY));
+        assert_eq!(secs("7d"), ENumber::from(7. * DAY));
+        assert_eq!(secs("2 weeks"), ENumber::from(14. * DAY));
+        assert_eq!(secs("1e34 y").exponent(), (1e34 * YEAR).log10().floor());
+        assert_eq!(secs("10 Gy").exponent(), 17.);
+        assert_eq!(secs("250 ms").exponent(), -1.);
+
+        let err = |text: &str| text.parse::<TimeScale>().unwrap_err();
+        assert!(matches!(
+            err("3 parsecs"),
+            ParseTimeScaleError::UnknownUnit(_)
+        ));
+        assert!(matches!(err("6 months"), ParseTimeScaleError::Ambiguous(_)));
+        assert!(matches!(err("1,5 h"), ParseTimeScaleError::Ambiguous(_)));
+        assert!(matches!(err("soon"), ParseTimeScaleError::UnknownUnit(_)));
+        assert!(matches!(err("d"), ParseTimeScaleError::Number(_)));
+    }
+
+    #[test]
+    fn test_word_multipliers() {
+        let secs = |text: &str| text.parse::<TimeScale>().unwrap().inner();
+        assert_eq!(
+            secs("13.8 billion years"),
+            ENumber::from(13.8) * GIGA * YEAR
+        );
+        assert_eq!(secs("2 million seconds"), ENumber::from(2e6));
+        assert_eq!(secs("3 Thousand Hours"), ENumber::from(3. * KILO * HOUR));
+        assert_eq!(secs("1 trillion y"), ENumber::from(TERA) * YEAR);
+        assert_eq!(secs("5 quadrillion s"), ENumber::from(5. * PETA));
+        assert_eq!(secs("4 Years"), ENumber::from(4.) * YEAR);
+        assert_eq!(secs("90 Minutes"), ENumber::from(90. * MINUTE));
+        assert_eq!(word_multiplier("Billion"), Some(GIGA));
+        assert_eq!(word_multiplier("bazillion"), None);
+
+        let err = |text: &str| text.parse::<TimeScale>().unwrap_err();
+        assert!(matches!(
+            err("4 billion"),
+            ParseTimeScaleError::Ambiguous(_)
+        ));
+        assert!(matches!(
+            err("2 million months"),
+            ParseTimeScaleError::Ambiguous(_)
+        ));
+        assert!(matches!(
+            err("1 billion parsecs"),
+            ParseTimeScaleError::UnknownUnit(_)
+        ));
+        assert!(matches!(err("7 Months"), ParseTimeScaleError::Ambiguous(_)));
+    }
 
     #[test]
     fn test_time_scale_format() {
@@ -220,7 +902,7 @@ mod tests {
             (DAY.into(), "24 h"),
             ((7. * DAY).into(), "7 d"),
             ((30.4 * DAY).into(), "30.4 d"),
-            (YEAR.into(), "365.24 d"),
+            (YEAR.into(), "365.2 d"),
             ((9.5 * YEAR).into(), "9.5 y"),
             ((MEGA * YEAR).into(), "1 My"),
             ((540. * MEGA * YEAR).into(), "540 My"),
@@ -236,4 +918,13 @@ mod tests {
             .iter()
             .for_each(|test| assert_eq!(format!("{}", test.0), test.1));
     }
+
+    #[test]
+    fn test_time_scale_fmt_in() {
+        let value = TimeScale::from(1e9);
+        assert_eq!(value.fmt_in(TimeUnit::Second), "1e9 s");
+        assert_eq!(value.fmt_in(TimeUnit::Day), "11570 d");
+        assert_eq!(value.fmt_in(TimeUnit::Year), "31.69 y");
+        assert_eq!(value.fmt_with(None), "31.69 y");
+    }
 }
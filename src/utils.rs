@@ -1,34 +1,152 @@
-use std::num::NonZeroUsize;
+use std::io;
+use std::path::Path;
+use std::process::Command;
 
-use lexical::{WriteFloatOptions, WriteFloatOptionsBuilder};
 use xilem::masonry::TextAlignOptions;
 use xilem::masonry::core::BrushIndex;
 use xilem::masonry::parley::{
     FontContext, FontFamily, FontStack, GenericFamily, Layout, LayoutContext, StyleProperty,
 };
 use xilem::vello::Scene;
-use xilem::vello::kurbo::{Affine, Axis, Line, Stroke, Vec2};
+use xilem::vello::kurbo::{Affine, Axis, Line, Size, Stroke, Vec2};
 use xilem::{Color, FontWeight, TextAlign};
 
-pub const FORMAT: u128 = lexical::format::STANDARD;
-pub const WF_OPTIONS: WriteFloatOptions = WriteFloatOptionsBuilder::new()
-    .trim_floats(true)
-    .max_significant_digits(NonZeroUsize::new(5))
-    .build_strict();
+pub const DEFAULT_SIGNIFICANT_DIGITS: usize = 4;
+pub const MAX_SIGNIFICANT_DIGITS: usize = 15;
 
 #[inline]
 pub fn float_to_string(value: f64) -> String {
-    lexical::to_string_with_options::<_, { FORMAT }>(value, &WF_OPTIONS)
+    float_to_string_with(value, DEFAULT_SIGNIFICANT_DIGITS)
 }
 
+pub fn float_to_string_with(value: f64, digits: usize) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    if value == 0. {
+        return "0".to_string();
+    }
+    let digits = digits.clamp(1, MAX_SIGNIFICANT_DIGITS);
+    // the exponent after rounding, so 9.99996 counts as 10
+    let scientific = format!("{:.*e}", digits - 1, value);
+    let (_, exponent) = scientific
+        .split_once('e')
+        .expect("Scientific formatting always has an exponent");
+    let exponent = exponent
+        .parse::<i32>()
+        .expect("Scientific exponents are integers");
+    let decimals = digits as i32 - 1 - exponent;
+    let text = if decimals >= 0 {
+        format!("{:.*}", decimals as usize, value)
+    } else {
+        let step = 10_f64.powi(-decimals);
+        format!("{:.0}", (value / step).round() * step)
+    };
+    match trim_fraction(&text) {
+        "-0" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+fn trim_fraction(text: &str) -> &str {
+    match text.contains('.') {
+        true => text.trim_end_matches('0').trim_end_matches('.'),
+        false => text,
+    }
+}
+
+pub fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+pub fn superscript_exponents(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let starts_exponent = matches!(chars.peek(), Some('-' | '0'..='9'));
+        if c != 'e' || !starts_exponent || !result.ends_with(|c: char| c.is_ascii_digit()) {
+            result.push(c);
+            continue;
+        }
+        result.push_str("×10");
+        while let Some(&c) = chars.peek() {
+            let sup = match c {
+                '-' => '⁻',
+                '0' => '⁰',
+                '1' => '¹',
+                '2' => '²',
+                '3' => '³',
+                '4' => '⁴',
+                '5' => '⁵',
+                '6' => '⁶',
+                '7' => '⁷',
+                '8' => '⁸',
+                '9' => '⁹',
+                _ => break,
+            };
+            result.push(sup);
+            chars.next();
+        }
+    }
+    result
+}
+
+pub fn copy_to_clipboard(text: &str) -> Result<(), arboard::Error> {
+    arboard::Clipboard::new()?.set_text(text)
+}
+
+pub fn open_in_file_manager(path: &Path) -> io::Result<()> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program).arg(path).spawn().map(|_| ())
+}
+
+// The chart has two coordinate conventions. World space is y-up: bars grow
+// from the baseline towards positive y and the camera moves through it.
+// Text is laid out by parley y-down, with the origin at the top-left corner
+// of the layout. The canvas itself is y-down with the origin at its corner.
+
+/// Mirrors `trans` across the x axis, turning a transform written for y-up
+/// coordinates into the same motion in y-down coordinates and back.
+///
+/// A translation by `(x, y)` becomes one by `(x, -y)`; applying it twice gives
+/// back `trans`.
 pub fn y_flipped(trans: Affine) -> Affine {
     (Affine::FLIP_Y * trans) * Affine::FLIP_Y
 }
 
+/// A world space translation to `p`, for use in y-down text space.
 pub fn y_flipped_translate<V: Into<Vec2>>(p: V) -> Affine {
     y_flipped(Affine::translate(p))
 }
 
+/// Keeps what `trans` does along y and lets x through unchanged, so that
+/// things pinned to the chart margin follow the camera vertically only.
 pub fn ignore_x(trans: Affine) -> Affine {
     let mut c = trans.as_coeffs();
     c[0] = 1.;
@@ -37,6 +155,13 @@ pub fn ignore_x(trans: Affine) -> Affine {
     Affine::new(c)
 }
 
+/// Keeps only where `trans` puts the origin, so text is never rotated or
+/// mirrored.
+pub fn upright(trans: Affine) -> Affine {
+    Affine::translate(trans.translation())
+}
+
+/// Keeps what `trans` does along x and lets y through unchanged.
 pub fn ignore_y(trans: Affine) -> Affine {
     let mut c = trans.as_coeffs();
     c[1] = 0.;
@@ -45,6 +170,51 @@ pub fn ignore_y(trans: Affine) -> Affine {
     Affine::new(c)
 }
 
+/// The transforms from chart spaces to the canvas, shared by the preview and
+/// the exporters.
+///
+/// `world_trans` maps world space to the canvas with the world origin at its
+/// center, and `text_trans` does the same for text space. The `_camera`
+/// variants apply `camera`, the transform from world to camera space, first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CanvasSpaces {
+    pub world_trans: Affine,
+    pub text_trans: Affine,
+    pub world_camera: Affine,
+    pub text_camera: Affine,
+    pub camera: Affine,
+}
+
+impl CanvasSpaces {
+    /// Spaces for an upright chart in a canvas of `size`.
+    pub fn new(size: Size, camera: Affine) -> Self {
+        Self::with_world_trans(Affine::FLIP_Y.then_translate(size.to_vec2() / 2.), camera)
+    }
+
+    pub fn with_world_trans(world_trans: Affine, camera: Affine) -> Self {
+        let text_trans = world_trans * Affine::FLIP_Y;
+        Self {
+            world_trans,
+            text_trans,
+            world_camera: world_trans * camera,
+            text_camera: text_trans * y_flipped(camera),
+            camera,
+        }
+    }
+
+    /// Where to draw a text layout whose top-left corner sits at `point` in
+    /// world space.
+    pub fn text_at(&self, point: impl Into<Vec2>) -> Affine {
+        upright(self.text_camera * y_flipped_translate(point))
+    }
+
+    /// Like `text_at`, but `point.x` is measured from the canvas center
+    /// instead of following the camera, for labels in the axis margin.
+    pub fn margin_text(&self, point: impl Into<Vec2>) -> Affine {
+        upright(self.text_trans * y_flipped(ignore_x(self.camera)) * y_flipped_translate(point))
+    }
+}
+
 pub fn infinite_line(half_size: Vec2, axis: Axis, position: f64, padding: (f64, f64)) -> Line {
     match axis {
         Axis::Horizontal => Line::new(
@@ -58,6 +228,78 @@ pub fn infinite_line(half_size: Vec2, axis: Axis, position: f64, padding: (f64,
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct LineParams {
+    pub axis: Axis,
+    pub position: f64,
+    pub color: Color,
+    pub width: f64,
+    pub padding: (f64, f64),
+}
+
+impl LineParams {
+    pub fn new(axis: Axis, position: f64) -> Self {
+        Self {
+            axis,
+            position,
+            color: Color::WHITE,
+            width: 1.,
+            padding: (0., 0.),
+        }
+    }
+
+    pub fn horizontal(position: f64) -> Self {
+        Self::new(Axis::Horizontal, position)
+    }
+
+    pub fn vertical(position: f64) -> Self {
+        Self::new(Axis::Vertical, position)
+    }
+
+    pub fn color(self, color: Color) -> Self {
+        Self { color, ..self }
+    }
+
+    pub fn width(self, width: f64) -> Self {
+        Self { width, ..self }
+    }
+
+    pub fn padding(self, start: f64, end: f64) -> Self {
+        Self {
+            padding: (start, end),
+            ..self
+        }
+    }
+
+    pub fn line(&self, half_size: Vec2) -> Line {
+        infinite_line(half_size, self.axis, self.position, self.padding)
+    }
+
+    pub fn transform(&self, world_trans: Affine, camera: Affine) -> Affine {
+        match self.axis {
+            Axis::Horizontal => world_trans * ignore_x(camera),
+            Axis::Vertical => world_trans * ignore_y(camera),
+        }
+    }
+}
+
+pub fn stroke_line(
+    scene: &mut Scene,
+    world_trans: Affine,
+    camera: Affine,
+    half_size: Vec2,
+    params: &LineParams,
+) {
+    scene.stroke(
+        &Stroke::new(params.width),
+        params.transform(world_trans, camera),
+        params.color,
+        None,
+        &params.line(half_size),
+    );
+}
+
+#[deprecated(note = "use `stroke_line` with `LineParams`")]
 pub fn stroke_inf_line(
     scene: &mut Scene,
     world_trans: Affine,
@@ -65,14 +307,11 @@ pub fn stroke_inf_line(
     half_size: Vec2,
     (axis, position, color, width): (Axis, f64, Color, f64),
 ) {
-    let line = infinite_line(half_size, axis, position, (0., 0.));
-    let transform = match axis {
-        Axis::Horizontal => world_trans * ignore_x(camera),
-        Axis::Vertical => world_trans * ignore_y(camera),
-    };
-    scene.stroke(&Stroke::new(width), transform, color, None, &line);
+    let params = LineParams::new(axis, position).color(color).width(width);
+    stroke_line(scene, world_trans, camera, half_size, &params);
 }
 
+#[deprecated(note = "use `stroke_line` with `LineParams::padding`")]
 pub fn stroke_inf_line_pad(
     scene: &mut Scene,
     world_trans: Affine,
@@ -81,18 +320,82 @@ pub fn stroke_inf_line_pad(
     (axis, position, color, width): (Axis, f64, Color, f64),
     padding: (f64, f64),
 ) {
-    let line = infinite_line(half_size, axis, position, padding);
-    let transform = match axis {
-        Axis::Horizontal => world_trans * ignore_x(camera),
-        Axis::Vertical => world_trans * ignore_y(camera),
-    };
-    scene.stroke(&Stroke::new(width), transform, color, None, &line);
+    let params = LineParams::new(axis, position)
+        .color(color)
+        .width(width)
+        .padding(padding.0, padding.1);
+    stroke_line(scene, world_trans, camera, half_size, &params);
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct TextParams<'a> {
+    pub text: &'a str,
+    pub size: f32,
+    pub family: GenericFamily,
+    pub weight: Option<f32>,
+    pub max_advance: Option<f32>,
+    pub alignment: TextAlign,
+}
+
+impl<'a> TextParams<'a> {
+    pub fn new(text: &'a str, size: f32) -> Self {
+        Self {
+            text,
+            size,
+            family: GenericFamily::SansSerif,
+            weight: None,
+            max_advance: None,
+            alignment: TextAlign::Start,
+        }
+    }
+
+    pub fn family(self, family: GenericFamily) -> Self {
+        Self { family, ..self }
+    }
+
+    pub fn weight(self, weight: f32) -> Self {
+        Self {
+            weight: Some(weight),
+            ..self
+        }
+    }
+
+    pub fn max_advance(self, max_advance: f32) -> Self {
+        Self {
+            max_advance: Some(max_advance),
+            ..self
+        }
+    }
+
+    pub fn alignment(self, alignment: TextAlign) -> Self {
+        Self { alignment, ..self }
+    }
+}
+
+pub fn layout_text(
+    fcx: &mut FontContext,
+    lcx: &mut LayoutContext<BrushIndex>,
+    params: &TextParams,
+) -> Layout<BrushIndex> {
+    let mut text_layout_builder = lcx.ranged_builder(fcx, params.text, 1., false);
+    text_layout_builder.push_default(StyleProperty::FontStack(FontStack::Single(
+        FontFamily::Generic(params.family),
+    )));
+    if let Some(weight) = params.weight {
+        text_layout_builder.push_default(StyleProperty::FontWeight(FontWeight::new(weight)));
+    }
+    text_layout_builder.push_default(StyleProperty::FontSize(params.size));
+    let mut text_layout = text_layout_builder.build(params.text);
+    text_layout.break_all_lines(params.max_advance);
+    text_layout.align(None, params.alignment, TextAlignOptions::default());
+    text_layout
+}
+
+#[deprecated(note = "use `layout_text` with `TextParams`")]
 pub fn text_layout(
     fcx: &mut FontContext,
     lcx: &mut LayoutContext<BrushIndex>,
-    (text, size, generic_family, weight, max_advance, alignment): (
+    (text, size, family, weight, max_advance, alignment): (
         &str,
         f32,
         GenericFamily,
@@ -101,16 +404,230 @@ pub fn text_layout(
         TextAlign,
     ),
 ) -> Layout<BrushIndex> {
-    let mut text_layout_builder = lcx.ranged_builder(fcx, text, 1., false);
-    text_layout_builder.push_default(StyleProperty::FontStack(FontStack::Single(
-        FontFamily::Generic(generic_family),
-    )));
-    if let Some(weight) = weight {
-        text_layout_builder.push_default(StyleProperty::FontWeight(FontWeight::new(weight)));
+    let params = TextParams {
+        text,
+        size,
+        family,
+        weight,
+        max_advance,
+        alignment,
+    };
+    layout_text(fcx, lcx, &params)
+}
+
+pub const MIN_CONTRAST: f64 = 4.5;
+
+pub fn relative_luminance(color: Color) -> f64 {
+    let [r, g, b, _] = color.components.map(|c| {
+        let c = c as f64;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    });
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+pub fn contrast_ratio(a: Color, b: Color) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+pub fn readable_color(background: Color, preferred: Color) -> Color {
+    if contrast_ratio(background, preferred) >= MIN_CONTRAST {
+        return preferred;
+    }
+    if contrast_ratio(background, Color::BLACK) > contrast_ratio(background, Color::WHITE) {
+        Color::BLACK
+    } else {
+        Color::WHITE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xilem::vello::kurbo::Point;
+
+    use super::*;
+
+    fn assert_near(a: Point, b: Point) {
+        assert!((a - b).hypot() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_flip_helpers() {
+        let trans = Affine::translate((5., 7.));
+        assert_eq!(y_flipped(trans), Affine::translate((5., -7.)));
+        assert_eq!(y_flipped(y_flipped(trans)), trans);
+        assert_eq!(y_flipped_translate((5., 7.)), Affine::translate((5., -7.)));
+        assert_eq!(ignore_x(trans), Affine::translate((0., 7.)));
+        assert_eq!(ignore_y(trans), Affine::translate((5., 0.)));
+        let scaled = Affine::scale_non_uniform(2., 3.).then_translate(Vec2::new(5., 7.));
+        assert_eq!(ignore_x(scaled) * Point::new(1., 1.), Point::new(1., 10.));
+        assert_eq!(
+            upright(Affine::FLIP_Y * trans),
+            Affine::translate((5., -7.))
+        );
+    }
+
+    #[test]
+    fn test_canvas_spaces_round_trip() {
+        let size = Size::new(800., 600.);
+        for camera in [
+            Affine::IDENTITY,
+            Affine::translate((0., -350.)),
+            Affine::translate((-120.5, 42.)),
+        ] {
+            let spaces = CanvasSpaces::new(size, camera);
+            for point in [Point::ZERO, Point::new(10., 400.), Point::new(-380., -1e3)] {
+                let screen = spaces.world_camera * point;
+                assert_near(spaces.world_camera.inverse() * screen, point);
+            }
+        }
+
+        // the camera sits 350 up the world, so y = 400 shows 50 above the center
+        let spaces = CanvasSpaces::new(size, Affine::translate((0., -350.)));
+        assert_near(
+            spaces.world_camera * Point::new(10., 400.),
+            Point::new(410., 250.),
+        );
+        assert_near(spaces.world_trans * Point::ZERO, Point::new(400., 300.));
+        assert_near(
+            spaces.text_trans * Point::new(0., 20.),
+            Point::new(400., 320.),
+        );
+    }
+
+    #[test]
+    fn test_canvas_spaces_text_positions() {
+        let size = Size::new(800., 600.);
+        let spaces = CanvasSpaces::new(size, Affine::translate((-100., -350.)));
+
+        // text placed at a world point starts there and runs down the screen
+        let text = spaces.text_at((10., 400.));
+        assert_eq!(text.as_coeffs()[..4], [1., 0., 0., 1.]);
+        assert_near(text * Point::ZERO, Point::new(310., 250.));
+        assert_near(text * Point::new(30., 20.), Point::new(340., 270.));
+        assert_near(
+            text * Point::ZERO,
+            spaces.world_camera * Point::new(10., 400.),
+        );
+
+        // margin text ignores the camera's x but still follows it along y
+        let margin = spaces.margin_text((-385., 400.));
+        assert_eq!(margin.as_coeffs()[..4], [1., 0., 0., 1.]);
+        assert_near(margin * Point::ZERO, Point::new(15., 250.));
+        let moved = CanvasSpaces::new(size, Affine::translate((-900., -300.)));
+        assert_near(
+            moved.margin_text((-385., 400.)) * Point::ZERO,
+            Point::new(15., 200.),
+        );
+    }
+
+    #[test]
+    fn test_line_padding() {
+        let half_size = Vec2::new(400., 300.);
+        let line = LineParams::horizontal(50.).line(half_size);
+        assert_eq!(line, Line::new((-400., 50.), (400., 50.)));
+
+        let line = LineParams::horizontal(50.).padding(80., 0.).line(half_size);
+        assert_eq!(line, Line::new((-320., 50.), (400., 50.)));
+
+        let line = LineParams::horizontal(-20.)
+            .padding(0., 25.)
+            .line(half_size);
+        assert_eq!(line, Line::new((-400., -20.), (375., -20.)));
+
+        let line = LineParams::vertical(10.).padding(30., 45.).line(half_size);
+        assert_eq!(line, Line::new((10., -270.), (10., 255.)));
+    }
+
+    #[test]
+    fn test_line_params_defaults() {
+        let params = LineParams::vertical(3.).width(0.8);
+        assert_eq!(params.axis, Axis::Vertical);
+        assert_eq!(params.position, 3.);
+        assert_eq!(params.width, 0.8);
+        assert_eq!(params.padding, (0., 0.));
+
+        let params = TextParams::new("1 h", 14.).weight(600.);
+        assert_eq!(params.weight, Some(600.));
+        assert_eq!(params.max_advance, None);
+        assert!(matches!(params.family, GenericFamily::SansSerif));
+    }
+
+    #[test]
+    fn test_contrast() {
+        assert!((contrast_ratio(Color::BLACK, Color::WHITE) - 21.).abs() < 1e-9);
+        assert!((contrast_ratio(Color::WHITE, Color::WHITE) - 1.).abs() < 1e-9);
+        assert_eq!(relative_luminance(Color::BLACK), 0.);
+        assert!((relative_luminance(Color::WHITE) - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_readable_color() {
+        let pale = Color::from_rgb8(250, 240, 200);
+        let dark = Color::from_rgb8(25, 25, 25);
+        let green = Color::from_rgb8(0, 250, 154);
+        assert_eq!(readable_color(pale, Color::WHITE), Color::BLACK);
+        assert_eq!(readable_color(dark, Color::WHITE), Color::WHITE);
+        assert_eq!(readable_color(dark, green), green);
+        assert_eq!(readable_color(pale, green), Color::BLACK);
+        assert_eq!(
+            readable_color(Color::from_rgb8(0, 0, 139), Color::BLACK),
+            Color::WHITE
+        );
+    }
+
+    #[test]
+    fn test_float_to_string_table() {
+        let cases: [(f64, usize, &str); 31] = [
+            (0., 4, "0"),
+            (-0., 4, "0"),
+            (1., 4, "1"),
+            (1.5, 4, "1.5"),
+            (365.2425, 4, "365.2"),
+            (365.2425, 5, "365.24"),
+            (9.499999, 4, "9.5"),
+            (9.99996, 4, "10"),
+            (0.1 + 0.2, 4, "0.3"),
+            (0.1 + 0.2, 15, "0.3"),
+            (1. - 0.9, 4, "0.1"),
+            (3.0000000000000004, 4, "3"),
+            (2.5e-3, 4, "0.0025"),
+            (0.000123456, 4, "0.0001235"),
+            (-0.000123456, 2, "-0.00012"),
+            (-42.195, 4, "-42.2"),
+            (-0.00004, 4, "-0.00004"),
+            (-0.00000000001, 1, "-0.00000000001"),
+            (123456., 4, "123500"),
+            (123456., 2, "120000"),
+            (-987654.3, 3, "-988000"),
+            (1e20, 4, "100000000000000000000"),
+            (1e9, 5, "1000000000"),
+            (9.99996e9, 4, "10000000000"),
+            (0.00001, 5, "0.00001"),
+            (1.901285e-6, 5, "0.0000019013"),
+            (-2.5e-7, 5, "-0.00000025"),
+            (6.048, 4, "6.048"),
+            (1.23456789, 0, "1"),
+            (1.23456789, 99, "1.23456789"),
+            (100., 1, "100"),
+        ];
+        for (value, digits, expected) in cases {
+            assert_eq!(float_to_string_with(value, digits), expected, "{value}");
+        }
+        assert_eq!(float_to_string_with(f64::NAN, 4), "NaN");
+        assert_eq!(float_to_string_with(f64::NEG_INFINITY, 4), "-inf");
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Cosmic Timescales"), "cosmic-timescales");
+        assert_eq!(slugify("  Durations in fiction! "), "durations-in-fiction");
+        assert_eq!(slugify("Rock & Roll -- 1950s"), "rock-roll-1950s");
+        assert_eq!(slugify("Énergie_solaire"), "énergie-solaire");
+        assert_eq!(slugify("!!!"), "");
     }
-    text_layout_builder.push_default(StyleProperty::FontSize(size));
-    let mut text_layout = text_layout_builder.build(text);
-    text_layout.break_all_lines(max_advance);
-    text_layout.align(None, alignment, TextAlignOptions::default());
-    text_layout
 }
@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+
+use crate::thing::Thing;
+
+pub const MAX_EXACT_EXPONENT: f64 = 9_007_199_254_740_992.;
+pub const MIN_DECADE_GAP: f64 = 1.;
+pub const NEAR_DUPLICATE_DECADES: f64 = 0.01;
+pub const MAX_PLAUSIBLE_EXPONENT: f64 = 1000.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    OutOfOrder,
+    DuplicateName,
+    EmptyName,
+    NearDuplicate,
+    NonPositive,
+    AbsurdExponent,
+    ExceedsFormatting,
+}
+
+impl IssueKind {
+    pub fn severity(self) -> Severity {
+        match self {
+            IssueKind::NonPositive => Severity::Error,
+            IssueKind::OutOfOrder
+            | IssueKind::DuplicateName
+            | IssueKind::EmptyName
+            | IssueKind::NearDuplicate
+            | IssueKind::AbsurdExponent
+            | IssueKind::ExceedsFormatting => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    pub index: usize,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let severity = match self.kind.severity() {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: #{} {}", self.index + 1, self.message)
+    }
+}
+
+pub fn validate(things: &[Thing]) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut names = HashSet::new();
+    for (index, thing) in things.iter().enumerate() {
+        let value = thing.value.inner();
+        if value.significand() <= 0. {
+            issues.push(ValidationIssue {
+                index,
+                kind: IssueKind::NonPositive,
+                message: format!("\"{}\" has a non-positive value", thing.name),
+            });
+        }
+        if value.exponent().abs() >= MAX_EXACT_EXPONENT {
+            issues.push(ValidationIssue {
+                index,
+                kind: IssueKind::ExceedsFormatting,
+                message: format!("\"{}\" has an exponent too large to format", thing.name),
+            });
+        } else if value.exponent().abs() > MAX_PLAUSIBLE_EXPONENT {
+            issues.push(ValidationIssue {
+                index,
+                kind: IssueKind::AbsurdExponent,
+                message: format!(
+                    "\"{}\" has an implausible exponent ({})",
+                    thing.name,
+                    value.exponent()
+                ),
+            });
+        }
+        if thing.name.trim().is_empty() {
+            issues.push(ValidationIssue {
+                index,
+                kind: IssueKind::EmptyName,
+                message: "has an empty name".to_string(),
+            });
+        } else if !names.insert(thing.name.as_str()) {
+            issues.push(ValidationIssue {
+                index,
+                kind: IssueKind::DuplicateName,
+                message: format!("\"{}\" is a duplicate name", thing.name),
+            });
+        }
+        if let Some(scale) = thing.finite_scale()
+            && let Some(other) = things[..index].iter().find(|other| {
+                other
+                    .finite_scale()
+                    .is_some_and(|other| (other - scale).abs() < NEAR_DUPLICATE_DECADES)
+            })
+        {
+            issues.push(ValidationIssue {
+                index,
+                kind: IssueKind::NearDuplicate,
+                message: format!(
+                    "\"{}\" has nearly the same magnitude as \"{}\"",
+                    thing.name, other.name
+                ),
+            });
+        }
+        if let Some(prev) = index.checked_sub(1).map(|i| &things[i])
+            && prev.value.total_cmp(&thing.value).is_gt()
+        {
+            issues.push(ValidationIssue {
+                index,
+                kind: IssueKind::OutOfOrder,
+                message: format!("\"{}\" is smaller than \"{}\"", thing.name, prev.name),
+            });
+        }
+    }
+    issues
+}
+
+pub fn has_errors(issues: &[ValidationIssue]) -> bool {
+    issues
+        .iter()
+        .any(|issue| issue.kind.severity() == Severity::Error)
+}
+
+pub fn small_gaps(scales: &[f64]) -> Vec<usize> {
+    scales
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair.iter().all(|scale| scale.is_finite()))
+        .filter(|(_, pair)| (0. ..MIN_DECADE_GAP).contains(&(pair[1] - pair[0])))
+        .map(|(index, _)| index + 1)
+        .collect()
+}
+
+pub fn smallest_not_first(things: &[Thing]) -> Option<usize> {
+    let smallest = things
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.value.total_cmp(&b.value))?
+        .0;
+    (smallest != 0 && things[smallest].value.total_cmp(&things[0].value).is_lt())
+        .then_some(smallest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ENumber;
+
+    #[test]
+    fn test_validate_issue_kinds() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Also a blink", 0.301),
+            Thing::new("  ", 2.),
+            Thing::new("Debt", -5.),
+            Thing::new("Googolplex-ish", (1., 5000)),
+            Thing::new("Blink", ENumber::normalize(1., 1e20)),
+        ];
+        let kinds = validate(&things)
+            .into_iter()
+            .map(|issue| (issue.index, issue.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                (1, IssueKind::NearDuplicate),
+                (2, IssueKind::EmptyName),
+                (3, IssueKind::NonPositive),
+                (3, IssueKind::OutOfOrder),
+                (4, IssueKind::AbsurdExponent),
+                (5, IssueKind::ExceedsFormatting),
+                (5, IssueKind::DuplicateName),
+            ]
+        );
+        let issues = validate(&things);
+        assert_eq!(
+            issues[0].to_string(),
+            "warning: #2 \"Also a blink\" has nearly the same magnitude as \"Blink\""
+        );
+        assert_eq!(
+            issues[4].to_string(),
+            "warning: #5 \"Googolplex-ish\" has an implausible exponent (5000)"
+        );
+        assert_eq!(issues[1].to_string(), "warning: #3 has an empty name");
+        assert!(!has_errors(&validate(&things[..3])));
+        assert!(validate(&things[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_small_gaps() {
+        assert!(small_gaps(&[]).is_empty());
+        assert!(small_gaps(&[3.]).is_empty());
+        assert_eq!(small_gaps(&[0., 0.5, 2., 2.99, 4.]), [1, 3]);
+        assert!(small_gaps(&[0., 1., 2.]).is_empty());
+        assert!(small_gaps(&[f64::NEG_INFINITY, 0., 5.]).is_empty());
+        assert_eq!(small_gaps(&[5.78, 6.42, 7.5]), [1]);
+        assert!(small_gaps(&[7.5, 5.]).is_empty());
+    }
+
+    #[test]
+    fn test_smallest_not_first() {
+        assert_eq!(smallest_not_first(&[]), None);
+        let sorted = [Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        assert_eq!(smallest_not_first(&sorted), None);
+        let unsorted = [
+            Thing::new("Age of the universe", (4.35, 17)),
+            Thing::new("Week", 604800.),
+            Thing::new("Blink", 0.3),
+        ];
+        assert_eq!(smallest_not_first(&unsorted), Some(2));
+        let tied = [Thing::new("Blink", 0.3), Thing::new("Also a blink", 0.3)];
+        assert_eq!(smallest_not_first(&tied), None);
+    }
+}
@@ -1,261 +1,4209 @@
-use std::time::Duration;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::time::{Duration, Instant};
 
-use simple_easing::{cubic_in_out, cubic_out};
+use serde::{Deserialize, Serialize};
+use xilem::core::one_of::Either;
 use xilem::core::{Edit, fork, lens};
-use xilem::masonry::core::render_text;
-use xilem::masonry::parley::GenericFamily;
+use xilem::masonry::properties::types::AsUnit;
 use xilem::palette::css;
 use xilem::style::Style;
 use xilem::tokio::time;
-use xilem::vello::kurbo::{Affine, Axis, Rect, Vec2};
-use xilem::vello::peniko::Fill;
+use xilem::tokio::time::MissedTickBehavior;
+use xilem::vello::kurbo::{Affine, Point, Rect, Size, Vec2};
 use xilem::view::{
-    MainAxisAlignment, canvas, flex_col, flex_row, label, sized_box, task, text_button, zstack,
+    CrossAxisAlignment, FlexExt, MainAxisAlignment, button, canvas, checkbox, flex_col, flex_row,
+    label, portal, sized_box, task, zstack,
 };
-use xilem::{Color, TextAlign, WidgetView};
+use xilem::{Color, WidgetView};
 
-use crate::State;
-use crate::animation::{AnimStep, Animation};
+use crate::animation::{AnimStep, Animation, Easing, Pacing};
+use crate::comparison::{Bar, Side, slot_count, slot_scale};
+use crate::input::pointer::pointer_input;
 use crate::math::ENumber;
-use crate::thing::Thing;
-use crate::units::TimeScale;
-use crate::utils::{
-    ignore_x, stroke_inf_line, stroke_inf_line_pad, text_layout, y_flipped, y_flipped_translate,
-};
+use crate::notice::Notices;
+use crate::settings::Settings;
+use crate::summary::summarize;
+use crate::thing::{Baseline, Orientation, Thing};
+use crate::units::{DAY, HOUR, MINUTE, TimeScale, TimeUnit, YEAR};
+use crate::utils::{CanvasSpaces, copy_to_clipboard, float_to_string_with, superscript_exponents};
+use crate::validation::MIN_DECADE_GAP;
+use crate::viewport::layers::{Painter, Titles};
+use crate::{State, Tab};
+
+pub mod layers;
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MinorLabels {
+    #[default]
+    Off,
+    Digits,
+    Values,
+}
+
+impl std::fmt::Display for MinorLabels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MinorLabels::Off => write!(f, "Off"),
+            MinorLabels::Digits => write!(f, "2 and 5"),
+            MinorLabels::Values => write!(f, "Values"),
+        }
+    }
+}
+
+impl MinorLabels {
+    pub fn next(self) -> Self {
+        match self {
+            MinorLabels::Off => MinorLabels::Digits,
+            MinorLabels::Digits => MinorLabels::Values,
+            MinorLabels::Values => MinorLabels::Off,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AxisLabels {
+    #[default]
+    Time,
+    Powers,
+}
+
+impl std::fmt::Display for AxisLabels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AxisLabels::Time => write!(f, "Time"),
+            AxisLabels::Powers => write!(f, "Powers of ten"),
+        }
+    }
+}
+
+impl AxisLabels {
+    pub fn next(self) -> Self {
+        match self {
+            AxisLabels::Time => AxisLabels::Powers,
+            AxisLabels::Powers => AxisLabels::Time,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Spacing {
+    #[default]
+    Even,
+    Magnitude,
+}
+
+impl std::fmt::Display for Spacing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Spacing::Even => write!(f, "Even"),
+            Spacing::Magnitude => write!(f, "By magnitude"),
+        }
+    }
+}
+
+impl Spacing {
+    pub fn next(self) -> Self {
+        match self {
+            Spacing::Even => Spacing::Magnitude,
+            Spacing::Magnitude => Spacing::Even,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl Corner {
+    pub fn origin(self, size: Size, content: Size, margin: f64) -> Point {
+        let x = match self {
+            Corner::TopLeft | Corner::BottomLeft => margin,
+            Corner::TopRight | Corner::BottomRight => size.width - content.width - margin,
+        };
+        let y = match self {
+            Corner::TopLeft | Corner::TopRight => margin,
+            Corner::BottomLeft | Corner::BottomRight => size.height - content.height - margin,
+        };
+        Point::new(x, y)
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    #[default]
+    Leftward,
+    Rightward,
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Leftward => write!(f, "Leftward"),
+            Direction::Rightward => write!(f, "Rightward"),
+        }
+    }
+}
+
+impl Direction {
+    pub fn sign(self) -> f64 {
+        match self {
+            Direction::Leftward => -1.,
+            Direction::Rightward => 1.,
+        }
+    }
+
+    pub fn flipped(self) -> Self {
+        match self {
+            Direction::Leftward => Direction::Rightward,
+            Direction::Rightward => Direction::Leftward,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct ResizeDebounce {
+    pub settled: Option<Vec2>,
+    pub pending: Option<Vec2>,
+    pub frames: u32,
+}
+
+impl ResizeDebounce {
+    pub const FRAMES: u32 = 4;
+
+    pub fn update(&mut self, target: Vec2) -> Vec2 {
+        if self.settled.is_none() || self.settled == Some(target) {
+            self.settled = Some(target);
+            self.pending = None;
+            self.frames = 0;
+        } else if self.pending != Some(target) {
+            self.pending = Some(target);
+            self.frames = 0;
+        } else {
+            self.frames += 1;
+            if self.frames >= Self::FRAMES {
+                self.settled = Some(target);
+                self.pending = None;
+                self.frames = 0;
+            }
+        }
+        self.settled.unwrap_or(target)
+    }
+
+    pub fn settle(&mut self, target: Vec2) {
+        *self = Self {
+            settled: Some(target),
+            ..Self::default()
+        };
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Caption {
+    pub current: Option<usize>,
+    pub previous: Option<usize>,
+    pub fade: f64,
+}
+
+impl Caption {
+    pub const FADE_TIME: f64 = 0.3;
+
+    pub fn update(&mut self, focus: Option<usize>, dt: f64, instant: bool) {
+        if focus != self.current {
+            self.previous = self.current;
+            self.current = focus;
+            self.fade = Self::FADE_TIME;
+        } else {
+            self.fade = (self.fade - dt).max(0.);
+        }
+        if instant {
+            self.fade = 0.;
+        }
+    }
+
+    pub fn alphas(&self) -> (f32, f32) {
+        let progress = (1. - self.fade / Self::FADE_TIME) as f32;
+        (progress, 1. - progress)
+    }
+}
+
+// The animated part of the viewport, kept as a last known good copy so a
+// non-finite value from bad input cannot blank every following frame.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Motion {
+    pub step: AnimStep,
+    pub elapsed: f64,
+    pub scale: f64,
+    pub scale_speed: f64,
+    pub slow_scale_speed: f64,
+    pub prev_shift: f64,
+    pub last_shift: f64,
+    pub shift: f64,
+    pub camera: Affine,
+}
+
+impl Motion {
+    pub fn is_finite(&self) -> bool {
+        let timer = match self.step {
+            AnimStep::Idle(t)
+            | AnimStep::Slowing(t)
+            | AnimStep::Pausing(t)
+            | AnimStep::Shifting(t) => t,
+            AnimStep::Scaling | AnimStep::Finished => 0.,
+        };
+        [
+            timer,
+            self.elapsed,
+            self.scale,
+            self.scale_speed,
+            self.slow_scale_speed,
+            self.prev_shift,
+            self.last_shift,
+            self.shift,
+        ]
+        .iter()
+        .all(|value| value.is_finite())
+            && self.camera.is_finite()
+    }
+}
+
+// Everything a simulated run depends on besides its own clock.
+#[derive(Clone, Debug, PartialEq)]
+struct RunKey {
+    things: Vec<(u64, Option<f64>)>,
+    secondary: Option<Vec<Option<f64>>>,
+    decades: u32,
+    pacing: Pacing,
+    easing: (Easing, Easing),
+    examine: bool,
+    constant_speed: bool,
+    reduce_motion: bool,
+    play_range: PlayRange,
+    direction: Direction,
+    half_size: Option<Vec2>,
+    footer_height: f64,
+    slot_offsets: Vec<f64>,
+}
+
+#[derive(Clone, Debug)]
+struct Checkpoint {
+    motion: Motion,
+    frame: u64,
+    pacing: Pacing,
+    caption: Caption,
+    crossfade: f64,
+    visited: HashSet<u64>,
+}
+
+// One run from the start of the play range, with a checkpoint every
+// `EVERY` frames, so scrubbing and centering resume from the nearest one
+// instead of replaying everything before it. Rebuilt whenever its key changes.
+#[derive(Default)]
+pub struct RunCache {
+    key: Option<RunKey>,
+    range: (u64, u64),
+    reached: Vec<Option<u64>>,
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl RunCache {
+    pub const EVERY: u64 = 60;
+
+    pub fn frame_range(&self) -> (u64, u64) {
+        self.range
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BarGeometry {
+    pub index: usize,
+    pub side: Side,
+    pub name: String,
+    pub value: ENumber,
+    pub x: f64,
+    pub left: f64,
+    pub right: f64,
+    pub top: f64,
+    pub bottom: f64,
+    pub alpha: f32,
+    pub clamped: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinnedReference {
+    pub index: usize,
+    pub alpha: f32,
+    pub label: String,
+    pub height_label: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuItem {
+    Edit,
+    SetReference,
+    CopyValue,
+    Center,
+}
+
+impl std::fmt::Display for MenuItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MenuItem::Edit => write!(f, "Edit this thing"),
+            MenuItem::SetReference => write!(f, "Set as reference"),
+            MenuItem::CopyValue => write!(f, "Copy value"),
+            MenuItem::Center => write!(f, "Focus/center"),
+        }
+    }
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 4] = [
+        MenuItem::Edit,
+        MenuItem::SetReference,
+        MenuItem::CopyValue,
+        MenuItem::Center,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContextMenu {
+    pub index: usize,
+    pub id: u64,
+    pub origin: Point,
+}
+
+impl ContextMenu {
+    pub const ITEM_WIDTH: f64 = 150.;
+    pub const ITEM_HEIGHT: f64 = 24.;
+
+    pub fn item_rect(&self, item: usize, ui_scale: f64) -> Rect {
+        let size = Size::new(Self::ITEM_WIDTH, Self::ITEM_HEIGHT) * ui_scale;
+        Rect::from_origin_size(self.origin + Vec2::new(0., size.height * item as f64), size)
+    }
+
+    pub fn bounds(&self, ui_scale: f64) -> Rect {
+        self.item_rect(0, ui_scale)
+            .union(self.item_rect(MenuItem::ALL.len() - 1, ui_scale))
+    }
+
+    pub fn item_at(&self, point: Point, ui_scale: f64) -> Option<MenuItem> {
+        (0..MenuItem::ALL.len())
+            .find(|&item| self.item_rect(item, ui_scale).contains(point))
+            .map(|item| MenuItem::ALL[item])
+    }
+}
+
+// The stretch of things a run covers; `end: None` plays to the last one.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayRange {
+    pub start: usize,
+    pub end: Option<usize>,
+}
+
+impl std::fmt::Display for PlayRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.start, self.end) {
+            (0, None) => write!(f, "all"),
+            (start, None) => write!(f, "{}–end", start + 1),
+            (start, Some(end)) => write!(f, "{}–{}", start + 1, end + 1),
+        }
+    }
+}
+
+impl PlayRange {
+    pub fn clamped(self, len: usize) -> Self {
+        let last = len.saturating_sub(1);
+        let start = self.start.min(last);
+        Self {
+            start,
+            end: self.end.map(|end| end.clamp(start, last)),
+        }
+    }
+
+    // One past the last slot the run visits.
+    pub fn end_slot(self, count: usize) -> usize {
+        self.end.map_or(count, |end| (end + 1).min(count))
+    }
+
+    pub fn is_all(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Follows the first and last things of the range from `old` to their
+    // place in `new`. Removed things leave their index behind, and the full
+    // range keeps covering everything.
+    pub fn remapped(self, old: &[u64], new: &[u64]) -> Self {
+        if self.is_all() {
+            return self;
+        }
+        let follow = |index: usize| {
+            old.get(index)
+                .and_then(|id| new.iter().position(|other| other == id))
+                .unwrap_or(index)
+        };
+        let start = follow(self.start);
+        match self.end.map(follow) {
+            Some(end) => Self {
+                start: start.min(end),
+                end: Some(start.max(end)),
+            },
+            None => Self { start, end: None },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimelineEvent {
+    pub thing_index: usize,
+    pub start_secs: f64,
+    pub pause_secs: f64,
+}
+
+pub fn simulate_timeline(things: &[Thing], settings: Settings) -> Vec<TimelineEvent> {
+    Viewport::init_with(things, settings.visible_decades).timeline(things)
+}
 
 pub struct Viewport {
     pub animation: Animation,
+    pub direction: Direction,
+    pub compact_labels: bool,
+    pub motion_trail: bool,
+    pub pin_previous: bool,
+    pub examine: bool,
+    pub constant_speed: bool,
+    pub reduce_motion: bool,
+    pub crossfade: f64,
+    pub decades: u32,
+    pub baseline: Baseline,
+    pub orientation: Orientation,
+    pub spacing: Spacing,
+    pub slot_offsets: Vec<f64>,
     pub scale: f64,
     pub scale_speed: f64,
     pub slow_scale_speed: f64,
     pub prev_shift: f64,
+    pub last_shift: f64,
     pub shift: f64,
     pub camera: Affine,
+    pub half_size: Option<Vec2>,
+    pub resize_debounce: ResizeDebounce,
+    pub hover: Option<Point>,
+    pub context_menu: Option<ContextMenu>,
+    pub keyboard_focus: Option<usize>,
+    pub reference: Option<u64>,
+    pub play_range: PlayRange,
+    pub visited: HashSet<u64>,
+    pub caption: Caption,
+    pub footer_height: f64,
+    pub ids: Vec<u64>,
+    pub scales: Vec<Option<f64>>,
+    pub last_good: Motion,
+    pub repairs: u32,
+    pub run_cache: RunCache,
 }
 
 impl Viewport {
+    pub const BACKGROUND_COLOR: Color = Color::BLACK;
     pub const FOOTER_AREA_COLOR: Color = Color::from_rgb8(25, 25, 25);
     pub const MAJOR_COLOR: Color = css::LIGHT_GRAY;
     pub const MINOR_LINE_COLOR: Color = Color::from_rgb8(85, 85, 85);
+    pub const MINOR_LABEL_COLOR: Color = Color::from_rgb8(140, 140, 140);
 
     pub const MAX_HEIGHT: f64 = 1000.;
-    pub const MINOR_LINES: usize = 3;
-    pub const MINOR_OFFSET: f64 = (Self::MINOR_LINES as f64 + 1.).recip();
-    pub const SCALE_PADDING: f64 = 2.85;
+    pub const MINOR_LINES: usize = 8;
+    pub const LABELED_MINORS: [f64; 2] = [2., 5.];
+    pub const MINOR_HOVER_DISTANCE: f64 = 6.;
+    pub const DEFAULT_DECADES: u32 = 3;
+    pub const MIN_DECADES: u32 = 3;
+    pub const MAX_DECADES: u32 = 6;
+    pub const HEADROOM_MARGIN: f64 = 0.15;
+    pub const LABEL_MIN_HEIGHT: f64 = 12.;
+    pub const EXPORT_MARGIN: f64 = 20.;
+    pub const MIN_SCALING_TIME: f64 = 1.5;
+    pub const HALF_DECADE_SPAN: f64 = 1.5;
+    pub const FIFTH_DECADE_SPAN: f64 = 0.6;
     pub const IDLE_SCALE_SPEED: f64 = 0.025;
-    pub const SCALE_ACCELERATION: f64 = 0.25;
     pub const INITIAL_SLOW_SCALE_SPEED: f64 = 3.;
+    pub const EXAMINE_SCALE_SPEED: f64 = 0.1;
+    pub const CONSTANT_SCALE_SPEED: f64 = 1.;
     pub const INITIAL_CAMERA_POSITION: Vec2 = Vec2::new(0., 350.);
+    pub const GHOST_ALPHA: f32 = 0.3;
+    pub const OVERLAY_TEXT_SIZE: f32 = 14.;
+    pub const SPLIT_GAP: f64 = 2.;
+    pub const DEFAULT_FOOTER_HEIGHT: f64 = 100.;
+    pub const MIN_FOOTER_HEIGHT: f64 = 60.;
+    pub const MAX_FOOTER_HEIGHT: f64 = 300.;
+    pub const FOOTER_HEIGHT_STEP: f64 = 20.;
+    pub const RESIZE_FRAMES: f64 = 8.;
+    pub const RESIZE_SNAP_DISTANCE: f64 = 0.5;
+    pub const SCALE_BAR_BUDGET: f64 = 150.;
+    pub const SCALE_BAR_MIN: f64 = 2.;
+    pub const MAX_SIMULATED_FRAMES: u64 = 1_000_000;
+    pub const REPAIR_NOTICE: &str =
+        "The preview hit an invalid number and went back to its last good position";
+    pub const CROSSFADE_TIME: f64 = 0.25;
+    pub const PIN_HEIGHT: f64 = 30.;
+    pub const TRANSITION_NOTE_Y: f64 = 0.25;
+    pub const REVEAL_FADE: f32 = 0.3;
+    pub const REVEAL_MARGIN: f64 = 0.1;
+    pub const MARGIN_VALUE_X: f64 = 80.;
+    pub const MARGIN_VALUE_GAP: f64 = 14.;
+    pub const TINY_TICK_HEIGHT: f64 = 2.;
+    pub const TINY_ARROW_SIZE: f64 = 5.;
+    pub const TINY_ARROW_GAP: f64 = 3.;
+    pub const PIN_ALPHA: f32 = 0.5;
+    pub const PIN_FADE: f64 = 0.15;
+    pub const REFERENCE_COLOR: Color = css::GOLD;
+    pub const FOCUS_DIM_ALPHA: f32 = 0.6;
+    pub const FOCUS_TEXT_SCALE: f64 = 1.15;
+    pub const FOCUS_GLOW_COLOR: Color = css::WHITE;
+    pub const FOCUS_RING_COLOR: Color = css::DEEP_SKY_BLUE;
+    pub const DECADE_SPACING: f64 = 120.;
+    pub const STAGGER_ROWS: usize = 3;
+    pub const RULER_OFFSET: f64 = 24.;
+    pub const PANEL_ROWS: usize = 12;
+    pub const PANEL_WIDTH: f64 = 260.;
+    pub const DEFAULT_MAX_BARS: usize = 50;
+    pub const MIN_MAX_BARS: usize = 10;
+    pub const MAX_MAX_BARS: usize = 500;
+    pub const MAX_BARS_STEP: usize = 10;
 
     pub fn init(things: &[Thing]) -> Self {
+        Self::init_with(things, Self::DEFAULT_DECADES)
+    }
+
+    pub fn init_with(things: &[Thing], decades: u32) -> Self {
+        let decades = decades.clamp(Self::MIN_DECADES, Self::MAX_DECADES);
         let scale = things
-            .get(0)
-            .map(|thing| thing.scale() - Self::SCALE_PADDING)
-            .unwrap_or(0.);
-        Self {
+            .iter()
+            .find_map(Thing::finite_scale)
+            .map_or(0., |scale| scale - Self::headroom(decades));
+        let mut viewport = Self {
             animation: Animation::default(),
+            direction: Direction::default(),
+            compact_labels: false,
+            motion_trail: false,
+            pin_previous: false,
+            examine: false,
+            constant_speed: false,
+            reduce_motion: false,
+            crossfade: 0.,
+            decades,
+            baseline: Baseline::default(),
+            orientation: Orientation::default(),
+            spacing: Spacing::default(),
+            slot_offsets: Vec::new(),
             scale,
             scale_speed: Self::IDLE_SCALE_SPEED,
             slow_scale_speed: 0.,
             prev_shift: 0.,
+            last_shift: 0.,
             shift: 0.,
             camera: Affine::translate(Self::INITIAL_CAMERA_POSITION),
+            half_size: None,
+            resize_debounce: ResizeDebounce::default(),
+            hover: None,
+            context_menu: None,
+            keyboard_focus: None,
+            reference: None,
+            play_range: PlayRange::default(),
+            visited: HashSet::new(),
+            caption: Caption::default(),
+            footer_height: Self::DEFAULT_FOOTER_HEIGHT,
+            ids: things.iter().map(|thing| thing.id).collect(),
+            scales: things.iter().map(Thing::finite_scale).collect(),
+            last_good: Motion::default(),
+            repairs: 0,
+            run_cache: RunCache::default(),
+        };
+        viewport.last_good = viewport.motion();
+        viewport
+    }
+
+    pub fn motion(&self) -> Motion {
+        Motion {
+            step: self.animation.step,
+            elapsed: self.animation.elapsed,
+            scale: self.scale,
+            scale_speed: self.scale_speed,
+            slow_scale_speed: self.slow_scale_speed,
+            prev_shift: self.prev_shift,
+            last_shift: self.last_shift,
+            shift: self.shift,
+            camera: self.camera,
         }
     }
 
-    fn update_animation(&mut self, things: &[Thing]) {
-        let scaling_done = match self.shift.floor() {
-            ..=0. => true,
-            i => {
-                if let Some(thing) = things.get(i as usize - 1) {
-                    thing.scale() - self.scale <= Self::SCALE_PADDING
-                } else {
-                    false
+    fn set_motion(&mut self, motion: Motion) {
+        self.animation.step = motion.step;
+        self.animation.elapsed = motion.elapsed;
+        self.scale = motion.scale;
+        self.scale_speed = motion.scale_speed;
+        self.slow_scale_speed = motion.slow_scale_speed;
+        self.prev_shift = motion.prev_shift;
+        self.last_shift = motion.last_shift;
+        self.shift = motion.shift;
+        self.camera = motion.camera;
+    }
+
+    /// Keeps the motion state if it is finite, otherwise puts back the last
+    /// good one. Returns whether a repair was needed.
+    pub fn repair(&mut self) -> bool {
+        let motion = self.motion();
+        if motion.is_finite() {
+            self.last_good = motion;
+            return false;
+        }
+        self.set_motion(self.last_good);
+        self.repairs += 1;
+        tracing::warn!(
+            ?motion,
+            repairs = self.repairs,
+            "viewport motion was not finite, restored the last good state"
+        );
+        true
+    }
+
+    pub fn restart(&mut self, things: &[Thing]) {
+        self.reset(things);
+        self.skip_to_start(things);
+    }
+
+    fn reset(&mut self, things: &[Thing]) {
+        let animation = Animation {
+            active: self.animation.active,
+            manual_advance: self.animation.manual_advance,
+            shift_easing: self.animation.shift_easing,
+            slow_easing: self.animation.slow_easing,
+            ..Animation::with_pacing(self.animation.next_pacing)
+        };
+        *self = Self {
+            animation,
+            direction: self.direction,
+            compact_labels: self.compact_labels,
+            motion_trail: self.motion_trail,
+            pin_previous: self.pin_previous,
+            examine: self.examine,
+            constant_speed: self.constant_speed,
+            reduce_motion: self.reduce_motion,
+            half_size: self.half_size,
+            resize_debounce: self.resize_debounce,
+            baseline: self.baseline,
+            orientation: self.orientation,
+            spacing: self.spacing,
+            slot_offsets: self.slot_offsets.clone(),
+            reference: self.reference,
+            play_range: self.play_range,
+            footer_height: self.footer_height,
+            run_cache: std::mem::take(&mut self.run_cache),
+            ..Self::init_with(things, self.decades)
+        };
+        self.update_camera();
+    }
+
+    // Runs through the things before the play range, so that the run starts
+    // paused on its first thing with the clock at zero.
+    fn skip_to_start(&mut self, things: &[Thing]) {
+        let start = self.play_range.clamped(things.len()).start;
+        if start == 0 {
+            return;
+        }
+        self.seek(start, things, None);
+        self.animation.frame = 0;
+        self.animation.elapsed = 0.;
+        self.visited.retain(|&id| id == things[start].id);
+    }
+
+    pub fn set_play_range(&mut self, range: PlayRange, things: &[Thing]) {
+        self.play_range = range.clamped(things.len());
+        self.restart(things);
+    }
+
+    fn simulation(&self, things: &[Thing]) -> Self {
+        let animation = Animation {
+            shift_easing: self.animation.shift_easing,
+            slow_easing: self.animation.slow_easing,
+            ..Animation::with_pacing(self.animation.next_pacing)
+        };
+        let mut simulation = Self {
+            animation,
+            examine: self.examine,
+            constant_speed: self.constant_speed,
+            reduce_motion: self.reduce_motion,
+            play_range: self.play_range,
+            ..Self::init_with(things, self.decades)
+        };
+        simulation.skip_to_start(things);
+        simulation
+    }
+
+    pub fn timeline(&self, things: &[Thing]) -> Vec<TimelineEvent> {
+        let mut simulation = self.simulation(things);
+        let mut events = (simulation.prev_shift as usize)
+            .checked_sub(1)
+            .map(|index| TimelineEvent {
+                thing_index: index,
+                start_secs: 0.,
+                pause_secs: 0.,
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+        while !simulation.animation.finished()
+            && simulation.animation.frame < Self::MAX_SIMULATED_FRAMES
+        {
+            let (prev_shift, pausing) = (
+                simulation.prev_shift,
+                matches!(simulation.animation.step, AnimStep::Pausing(_)),
+            );
+            simulation.update_animation(things, None, Animation::FRAME_SECS);
+            let secs = simulation.animation.secs();
+            if simulation.prev_shift > prev_shift && simulation.prev_shift as usize <= things.len()
+            {
+                events.push(TimelineEvent {
+                    thing_index: simulation.prev_shift as usize - 1,
+                    start_secs: secs,
+                    pause_secs: secs,
+                });
+            }
+            if !pausing
+                && matches!(simulation.animation.step, AnimStep::Pausing(_))
+                && let Some(event) = events.last_mut()
+            {
+                event.pause_secs = secs;
+            }
+        }
+        events
+    }
+
+    pub fn frame_range(&self, things: &[Thing], secondary: Option<&[Thing]>) -> (u64, u64) {
+        let mut simulation = self.simulation(things);
+        let mut first = (simulation.shift >= 1.).then_some(0);
+        while !simulation.animation.finished()
+            && simulation.animation.frame < Self::MAX_SIMULATED_FRAMES
+        {
+            simulation.update_animation(things, secondary, Animation::FRAME_SECS);
+            if first.is_none() && simulation.shift >= 1. {
+                first = Some(simulation.animation.frame);
+            }
+        }
+        let last = simulation.animation.frame;
+        (first.unwrap_or(last), last)
+    }
+
+    fn run_key(&self, things: &[Thing], secondary: Option<&[Thing]>) -> RunKey {
+        RunKey {
+            things: things
+                .iter()
+                .map(|thing| (thing.id, thing.finite_scale()))
+                .collect(),
+            secondary: secondary.map(|things| things.iter().map(Thing::finite_scale).collect()),
+            decades: self.decades,
+            pacing: self.animation.next_pacing,
+            easing: (self.animation.shift_easing, self.animation.slow_easing),
+            examine: self.examine,
+            constant_speed: self.constant_speed,
+            reduce_motion: self.reduce_motion,
+            play_range: self.play_range,
+            direction: self.direction,
+            half_size: self.half_size,
+            footer_height: self.footer_height,
+            slot_offsets: self.slot_offsets.clone(),
+        }
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            motion: self.motion(),
+            frame: self.animation.frame,
+            pacing: self.animation.pacing,
+            caption: self.caption,
+            crossfade: self.crossfade,
+            visited: self.visited.clone(),
+        }
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint) {
+        self.set_motion(checkpoint.motion);
+        self.last_good = checkpoint.motion;
+        self.animation.frame = checkpoint.frame;
+        self.animation.pacing = checkpoint.pacing;
+        self.caption = checkpoint.caption;
+        self.crossfade = checkpoint.crossfade;
+        self.visited.clone_from(&checkpoint.visited);
+    }
+
+    // Plays the whole run once for the cache; leaves the viewport at its end.
+    fn cache_run(&mut self, things: &[Thing], secondary: Option<&[Thing]>) {
+        let key = self.run_key(things, secondary);
+        if self.run_cache.key.as_ref() == Some(&key) {
+            return;
+        }
+        let start = self.play_range.clamped(things.len()).start;
+        let manual_advance = std::mem::take(&mut self.animation.manual_advance);
+        self.restart(things);
+        let mut cache = RunCache {
+            key: Some(key),
+            reached: vec![None; slot_count(things, secondary)],
+            ..RunCache::default()
+        };
+        let mut first = None;
+        loop {
+            let frame = self.animation.frame;
+            if frame.is_multiple_of(RunCache::EVERY) {
+                cache.checkpoints.push(self.checkpoint());
+            }
+            if first.is_none() && self.shift >= 1. {
+                first = Some(frame);
+            }
+            if matches!(self.animation.step, AnimStep::Pausing(_)) {
+                let end = (self.shift as usize).min(cache.reached.len());
+                for reached in cache.reached.get_mut(start..end).unwrap_or_default() {
+                    reached.get_or_insert(frame);
                 }
             }
+            if self.animation.finished() || frame >= Self::MAX_SIMULATED_FRAMES {
+                break;
+            }
+            self.update_animation(things, secondary, Animation::FRAME_SECS);
+        }
+        let last = self.animation.frame;
+        cache.range = (first.unwrap_or(last), last);
+        self.animation.manual_advance = manual_advance;
+        self.animation.events.clear();
+        self.run_cache = cache;
+    }
+
+    pub fn replay(&mut self, things: &[Thing], secondary: Option<&[Thing]>, frame: u64) {
+        self.cache_run(things, secondary);
+        self.reset(things);
+        let checkpoints = std::mem::take(&mut self.run_cache.checkpoints);
+        let nearest = (frame / RunCache::EVERY) as usize;
+        if let Some(checkpoint) = checkpoints.get(nearest).or(checkpoints.last()) {
+            self.restore(checkpoint);
+        }
+        self.run_cache.checkpoints = checkpoints;
+        let manual_advance = std::mem::take(&mut self.animation.manual_advance);
+        while self.animation.frame < frame && !self.animation.finished() {
+            self.update_animation(things, secondary, Animation::FRAME_SECS);
+        }
+        self.animation.manual_advance = manual_advance;
+        self.animation.events.clear();
+    }
+
+    pub fn set_progress(&mut self, progress: f64, things: &[Thing], secondary: Option<&[Thing]>) {
+        self.cache_run(things, secondary);
+        let (first, last) = self.run_cache.range;
+        let frame = first + ((last - first) as f64 * progress.clamp(0., 1.)).round() as u64;
+        self.replay(things, secondary, frame);
+        self.animation.active = false;
+    }
+
+    // Things before the play range are not part of the cached run.
+    pub fn center_on(&mut self, index: usize, things: &[Thing], secondary: Option<&[Thing]>) {
+        self.cache_run(things, secondary);
+        match self.run_cache.reached.get(index).copied().flatten() {
+            Some(frame) => self.replay(things, secondary, frame),
+            None => {
+                self.reset(things);
+                self.seek(index, things, secondary);
+            }
+        }
+        self.animation.active = false;
+    }
+
+    fn seek(&mut self, index: usize, things: &[Thing], secondary: Option<&[Thing]>) {
+        let manual_advance = std::mem::take(&mut self.animation.manual_advance);
+        let target = (index + 1) as f64;
+        while !(self.shift >= target && matches!(self.animation.step, AnimStep::Pausing(_)))
+            && !self.animation.finished()
+            && self.animation.frame < Self::MAX_SIMULATED_FRAMES
+        {
+            self.update_animation(things, secondary, Animation::FRAME_SECS);
+        }
+        self.animation.manual_advance = manual_advance;
+        self.animation.events.clear();
+    }
+
+    pub fn sync(&mut self, things: &[Thing]) {
+        self.sync_ids(things);
+        self.repair();
+    }
+
+    fn sync_ids(&mut self, things: &[Thing]) {
+        let ids = things.iter().map(|thing| thing.id).collect::<Vec<_>>();
+        self.play_range = self.play_range.remapped(&self.ids, &ids).clamped(ids.len());
+        let Some(focus) = (self.prev_shift as usize).checked_sub(1) else {
+            if self.ids.first() == ids.first() {
+                self.retarget(0, things, 0);
+                self.ids = ids;
+                self.scales = things.iter().map(Thing::finite_scale).collect();
+            } else {
+                self.restart(things);
+            }
+            return;
+        };
+        let Some(index) = self
+            .ids
+            .get(focus)
+            .and_then(|id| ids.iter().position(|other| other == id))
+        else {
+            self.restart(things);
+            return;
         };
-        let slowing_done = self.scale_speed <= Self::IDLE_SCALE_SPEED;
+        let offset = index as f64 - focus as f64;
+        self.prev_shift += offset;
+        self.last_shift += offset;
+        self.shift += offset;
+        self.retarget(focus, things, index);
+        if self.animation.finished() && self.prev_shift < self.play_range.end_slot(ids.len()) as f64
+        {
+            self.animation.step = AnimStep::default();
+        }
+        self.ids = ids;
+        self.scales = things.iter().map(Thing::finite_scale).collect();
+        self.update_camera();
+    }
+
+    // Moves the scale by as much as the focused thing's value changed, so it
+    // stays framed the way it was.
+    fn retarget(&mut self, focus: usize, things: &[Thing], index: usize) {
+        let previous = self.scales.get(focus).copied().flatten();
+        let current = things.get(index).and_then(Thing::finite_scale);
+        if let (Some(previous), Some(current)) = (previous, current) {
+            self.scale += current - previous;
+        }
+    }
+
+    pub(crate) fn update_animation(
+        &mut self,
+        things: &[Thing],
+        secondary: Option<&[Thing]>,
+        dt: f64,
+    ) {
+        self.advance(things, secondary, dt);
+        self.repair();
+    }
+
+    fn advance(&mut self, things: &[Thing], secondary: Option<&[Thing]>, dt: f64) {
+        self.last_shift = self.shift;
+        let scaling_done = self.reduce_motion
+            || match self.shift.floor() {
+                ..=0. => true,
+                i => slot_scale(things, secondary, i as usize - 1)
+                    .is_none_or(|scale| scale - self.scale <= Self::headroom(self.decades)),
+            };
+        let slowing_done = self.constant_speed || self.scale_speed <= Self::IDLE_SCALE_SPEED;
+        let at_end = self.shift >= self.play_range.end_slot(slot_count(things, secondary)) as f64;
 
-        self.animation.tick(scaling_done, slowing_done);
+        self.animation.tick(dt, scaling_done, slowing_done, at_end);
+        self.caption
+            .update(self.focused_index(), dt, self.reduce_motion);
+        if matches!(self.animation.step, AnimStep::Pausing(_))
+            && let Some(thing) = self.focused_index().and_then(|index| things.get(index))
+        {
+            self.visited.insert(thing.id);
+        }
+
+        if self.reduce_motion {
+            self.cut_to_settled(things, secondary, dt);
+            self.update_camera();
+            return;
+        }
 
+        let gap = self.approach_gap(things, secondary);
+        let rest_speed = match gap {
+            Some(gap) if !self.constant_speed && !self.examine => Self::creep_speed(gap),
+            _ => Self::IDLE_SCALE_SPEED,
+        };
         match self.animation.step {
             AnimStep::Idle(_) | AnimStep::Pausing(_) => {
+                self.scale_speed = rest_speed;
+            }
+            AnimStep::Scaling if self.constant_speed => {
+                self.scale_speed = Self::CONSTANT_SCALE_SPEED;
+            }
+            AnimStep::Slowing(_) if self.constant_speed => {
                 self.scale_speed = Self::IDLE_SCALE_SPEED;
             }
             AnimStep::Scaling => {
-                self.scale_speed += Self::SCALE_ACCELERATION / Animation::FPS;
+                if self.examine {
+                    self.scale_speed = Self::EXAMINE_SCALE_SPEED;
+                } else {
+                    self.scale_speed += self.animation.pacing.acceleration * dt;
+                    if let Some(cap) = gap.and_then(Self::scaling_speed_cap) {
+                        self.scale_speed = self.scale_speed.min(cap);
+                    }
+                }
             }
-            AnimStep::Slowing(i) => {
-                if i == AnimStep::SLOWING_FRAMES {
-                    self.slow_scale_speed = self.scale_speed.min(Self::INITIAL_SLOW_SCALE_SPEED)
+            AnimStep::Slowing(t) => {
+                if t == self.animation.pacing.slowing_time {
+                    self.slow_scale_speed = self.scale_speed.min(Self::INITIAL_SLOW_SCALE_SPEED);
+                    if rest_speed < Self::IDLE_SCALE_SPEED {
+                        self.slow_scale_speed = rest_speed;
+                    }
                 }
-                if i > 0 {
-                    let progress = i as f32 / AnimStep::SLOWING_FRAMES as f32;
-                    self.scale_speed = Self::IDLE_SCALE_SPEED
-                        + (self.slow_scale_speed - Self::IDLE_SCALE_SPEED)
-                            * cubic_out(progress) as f64;
+                if t > 0. {
+                    let progress = (t / self.animation.pacing.slowing_time) as f32;
+                    let ease = self.animation.slow_easing.out();
+                    self.scale_speed =
+                        rest_speed + (self.slow_scale_speed - rest_speed) * ease(progress) as f64;
                 } else {
-                    self.scale_speed = Self::IDLE_SCALE_SPEED;
+                    self.scale_speed = rest_speed;
                 }
             }
-            AnimStep::Shifting(i) => {
-                if i > 0 {
-                    let progress = 1. - (i as f32 / AnimStep::SHIFTING_FRAMES as f32);
-                    self.shift = self.prev_shift + cubic_in_out(progress) as f64;
+            AnimStep::Shifting(t) => {
+                if t > 0. {
+                    let progress = self.animation.step.progress(&self.animation.pacing);
+                    let ease = self.animation.shift_easing.in_out();
+                    self.shift = self.prev_shift + ease(progress.unwrap_or(1.)) as f64;
                 } else {
                     self.prev_shift += 1.;
                     self.shift = self.prev_shift
                 }
             }
+            AnimStep::Finished => {
+                self.scale_speed = 0.;
+            }
         }
 
-        self.scale += self.scale_speed / Animation::FPS;
-        self.camera = self.camera.with_translation(
-            Self::INITIAL_CAMERA_POSITION + Vec2::new(-Thing::BAR_OFFSET * self.shift, 0.),
-        );
+        self.scale += self.scale_speed * self.animation.pacing.speed * dt;
+        self.update_camera();
     }
 
-    pub fn view(&mut self) -> impl WidgetView<Edit<State>> + use<> {
-        let canvas = canvas(
-            |State {
-                 things, viewport, ..
-             }: &mut State,
-             ctx,
-             scene,
-             size| {
-                let (fcx, lcx) = ctx.text_contexts();
-
-                let half_size = size.to_vec2() / 2.;
-                let world_trans = Affine::FLIP_Y.then_translate(half_size);
-                let text_trans = world_trans * Affine::FLIP_Y;
-                let camera = viewport.camera.inverse();
-                let world_camera = world_trans * camera;
-                let text_camera = text_trans * y_flipped(camera);
-
-                // things
-                for (i, thing) in things.iter().enumerate() {
-                    let position = thing.position(i, viewport.scale, half_size);
-                    let alpha = Thing::alpha(i, viewport.shift);
-                    thing.render_bar(position, alpha, scene, world_camera);
-                    thing.render_name(position, alpha, fcx, lcx, scene, text_camera);
-                }
+    fn cut_to_settled(&mut self, things: &[Thing], secondary: Option<&[Thing]>, dt: f64) {
+        self.crossfade = (self.crossfade - dt).max(0.);
+        if matches!(self.animation.step, AnimStep::Shifting(_)) {
+            self.prev_shift += 1.;
+            self.shift = self.prev_shift;
+            self.animation.step = AnimStep::Idle(self.animation.pacing.idle_time);
+            self.crossfade = Self::CROSSFADE_TIME;
+        }
+        let focus = self.focused_index();
+        if let Some(target) = focus.and_then(|focus| slot_scale(things, secondary, focus)) {
+            self.scale = self.scale.max(target - Self::headroom(self.decades));
+        }
+        self.scale_speed = 0.;
+    }
 
-                // visible logarithmic scale lines
-                for offset in -1..=3 {
-                    let scale = (viewport.scale + offset as f64).floor();
-                    let major_pos =
-                        ENumber::from_exp(scale).to_scale(viewport.scale, Self::MAX_HEIGHT);
-                    let major_alpha = major_pos.clamp(0., 1.) as f32;
-
-                    // major label
-                    let major_label = TimeScale::from(ENumber::from_exp(scale)).fmt_secs();
-                    let major_label_params = (
-                        major_label.as_str(),
-                        14.,
-                        GenericFamily::SansSerif,
-                        None,
-                        None,
-                        TextAlign::Start,
-                    );
-                    let major_text_layout = text_layout(fcx, lcx, major_label_params);
-                    render_text(
-                        scene,
-                        text_trans
-                            * y_flipped(ignore_x(camera))
-                            * y_flipped_translate((
-                                -half_size.x + 15.,
-                                major_pos + major_text_layout.height() as f64 / 2.,
-                            )),
-                        &major_text_layout,
-                        &[Self::MAJOR_COLOR.with_alpha(major_alpha).into()],
-                        true,
-                    );
-
-                    // major lines
-                    let major_line_params = (
-                        Axis::Horizontal,
-                        major_pos,
-                        Self::MAJOR_COLOR.with_alpha(major_alpha),
-                        0.8,
-                    );
-                    let major_line_padding = (major_text_layout.width() as f64 + 30., 0.);
-                    stroke_inf_line_pad(
-                        scene,
-                        world_trans,
-                        camera,
-                        half_size,
-                        major_line_params,
-                        major_line_padding,
-                    );
-
-                    // minor lines
-                    for i in 1..=Self::MINOR_LINES {
-                        let minor_pos = ENumber::from_exp(scale + Self::MINOR_OFFSET * i as f64)
-                            .to_scale(viewport.scale, Self::MAX_HEIGHT);
-                        let minor_alpha = minor_pos.clamp(0., 1.) as f32;
-                        let minor_line_params = (
-                            Axis::Horizontal,
-                            minor_pos,
-                            Self::MINOR_LINE_COLOR.with_alpha(minor_alpha),
-                            0.2,
-                        );
-                        stroke_inf_line(scene, world_trans, camera, half_size, minor_line_params);
-                    }
-                }
+    pub fn transition(&self) -> Option<(usize, usize, f32)> {
+        if !matches!(self.animation.step, AnimStep::Shifting(_)) {
+            return None;
+        }
+        let incoming = self.prev_shift as usize;
+        let progress = self.animation.step.progress(&self.animation.pacing)?;
+        Some((incoming.checked_sub(1)?, incoming, progress))
+    }
 
-                // area under axis line
-                let rect = Rect::new(-half_size.x, 0., half_size.x, -half_size.y);
-                scene.fill(
-                    Fill::NonZero,
-                    world_trans * ignore_x(camera),
-                    Self::FOOTER_AREA_COLOR,
-                    None,
-                    &rect,
-                );
+    pub fn transition_alpha(progress: f32) -> f32 {
+        (std::f32::consts::PI * progress).sin().max(0.)
+    }
 
-                // axis line
-                let x_line_params = (Axis::Horizontal, 0., Thing::VALUE_COLOR, 0.8);
-                stroke_inf_line(scene, world_trans, camera, half_size, x_line_params);
+    // Reveal text fades in at the start of the pause and out at the start of the shift.
+    pub fn reveal_alpha(step: &AnimStep, progress: f32) -> f32 {
+        match step {
+            AnimStep::Pausing(_) => (progress / Self::REVEAL_FADE).min(1.),
+            AnimStep::Shifting(_) => (1. - progress / Self::REVEAL_FADE).max(0.),
+            _ => 0.,
+        }
+    }
 
-                // thing values
-                for (i, thing) in things.iter().enumerate() {
-                    let position = thing.position(i, viewport.scale, half_size);
-                    let alpha = Thing::alpha(i, viewport.shift);
-                    thing.render_value(position, alpha, fcx, lcx, scene, text_camera);
-                }
-            },
-        );
+    pub fn reveal(&self) -> Option<(usize, f32)> {
+        let step = &self.animation.step;
+        let index = match step {
+            AnimStep::Pausing(_) => self.focused_index()?,
+            AnimStep::Shifting(_) => (self.prev_shift as usize).checked_sub(1)?,
+            _ => return None,
+        };
+        let progress = step.progress(&self.animation.pacing)?;
+        let alpha = Self::reveal_alpha(step, progress);
+        (alpha > 0.).then_some((index, alpha))
+    }
 
-        let playback_btn = lens(Animation::playback_button, move |state: &mut State, ()| {
-            &mut state.viewport.animation
-        });
-        let edit_btn = text_button("Edit", |state: &mut State| {
-            state.viewport.animation.active = false;
-            state.tab = crate::Tab::Data;
-        });
-        let controls = flex_row((playback_btn, edit_btn));
-        let debug = label(format!("{:?}", self.animation.step));
+    // the lower third of the canvas above the footer
+    pub fn reveal_area(size: Size, footer_height: f64) -> Rect {
+        let bottom = (size.height - footer_height).max(0.);
+        Rect::new(
+            size.width * Self::REVEAL_MARGIN,
+            bottom * 2. / 3.,
+            size.width * (1. - Self::REVEAL_MARGIN),
+            bottom,
+        )
+    }
 
-        let overlay =
-            sized_box(flex_col((debug, controls)).main_axis_alignment(MainAxisAlignment::End))
-                .expand()
-                .padding(15.);
+    pub fn crossfade_alpha(&self) -> f32 {
+        (self.crossfade / Self::CROSSFADE_TIME) as f32
+    }
 
+    fn approach_gap(&self, things: &[Thing], secondary: Option<&[Thing]>) -> Option<f64> {
+        let target = match self.animation.step {
+            AnimStep::Slowing(_) | AnimStep::Pausing(_) => self.shift.floor() as usize,
+            AnimStep::Shifting(_) => self.prev_shift as usize,
+            _ => self.focused_index()?,
+        };
+        let previous = slot_scale(things, secondary, target.checked_sub(1)?)?;
+        Some(slot_scale(things, secondary, target)? - previous)
+    }
+
+    pub fn creep_speed(gap: f64) -> f64 {
+        Self::IDLE_SCALE_SPEED * (gap / MIN_DECADE_GAP).clamp(0., 1.)
+    }
+
+    pub fn scaling_speed_cap(gap: f64) -> Option<f64> {
+        (gap < MIN_DECADE_GAP).then(|| (gap / Self::MIN_SCALING_TIME).max(0.))
+    }
+
+    pub fn camera_position(direction: Direction, shift: f64, half_size: Vec2) -> Vec2 {
+        Self::camera_at(direction, Thing::BAR_OFFSET * (shift - 1.), half_size)
+    }
+
+    fn camera_at(direction: Direction, offset: f64, half_size: Vec2) -> Vec2 {
+        Self::INITIAL_CAMERA_POSITION + Vec2::new(direction.sign() * (half_size.x + offset), 0.)
+    }
+
+    pub fn slot_offsets(
+        things: &[Thing],
+        secondary: Option<&[Thing]>,
+        spacing: Spacing,
+    ) -> Vec<f64> {
+        let count = slot_count(things, secondary);
+        match spacing {
+            Spacing::Even => (0..count)
+                .map(|index| Thing::BAR_OFFSET * index as f64)
+                .collect(),
+            Spacing::Magnitude => {
+                let first = (0..count)
+                    .find_map(|index| slot_scale(things, secondary, index))
+                    .unwrap_or(0.);
+                let mut offset = 0.;
+                (0..count)
+                    .map(|index| {
+                        if let Some(scale) = slot_scale(things, secondary, index) {
+                            offset = Self::DECADE_SPACING * (scale - first);
+                        }
+                        offset
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    pub fn set_spacing(&mut self, things: &[Thing], secondary: Option<&[Thing]>, spacing: Spacing) {
+        self.spacing = spacing;
+        self.slot_offsets = Self::slot_offsets(things, secondary, spacing);
+        self.update_camera();
+    }
+
+    pub fn slot_offset(&self, index: usize) -> f64 {
+        let known = index.min(self.slot_offsets.len().saturating_sub(1));
+        let offset = self.slot_offsets.get(known).copied().unwrap_or(0.);
+        offset + Thing::BAR_OFFSET * (index - known) as f64
+    }
+
+    pub fn shift_offset(&self, shift: f64) -> f64 {
+        let position = shift - 1.;
+        if position < 0. {
+            return self.slot_offset(0) + Thing::BAR_OFFSET * position;
+        }
+        let index = position.floor() as usize;
+        let (from, to) = (self.slot_offset(index), self.slot_offset(index + 1));
+        from + (to - from) * position.fract()
+    }
+
+    pub fn shift_camera_position(&self, shift: f64, half_size: Vec2) -> Vec2 {
+        Self::camera_at(self.direction, self.shift_offset(shift), half_size)
+    }
+
+    pub fn stagger_rows(offsets: &[f64], min_gap: f64) -> Vec<usize> {
+        let mut rows: Vec<usize> = Vec::with_capacity(offsets.len());
+        for (index, offset) in offsets.iter().enumerate() {
+            let row = match index.checked_sub(1) {
+                Some(prev) if (offset - offsets[prev]).abs() < min_gap => {
+                    (rows[prev] + 1) % Self::STAGGER_ROWS
+                }
+                _ => 0,
+            };
+            rows.push(row);
+        }
+        rows
+    }
+
+    pub fn stagger(&self, bars: &mut [Bar]) {
+        if self.spacing == Spacing::Even {
+            return;
+        }
+        let rows = Self::stagger_rows(&self.slot_offsets, Thing::BAR_OFFSET);
+        let rows_per_slot = bars.iter().map(|bar| bar.row + 1).max().unwrap_or(1);
+        for bar in bars {
+            bar.row += rows_per_slot * rows.get(bar.index).copied().unwrap_or(0);
+        }
+    }
+
+    pub fn visible_slots(&self, count: usize, max_bars: usize) -> Range<usize> {
+        if count <= max_bars {
+            return 0..count;
+        }
+        let center = self.shift.round().max(0.) as usize;
+        let start = center.saturating_sub(max_bars / 2).min(count - max_bars);
+        start..start + max_bars
+    }
+
+    pub fn ruler_ticks(&self, things: &[Thing], secondary: Option<&[Thing]>) -> Vec<(f64, i32)> {
+        let scales = (0..slot_count(things, secondary))
+            .filter_map(|index| slot_scale(things, secondary, index))
+            .collect::<Vec<_>>();
+        let (Some(&first), Some(min), Some(max)) = (
+            scales.first(),
+            scales.iter().copied().reduce(f64::min),
+            scales.iter().copied().reduce(f64::max),
+        ) else {
+            return Vec::new();
+        };
+        (min.ceil() as i32..=max.floor() as i32)
+            .map(|exponent| (Self::DECADE_SPACING * (exponent as f64 - first), exponent))
+            .collect()
+    }
+
+    pub fn focused_index(&self) -> Option<usize> {
+        (self.shift.floor() as usize).checked_sub(1)
+    }
+
+    pub fn move_focus(
+        &mut self,
+        things: &[Thing],
+        secondary: Option<&[Thing]>,
+        step: isize,
+    ) -> Option<usize> {
+        let len = things.len() as isize;
+        let next = match (self.keyboard_focus, len) {
+            (_, 0) => None,
+            (None, _) if step < 0 => Some(len - 1),
+            (None, _) => Some(0),
+            (Some(index), _) => Some((index as isize + step).rem_euclid(len)),
+        }
+        .map(|index| index as usize);
+        if let Some(index) = next {
+            self.center_on(index, things, secondary);
+        }
+        self.keyboard_focus = next;
+        next
+    }
+
+    pub fn focus_announcement(&self, things: &[Thing]) -> Option<String> {
+        let index = self.keyboard_focus?;
+        let thing = things.get(index)?;
+        Some(format!(
+            "Bar {} of {}: {}, {}",
+            index + 1,
+            things.len(),
+            thing.name,
+            thing.value
+        ))
+    }
+
+    pub fn panel_window(len: usize, focused: Option<usize>) -> Range<usize> {
+        let rows = Self::PANEL_ROWS.min(len);
+        let start = focused
+            .unwrap_or(0)
+            .saturating_sub(rows / 2)
+            .min(len - rows);
+        start..start + rows
+    }
+
+    fn things_panel(
+        &self,
+        things: &[Thing],
+        settings: Settings,
+        text_size: f32,
+    ) -> impl WidgetView<Edit<State>> + use<> {
+        let palette = settings.palette;
+        let toggle = button(
+            label(if settings.things_panel { "›" } else { "‹" }).text_size(text_size),
+            |state: &mut State| {
+                state.settings.things_panel = !state.settings.things_panel;
+                let _ = state.settings.save();
+            },
+        )
+        .background_color(palette.button);
+        if !settings.things_panel {
+            return Either::A(
+                sized_box(toggle)
+                    .padding(4.)
+                    .corner_radius(6.)
+                    .background_color(palette.background),
+            );
+        }
+        let focused = self.focused_index();
+        let rows = Self::panel_window(things.len(), focused)
+            .map(|index| {
+                let thing = &things[index];
+                let visited = self.visited.contains(&thing.id);
+                let color = match (focused == Some(index), visited) {
+                    (true, _) => palette.accent,
+                    (false, true) => palette.muted_text,
+                    (false, false) => palette.text,
+                };
+                let mark = if visited { "✓" } else { "  " };
+                button(
+                    label(format!("{mark} {}  {}", thing.name, thing.value))
+                        .text_size(text_size)
+                        .color(color),
+                    move |state: &mut State| {
+                        state
+                            .viewport
+                            .center_on(index, &state.things, state.secondary.as_deref());
+                    },
+                )
+                .background_color(match focused == Some(index) {
+                    true => palette.button,
+                    false => palette.background,
+                })
+            })
+            .collect::<Vec<_>>();
+        let here = focused.unwrap_or(0);
+        let range_button = |text: &str, range: PlayRange| {
+            button(
+                label(text).text_size(text_size),
+                move |state: &mut State| {
+                    state.viewport.set_play_range(range, &state.things);
+                    state.mark_edited();
+                },
+            )
+            .background_color(palette.button)
+        };
+        let range = self.play_range;
+        Either::B(
+            sized_box(flex_col((
+                flex_row((
+                    toggle,
+                    label(format!("{} things", things.len()))
+                        .text_size(text_size)
+                        .color(palette.muted_text),
+                )),
+                flex_row((
+                    label(format!("Play: {range}"))
+                        .text_size(text_size)
+                        .color(palette.muted_text),
+                    range_button(
+                        "From here",
+                        PlayRange {
+                            start: here,
+                            ..range
+                        },
+                    ),
+                    range_button(
+                        "To here",
+                        PlayRange {
+                            end: Some(here),
+                            ..range
+                        },
+                    ),
+                    range_button("All", PlayRange::default()),
+                )),
+                portal(flex_col(rows).cross_axis_alignment(CrossAxisAlignment::Start)),
+            )))
+            .width((Self::PANEL_WIDTH * settings.ui_scale).px())
+            .padding(10.)
+            .corner_radius(10.)
+            .background_color(palette.background),
+        )
+    }
+
+    pub fn focus_amount(&self) -> f32 {
+        match self.animation.step {
+            AnimStep::Idle(_) | AnimStep::Scaling => 0.,
+            AnimStep::Slowing(t) => match self.animation.pacing.slowing_time {
+                0. => 1.,
+                time => 1. - (t / time) as f32,
+            },
+            AnimStep::Pausing(_) | AnimStep::Finished => 1.,
+            AnimStep::Shifting(t) => (t / self.animation.pacing.shifting_time) as f32,
+        }
+    }
+
+    pub fn focus_alpha(&self, index: usize) -> f32 {
+        match self.focused_index() {
+            Some(focused) if focused != index => {
+                1. - (1. - Self::FOCUS_DIM_ALPHA) * self.focus_amount()
+            }
+            _ => 1.,
+        }
+    }
+
+    pub fn focus_text_scale(&self, index: usize) -> f64 {
+        match self.focused_index() {
+            Some(focused) if focused == index => {
+                1. + (Self::FOCUS_TEXT_SCALE - 1.) * self.focus_amount() as f64
+            }
+            _ => 1.,
+        }
+    }
+
+    pub fn labels_visible(step: &AnimStep, compact_labels: bool) -> bool {
+        !compact_labels
+            || matches!(
+                step,
+                AnimStep::Idle(_) | AnimStep::Pausing(_) | AnimStep::Finished
+            )
+    }
+
+    pub fn thing_position(&self, thing: &Thing, index: usize, half_size: Vec2) -> Vec2 {
+        thing.position_at(
+            self.slot_offset(index),
+            self.render_scale(),
+            half_size,
+            self.direction,
+        )
+    }
+
+    pub fn bar_position(&self, bar: &Bar, half_size: Vec2) -> Vec2 {
+        self.thing_position(bar.thing, bar.index, half_size) + Vec2::new(bar.offset, 0.)
+    }
+
+    pub fn world_camera(&self, size: Size) -> Affine {
+        CanvasSpaces::with_world_trans(
+            self.orientation.world_trans(self.layout_half_size(size)),
+            self.camera.inverse(),
+        )
+        .world_camera
+    }
+
+    pub fn screen_to_world(&self, point: Point, size: Size) -> Point {
+        self.world_camera(size).inverse() * point
+    }
+
+    // Snapshots and screen queries read the staggered, culled bars the
+    // painter draws on the main pane.
+    pub fn geometry_snapshot(
+        &self,
+        (things, secondary): (&[Thing], Option<&[Thing]>),
+        settings: &Settings,
+        size: Size,
+    ) -> Vec<BarGeometry> {
+        let half_size = self.layout_half_size(size);
+        let world_camera = self.world_camera(size);
+        let render_scale = self.render_scale();
+        self.frame_bars((things, secondary), settings, Side::Primary)
+            .iter()
+            .map(|bar| {
+                let position = self.bar_position(bar, half_size);
+                let bounds = world_camera.transform_rect_bbox(self.bar_bounds(bar, half_size));
+                let value = bar.thing.value.inner();
+                BarGeometry {
+                    index: bar.index,
+                    side: bar.side,
+                    name: bar.thing.name.clone(),
+                    value,
+                    x: (world_camera * Point::new(position.x, 0.)).x,
+                    left: bounds.x0,
+                    right: bounds.x1,
+                    top: bounds.y0,
+                    bottom: bounds.y1,
+                    alpha: Thing::alpha(bar.index, self.shift) * self.focus_alpha(bar.index),
+                    clamped: value.to_scale(render_scale, Self::MAX_HEIGHT) >= Self::MAX_HEIGHT,
+                }
+            })
+            .collect()
+    }
+
+    pub fn bar_screen_positions(
+        &self,
+        (things, secondary): (&[Thing], Option<&[Thing]>),
+        settings: &Settings,
+        size: Size,
+    ) -> Vec<(usize, Point)> {
+        let half_size = self.layout_half_size(size);
+        let world_camera = self.world_camera(size);
+        self.frame_bars((things, secondary), settings, Side::Primary)
+            .iter()
+            .map(|bar| {
+                let position = self.bar_position(bar, half_size);
+                let top = self.bar_bounds(bar, half_size).y1;
+                (bar.index, world_camera * Point::new(position.x, top))
+            })
+            .collect()
+    }
+
+    pub fn bar_screen_bounds(
+        &self,
+        (things, secondary): (&[Thing], Option<&[Thing]>),
+        settings: &Settings,
+        size: Size,
+    ) -> Vec<(usize, Rect)> {
+        let half_size = self.layout_half_size(size);
+        let world_camera = self.world_camera(size);
+        self.frame_bars((things, secondary), settings, Side::Primary)
+            .iter()
+            .filter(|bar| Thing::alpha(bar.index, self.shift) > 0.)
+            .map(|bar| {
+                let bounds = self.bar_bounds(bar, half_size);
+                (bar.index, world_camera.transform_rect_bbox(bounds))
+            })
+            .collect()
+    }
+
+    pub fn visible_bounds(
+        &self,
+        (things, secondary): (&[Thing], Option<&[Thing]>),
+        settings: &Settings,
+        size: Size,
+    ) -> Rect {
+        let window = size.to_rect();
+        let visible = self
+            .bar_screen_bounds((things, secondary), settings, size)
+            .into_iter()
+            .map(|(_, bounds)| bounds.intersect(window))
+            .filter(|bounds| bounds.width() > 0. && bounds.height() > 0.)
+            .reduce(|a, b| a.union(b));
+        match visible {
+            Some(bounds) => bounds.inflate(Self::EXPORT_MARGIN, Self::EXPORT_MARGIN),
+            None => {
+                let axis_y = (self.world_camera(size) * Point::ORIGIN).y;
+                Rect::new(0., axis_y, size.width, axis_y).inflate(0., Self::EXPORT_MARGIN)
+            }
+        }
+        .intersect(window)
+    }
+
+    pub fn hit_test(&self, bars: &[Bar], point: Point, size: Size) -> Option<usize> {
+        let half_size = self.layout_half_size(size);
+        let world = self.screen_to_world(point, size);
+        bars.iter()
+            .position(|bar| self.bar_bounds(bar, half_size).contains(world))
+    }
+
+    pub fn open_context_menu(&mut self, bars: &[Bar], point: Point, size: Size) -> Option<usize> {
+        let index = self.hit_test(bars, point, size)?;
+        self.context_menu = Some(ContextMenu {
+            index,
+            id: bars[index].thing.id,
+            origin: point,
+        });
+        Some(index)
+    }
+
+    pub fn menu_click(&mut self, point: Point, ui_scale: f64) -> Option<(u64, MenuItem)> {
+        let menu = self.context_menu.take()?;
+        Some((menu.id, menu.item_at(point, ui_scale)?))
+    }
+
+    pub fn bar_bounds(&self, bar: &Bar, half_size: Vec2) -> Rect {
+        let position = self.bar_position(bar, half_size);
+        let bounds = bar.thing.marker.bounds(position, bar.width);
+        self.baseline
+            .transform(position.y)
+            .transform_rect_bbox(bounds)
+    }
+
+    pub fn tooltip_visible(&self) -> bool {
+        !matches!(self.animation.step, AnimStep::Scaling)
+    }
+
+    pub fn headroom(decades: u32) -> f64 {
+        decades as f64 - Self::HEADROOM_MARGIN
+    }
+
+    pub fn render_scale(&self) -> f64 {
+        self.scale + self.decades as f64 - Self::MAX_HEIGHT.log10()
+    }
+
+    pub fn set_decades(&mut self, decades: u32) {
+        let decades = decades.clamp(Self::MIN_DECADES, Self::MAX_DECADES);
+        self.scale += Self::headroom(self.decades) - Self::headroom(decades);
+        self.decades = decades;
+        self.repair();
+    }
+
+    pub fn major_exponents(scale: f64, decades: u32) -> impl Iterator<Item = f64> {
+        Self::major_exponents_in(scale, decades as f64)
+    }
+
+    pub fn major_step(span: f64) -> f64 {
+        match span {
+            Self::HALF_DECADE_SPAN.. => 1.,
+            Self::FIFTH_DECADE_SPAN.. => 0.5,
+            _ => 0.2,
+        }
+    }
+
+    pub fn major_exponents_in(scale: f64, span: f64) -> impl Iterator<Item = f64> {
+        let step = Self::major_step(span);
+        let first = (scale / step).floor();
+        (-1..=(span / step).ceil() as i32).map(move |offset| {
+            let exponent = (first + offset as f64) * step;
+            (exponent * 10.).round() / 10.
+        })
+    }
+
+    pub fn line_alpha(exponent: f64, scale: f64) -> f32 {
+        10_f64.powf(exponent - scale).clamp(0., 1.) as f32
+    }
+
+    pub fn major_label_alpha(line_alpha: f32, position: f64, ui_scale: f64, opaque: bool) -> f32 {
+        match opaque {
+            true => 1.,
+            false => line_alpha * Self::label_alpha(position, ui_scale),
+        }
+    }
+
+    pub fn label_alpha(position: f64, ui_scale: f64) -> f32 {
+        (position / (Self::LABEL_MIN_HEIGHT * ui_scale)).clamp(0., 1.) as f32
+    }
+
+    pub fn major_label(exponent: f64, labels: AxisLabels) -> String {
+        let floor = exponent.floor();
+        let significand = (10_f64.powf(exponent - floor) * 100.).round() / 100.;
+        let number = ENumber::new(significand, floor as i32);
+        match labels {
+            AxisLabels::Time => TimeScale::from(number).fmt_secs(),
+            AxisLabels::Powers => number.to_string(),
+        }
+    }
+
+    pub fn round_units() -> Vec<(ENumber, TimeUnit)> {
+        let seconds = (-30..=1).map(|exponent| (ENumber::new(1., exponent), TimeUnit::Second));
+        let named = [
+            (MINUTE, TimeUnit::Minute),
+            (10. * MINUTE, TimeUnit::Minute),
+            (HOUR, TimeUnit::Hour),
+            (DAY, TimeUnit::Day),
+            (7. * DAY, TimeUnit::Day),
+        ]
+        .map(|(secs, unit)| (ENumber::from(secs), unit));
+        let years = (0..=30).map(|exponent| (ENumber::new(1., exponent) * YEAR, TimeUnit::Year));
+        seconds.chain(named).chain(years).collect()
+    }
+
+    pub fn scale_bar(scale: f64, budget: f64) -> Option<(String, f64)> {
+        Self::round_units()
+            .into_iter()
+            .map(|(value, unit)| {
+                let length = value.to_scale(scale, f64::INFINITY);
+                (TimeScale::from(value).fmt_in(unit), length)
+            })
+            .take_while(|(_, length)| *length <= budget)
+            .last()
+            .filter(|(_, length)| *length >= Self::SCALE_BAR_MIN)
+    }
+
+    // Factors between 1 and 10 spaced evenly in value, so the default count
+    // lands on 2, 3, ..., 9.
+    pub fn minor_factors(count: usize) -> Vec<f64> {
+        let step = 9. / (count as f64 + 1.);
+        (1..=count).map(|i| 1. + step * i as f64).collect()
+    }
+
+    pub fn minor_offsets(count: usize) -> Vec<f64> {
+        Self::minor_factors(count)
+            .into_iter()
+            .map(f64::log10)
+            .collect()
+    }
+
+    pub fn minor_gridlines(
+        major: f64,
+        minor_lines: bool,
+        labels: MinorLabels,
+    ) -> Vec<(f64, Option<String>)> {
+        let count = if minor_lines { Self::MINOR_LINES } else { 0 };
+        Self::minor_gridlines_with(major, count, labels)
+    }
+
+    pub fn minor_gridlines_with(
+        major: f64,
+        count: usize,
+        labels: MinorLabels,
+    ) -> Vec<(f64, Option<String>)> {
+        Self::minor_factors(count)
+            .into_iter()
+            .map(|factor| {
+                let label = match labels {
+                    _ if !Self::LABELED_MINORS.contains(&factor) => None,
+                    MinorLabels::Off => None,
+                    MinorLabels::Digits => Some(factor.to_string()),
+                    MinorLabels::Values => {
+                        Some(TimeScale::from(ENumber::new(factor, major as i32)).fmt_secs())
+                    }
+                };
+                (major + factor.log10(), label)
+            })
+            .collect()
+    }
+
+    pub fn minor_lines(&self) -> Vec<(f64, f64)> {
+        let span = self.decades as f64;
+        if Self::major_step(span) != 1. {
+            return Vec::new();
+        }
+        let render_scale = self.render_scale();
+        Self::major_exponents_in(self.scale, span)
+            .flat_map(|major| {
+                Self::minor_offsets(Self::MINOR_LINES)
+                    .into_iter()
+                    .map(move |offset| major + offset)
+            })
+            .filter_map(|minor| {
+                let position = ENumber::from_exp(minor).to_scale(render_scale, Self::MAX_HEIGHT);
+                let line = self.baseline.gridline(position)?;
+                (line > 0.).then_some((minor, line))
+            })
+            .collect()
+    }
+
+    pub fn minor_line_at(&self, point: Point, size: Size, ui_scale: f64) -> Option<(f64, f64)> {
+        let world = self.screen_to_world(point, size);
+        self.minor_lines()
+            .into_iter()
+            .map(|(minor, line)| (minor, line, (line - world.y).abs()))
+            .filter(|&(.., distance)| distance <= Self::MINOR_HOVER_DISTANCE * ui_scale)
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(minor, line, _)| (minor, line))
+    }
+
+    pub fn ghost_shift(&self) -> Option<f64> {
+        let shifting = matches!(self.animation.step, AnimStep::Shifting(_));
+        (self.motion_trail && self.animation.active && shifting && self.last_shift != self.shift)
+            .then_some(self.last_shift)
+    }
+
+    pub fn pin_alpha(progress: f64) -> f32 {
+        let fade = (progress.min(1. - progress) / Self::PIN_FADE).clamp(0., 1.);
+        Self::PIN_ALPHA * fade as f32
+    }
+
+    // Pushes labels up until neighbours are at least `min_gap` apart, keeping
+    // the input order.
+    pub fn spread_labels(heights: &[f64], min_gap: f64) -> Vec<f64> {
+        let mut order = (0..heights.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| heights[a].total_cmp(&heights[b]));
+        let mut positions = heights.to_vec();
+        let mut previous = f64::NEG_INFINITY;
+        for index in order {
+            positions[index] = heights[index].max(previous + min_gap);
+            previous = positions[index];
+        }
+        positions
+    }
+
+    // The bar, the line its top reaches and where its margin label goes.
+    pub fn margin_values<'a>(
+        &self,
+        bars: &'a [Bar<'a>],
+        half_size: Vec2,
+        min_gap: f64,
+    ) -> Vec<(&'a Bar<'a>, f64, f64)> {
+        if !matches!(self.animation.step, AnimStep::Pausing(_)) {
+            return Vec::new();
+        }
+        let lines = bars
+            .iter()
+            .filter_map(|bar| {
+                let line = self
+                    .baseline
+                    .gridline(self.bar_position(bar, half_size).y)?;
+                Some((bar, line))
+            })
+            .collect::<Vec<_>>();
+        let heights = lines.iter().map(|&(_, line)| line).collect::<Vec<_>>();
+        lines
+            .into_iter()
+            .zip(Self::spread_labels(&heights, min_gap))
+            .map(|((bar, line), label)| (bar, line, label))
+            .collect()
+    }
+
+    pub fn would_be_height(&self, thing: &Thing) -> ENumber {
+        thing.value.inner() / ENumber::from_exp(self.render_scale())
+    }
+
+    // the unclamped height of an already visited thing that fell below the threshold
+    pub fn tiny_height(&self, thing: &Thing, threshold: f64) -> Option<ENumber> {
+        let height = self.would_be_height(thing);
+        let tiny = self.visited.contains(&thing.id)
+            && height.significand() > 0.
+            && height.collapse().is_some_and(|height| height < threshold);
+        tiny.then_some(height)
+    }
+
+    pub fn tiny_label(height: ENumber) -> String {
+        let significand = float_to_string_with(height.significand(), 1);
+        let size = superscript_exponents(&format!("{significand}e{}", height.exponent()));
+        let size = size.strip_prefix("1×").unwrap_or(&size);
+        format!("too small to show at this scale (would be {size} px)")
+    }
+
+    pub fn pinned_previous(&self, things: &[Thing]) -> Option<PinnedReference> {
+        if !self.pin_previous || self.animation.step != AnimStep::Scaling {
+            return None;
+        }
+        let focus = self.focused_index()?;
+        let index = focus.checked_sub(1)?;
+        let (previous, target) = (things.get(index)?, things.get(focus)?);
+        let start = previous.scale() - Self::headroom(self.decades);
+        let span = target.scale() - previous.scale();
+        let progress = if span > 0. {
+            ((self.scale - start) / span).clamp(0., 1.)
+        } else {
+            1.
+        };
+        let value = previous.value.inner();
+        let height = self.would_be_height(previous);
+        Some(PinnedReference {
+            index,
+            alpha: Self::pin_alpha(progress),
+            label: format!("← previous: {} ({value} s)", previous.name),
+            height_label: format!(
+                "now {:.2}e{} px tall",
+                height.significand(),
+                height.exponent()
+            ),
+        })
+    }
+
+    pub fn clamp_camera_y(y: f64, half_size: Vec2, footer_height: f64) -> f64 {
+        y.min(half_size.y - footer_height).max(0.)
+    }
+
+    pub fn ease_half_size(current: Vec2, target: Vec2) -> Vec2 {
+        let delta = target - current;
+        if delta.hypot() <= Self::RESIZE_SNAP_DISTANCE {
+            target
+        } else {
+            current + delta / Self::RESIZE_FRAMES
+        }
+    }
+
+    pub(crate) fn layout_half_size(&self, size: Size) -> Vec2 {
+        self.half_size
+            .unwrap_or(self.orientation.transpose(size).to_vec2() / 2.)
+    }
+
+    fn resize(&mut self, size: Size) -> Vec2 {
+        let target = size.to_vec2() / 2.;
+        if !target.is_finite() {
+            return self.layout_half_size(Size::ZERO);
+        }
+        let half_size = match self.half_size {
+            Some(current) if self.animation.active => {
+                Self::ease_half_size(current, self.resize_debounce.update(target))
+            }
+            _ => {
+                self.resize_debounce.settle(target);
+                target
+            }
+        };
+        self.half_size = Some(half_size);
+        self.update_camera();
+        half_size
+    }
+
+    pub(crate) fn update_camera(&mut self) {
+        let half_size = self.half_size.unwrap_or_default();
+        let mut position = self.shift_camera_position(self.shift, half_size);
+        if let Some(half_size) = self.half_size {
+            position.y = Self::clamp_camera_y(position.y, half_size, self.footer_height);
+        }
+        self.camera = self.camera.with_translation(position);
+    }
+
+    fn canvas_view(pane: Side) -> impl WidgetView<Edit<State>> + use<> {
+        canvas(
+            move |State {
+                      things,
+                      secondary,
+                      viewport,
+                      split,
+                      settings,
+                      project,
+                      name,
+                      author,
+                      axis_title,
+                      chart_title,
+                      ..
+                  }: &mut State,
+                  ctx,
+                  scene,
+                  size| {
+                let (fcx, lcx) = ctx.text_contexts();
+                let (viewport, things, secondary) = match (pane, split.as_mut()) {
+                    (Side::Secondary, Some(split)) => {
+                        (split, secondary.as_deref().unwrap_or_default(), None)
+                    }
+                    (_, Some(_)) => (viewport, &things[..], None),
+                    (_, None) => (viewport, &things[..], secondary.as_deref()),
+                };
+                viewport.fit(size);
+                let titles = Titles {
+                    project,
+                    name,
+                    author,
+                    axis_title,
+                    chart_title: *chart_title,
+                };
+                let frame = viewport.frame((things, secondary), titles, settings, size, pane);
+                frame.paint(&mut Painter::new(scene, fcx, lcx));
+            },
+        )
+    }
+
+    // Which pane of the chart `point` falls in, and where it lands on that
+    // pane's canvas. Split panes share the chart's width less `SPLIT_GAP`.
+    pub fn pane_at(point: Point, size: Size, split: bool) -> (Side, Point, Size) {
+        if !split {
+            return (Side::Primary, point, size);
+        }
+        let pane = Size::new((size.width - Self::SPLIT_GAP).max(0.) / 2., size.height);
+        let right = pane.width + Self::SPLIT_GAP;
+        match point.x < right {
+            true => (Side::Primary, point, pane),
+            false => (Side::Secondary, point - Vec2::new(right, 0.), pane),
+        }
+    }
+
+    pub fn follow(&mut self, leader: &Viewport) {
+        self.animation.active = leader.animation.active;
+        self.animation.step = leader.animation.step;
+        self.direction = leader.direction;
+        self.decades = leader.decades;
+        self.scale = leader.scale;
+        self.scale_speed = leader.scale_speed;
+        self.slow_scale_speed = leader.slow_scale_speed;
+        self.prev_shift = leader.prev_shift;
+        self.last_shift = leader.last_shift;
+        self.shift = leader.shift;
+        self.update_camera();
+    }
+
+    pub fn view(
+        &mut self,
+        things: &[Thing],
+        comparison: Option<&str>,
+        split: bool,
+        settings: Settings,
+    ) -> impl WidgetView<Edit<State>> + use<> {
+        let canvas = if split {
+            Either::A(
+                flex_row((
+                    Self::canvas_view(Side::Primary).flex(1.),
+                    Self::canvas_view(Side::Secondary).flex(1.),
+                ))
+                .gap(Self::SPLIT_GAP.px())
+                .background_color(Self::MINOR_LINE_COLOR),
+            )
+        } else {
+            Either::B(Self::canvas_view(Side::Primary))
+        };
+
+        let text_size = Self::OVERLAY_TEXT_SIZE * settings.ui_scale as f32;
+        let playback_btn = lens(
+            move |animation: &mut Animation| animation.playback_button(text_size),
+            |state: &mut State, ()| &mut state.viewport.animation,
+        );
+        let edit_btn = button(label("Edit").text_size(text_size), |state: &mut State| {
+            state.viewport.animation.active = false;
+            state.set_tab(Tab::Data);
+        });
+        let direction_btn = button(
+            label(self.direction.to_string()).text_size(text_size),
+            |state: &mut State| {
+                state.viewport.direction = state.viewport.direction.flipped();
+                state.viewport.update_camera();
+            },
+        );
+        let compact_checkbox = checkbox(
+            "Compact while moving",
+            self.compact_labels,
+            |state: &mut State, checked| {
+                state.viewport.compact_labels = checked;
+            },
+        );
+        let examine_checkbox = checkbox("Examine", self.examine, |state: &mut State, checked| {
+            state.viewport.examine = checked;
+        });
+        let constant_checkbox = checkbox(
+            "Constant speed",
+            self.constant_speed,
+            |state: &mut State, checked| {
+                state.viewport.constant_speed = checked;
+            },
+        );
+        let minor_checkbox = checkbox(
+            "Minor gridlines",
+            settings.minor_lines,
+            |state: &mut State, checked| {
+                state.settings.minor_lines = checked;
+                let _ = state.settings.save();
+            },
+        );
+        let minor_labels_btn = button(
+            label(format!("Minor labels: {}", settings.minor_labels)).text_size(text_size),
+            |state: &mut State| {
+                state.settings.minor_labels = state.settings.minor_labels.next();
+                let _ = state.settings.save();
+            },
+        );
+        let trail_checkbox = checkbox(
+            "Motion trail",
+            self.motion_trail,
+            |state: &mut State, checked| {
+                state.viewport.motion_trail = checked;
+            },
+        );
+        let pin_checkbox = checkbox(
+            "Pin previous",
+            self.pin_previous,
+            |state: &mut State, checked| {
+                state.viewport.pin_previous = checked;
+            },
+        );
+        let easing_btns = lens(
+            move |animation: &mut Animation| animation.easing_buttons(text_size),
+            |state: &mut State, ()| &mut state.viewport.animation,
+        );
+        let advance_btns = lens(
+            move |animation: &mut Animation| animation.advance_buttons(text_size),
+            |state: &mut State, ()| &mut state.viewport.animation,
+        );
+        let ui_scale_btns = flex_row((
+            button(label("A−").text_size(text_size), |state: &mut State| {
+                state.settings = state.settings.scaled_down();
+                let _ = state.settings.save();
+            }),
+            label(format!("{:.0}%", settings.ui_scale * 100.)).text_size(text_size),
+            button(label("A+").text_size(text_size), |state: &mut State| {
+                state.settings = state.settings.scaled_up();
+                let _ = state.settings.save();
+            }),
+        ));
+        let settings_btn = button(
+            label("Settings").text_size(text_size),
+            |state: &mut State| {
+                state.viewport.animation.active = false;
+                state.set_tab(Tab::Settings);
+            },
+        );
+        let about_btn = button(label("About").text_size(text_size), |state: &mut State| {
+            state.show_about = true;
+        });
+        let focus_btns = flex_row((
+            button(
+                label("◀ Bar").text_size(text_size),
+                |state: &mut State| {
+                    state
+                        .viewport
+                        .move_focus(&state.things, state.secondary.as_deref(), -1);
+                },
+            ),
+            button(
+                label("Bar ▶").text_size(text_size),
+                |state: &mut State| {
+                    state
+                        .viewport
+                        .move_focus(&state.things, state.secondary.as_deref(), 1);
+                },
+            ),
+        ));
+        let announcement = self.focus_announcement(things).map(|text| {
+            label(text)
+                .text_size(text_size)
+                .color(Self::FOCUS_RING_COLOR)
+        });
+        let controls = flex_row((
+            playback_btn,
+            edit_btn,
+            direction_btn,
+            examine_checkbox,
+            constant_checkbox,
+            compact_checkbox,
+            trail_checkbox,
+            pin_checkbox,
+            minor_checkbox,
+            minor_labels_btn,
+            flex_row((easing_btns, advance_btns)),
+            ui_scale_btns,
+            settings_btn,
+            about_btn,
+            focus_btns,
+        ));
+        let debug = label(format!("{:?}", self.animation.step)).text_size(text_size);
+
+        let summary = self.animation.finished().then(|| {
+            let summary = summarize(things);
+            sized_box(flex_col((
+                label(summary.clone()).text_size(text_size),
+                button(
+                    label("Copy summary").text_size(text_size),
+                    move |_: &mut State| {
+                        let _ = copy_to_clipboard(&summary);
+                    },
+                ),
+            )))
+            .width((500. * settings.ui_scale).px())
+            .padding(10.)
+            .corner_radius(10.)
+            .background_color(Self::FOOTER_AREA_COLOR)
+        });
+
+        let legend = comparison.map(|comparison| {
+            flex_row((
+                label("■ Data")
+                    .text_size(text_size)
+                    .color(Side::Primary.bar_color()),
+                label(format!("■ {comparison}"))
+                    .text_size(text_size)
+                    .color(Side::Secondary.bar_color()),
+            ))
+        });
+
+        let notices = lens(Notices::view, |state: &mut State, ()| &mut state.notices);
+        let panel = self.things_panel(things, settings, text_size);
+
+        let overlay = sized_box(
+            flex_row((
+                flex_col((notices, summary, legend, announcement, debug, controls))
+                    .main_axis_alignment(MainAxisAlignment::End)
+                    .flex(1.),
+                panel,
+            ))
+            .cross_axis_alignment(CrossAxisAlignment::Start),
+        )
+        .expand()
+        .padding(15.);
+
+        let frame_interval = Animation::frame_interval(settings.frame_rate);
         let animation = self.animation.active.then_some(task(
-            |proxy, _| async move {
-                let mut interval = time::interval(Duration::from_millis(Animation::FRAME_DURATION));
+            move |proxy, _| async move {
+                let mut interval = time::interval(frame_interval);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                let mut last = Instant::now();
                 loop {
                     interval.tick().await;
-                    let Ok(()) = proxy.message(()) else {
+                    let now = Instant::now();
+                    let Ok(()) = proxy.message(now - last) else {
                         break;
                     };
+                    last = now;
                 }
             },
-            |state: &mut State, _| {
-                state.viewport.update_animation(&state.things);
+            |state: &mut State, dt: Duration| {
+                state.update_live();
+                state.update_animation(dt.as_secs_f64());
             },
         ));
 
-        fork(zstack((canvas, overlay)), animation)
+        fork(pointer_input(zstack((canvas, overlay))), animation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{AnimEvent, Easing};
+    use crate::comparison::bars;
+    use crate::thing::MarkerStyle;
+    use crate::utils::LineParams;
+
+    #[test]
+    fn test_labels_hidden_while_moving() {
+        let shifting = AnimStep::Shifting(AnimStep::SHIFTING_TIME);
+        assert!(Viewport::labels_visible(&shifting, false));
+        assert!(!Viewport::labels_visible(&shifting, true));
+        assert!(!Viewport::labels_visible(&AnimStep::Scaling, true));
+        assert!(Viewport::labels_visible(&AnimStep::Idle(0.), true));
+        assert!(Viewport::labels_visible(&AnimStep::Pausing(0.), true));
+    }
+
+    fn landed_on(things: &[Thing], shift: f64) -> Viewport {
+        let mut viewport = Viewport::init(things);
+        viewport.prev_shift = shift;
+        viewport.last_shift = shift;
+        viewport.shift = shift;
+        viewport.scale = 4.;
+        viewport.animation.step = AnimStep::Pausing(0.16);
+        viewport
+    }
+
+    #[test]
+    fn test_sync_keeps_position_on_edits() {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = landed_on(&things, 2.);
+        things[1].name = "A week".to_string();
+        things[0].value = 0.4.into();
+        viewport.sync(&things);
+        assert_eq!(viewport.shift, 2.);
+        assert_eq!(viewport.prev_shift, 2.);
+        assert_eq!(viewport.scale, 4.);
+        assert_eq!(viewport.animation.step, AnimStep::Pausing(0.16));
+    }
+
+    #[test]
+    fn test_sync_follows_focus_when_count_changes() {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = landed_on(&things, 2.);
+        things.insert(0, Thing::new("Planck time", (5.39, -44)));
+        viewport.sync(&things);
+        assert_eq!(viewport.prev_shift, 3.);
+        assert_eq!(viewport.shift, 3.);
+        assert_eq!(viewport.ids[2], things[2].id);
+
+        things.remove(0);
+        things.remove(0);
+        viewport.sync(&things);
+        assert_eq!(viewport.prev_shift, 1.);
+        assert_eq!(things[0].name, "Week");
+
+        things.push(Thing::new("Decade", 315569520.));
+        viewport.sync(&things);
+        assert_eq!(viewport.prev_shift, 1.);
+        assert_eq!(viewport.ids.len(), 3);
+    }
+
+    #[test]
+    fn test_sync_resets_when_focus_is_gone() {
+        let mut things = vec![Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        let mut viewport = landed_on(&things, 2.);
+        viewport.direction = Direction::Leftward;
+        things.pop();
+        viewport.sync(&things);
+        assert_eq!(viewport.shift, 0.);
+        assert_eq!(viewport.prev_shift, 0.);
+        assert_eq!(
+            viewport.scale,
+            things[0].scale() - Viewport::headroom(Viewport::DEFAULT_DECADES)
+        );
+        assert_eq!(viewport.direction, Direction::Leftward);
+
+        let mut viewport = Viewport::init(&things);
+        viewport.scale = 4.;
+        things.insert(0, Thing::new("Planck time", (5.39, -44)));
+        viewport.sync(&things);
+        assert_eq!(
+            viewport.scale,
+            things[0].scale() - Viewport::headroom(Viewport::DEFAULT_DECADES)
+        );
+    }
+
+    #[test]
+    fn test_sync_retargets_when_the_focus_changes_value() {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = landed_on(&things, 2.);
+        things[1].value = 6048000.0.into();
+        viewport.sync(&things);
+        assert_eq!(viewport.prev_shift, 2.);
+        assert!((viewport.scale - 5.).abs() < 1e-9);
+
+        things.insert(0, Thing::new("Planck time", (5.39, -44)));
+        things[2].value = 604800.0.into();
+        viewport.sync(&things);
+        assert_eq!(viewport.prev_shift, 3.);
+        assert!((viewport.scale - 4.).abs() < 1e-9);
+
+        let mut viewport = Viewport::init(&things);
+        let start = viewport.scale;
+        things[0].value = (5.39, -43).into();
+        viewport.sync(&things);
+        assert!((viewport.scale - start - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sync_resumes_after_finish() {
+        let mut things = vec![Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        let mut viewport = landed_on(&things, 2.);
+        viewport.animation.step = AnimStep::Finished;
+        viewport.sync(&things);
+        assert!(viewport.animation.finished());
+        things.push(Thing::new("Year", 31556952.));
+        viewport.sync(&things);
+        assert_eq!(viewport.animation.step, AnimStep::default());
+        assert_eq!(viewport.prev_shift, 2.);
+    }
+
+    #[test]
+    fn test_hit_test() {
+        let things = [Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 1.;
+        viewport.update_camera();
+        let size = Size::new(800., 600.);
+        let half_size = size.to_vec2() / 2.;
+        let world_camera = Affine::FLIP_Y.then_translate(half_size) * viewport.camera.inverse();
+        let bars = bars(&things, None);
+        for (i, bar) in bars.iter().enumerate() {
+            let position = viewport.bar_position(bar, half_size);
+            let inside = world_camera * Point::new(position.x, position.y / 2.);
+            assert_eq!(viewport.screen_to_world(inside, size).x, position.x);
+            assert_eq!(viewport.hit_test(&bars, inside, size), Some(i));
+            let beside = world_camera * Point::new(position.x + bar.width, position.y / 2.);
+            assert_eq!(viewport.hit_test(&bars, beside, size), None);
+            let above = world_camera * Point::new(position.x, position.y + 1.);
+            assert_eq!(viewport.hit_test(&bars, above, size), None);
+        }
+    }
+
+    #[test]
+    fn test_hovered_y_maps_to_nearest_minor_line() {
+        let things = [Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        let viewport = Viewport::init(&things);
+        let size = Size::new(800., 600.);
+        let world_camera = viewport.world_camera(size);
+        let lines = viewport.minor_lines();
+        assert!(lines.len() >= Viewport::MINOR_LINES);
+        for &(minor, line) in &lines {
+            let screen = world_camera * Point::new(0., line);
+            if screen.y < 0. || screen.y > size.height {
+                continue;
+            }
+            let near = screen + Vec2::new(120., 2.);
+            let (exponent, _) = viewport.minor_line_at(near, size, 1.).unwrap();
+            assert_eq!(exponent, minor);
+            let magnitude = ENumber::from_exp(exponent);
+            let value = magnitude.collapse().unwrap().log10();
+            assert!((value - minor).abs() < 1e-9);
+            assert!(value > minor.floor() && value < minor.floor() + 1.);
+        }
+        let (first, second) = lines
+            .windows(2)
+            .map(|pair| (pair[0].1, pair[1].1))
+            .max_by(|a, b| (a.1 - a.0).total_cmp(&(b.1 - b.0)))
+            .unwrap();
+        let between = world_camera * Point::new(0., (first + second) / 2.);
+        assert_eq!(viewport.minor_line_at(between, size, 1.), None);
+    }
+
+    #[test]
+    fn test_geometry_snapshot() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 1.;
+        viewport.scale = 2.;
+        let size = Size::new(800., 600.);
+        viewport.resize(size);
+        assert_eq!(viewport.render_scale(), 2.);
+
+        let snapshot = viewport.geometry_snapshot((&things, None), &Settings::default(), size);
+        let bottom = (viewport.world_camera(size) * Point::ZERO).y;
+        let x = |index: f64| 400. + viewport.direction.sign() * Thing::BAR_OFFSET * index;
+        let expected = [
+            ("Blink", x(0.), bottom - 0.003, 1., false),
+            ("Week", x(1.), bottom - Viewport::MAX_HEIGHT, 0., true),
+            ("Year", x(2.), bottom - Viewport::MAX_HEIGHT, 0., true),
+        ];
+        assert_eq!(snapshot.len(), expected.len());
+        for (i, (geometry, (name, x, top, alpha, clamped))) in
+            snapshot.iter().zip(expected).enumerate()
+        {
+            assert_eq!(geometry.index, i);
+            assert_eq!(geometry.name, name);
+            assert_eq!(geometry.value, things[i].value.inner());
+            assert_eq!(geometry.side, Side::Primary);
+            assert!((geometry.x - x).abs() < 1e-9, "{name} x");
+            assert!(geometry.left < geometry.x && geometry.x < geometry.right);
+            assert!((geometry.top - top).abs() < 1e-9, "{name} top");
+            assert_eq!(geometry.bottom, bottom);
+            assert_eq!(geometry.alpha, alpha);
+            assert_eq!(geometry.clamped, clamped);
+        }
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains(r#""name":"Week""#) && json.contains(r#""clamped":true"#));
+        let loaded: Vec<BarGeometry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, snapshot);
+    }
+
+    #[test]
+    fn test_bar_screen_positions() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 2.;
+        let size = Size::new(800., 600.);
+        viewport.resize(size);
+        assert_eq!(viewport.camera.translation().y, 200.);
+
+        let positions = viewport.bar_screen_positions((&things, None), &Settings::default(), size);
+        let indices = positions
+            .iter()
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, [0, 1, 2]);
+        for (index, point) in positions {
+            let expected_x =
+                400. + viewport.direction.sign() * Thing::BAR_OFFSET * (index as f64 - 1.);
+            let bar_top = things[index]
+                .position(
+                    index,
+                    viewport.scale,
+                    Vec2::new(400., 300.),
+                    viewport.direction,
+                )
+                .y;
+            assert_eq!(point.x, expected_x);
+            assert!((point.y - (300. + 200. - bar_top)).abs() < 1e-9);
+            let inside = Point::new(point.x, point.y + 1.);
+            if bar_top > 1. {
+                assert_eq!(
+                    viewport.hit_test(&bars(&things, None), inside, size),
+                    Some(index)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_visible_bounds() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 2.;
+        let size = Size::new(800., 600.);
+        viewport.resize(size);
+
+        let bars = viewport.bar_screen_bounds((&things, None), &Settings::default(), size);
+        assert_eq!(bars.len(), 2);
+        let bounds = viewport.visible_bounds((&things, None), &Settings::default(), size);
+        let axis_y = 300. + viewport.camera.translation().y;
+        assert!((bounds.y1 - (axis_y + Viewport::EXPORT_MARGIN)).abs() < 1e-9);
+        for (_, bar) in &bars {
+            assert!(bounds.contains(bar.center()));
+        }
+        let left = bars
+            .iter()
+            .map(|(_, bar)| bar.x0)
+            .fold(f64::INFINITY, f64::min);
+        let right = bars.iter().map(|(_, bar)| bar.x1).fold(0., f64::max);
+        assert_eq!(bounds.x0, left - Viewport::EXPORT_MARGIN);
+        assert_eq!(bounds.x1, right + Viewport::EXPORT_MARGIN);
+        assert!(bounds.width() < size.width);
+
+        viewport.shift = 0.;
+        let empty = viewport.visible_bounds((&things, None), &Settings::default(), size);
+        assert_eq!(
+            empty,
+            Rect::new(
+                0.,
+                axis_y - Viewport::EXPORT_MARGIN,
+                800.,
+                axis_y + Viewport::EXPORT_MARGIN
+            )
+        );
+    }
+
+    #[test]
+    fn test_snapshots_skip_culled_bars() {
+        let things = (0..15)
+            .map(|i| Thing::new(&format!("Thing {i}"), 10f64.powi(i)))
+            .collect::<Vec<_>>();
+        let secondary = vec![Thing::new("Minute", 60.)];
+        let settings = Settings {
+            max_bars: Viewport::MIN_MAX_BARS,
+            ..Settings::default()
+        };
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 12.;
+        viewport.apply_settings((&things, Some(&secondary)), &settings);
+        let size = Size::new(800., 600.);
+        viewport.fit(size);
+        let painted = viewport
+            .frame_bars((&things, Some(&secondary)), &settings, Side::Primary)
+            .iter()
+            .map(|bar| (bar.index, bar.side))
+            .collect::<Vec<_>>();
+        assert_eq!(painted.first(), Some(&(5, Side::Primary)));
+        let snapshot = viewport
+            .geometry_snapshot((&things, Some(&secondary)), &settings, size)
+            .iter()
+            .map(|bar| (bar.index, bar.side))
+            .collect::<Vec<_>>();
+        assert_eq!(snapshot, painted);
+        let positions = viewport.bar_screen_positions((&things, None), &settings, size);
+        assert_eq!(positions.len(), Viewport::MIN_MAX_BARS);
+        assert_eq!(positions[0].0, 5);
+    }
+
+    #[test]
+    fn test_context_menu_hit_test() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Minute", 60.),
+            Thing::new("Hour", 3600.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 2.;
+        viewport.update_camera();
+        let size = Size::new(800., 600.);
+        let half_size = size.to_vec2() / 2.;
+        let world_camera = Affine::FLIP_Y.then_translate(half_size) * viewport.camera.inverse();
+        let bars = bars(&things, None);
+        for (i, bar) in bars.iter().enumerate() {
+            let position = viewport.bar_position(bar, half_size);
+            let inside = world_camera * Point::new(position.x, position.y / 2.);
+            assert_eq!(viewport.open_context_menu(&bars, inside, size), Some(i));
+            let menu = viewport.context_menu.unwrap();
+            assert_eq!(
+                (menu.index, menu.id, menu.origin),
+                (i, things[i].id, inside)
+            );
+        }
+
+        let empty = world_camera * Point::new(-1000., 10.);
+        viewport.context_menu = None;
+        assert_eq!(viewport.open_context_menu(&bars, empty, size), None);
+        assert_eq!(viewport.context_menu, None);
+
+        let position = viewport.bar_position(&bars[1], half_size);
+        let inside = world_camera * Point::new(position.x, position.y / 2.);
+        viewport.open_context_menu(&bars, inside, size);
+        let menu = viewport.context_menu.unwrap();
+        let second_item = menu.item_rect(1, 2.).center();
+        assert_eq!(menu.item_at(second_item, 2.), Some(MenuItem::SetReference));
+        assert!(menu.bounds(2.).contains(menu.item_rect(3, 2.).center()));
+        assert_eq!(
+            viewport.menu_click(menu.item_rect(2, 1.).center(), 1.),
+            Some((things[1].id, MenuItem::CopyValue))
+        );
+        assert_eq!(viewport.context_menu, None);
+
+        viewport.open_context_menu(&bars, inside, size);
+        assert_eq!(viewport.menu_click(inside - Vec2::new(10., 10.), 1.), None);
+        assert_eq!(viewport.context_menu, None);
+    }
+
+    #[test]
+    fn test_center_on_settles_on_bar() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Minute", 60.),
+            Thing::new("Day", 86400.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.reference = Some(things[0].id);
+        viewport.center_on(2, &things, None);
+        assert_eq!(viewport.shift, 3.);
+        assert!(matches!(viewport.animation.step, AnimStep::Pausing(_)));
+        assert!(!viewport.animation.active);
+        assert_eq!(viewport.reference, Some(things[0].id));
+    }
+
+    #[test]
+    fn test_hit_test_markers() {
+        let mut things = [Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        things[0].marker = MarkerStyle::Dot;
+        things[1].marker = MarkerStyle::Tick;
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 1.;
+        viewport.update_camera();
+        let size = Size::new(800., 600.);
+        let half_size = size.to_vec2() / 2.;
+        let world_camera = Affine::FLIP_Y.then_translate(half_size) * viewport.camera.inverse();
+        let bars = bars(&things, None);
+        for (i, bar) in bars.iter().enumerate() {
+            let position = viewport.bar_position(bar, half_size);
+            let center = world_camera * position.to_point();
+            assert_eq!(viewport.hit_test(&bars, center, size), Some(i));
+            let below = world_camera * Point::new(position.x, position.y / 2.);
+            assert_eq!(viewport.hit_test(&bars, below, size), None);
+        }
+    }
+
+    #[test]
+    fn test_horizontal_orientation_transposes_positions_and_gridlines() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 3.156e7),
+        ];
+        let screen = Size::new(800., 600.);
+        let mut vertical = Viewport::init(&things);
+        vertical.shift = 2.;
+        let mut horizontal = Viewport::init(&things);
+        horizontal.shift = 2.;
+        horizontal.orientation = Orientation::Horizontal;
+        let half_size = vertical.resize(Size::new(600., 800.));
+        assert_eq!(
+            horizontal.resize(Orientation::Horizontal.transpose(screen)),
+            half_size
+        );
+
+        let upright = vertical.bar_screen_positions(
+            (&things, None),
+            &Settings::default(),
+            Size::new(600., 800.),
+        );
+        let sideways =
+            horizontal.bar_screen_positions((&things, None), &Settings::default(), screen);
+        for ((index, a), (_, b)) in upright.into_iter().zip(sideways) {
+            assert!(
+                (b - Point::new(screen.width - a.y, a.x)).hypot() < 1e-9,
+                "bar {index}"
+            );
+        }
+
+        let gridline = LineParams::horizontal(300.);
+        let world_trans = horizontal.orientation.world_trans(half_size);
+        let line =
+            gridline.transform(world_trans, horizontal.camera.inverse()) * gridline.line(half_size);
+        assert_eq!(line.p0.x, line.p1.x);
+        assert_eq!((line.p0.y, line.p1.y), (0., screen.height));
+        let line = gridline.transform(
+            Orientation::Vertical.world_trans(half_size),
+            Affine::IDENTITY,
+        ) * gridline.line(half_size);
+        assert_eq!(line.p0.y, line.p1.y);
+    }
+
+    #[test]
+    fn test_hit_test_follows_baseline() {
+        let things = [Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 1.;
+        viewport.update_camera();
+        let size = Size::new(800., 600.);
+        let half_size = size.to_vec2() / 2.;
+        let world_camera = viewport.world_camera(size);
+        let bars = bars(&things, None);
+        let position = viewport.bar_position(&bars[0], half_size);
+        let near_axis = world_camera * Point::new(position.x, 1.);
+        let near_top = world_camera * Point::new(position.x, Viewport::MAX_HEIGHT - 1.);
+        assert_eq!(viewport.hit_test(&bars, near_axis, size), Some(0));
+        assert_eq!(viewport.hit_test(&bars, near_top, size), None);
+
+        viewport.baseline = Baseline::Top;
+        assert_eq!(viewport.hit_test(&bars, near_axis, size), None);
+        assert_eq!(viewport.hit_test(&bars, near_top, size), Some(0));
+        assert_eq!(
+            viewport.bar_bounds(&bars[0], half_size).y1,
+            Viewport::MAX_HEIGHT
+        );
+    }
+
+    #[test]
+    fn test_set_progress_spans_all_bars() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.set_progress(0., &things, None);
+        assert_eq!(viewport.shift, 1.);
+        assert!(!viewport.animation.active);
+        viewport.set_progress(1., &things, None);
+        assert_eq!(viewport.shift, things.len() as f64);
+        assert!(viewport.animation.finished());
+
+        let mut previous = 0.;
+        for step in 0..=20 {
+            viewport.set_progress(step as f64 / 20., &things, None);
+            assert!(viewport.shift >= previous);
+            previous = viewport.shift;
+        }
+        viewport.set_progress(-1., &things, None);
+        assert_eq!(viewport.shift, 1.);
+    }
+
+    #[test]
+    fn test_set_progress_with_manual_advance() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.animation.manual_advance = true;
+        viewport.set_progress(1., &things, None);
+        assert_eq!(viewport.shift, things.len() as f64);
+        assert!(viewport.animation.finished());
+        assert!(viewport.animation.manual_advance);
+
+        let (first, last) = viewport.frame_range(&things, None);
+        viewport.set_progress(0.5, &things, None);
+        assert_eq!(viewport.animation.frame, first + (last - first).div_ceil(2));
+        assert!(viewport.animation.manual_advance);
+    }
+
+    #[test]
+    fn test_scrubbing_resumes_from_checkpoints() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let secondary = [Thing::new("Minute", 60.), Thing::new("Century", 3.156e9)];
+        let mut viewport = Viewport::init(&things);
+        viewport.set_progress(1., &things, Some(&secondary));
+        assert_eq!(
+            viewport.run_cache.frame_range(),
+            viewport.frame_range(&things, Some(&secondary))
+        );
+        assert_ne!(
+            viewport.run_cache.frame_range(),
+            viewport.frame_range(&things, None)
+        );
+
+        // every frame reached from a checkpoint matches a straight run
+        let mut straight = Viewport::init(&things);
+        straight.restart(&things);
+        for frame in [0, 1, 59, 60, 61, 200, 437] {
+            while straight.animation.frame < frame && !straight.animation.finished() {
+                straight.update_animation(&things, Some(&secondary), Animation::FRAME_SECS);
+            }
+            viewport.replay(&things, Some(&secondary), frame);
+            assert_eq!(viewport.motion(), straight.motion(), "frame {frame}");
+            assert_eq!(viewport.visited, straight.visited, "frame {frame}");
+        }
+
+        let (_, last) = viewport.run_cache.frame_range();
+        let mut things = things;
+        things[2].value = TimeScale::from(ENumber::new(1., 12));
+        viewport.set_progress(1., &things, Some(&secondary));
+        assert_ne!(viewport.run_cache.frame_range().1, last);
+        assert_eq!(
+            viewport.run_cache.frame_range(),
+            viewport.frame_range(&things, Some(&secondary))
+        );
+    }
+
+    #[test]
+    fn test_center_on_uses_the_cached_run() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Minute", 60.),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.center_on(2, &things, None);
+        let cached = viewport.motion();
+        assert_eq!(viewport.shift, 3.);
+        assert!(matches!(viewport.animation.step, AnimStep::Pausing(_)));
+
+        let mut fresh = Viewport::init(&things);
+        fresh.seek(2, &things, None);
+        assert_eq!(cached, fresh.motion());
+
+        // before the play range there is nothing cached to resume from
+        viewport.set_play_range(
+            PlayRange {
+                start: 2,
+                end: None,
+            },
+            &things,
+        );
+        viewport.center_on(0, &things, None);
+        assert_eq!(viewport.shift, 1.);
+    }
+
+    #[test]
+    fn test_spread_labels_keeps_a_minimum_gap() {
+        assert_eq!(
+            Viewport::spread_labels(&[100., 10., 500.], 14.),
+            [100., 10., 500.]
+        );
+        assert_eq!(
+            Viewport::spread_labels(&[105., 100., 300., 110.], 14.),
+            [114., 100., 300., 128.]
+        );
+        assert!(Viewport::spread_labels(&[], 14.).is_empty());
+    }
+
+    #[test]
+    fn test_margin_values_match_bar_heights() {
+        let things = [
+            Thing::new("Second", 1.),
+            Thing::new("Ten seconds", 10.),
+            Thing::new("Eleven seconds", 11.),
+            Thing::new("Minute", 60.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        let half_size = Vec2::new(400., 300.);
+        let bars = bars(&things, None);
+        assert!(viewport.margin_values(&bars, half_size, 14.).is_empty());
+
+        viewport.animation.step = AnimStep::Pausing(AnimStep::PAUSING_TIME);
+        // a minute reaches 600 and ten seconds 100, so none is clamped
+        viewport.scale = Viewport::MAX_HEIGHT.log10() - viewport.decades as f64 - 1.;
+        let heights = things
+            .iter()
+            .enumerate()
+            .map(|(index, thing)| viewport.thing_position(thing, index, half_size).y)
+            .collect::<Vec<_>>();
+        let values = viewport.margin_values(&bars, half_size, 14.);
+        assert_eq!(values.len(), things.len());
+        for (index, (&(bar, line, _), height)) in values.iter().zip(&heights).enumerate() {
+            assert_eq!(line, *height);
+            assert_eq!(bar.index, index);
+            assert_eq!(bar.thing.id, things[index].id);
+        }
+        assert_eq!(values[0].2, heights[0]);
+        assert_eq!(values[1].2, heights[1]);
+        assert!((heights[3] - 600.).abs() < 1e-9);
+        assert!(heights[2] - heights[1] < 14.);
+        assert_eq!(values[2].2, heights[1] + 14.);
+        assert_eq!(values[3].2, heights[3]);
+
+        viewport.baseline = Baseline::Top;
+        let values = viewport.margin_values(&bars, half_size, 0.);
+        assert_eq!(values[3].1, Viewport::MAX_HEIGHT - heights[3]);
+        viewport.baseline = Baseline::Center;
+        assert!(viewport.margin_values(&bars, half_size, 14.).is_empty());
+    }
+
+    #[test]
+    fn test_play_range_clamps() {
+        let range = PlayRange {
+            start: 9,
+            end: Some(2),
+        };
+        assert_eq!(
+            range.clamped(5),
+            PlayRange {
+                start: 4,
+                end: Some(4)
+            }
+        );
+        let range = PlayRange {
+            start: 1,
+            end: Some(10),
+        };
+        assert_eq!(range.clamped(5).end, Some(4));
+        assert_eq!(
+            range.clamped(0),
+            PlayRange {
+                start: 0,
+                end: Some(0)
+            }
+        );
+        assert_eq!(range.end_slot(5), 5);
+        assert_eq!(range.clamped(5).end_slot(3), 3);
+        assert_eq!(PlayRange::default().end_slot(5), 5);
+        assert_eq!(range.to_string(), "2–11");
+    }
+
+    #[test]
+    fn test_play_range_limits_the_run() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Second", 1.),
+            Thing::new("Day", 86400.),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        let full = viewport.timeline(&things);
+        viewport.set_play_range(
+            PlayRange {
+                start: 1,
+                end: Some(3),
+            },
+            &things,
+        );
+        assert_eq!(viewport.focused_index(), Some(1));
+        assert!(matches!(viewport.animation.step, AnimStep::Pausing(_)));
+        assert_eq!(viewport.animation.frame, 0);
+        assert_eq!(viewport.visited, HashSet::from([things[1].id]));
+
+        let timeline = viewport.timeline(&things);
+        let indices = timeline
+            .iter()
+            .map(|event| event.thing_index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, [1, 2, 3]);
+        assert_eq!(timeline[0].start_secs, 0.);
+        assert!(timeline.last().unwrap().pause_secs < full.last().unwrap().pause_secs);
+
+        while !viewport.animation.finished() {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        }
+        assert_eq!(viewport.focused_index(), Some(3));
+
+        viewport.set_progress(0., &things, None);
+        assert_eq!(viewport.focused_index(), Some(1));
+        viewport.set_progress(1., &things, None);
+        assert_eq!(viewport.focused_index(), Some(3));
+        assert!(viewport.animation.finished());
+    }
+
+    #[test]
+    fn test_play_range_follows_its_things() {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Second", 1.),
+            Thing::new("Day", 86400.),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.set_play_range(
+            PlayRange {
+                start: 1,
+                end: Some(3),
+            },
+            &things,
+        );
+        things.insert(0, Thing::new("Planck time", (5.39, -44)));
+        viewport.sync(&things);
+        assert_eq!(
+            viewport.play_range,
+            PlayRange {
+                start: 2,
+                end: Some(4)
+            }
+        );
+        assert_eq!(things[viewport.play_range.start].name, "Second");
+
+        things.swap(2, 4);
+        viewport.sync(&things);
+        assert_eq!(
+            viewport.play_range,
+            PlayRange {
+                start: 2,
+                end: Some(4)
+            }
+        );
+
+        let from_day = PlayRange {
+            start: 3,
+            end: None,
+        };
+        viewport.set_play_range(from_day, &things);
+        things.remove(0);
+        viewport.sync(&things);
+        assert_eq!(things[viewport.play_range.start].name, "Day");
+        assert_eq!(viewport.play_range.end, None);
+    }
+
+    #[test]
+    fn test_editing_things_revalidates_the_play_range() {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Second", 1.),
+            Thing::new("Day", 86400.),
+            Thing::new("Week", 604800.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.set_play_range(
+            PlayRange {
+                start: 2,
+                end: Some(3),
+            },
+            &things,
+        );
+        things.truncate(2);
+        viewport.sync(&things);
+        assert_eq!(
+            viewport.play_range,
+            PlayRange {
+                start: 1,
+                end: Some(1)
+            }
+        );
+        viewport.restart(&things);
+        while !viewport.animation.finished() {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        }
+        assert_eq!(viewport.focused_index(), Some(1));
+    }
+
+    fn ticked_timeline(viewport: &mut Viewport, things: &[Thing]) -> Vec<(usize, f64, f64)> {
+        let mut events = Vec::new();
+        let mut focused = 0;
+        while !viewport.animation.finished() {
+            let was_pausing = matches!(viewport.animation.step, AnimStep::Pausing(_));
+            viewport.update_animation(things, None, Animation::FRAME_SECS);
+            let secs = viewport.animation.frame as f64 / Animation::FPS;
+            if viewport.shift >= (focused + 1) as f64 {
+                events.push((focused, secs, secs));
+                focused += 1;
+            }
+            if !was_pausing && matches!(viewport.animation.step, AnimStep::Pausing(_)) {
+                events.last_mut().unwrap().2 = secs;
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_timeline_matches_ticked_viewport() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Second", 1.),
+            Thing::new("Week", 604800.),
+            Thing::new("Eight days", 691200.),
+            Thing::new("Age of the universe", 4.35e17),
+        ];
+        let frame = 1. / Animation::FPS;
+        for (examine, constant_speed, easing) in [
+            (false, false, Easing::Cubic),
+            (true, false, Easing::Linear),
+            (false, true, Easing::Bounce),
+        ] {
+            let mut viewport = Viewport::init_with(&things, 4);
+            viewport.examine = examine;
+            viewport.constant_speed = constant_speed;
+            viewport.animation.shift_easing = easing;
+            viewport.animation.slow_easing = easing;
+            let timeline = viewport.timeline(&things);
+            let ticked = ticked_timeline(&mut viewport, &things);
+            assert_eq!(timeline.len(), things.len());
+            assert_eq!(ticked.len(), things.len());
+            for (event, (index, start, pause)) in timeline.iter().zip(ticked) {
+                assert_eq!(event.thing_index, index);
+                assert!((event.start_secs - start).abs() <= frame);
+                assert!((event.pause_secs - pause).abs() <= frame);
+                assert!(event.pause_secs > event.start_secs);
+            }
+            assert!(
+                timeline
+                    .windows(2)
+                    .all(|pair| pair[1].start_secs > pair[0].pause_secs)
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulate_timeline_uses_settings() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Age of the Earth", 1.434e17),
+        ];
+        let settings = Settings::default().with_visible_decades(6);
+        let timeline = simulate_timeline(&things, settings);
+        assert_eq!(timeline, Viewport::init_with(&things, 6).timeline(&things));
+        assert_eq!(timeline, simulate_timeline(&things, settings));
+        assert!((timeline[0].start_secs - AnimStep::SHIFTING_TIME).abs() <= 1. / Animation::FPS);
+        assert!(simulate_timeline(&[], settings).is_empty());
+    }
+
+    #[test]
+    fn test_scale_advance_is_frame_rate_independent() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Age of the universe", 4.35e17),
+        ];
+        let advance = |fps: f64, step: AnimStep| {
+            let mut viewport = Viewport::init(&things);
+            viewport.prev_shift = 2.;
+            viewport.shift = 2.;
+            viewport.animation.step = step;
+            let start = viewport.scale;
+            for _ in 0..fps as usize {
+                viewport.update_animation(&things, None, 1. / fps);
+            }
+            viewport.scale - start
+        };
+        for step in [AnimStep::Pausing(AnimStep::PAUSING_TIME), AnimStep::Scaling] {
+            let reference = advance(60., step);
+            for fps in [30., 90., 120., 144., 240.] {
+                let relative = (advance(fps, step) - reference).abs() / reference;
+                assert!(relative < 0.02, "{step:?} at {fps} fps: {relative}");
+            }
+        }
+        let idle = advance(144., AnimStep::Pausing(AnimStep::PAUSING_TIME));
+        assert!((idle - Viewport::IDLE_SCALE_SPEED).abs() < 1e-9);
+
+        for fps in [30., 62.5, 144.] {
+            let mut viewport = Viewport::init(&things);
+            while viewport.prev_shift < 1. {
+                viewport.update_animation(&things, None, 1. / fps);
+            }
+            let secs = viewport.animation.secs();
+            assert!(
+                (secs - AnimStep::SHIFTING_TIME).abs() <= 1. / fps + 1e-9,
+                "{fps}: {secs}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reduce_motion_cuts_between_settled_states() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Age of the universe", 4.35e17),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.reduce_motion = true;
+        let mut focused = 0.;
+        while !viewport.animation.finished() {
+            let scale = viewport.scale;
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+            assert_eq!(viewport.shift.fract(), 0.);
+            assert!(!matches!(viewport.animation.step, AnimStep::Shifting(_)));
+            if viewport.shift != focused {
+                assert_eq!(viewport.shift, focused + 1.);
+                assert_eq!(viewport.crossfade, Viewport::CROSSFADE_TIME);
+                let target = things[focused as usize].scale();
+                assert_eq!(
+                    viewport.scale,
+                    target - Viewport::headroom(viewport.decades)
+                );
+                focused = viewport.shift;
+            } else {
+                assert_eq!(viewport.scale, scale);
+            }
+        }
+        assert_eq!(focused, things.len() as f64);
+        assert_eq!(viewport.crossfade, 0.);
+        assert_eq!(viewport.crossfade_alpha(), 0.);
+
+        let mut smooth = Viewport::init(&things);
+        let reduced = viewport.timeline(&things);
+        assert!(
+            reduced.last().unwrap().pause_secs
+                < smooth.timeline(&things).last().unwrap().pause_secs
+        );
+        smooth.update_animation(&things, None, Animation::FRAME_SECS);
+        assert!(smooth.shift > 0. && smooth.shift < 1.);
+    }
+
+    #[test]
+    fn test_focused_index_follows_animation() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        assert_eq!(viewport.focused_index(), None);
+        assert_eq!(viewport.focus_alpha(1), 1.);
+        let mut seen = Vec::new();
+        while !viewport.animation.finished() {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+            let focused = viewport.focused_index();
+            if seen.last() != Some(&focused) {
+                seen.push(focused);
+            }
+            let amount = viewport.focus_amount();
+            assert!((0. ..=1.).contains(&amount));
+            match viewport.animation.step {
+                AnimStep::Pausing(_) => assert_eq!(amount, 1.),
+                AnimStep::Idle(_) | AnimStep::Scaling => assert_eq!(amount, 0.),
+                AnimStep::Shifting(t) if t < AnimStep::SHIFTING_TIME - Animation::FRAME_SECS => {
+                    assert!(amount < 1.)
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(seen, [None, Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_focus_dimming_composes_with_reveal() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 2.;
+        viewport.animation.step = AnimStep::Pausing(0.16);
+        assert_eq!(viewport.focused_index(), Some(1));
+        assert_eq!(viewport.focus_alpha(1), 1.);
+        assert_eq!(viewport.focus_alpha(0), Viewport::FOCUS_DIM_ALPHA);
+        assert_eq!(viewport.focus_text_scale(1), Viewport::FOCUS_TEXT_SCALE);
+        assert_eq!(viewport.focus_text_scale(0), 1.);
+        let composed = |viewport: &Viewport, index| {
+            Thing::alpha(index, viewport.shift) * viewport.focus_alpha(index)
+        };
+        assert_eq!(composed(&viewport, 0), Viewport::FOCUS_DIM_ALPHA);
+        assert_eq!(composed(&viewport, 1), 1.);
+        assert_eq!(composed(&viewport, 2), 0.);
+
+        viewport.animation.step = AnimStep::Slowing(AnimStep::SLOWING_TIME / 2.);
+        let half = viewport.focus_amount();
+        assert!(half > 0. && half < 1.);
+        assert_eq!(
+            viewport.focus_alpha(0),
+            1. - (1. - Viewport::FOCUS_DIM_ALPHA) * half
+        );
+
+        viewport.shift = 2.5;
+        viewport.animation.step = AnimStep::Shifting(AnimStep::SHIFTING_TIME / 2.);
+        assert_eq!(viewport.focused_index(), Some(1));
+        assert!((viewport.focus_amount() - 0.5).abs() < 0.01);
+        assert!(composed(&viewport, 2) < 0.5 * (1. - (1. - Viewport::FOCUS_DIM_ALPHA) / 2.) + 1e-6);
+    }
+
+    #[test]
+    fn test_zero_value_never_freezes() {
+        let things = [
+            Thing::new("Nothing", 0.),
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        assert_eq!(
+            viewport.scale,
+            0.3_f64.log10() - Viewport::headroom(Viewport::DEFAULT_DECADES)
+        );
+        for _ in 0..1000 {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+            assert!(viewport.scale.is_finite());
+        }
+        assert_eq!(viewport.shift, 3.);
+        for _ in 0..1000 {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        }
+        assert!(viewport.animation.finished());
+
+        let things = [Thing::new("Nothing", 0.), Thing::new("Debt", -5.)];
+        let mut viewport = Viewport::init(&things);
+        assert_eq!(viewport.scale, 0.);
+        for _ in 0..1000 {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+            assert!(viewport.scale.is_finite());
+        }
+        assert!(viewport.animation.finished());
+    }
+
+    #[test]
+    fn test_non_finite_motion_is_repaired() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 3.156e7),
+        ];
+        let size = Size::new(800., 600.);
+        let mut viewport = Viewport::init(&things);
+        for _ in 0..200 {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        }
+        let good = viewport.motion();
+        assert!(good.is_finite());
+
+        viewport.update_animation(&things, None, f64::NAN);
+        assert_eq!(viewport.motion(), good);
+        assert_eq!(viewport.repairs, 1);
+
+        viewport.scale = f64::INFINITY;
+        viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        assert_eq!(viewport.motion(), good);
+
+        viewport.scale = f64::NAN;
+        viewport.set_decades(Viewport::DEFAULT_DECADES + 1);
+        assert!(viewport.scale.is_finite());
+
+        viewport.shift = f64::NAN;
+        viewport.prev_shift = f64::NAN;
+        viewport.sync(&things);
+        assert!(viewport.motion().is_finite());
+        assert_eq!(viewport.repairs, 4);
+
+        viewport.animation.step = AnimStep::Pausing(f64::NAN);
+        viewport.camera = Affine::translate((f64::NAN, 0.));
+        viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        assert!(viewport.motion().is_finite());
+
+        viewport.set_progress(f64::NAN, &things, None);
+        assert!(viewport.motion().is_finite());
+
+        for _ in 0..2000 {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        }
+        assert!(viewport.animation.finished());
+        let geometry = viewport.geometry_snapshot((&things, None), &Settings::default(), size);
+        assert_eq!(geometry.len(), things.len());
+        assert!(
+            geometry
+                .iter()
+                .all(|bar| bar.x.is_finite() && bar.top.is_finite())
+        );
+    }
+
+    fn scaling_frames(things: &[Thing]) -> Vec<u32> {
+        let mut viewport = Viewport::init(things);
+        let mut frames = vec![0; things.len()];
+        while !viewport.animation.finished() {
+            viewport.update_animation(things, None, Animation::FRAME_SECS);
+            if viewport.animation.step == AnimStep::Scaling {
+                frames[viewport.shift as usize - 1] += 1;
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn test_small_gaps_stretch_scaling() {
+        let things = [
+            Thing::new("Second", 1.),
+            Thing::new("Week", 604800.),
+            Thing::new("Eight days", 691200.),
+        ];
+        let frames = scaling_frames(&things);
+        let min_frames = (Viewport::MIN_SCALING_TIME * Animation::FPS * 0.8) as u32;
+        assert!(frames[2] >= min_frames, "{frames:?}");
+
+        let gap = things[2].scale() - things[1].scale();
+        let cap = Viewport::scaling_speed_cap(gap).unwrap();
+        assert!(Viewport::creep_speed(gap) < Viewport::IDLE_SCALE_SPEED / 10.);
+        assert!((cap * Viewport::MIN_SCALING_TIME - gap).abs() < 1e-9);
+        assert_eq!(Viewport::scaling_speed_cap(1.5), None);
+        assert_eq!(Viewport::creep_speed(1.5), Viewport::IDLE_SCALE_SPEED);
+    }
+
+    #[test]
+    fn test_examine_caps_scale_speed() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Heat death", (1., 100)),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 2.;
+        viewport.prev_shift = 2.;
+        viewport.animation.step = AnimStep::Scaling;
+        for _ in 0..100 {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        }
+        assert!(viewport.scale_speed > Viewport::EXAMINE_SCALE_SPEED);
+
+        viewport.examine = true;
+        for _ in 0..100 {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+            assert!(matches!(viewport.animation.step, AnimStep::Scaling));
+            assert_eq!(viewport.scale_speed, Viewport::EXAMINE_SCALE_SPEED);
+        }
+
+        viewport.examine = false;
+        viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        assert!(viewport.scale_speed > Viewport::EXAMINE_SCALE_SPEED);
+    }
+
+    #[test]
+    fn test_constant_speed_never_accelerates() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Heat death", (1., 100)),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.constant_speed = true;
+        let mut scaled = 0;
+        let mut scaling = false;
+        while !viewport.animation.finished() {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+            assert!(viewport.scale_speed <= Viewport::CONSTANT_SCALE_SPEED);
+            if viewport.animation.step == AnimStep::Scaling {
+                assert_eq!(viewport.scale_speed, Viewport::CONSTANT_SCALE_SPEED);
+                scaling = true;
+            }
+            if scaling && viewport.animation.step == AnimStep::Pausing(AnimStep::PAUSING_TIME) {
+                let target = slot_scale(&things, None, viewport.shift as usize - 1).unwrap();
+                assert!(target - viewport.scale <= Viewport::headroom(Viewport::DEFAULT_DECADES));
+                scaled += 1;
+                scaling = false;
+            }
+        }
+        assert_eq!(scaled, things.len());
+        assert_eq!(viewport.shift, things.len() as f64);
+    }
+
+    #[test]
+    fn test_resize_is_continuous() {
+        let mut viewport = Viewport::init(&[]);
+        viewport.animation.active = true;
+        let sizes = [(800., 600.), (1200., 900.), (1200., 900.), (640., 480.)];
+        let mut previous = viewport.resize(Size::new(800., 600.));
+        for (width, height) in sizes {
+            let target = Vec2::new(width, height) / 2.;
+            for _ in 0..60 {
+                let distance = (target - previous).hypot();
+                let half_size = viewport.resize(Size::new(width, height));
+                let step = (half_size - previous).hypot();
+                assert!(step <= distance / Viewport::RESIZE_FRAMES + 0.5);
+                assert!((target - half_size).hypot() <= distance);
+                previous = half_size;
+            }
+            assert_eq!(previous, target);
+        }
+
+        viewport.animation.active = false;
+        let half_size = viewport.resize(Size::new(300., 200.));
+        assert_eq!(half_size, Vec2::new(150., 100.));
+    }
+
+    #[test]
+    fn test_resize_debounce() {
+        let small = Vec2::new(400., 300.);
+        let large = Vec2::new(600., 450.);
+        let mut debounce = ResizeDebounce::default();
+        assert_eq!(debounce.update(small), small);
+        for i in 0..10 {
+            let jitter = if i % 2 == 0 { large } else { small * 1.1 };
+            assert_eq!(debounce.update(jitter), small);
+        }
+        for _ in 0..ResizeDebounce::FRAMES {
+            assert_eq!(debounce.update(large), small);
+        }
+        assert_eq!(debounce.update(large), large);
+        assert_eq!(debounce.pending, None);
+    }
+
+    #[test]
+    fn test_reframing_across_sizes() {
+        let mut viewport = Viewport::init(&[]);
+        viewport.resize(Size::new(1200., 1000.));
+        viewport.animation.active = true;
+        let expected_y = |height: f64| {
+            Viewport::clamp_camera_y(
+                350.,
+                Vec2::new(0., height / 2.),
+                Viewport::DEFAULT_FOOTER_HEIGHT,
+            )
+        };
+
+        for height in [500., 900., 520., 880.] {
+            viewport.resize(Size::new(1200., height));
+        }
+        assert_eq!(viewport.half_size, Some(Vec2::new(600., 500.)));
+        assert_eq!(viewport.camera.translation().y, expected_y(1000.));
+
+        let mut previous_y = viewport.camera.translation().y;
+        for _ in 0..80 {
+            viewport.resize(Size::new(1200., 500.));
+            let camera_y = viewport.camera.translation().y;
+            assert!(camera_y <= previous_y);
+            assert!(previous_y - camera_y <= 500. / Viewport::RESIZE_FRAMES);
+            previous_y = camera_y;
+        }
+        assert_eq!(viewport.half_size, Some(Vec2::new(600., 250.)));
+        assert_eq!(previous_y, expected_y(500.));
+        assert!(expected_y(500.) < expected_y(1000.));
+    }
+
+    #[test]
+    fn test_camera_keeps_footer_on_screen() {
+        let mut viewport = Viewport::init(&[]);
+        viewport.resize(Size::new(1200., 1000.));
+        assert_eq!(
+            viewport.camera.translation().y,
+            Viewport::INITIAL_CAMERA_POSITION.y
+        );
+        viewport.resize(Size::new(1200., 500.));
+        let camera_y = viewport.camera.translation().y;
+        assert_eq!(camera_y, 250. - Viewport::DEFAULT_FOOTER_HEIGHT);
+        let axis_y = 250. + camera_y;
+        assert!(500. - axis_y >= Viewport::DEFAULT_FOOTER_HEIGHT);
+        assert_eq!(
+            Viewport::clamp_camera_y(350., Vec2::new(50., 40.), Viewport::MIN_FOOTER_HEIGHT),
+            0.
+        );
+
+        viewport.footer_height = Viewport::MAX_FOOTER_HEIGHT;
+        viewport.resize(Size::new(1200., 1000.));
+        assert_eq!(viewport.camera.translation().y, 200.);
+    }
+
+    #[test]
+    fn test_caption_crossfades_on_focus_change() {
+        let mut caption = Caption::default();
+        caption.update(None, 0.1, false);
+        assert_eq!(caption, Caption::default());
+
+        caption.update(Some(0), 0.1, false);
+        assert_eq!((caption.current, caption.previous), (Some(0), None));
+        assert_eq!(caption.alphas(), (0., 1.));
+        caption.update(Some(0), Caption::FADE_TIME / 2., false);
+        assert_eq!(caption.alphas(), (0.5, 0.5));
+        caption.update(Some(0), Caption::FADE_TIME, false);
+        assert_eq!(caption.alphas(), (1., 0.));
+
+        caption.update(Some(1), 0.1, false);
+        assert_eq!((caption.current, caption.previous), (Some(1), Some(0)));
+        assert_eq!(caption.fade, Caption::FADE_TIME);
+        caption.update(Some(2), 0.1, true);
+        assert_eq!((caption.current, caption.previous), (Some(2), Some(1)));
+        assert_eq!(caption.alphas(), (1., 0.));
+    }
+
+    #[test]
+    fn test_tooltip_hidden_while_scaling() {
+        let mut viewport = Viewport::init(&[]);
+        assert!(viewport.tooltip_visible());
+        viewport.animation.step = AnimStep::Scaling;
+        assert!(!viewport.tooltip_visible());
+    }
+
+    #[test]
+    fn test_decades_derive_headroom_and_gridlines() {
+        let things = [Thing::new("Week", 604800.)];
+        for decades in Viewport::MIN_DECADES..=Viewport::MAX_DECADES {
+            let viewport = Viewport::init_with(&things, decades);
+            assert_eq!(
+                viewport.scale,
+                things[0].scale() - Viewport::headroom(decades)
+            );
+            assert_eq!(
+                Viewport::headroom(decades),
+                decades as f64 - Viewport::HEADROOM_MARGIN
+            );
+            let focused = things[0]
+                .value
+                .inner()
+                .to_scale(viewport.render_scale(), Viewport::MAX_HEIGHT);
+            assert!((focused - 10_f64.powf(3. - Viewport::HEADROOM_MARGIN)).abs() < 1e-6);
+
+            for scale in [-4.2, 0., 0.5, 2.99, 17.] {
+                let majors = Viewport::major_exponents(scale, decades).collect::<Vec<_>>();
+                let lowest = (scale - 1_f64).ceil() as i32;
+                let highest = (scale + decades as f64).floor() as i32;
+                for exponent in lowest..=highest {
+                    assert!(majors.contains(&(exponent as f64)), "{exponent} at {scale}");
+                }
+                let render_scale = scale + decades as f64 - 3.;
+                let top = ENumber::from_exp(highest as f64).to_scale(render_scale, f64::INFINITY);
+                assert!(top <= Viewport::MAX_HEIGHT + 1e-9);
+                assert_eq!(Viewport::line_alpha(scale.ceil(), scale), 1.);
+                assert!(Viewport::line_alpha(scale - 1., scale) <= 0.1 + 1e-6);
+            }
+        }
+        assert_eq!(
+            Viewport::init_with(&things, 10).decades,
+            Viewport::MAX_DECADES
+        );
+        assert_eq!(
+            Viewport::init_with(&things, 1).decades,
+            Viewport::MIN_DECADES
+        );
+    }
+
+    #[test]
+    fn test_set_decades_keeps_focus() {
+        let things = [Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        let mut viewport = Viewport::init(&things);
+        let before = things[0]
+            .value
+            .inner()
+            .to_scale(viewport.render_scale(), Viewport::MAX_HEIGHT);
+        viewport.set_decades(5);
+        assert_eq!(viewport.decades, 5);
+        let after = things[0]
+            .value
+            .inner()
+            .to_scale(viewport.render_scale(), Viewport::MAX_HEIGHT);
+        assert!((before - after).abs() < 1e-9);
+        assert_eq!(Viewport::label_alpha(0., 1.), 0.);
+        assert_eq!(Viewport::label_alpha(Viewport::LABEL_MIN_HEIGHT, 1.), 1.);
+    }
+
+    #[test]
+    fn test_opaque_axis_labels() {
+        for position in [-50., 0., 3., Viewport::LABEL_MIN_HEIGHT, 500.] {
+            for exponent in [-1., -0.5, 0., 2.] {
+                let line_alpha = Viewport::line_alpha(exponent, 0.);
+                assert_eq!(
+                    Viewport::major_label_alpha(line_alpha, position, 1.5, true),
+                    1.
+                );
+                assert_eq!(
+                    Viewport::major_label_alpha(line_alpha, position, 1.5, false),
+                    line_alpha * Viewport::label_alpha(position, 1.5)
+                );
+            }
+        }
+        assert_eq!(Viewport::major_label_alpha(1., 0., 1., false), 0.);
+        assert!(!Settings::default().opaque_axis_labels);
+    }
+
+    #[test]
+    fn test_sub_decade_majors() {
+        assert_eq!(Viewport::major_step(3.), 1.);
+        assert_eq!(Viewport::major_step(Viewport::HALF_DECADE_SPAN), 1.);
+        assert_eq!(Viewport::major_step(1.), 0.5);
+        assert_eq!(Viewport::major_step(0.4), 0.2);
+
+        let majors = Viewport::major_exponents_in(2.3, 1.).collect::<Vec<_>>();
+        assert_eq!(majors, [1.5, 2., 2.5, 3.]);
+        let majors = Viewport::major_exponents_in(-4.3, 0.4).collect::<Vec<_>>();
+        assert_eq!(majors, [-4.6, -4.4, -4.2, -4.]);
+        assert_eq!(
+            Viewport::major_exponents_in(2.5, 3.).collect::<Vec<_>>(),
+            Viewport::major_exponents(2.5, 3).collect::<Vec<_>>()
+        );
+
+        for (exponent, time, powers) in [
+            (2.5, "316 s", "3.16e2"),
+            (0.2, "1.58 s", "1.58e0"),
+            (-3.4, "3.98e-4 s", "3.98e-4"),
+        ] {
+            assert_eq!(Viewport::major_label(exponent, AxisLabels::Time), time);
+            assert_eq!(Viewport::major_label(exponent, AxisLabels::Powers), powers);
+        }
+    }
+
+    #[test]
+    fn test_minor_lines_can_be_disabled() {
+        let majors = Viewport::major_exponents(2.5, 3).collect::<Vec<_>>();
+        assert_eq!(majors, [1., 2., 3., 4., 5.]);
+        let minors = Viewport::minor_gridlines(2., true, MinorLabels::Off);
+        assert_eq!(minors.len(), Viewport::MINOR_LINES);
+        assert_eq!(minors[0], (2. + 2_f64.log10(), None));
+        assert_eq!(minors[7], (2. + 9_f64.log10(), None));
+        for major in majors {
+            for labels in [MinorLabels::Off, MinorLabels::Digits, MinorLabels::Values] {
+                assert!(Viewport::minor_gridlines(major, false, labels).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_minor_lines() {
+        assert!(Viewport::minor_offsets(0).is_empty());
+        assert_eq!(Viewport::minor_offsets(1), [5.5_f64.log10()]);
+        assert_eq!(
+            Viewport::minor_factors(Viewport::MINOR_LINES),
+            [2., 3., 4., 5., 6., 7., 8., 9.]
+        );
+        assert_eq!(
+            Viewport::minor_offsets(Viewport::MINOR_LINES).len(),
+            Viewport::MINOR_LINES
+        );
+        for count in 1..10 {
+            let offsets = Viewport::minor_offsets(count);
+            assert!(
+                offsets
+                    .iter()
+                    .all(|offset| offset.is_finite() && (0. ..1.).contains(offset))
+            );
+        }
+        for labels in [MinorLabels::Off, MinorLabels::Digits, MinorLabels::Values] {
+            assert!(Viewport::minor_gridlines_with(2., 0, labels).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_minor_labels() {
+        let off = Viewport::minor_gridlines(3., true, MinorLabels::Off);
+        assert!(off.iter().all(|(_, label)| label.is_none()));
+        let digits = Viewport::minor_gridlines(3., true, MinorLabels::Digits);
+        let labeled = digits
+            .iter()
+            .filter_map(|(exponent, label)| Some((*exponent, label.as_deref()?)))
+            .collect::<Vec<_>>();
+        assert_eq!(labeled.len(), 2);
+        for ((exponent, label), factor) in labeled.into_iter().zip(Viewport::LABELED_MINORS) {
+            assert!((10_f64.powf(exponent - 3.) - factor).abs() < 1e-9);
+            assert_eq!(label, factor.to_string());
+        }
+        let values = Viewport::minor_gridlines(-23., true, MinorLabels::Values);
+        assert_eq!(values[0].1.as_deref(), Some("2e-23 s"));
+        assert_eq!(values[3].1.as_deref(), Some("5e-23 s"));
+        let values = Viewport::minor_gridlines(0., true, MinorLabels::Values);
+        assert_eq!(values[0].1.as_deref(), Some("2 s"));
+    }
+
+    #[test]
+    fn test_minor_labels_keep_line_positions() {
+        let positions = |labels| {
+            Viewport::minor_gridlines(3., true, labels)
+                .into_iter()
+                .map(|(exponent, _)| exponent)
+                .collect::<Vec<_>>()
+        };
+        let off = positions(MinorLabels::Off);
+        assert_eq!(positions(MinorLabels::Digits), off);
+        assert_eq!(positions(MinorLabels::Values), off);
+    }
+
+    #[test]
+    fn test_scale_bar_units() {
+        let values = Viewport::round_units()
+            .into_iter()
+            .map(|(value, _)| value)
+            .collect::<Vec<_>>();
+        assert!(
+            values
+                .windows(2)
+                .all(|pair| pair[0].total_cmp(&pair[1]).is_lt())
+        );
+        let budget = Viewport::SCALE_BAR_BUDGET;
+        for scale in -28..32 {
+            let scale = scale as f64 + 0.5;
+            let (_, length) = Viewport::scale_bar(scale, budget)
+                .unwrap_or_else(|| panic!("no scale bar at 1e{scale}"));
+            assert!((Viewport::SCALE_BAR_MIN..=budget).contains(&length));
+            let index = values
+                .iter()
+                .position(|value| (value.to_scale(scale, f64::INFINITY) - length).abs() < 1e-9)
+                .unwrap();
+            if let Some(larger) = values.get(index + 1) {
+                assert!(larger.to_scale(scale, f64::INFINITY) > budget);
+            }
+        }
+        let label = |scale| Viewport::scale_bar(scale, budget).unwrap().0;
+        assert_eq!(label(-1.5), "1 s");
+        assert_eq!(label(1.5), "1 h");
+        assert_eq!(label(3.5), "1 d");
+        assert_eq!(label(7.5), "100 y");
+        assert_eq!(Viewport::scale_bar(-40., budget), None);
+        assert_eq!(Viewport::scale_bar(45., budget), None);
+    }
+
+    #[test]
+    fn test_corner_origin() {
+        let content = Size::new(120., 30.);
+        for (size, far) in [
+            (Size::new(800., 600.), Point::new(670., 560.)),
+            (Size::new(1920., 1080.), Point::new(1790., 1040.)),
+        ] {
+            let origin = |corner: Corner| corner.origin(size, content, 10.);
+            assert_eq!(origin(Corner::TopLeft), Point::new(10., 10.));
+            assert_eq!(origin(Corner::TopRight), Point::new(far.x, 10.));
+            assert_eq!(origin(Corner::BottomLeft), Point::new(10., far.y));
+            assert_eq!(origin(Corner::BottomRight), far);
+        }
+    }
+
+    #[test]
+    fn test_axis_labels() {
+        let exponents = [-23., 0., 3., 9.];
+        let labels = |mode| {
+            exponents
+                .iter()
+                .map(|&exponent| Viewport::major_label(exponent, mode))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(AxisLabels::Powers), ["1e-23", "1e0", "1e3", "1e9"]);
+        let time = labels(AxisLabels::Time);
+        assert_eq!(time[1], "1 s");
+        for (time, powers) in time.iter().zip(labels(AxisLabels::Powers)) {
+            assert_ne!(*time, powers);
+        }
+        assert_eq!(AxisLabels::default(), AxisLabels::Time);
+        assert_eq!(AxisLabels::Time.next().next(), AxisLabels::Time);
+    }
+
+    #[test]
+    fn test_playback_queues_events_but_seeking_does_not() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        let mut events = Vec::new();
+        while !viewport.animation.finished() {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+            events.append(&mut viewport.animation.events);
+        }
+        let shifts = events
+            .iter()
+            .filter(|&&event| event == AnimEvent::ShiftStarted)
+            .count();
+        assert_eq!(shifts, things.len() - 1);
+        assert_eq!(events.last(), Some(&AnimEvent::Reached));
+
+        viewport.center_on(1, &things, None);
+        assert!(viewport.animation.events.is_empty());
+        viewport.set_progress(0.5, &things, None);
+        assert!(viewport.animation.events.is_empty());
+    }
+
+    #[test]
+    fn test_pinned_previous_follows_scaling() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Heat death", (1., 100)),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.pin_previous = true;
+        let mut pinned = Vec::new();
+        while !viewport.animation.finished() {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+            let pin = viewport.pinned_previous(&things);
+            if viewport.animation.step != AnimStep::Scaling {
+                assert_eq!(pin, None);
+                continue;
+            }
+            let focus = viewport.shift as usize - 1;
+            if focus == 0 {
+                assert_eq!(pin, None);
+                continue;
+            }
+            let pin = pin.unwrap();
+            assert_eq!(pin.index, focus - 1);
+            assert!(pin.alpha <= Viewport::PIN_ALPHA);
+            pinned.push(pin);
+        }
+        assert!(pinned.iter().any(|pin| pin.index == 0));
+        pinned.retain(|pin| pin.index == 1);
+        assert!(!pinned.is_empty());
+        assert_eq!(pinned[0].label, "← previous: Week (6.048e5 s)");
+        assert!(pinned.iter().any(|pin| pin.alpha == Viewport::PIN_ALPHA));
+        assert_ne!(pinned[0].height_label, pinned.last().unwrap().height_label);
+        assert!(pinned.last().unwrap().height_label.contains("e-"));
+
+        viewport.pin_previous = false;
+        viewport.animation.step = AnimStep::Scaling;
+        viewport.shift = 3.;
+        assert_eq!(viewport.pinned_previous(&things), None);
+    }
+
+    fn magnitude_things() -> Vec<Thing> {
+        vec![
+            Thing::new("One second", 1.),
+            Thing::new("Kilosecond", 1e3),
+            Thing::new("Zero", 0.),
+            Thing::new("Nearby", 1.5e3),
+            Thing::new("Megasecond", 1e6),
+        ]
+    }
+
+    #[test]
+    fn test_magnitude_slot_offsets() {
+        let things = magnitude_things();
+        let even = Viewport::slot_offsets(&things, None, Spacing::Even);
+        assert_eq!(even[4], 4. * Thing::BAR_OFFSET);
+        let offsets = Viewport::slot_offsets(&things, None, Spacing::Magnitude);
+        let decades = offsets
+            .iter()
+            .map(|offset| offset / Viewport::DECADE_SPACING)
+            .collect::<Vec<_>>();
+        assert_eq!(decades[..3], [0., 3., 3.]);
+        assert!((decades[3] - 1.5e3_f64.log10()).abs() < 1e-9);
+        assert!((decades[4] - 6.).abs() < 1e-9);
+
+        let mut viewport = Viewport::init(&things);
+        assert_eq!(viewport.slot_offset(4), 4. * Thing::BAR_OFFSET);
+        viewport.set_spacing(&things, None, Spacing::Magnitude);
+        assert_eq!(viewport.slot_offset(1), offsets[1]);
+        assert_eq!(viewport.slot_offset(6), offsets[4] + 2. * Thing::BAR_OFFSET);
+    }
+
+    #[test]
+    fn test_magnitude_camera_tracks_exponent_distance() {
+        let things = magnitude_things();
+        let half_size = Vec2::new(400., 300.);
+        for direction in [Direction::Rightward, Direction::Leftward] {
+            let mut viewport = Viewport::init(&things);
+            viewport.direction = direction;
+            for spacing in [Spacing::Even, Spacing::Magnitude] {
+                viewport.set_spacing(&things, None, spacing);
+                for (index, thing) in things.iter().enumerate() {
+                    let bar = viewport.thing_position(thing, index, half_size).x;
+                    let camera = viewport
+                        .shift_camera_position(index as f64 + 1., half_size)
+                        .x;
+                    assert!((bar - camera).abs() < 1e-9, "{spacing} {index}");
+                }
+            }
+            viewport.set_spacing(&things, None, Spacing::Even);
+            for shift in [0., 0.5, 1., 2.25, 4.] {
+                assert!(
+                    (viewport.shift_camera_position(shift, half_size)
+                        - Viewport::camera_position(direction, shift, half_size))
+                    .hypot()
+                        < 1e-9
+                );
+            }
+        }
+
+        let mut viewport = Viewport::init(&things);
+        viewport.set_spacing(&things, None, Spacing::Magnitude);
+        let offset = |shift| viewport.shift_offset(shift);
+        assert_eq!(offset(1.5), Viewport::DECADE_SPACING * 1.5);
+        assert_eq!(offset(2.5), Viewport::DECADE_SPACING * 3.);
+        let midway = (viewport.slot_offset(3) + viewport.slot_offset(4)) / 2.;
+        assert!((offset(4.5) - midway).abs() < 1e-9);
+        assert_eq!(offset(0.5), -Thing::BAR_OFFSET / 2.);
+    }
+
+    #[test]
+    fn test_magnitude_stagger_rows() {
+        assert_eq!(
+            Viewport::stagger_rows(&[0., 10., 20., 30., 300., 310.], 50.),
+            [0, 1, 2, 0, 0, 1]
+        );
+        assert_eq!(Viewport::stagger_rows(&[0., 100., 200.], 50.), [0, 0, 0]);
+
+        let things = magnitude_things();
+        let mut viewport = Viewport::init(&things);
+        let mut even_bars = bars(&things, None);
+        viewport.stagger(&mut even_bars);
+        assert!(even_bars.iter().all(|bar| bar.row == 0));
+
+        viewport.set_spacing(&things, None, Spacing::Magnitude);
+        let mut magnitude_bars = bars(&things, None);
+        viewport.stagger(&mut magnitude_bars);
+        let rows = magnitude_bars.iter().map(|bar| bar.row).collect::<Vec<_>>();
+        assert_eq!(rows, [0, 0, 1, 2, 0]);
+
+        let secondary = [Thing::new("Other", 2.), Thing::new("Other kilo", 2e3)];
+        viewport.set_spacing(&things, Some(&secondary), Spacing::Magnitude);
+        let mut paired = bars(&things, Some(&secondary));
+        viewport.stagger(&mut paired);
+        assert!(paired.iter().all(|bar| bar.index != 0 || bar.row < 2));
+    }
+
+    #[test]
+    fn test_ruler_ticks() {
+        let things = magnitude_things();
+        let mut viewport = Viewport::init(&things);
+        viewport.set_spacing(&things, None, Spacing::Magnitude);
+        let ticks = viewport.ruler_ticks(&things, None);
+        let exponents = ticks
+            .iter()
+            .map(|&(_, exponent)| exponent)
+            .collect::<Vec<_>>();
+        assert_eq!(exponents, [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(ticks[3].0, viewport.slot_offset(1));
+        assert!(viewport.ruler_ticks(&[], None).is_empty());
+    }
+
+    #[test]
+    fn test_visited_things() {
+        let mut things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.replay(&things, None, 130);
+        assert_eq!(viewport.focused_index(), Some(0));
+        assert!(viewport.visited.is_empty());
+        while !viewport.visited.contains(&things[0].id) {
+            viewport.update_animation(&things, None, Animation::FRAME_SECS);
+        }
+        assert_eq!(viewport.visited.len(), 1);
+
+        things.insert(0, Thing::new("Camera flash", 1e-3));
+        viewport.sync(&things);
+        assert!(viewport.visited.contains(&things[1].id));
+
+        viewport.center_on(2, &things, None);
+        let ids = things[..3]
+            .iter()
+            .map(|thing| thing.id)
+            .collect::<HashSet<_>>();
+        assert_eq!(viewport.visited, ids);
+
+        viewport.restart(&things);
+        assert!(viewport.visited.is_empty());
+    }
+
+    #[test]
+    fn test_reveal_alpha_envelope() {
+        let pausing = AnimStep::Pausing(1.);
+        let shifting = AnimStep::Shifting(1.);
+        assert_eq!(Viewport::reveal_alpha(&pausing, 0.), 0.);
+        assert_eq!(
+            Viewport::reveal_alpha(&pausing, Viewport::REVEAL_FADE / 2.),
+            0.5
+        );
+        assert_eq!(Viewport::reveal_alpha(&pausing, Viewport::REVEAL_FADE), 1.);
+        assert_eq!(Viewport::reveal_alpha(&pausing, 1.), 1.);
+        assert_eq!(Viewport::reveal_alpha(&shifting, 0.), 1.);
+        assert_eq!(
+            Viewport::reveal_alpha(&shifting, Viewport::REVEAL_FADE / 2.),
+            0.5
+        );
+        assert_eq!(Viewport::reveal_alpha(&shifting, 1.), 0.);
+        assert_eq!(Viewport::reveal_alpha(&AnimStep::Scaling, 1.), 0.);
+
+        let things = [Thing::new("Blink", 0.3), Thing::new("Hour", 3600.)];
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 1.;
+        viewport.animation.step = AnimStep::Pausing(0.);
+        assert_eq!(viewport.reveal(), Some((0, 1.)));
+        viewport.prev_shift = 1.;
+        viewport.animation.step = AnimStep::Shifting(0.);
+        assert_eq!(viewport.reveal(), None);
+        viewport.animation.step = AnimStep::Scaling;
+        assert_eq!(viewport.reveal(), None);
+
+        let area = Viewport::reveal_area(Size::new(1000., 700.), 100.);
+        assert_eq!(area, Rect::new(100., 400., 900., 600.));
+    }
+
+    #[test]
+    fn test_tiny_height_is_computed_before_clamping() {
+        let things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Ten universes", 4.35e18),
+        ];
+        let mut viewport = Viewport::init(&things);
+        viewport.scale = 15. - viewport.decades as f64 + Viewport::MAX_HEIGHT.log10();
+        let height = viewport.would_be_height(&things[0]);
+        assert!(height.relative_eq(&ENumber::new(3., -16), 1e-9));
+        let huge = viewport.would_be_height(&things[2]);
+        assert!(huge.relative_eq(&ENumber::from(4350.), 1e-9));
+        let clamped = things[2]
+            .value
+            .inner()
+            .to_scale(viewport.render_scale(), Viewport::MAX_HEIGHT);
+        assert_eq!(clamped, Viewport::MAX_HEIGHT);
+
+        assert_eq!(viewport.tiny_height(&things[0], 1.), None);
+        viewport.visited.extend(things.iter().map(|thing| thing.id));
+        assert!(viewport.tiny_height(&things[0], 1.).is_some());
+        assert!(viewport.tiny_height(&things[1], 1.).is_some());
+        assert_eq!(viewport.tiny_height(&things[2], 1.), None);
+        assert_eq!(
+            Viewport::tiny_label(height),
+            "too small to show at this scale (would be 3×10⁻¹⁶ px)"
+        );
+        assert_eq!(
+            Viewport::tiny_label(ENumber::new(1., -14)),
+            "too small to show at this scale (would be 10⁻¹⁴ px)"
+        );
+    }
+
+    #[test]
+    fn test_tab_moves_keyboard_focus_with_wraparound() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", 86400.),
+            Thing::new("Year", 31556952.),
+        ];
+        let mut viewport = Viewport::init(&things);
+        assert_eq!(viewport.focus_announcement(&things), None);
+        let focused = (0..4)
+            .map(|_| viewport.move_focus(&things, None, 1))
+            .collect::<Vec<_>>();
+        assert_eq!(focused, [Some(0), Some(1), Some(2), Some(0)]);
+        assert_eq!(viewport.focused_index(), Some(0));
+        assert!(!viewport.animation.active);
+        assert_eq!(viewport.move_focus(&things, None, -1), Some(2));
+        assert_eq!(viewport.focused_index(), Some(2));
+        assert_eq!(
+            viewport.focus_announcement(&things).as_deref(),
+            Some("Bar 3 of 3: Year, 365.2 d")
+        );
+
+        let mut viewport = Viewport::init(&things);
+        assert_eq!(viewport.move_focus(&things, None, -1), Some(2));
+        assert_eq!(viewport.move_focus(&[], None, 1), None);
+        assert_eq!(viewport.keyboard_focus, None);
+    }
+
+    #[test]
+    fn test_panel_window_follows_focus() {
+        assert_eq!(Viewport::panel_window(0, None), 0..0);
+        assert_eq!(Viewport::panel_window(5, Some(4)), 0..5);
+        let rows = Viewport::PANEL_ROWS;
+        assert_eq!(Viewport::panel_window(40, None), 0..rows);
+        assert_eq!(Viewport::panel_window(40, Some(3)), 0..rows);
+        assert_eq!(
+            Viewport::panel_window(40, Some(20)),
+            20 - rows / 2..20 - rows / 2 + rows
+        );
+        assert_eq!(Viewport::panel_window(40, Some(39)), 40 - rows..40);
+        for focused in 0..40 {
+            assert!(Viewport::panel_window(40, Some(focused)).contains(&focused));
+        }
+    }
+
+    #[test]
+    fn test_pin_alpha_schedule() {
+        assert_eq!(Viewport::pin_alpha(0.), 0.);
+        assert_eq!(Viewport::pin_alpha(1.), 0.);
+        assert_eq!(Viewport::pin_alpha(0.5), Viewport::PIN_ALPHA);
+        assert!(Viewport::pin_alpha(0.05) < Viewport::pin_alpha(0.1));
+        assert!(Viewport::pin_alpha(0.95) < Viewport::pin_alpha(0.9));
+    }
+
+    #[test]
+    fn test_ghost_only_while_shifting() {
+        let mut viewport = Viewport::init(&[]);
+        viewport.motion_trail = true;
+        viewport.animation.active = true;
+        viewport.last_shift = 0.4;
+        viewport.shift = 0.5;
+        assert_eq!(viewport.ghost_shift(), Some(0.4));
+
+        for step in [
+            AnimStep::Idle(0.),
+            AnimStep::Scaling,
+            AnimStep::Slowing(0.),
+            AnimStep::Pausing(0.),
+            AnimStep::Finished,
+        ] {
+            viewport.animation.step = step;
+            assert_eq!(viewport.ghost_shift(), None);
+        }
+
+        viewport.animation.step = AnimStep::Shifting(0.16);
+        viewport.animation.active = false;
+        assert_eq!(viewport.ghost_shift(), None);
+
+        viewport.animation.active = true;
+        viewport.motion_trail = false;
+        assert_eq!(viewport.ghost_shift(), None);
+    }
+
+    #[test]
+    fn test_settled_camera_centers_focused_bar() {
+        let things = [
+            Thing::new("Blink", 0.3),
+            Thing::new("Week", 604800.),
+            Thing::new("Year", 31556952.),
+        ];
+        let size = Size::new(900., 600.);
+        let mut viewport = Viewport::init(&things);
+        for direction in [Direction::Rightward, Direction::Leftward] {
+            viewport.direction = direction;
+            for (index, thing) in things.iter().enumerate() {
+                viewport.shift = index as f64 + 1.;
+                let half_size = viewport.resize(size);
+                let world_camera =
+                    Affine::FLIP_Y.then_translate(half_size) * viewport.camera.inverse();
+                let position = thing.position(index, viewport.scale, half_size, direction);
+                assert_eq!((world_camera * position.to_point()).x, half_size.x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_direction_flips_bars_and_shift() {
+        let thing = Thing::new("Week", 604800.);
+        let half_size = Vec2::new(400., 300.);
+        for index in 0..4 {
+            let shift = index as f64 + 1.;
+            let screen_x = |direction| {
+                let bar = thing.position(index, 0., half_size, direction).x;
+                let camera = Viewport::camera_position(direction, shift, half_size).x;
+                bar - camera
+            };
+            assert_eq!(
+                thing.position(index, 0., half_size, Direction::Rightward).x,
+                -thing.position(index, 0., half_size, Direction::Leftward).x
+            );
+            assert_eq!(
+                Viewport::camera_position(Direction::Rightward, shift, half_size).x,
+                -Viewport::camera_position(Direction::Leftward, shift, half_size).x
+            );
+            assert_eq!(
+                screen_x(Direction::Rightward),
+                -screen_x(Direction::Leftward)
+            );
+        }
     }
 }
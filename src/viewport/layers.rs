@@ -0,0 +1,1382 @@
+use xilem::TextAlign;
+use xilem::masonry::core::{BrushIndex, render_text};
+use xilem::masonry::parley::{FontContext, GenericFamily, LayoutContext};
+use xilem::vello::Scene;
+use xilem::vello::kurbo::{Affine, BezPath, Point, Rect, RoundedRect, Shape, Size, Stroke, Vec2};
+use xilem::vello::peniko::Fill;
+
+use crate::comparison::{Bar, Side, bars, slot_count};
+use crate::dataset::Dataset;
+use crate::math::ENumber;
+use crate::settings::Settings;
+use crate::summary::describe_jump;
+use crate::thing::{BarDraw, NameDraw, Thing, ValueDraw};
+use crate::units::TimeScale;
+use crate::utils::{CanvasSpaces, LineParams, TextParams, ignore_x, layout_text, stroke_line};
+use crate::viewport::{MenuItem, Spacing, Viewport};
+
+// Layers are painted back to front in `Layer::ORDER`. Anything text-like
+// goes into `Labels` so that no bar, band or footer painted later can cover it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Grid,
+    Bands,
+    Bars,
+    Footer,
+    Axis,
+    Labels,
+    Focus,
+}
+
+impl Layer {
+    pub const ORDER: [Layer; 7] = [
+        Layer::Grid,
+        Layer::Bands,
+        Layer::Bars,
+        Layer::Footer,
+        Layer::Axis,
+        Layer::Labels,
+        Layer::Focus,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawOp {
+    Gridline,
+    Ghost(usize),
+    Reference,
+    Bar(usize),
+    TinyMarker(usize),
+    Pin(usize),
+    Footer,
+    AxisLine,
+    Ruler,
+    ScaleBar,
+    Name(usize),
+    Value(usize),
+    Caption,
+    Transition,
+    Reveal(usize),
+    TinyLabel(usize),
+    MarginValue(usize),
+    Title,
+    FooterCredit,
+    FooterCaption(usize),
+    Glow(usize),
+    Ring(usize),
+    Crossfade,
+    MinorLabel,
+    Tooltip(usize),
+    Menu,
+}
+
+pub struct Painter<'a> {
+    pub scene: &'a mut Scene,
+    pub fcx: &'a mut FontContext,
+    pub lcx: &'a mut LayoutContext<BrushIndex>,
+    layer: Layer,
+    ops: Option<Vec<(Layer, DrawOp)>>,
+}
+
+impl<'a> Painter<'a> {
+    pub fn new(
+        scene: &'a mut Scene,
+        fcx: &'a mut FontContext,
+        lcx: &'a mut LayoutContext<BrushIndex>,
+    ) -> Self {
+        Self {
+            scene,
+            fcx,
+            lcx,
+            layer: Layer::Grid,
+            ops: None,
+        }
+    }
+
+    pub fn recording(
+        scene: &'a mut Scene,
+        fcx: &'a mut FontContext,
+        lcx: &'a mut LayoutContext<BrushIndex>,
+    ) -> Self {
+        Self {
+            ops: Some(Vec::new()),
+            ..Self::new(scene, fcx, lcx)
+        }
+    }
+
+    pub fn ops(&self) -> &[(Layer, DrawOp)] {
+        self.ops.as_deref().unwrap_or_default()
+    }
+
+    fn record(&mut self, op: DrawOp) {
+        if let Some(ops) = &mut self.ops {
+            ops.push((self.layer, op));
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct Titles<'a> {
+    pub project: &'a str,
+    pub name: &'a str,
+    pub author: &'a str,
+    pub axis_title: &'a str,
+    pub chart_title: bool,
+}
+
+pub struct Frame<'a> {
+    viewport: &'a Viewport,
+    things: &'a [Thing],
+    secondary: Option<&'a [Thing]>,
+    bars: Vec<Bar<'a>>,
+    settings: &'a Settings,
+    titles: Titles<'a>,
+    pane: Side,
+    size: Size,
+    half_size: Vec2,
+    spaces: CanvasSpaces,
+    labels_visible: bool,
+}
+
+impl Viewport {
+    // The bars a frame paints, staggered and culled, so that hit tests and
+    // snapshots see the same list as the painter.
+    pub fn frame_bars<'a>(
+        &self,
+        (things, secondary): (&'a [Thing], Option<&'a [Thing]>),
+        settings: &Settings,
+        pane: Side,
+    ) -> Vec<Bar<'a>> {
+        let mut bars = bars(things, secondary);
+        if pane == Side::Secondary {
+            for bar in &mut bars {
+                bar.side = Side::Secondary;
+            }
+        }
+        self.stagger(&mut bars);
+        let visible = self.visible_slots(slot_count(things, secondary), settings.max_bars);
+        bars.retain(|bar| visible.contains(&bar.index));
+        bars
+    }
+
+    // Takes on the layout settings. The state does this before every view
+    // rebuild, so that painting only reads the viewport.
+    pub fn apply_settings(
+        &mut self,
+        (things, secondary): (&[Thing], Option<&[Thing]>),
+        settings: &Settings,
+    ) {
+        self.footer_height = settings.footer_height;
+        self.orientation = settings.bar_style.orientation;
+        self.baseline = settings.bar_style.baseline;
+        self.set_spacing(things, secondary, settings.spacing);
+    }
+
+    // Eases towards the canvas size, the one input only the painter knows.
+    pub fn fit(&mut self, size: Size) -> Vec2 {
+        self.resize(self.orientation.transpose(size))
+    }
+
+    pub fn frame<'a>(
+        &'a self,
+        (things, secondary): (&'a [Thing], Option<&'a [Thing]>),
+        titles: Titles<'a>,
+        settings: &'a Settings,
+        size: Size,
+        pane: Side,
+    ) -> Frame<'a> {
+        let half_size = self.layout_half_size(size);
+        let bars = self.frame_bars((things, secondary), settings, pane);
+        let labels_visible = Self::labels_visible(&self.animation.step, self.compact_labels);
+        let spaces = CanvasSpaces::with_world_trans(
+            self.orientation.world_trans(half_size),
+            self.camera.inverse(),
+        );
+        Frame {
+            viewport: self,
+            things,
+            secondary,
+            bars,
+            settings,
+            titles,
+            pane,
+            size,
+            half_size,
+            spaces,
+            labels_visible,
+        }
+    }
+}
+
+impl Frame<'_> {
+    pub fn paint(&self, painter: &mut Painter) {
+        for layer in Layer::ORDER {
+            painter.layer = layer;
+            match layer {
+                Layer::Grid => self.grid_layer(painter),
+                Layer::Bands => self.bands_layer(painter),
+                Layer::Bars => self.bars_layer(painter),
+                Layer::Footer => self.footer_layer(painter),
+                Layer::Axis => self.axis_layer(painter),
+                Layer::Labels => self.labels_layer(painter),
+                Layer::Focus => self.focus_layer(painter),
+            }
+        }
+    }
+
+    fn ui_scale(&self) -> f64 {
+        self.settings.ui_scale
+    }
+
+    fn bar_position(&self, bar: &Bar) -> Vec2 {
+        self.viewport.bar_position(bar, self.half_size)
+    }
+
+    fn reference(&self) -> Option<(usize, f64)> {
+        let id = self.viewport.reference?;
+        let index = self.things.iter().position(|thing| thing.id == id)?;
+        let height = self
+            .viewport
+            .thing_position(&self.things[index], index, self.half_size)
+            .y;
+        Some((index, self.viewport.baseline.gridline(height)?))
+    }
+
+    fn tiny_height(&self, bar: &Bar) -> Option<ENumber> {
+        self.viewport
+            .tiny_height(bar.thing, self.settings.tiny_threshold)
+            .filter(|_| self.settings.tiny_markers)
+    }
+
+    // a short tick with a downward arrow above it, and the screen bounds of both
+    fn tiny_marker(&self, bar: &Bar) -> (Affine, Rect, BezPath, Rect) {
+        let ui_scale = self.ui_scale();
+        let x = self.bar_position(bar).x;
+        let transform =
+            self.spaces.world_camera * self.viewport.baseline.transform(Viewport::TINY_TICK_HEIGHT);
+        let tick = Rect::new(
+            x - bar.width / 2.,
+            0.,
+            x + bar.width / 2.,
+            Viewport::TINY_TICK_HEIGHT,
+        );
+        let tip = Viewport::TINY_TICK_HEIGHT + Viewport::TINY_ARROW_GAP * ui_scale;
+        let size = Viewport::TINY_ARROW_SIZE * ui_scale;
+        let mut arrow = BezPath::new();
+        arrow.move_to((x, tip));
+        arrow.line_to((x - size, tip + 2. * size));
+        arrow.line_to((x + size, tip + 2. * size));
+        arrow.close_path();
+        let bounds = transform.transform_rect_bbox(tick.union(arrow.bounding_box()));
+        (transform, tick, arrow, bounds)
+    }
+
+    fn pin_x(&self, index: usize) -> f64 {
+        self.viewport
+            .thing_position(&self.things[index], index, self.half_size)
+            .x
+    }
+
+    // visible logarithmic scale lines and their labels
+    fn grid_layer(&self, painter: &mut Painter) {
+        let Self {
+            viewport,
+            settings,
+            half_size,
+            ..
+        } = *self;
+        let (ui_scale, camera) = (self.ui_scale(), self.spaces.camera);
+        let world_trans = self.spaces.world_trans;
+        let render_scale = viewport.render_scale();
+        let span = viewport.decades as f64;
+        let whole_decades = Viewport::major_step(span) == 1.;
+        for scale in Viewport::major_exponents_in(viewport.scale, span) {
+            let major_pos = ENumber::from_exp(scale).to_scale(render_scale, Viewport::MAX_HEIGHT);
+            let Some(major_line) = viewport.baseline.gridline(major_pos) else {
+                continue;
+            };
+            let major_alpha = Viewport::line_alpha(scale, viewport.scale);
+            let major_label_alpha = Viewport::major_label_alpha(
+                major_alpha,
+                major_pos,
+                ui_scale,
+                settings.opaque_axis_labels,
+            );
+
+            // major label
+            let major_label = Viewport::major_label(scale, settings.axis_labels);
+            let major_label_params = TextParams::new(&major_label, 14. * ui_scale as f32);
+            let major_text_layout = layout_text(painter.fcx, painter.lcx, &major_label_params);
+            render_text(
+                painter.scene,
+                self.spaces.margin_text((
+                    -half_size.x + 15. * ui_scale,
+                    major_line + major_text_layout.height() as f64 / 2.,
+                )),
+                &major_text_layout,
+                &[Viewport::MAJOR_COLOR.with_alpha(major_label_alpha).into()],
+                true,
+            );
+
+            // major lines
+            let major_line_params = LineParams::horizontal(major_line)
+                .color(Viewport::MAJOR_COLOR.with_alpha(major_alpha))
+                .width(0.8 * ui_scale)
+                .padding(major_text_layout.width() as f64 + 30. * ui_scale, 0.);
+            stroke_line(
+                painter.scene,
+                world_trans,
+                camera,
+                half_size,
+                &major_line_params,
+            );
+            painter.record(DrawOp::Gridline);
+
+            // minor lines
+            let minors = Viewport::minor_gridlines(
+                scale,
+                settings.minor_lines && whole_decades,
+                settings.minor_labels,
+            );
+            for (minor, minor_label) in minors {
+                let minor_pos =
+                    ENumber::from_exp(minor).to_scale(render_scale, Viewport::MAX_HEIGHT);
+                let minor_line = viewport.baseline.gridline(minor_pos).unwrap_or_default();
+                let minor_alpha = Viewport::line_alpha(minor, viewport.scale);
+                let minor_line_params = LineParams::horizontal(minor_line)
+                    .color(Viewport::MINOR_LINE_COLOR.with_alpha(minor_alpha))
+                    .width(0.2 * ui_scale);
+                let Some(minor_label) = minor_label else {
+                    stroke_line(
+                        painter.scene,
+                        world_trans,
+                        camera,
+                        half_size,
+                        &minor_line_params,
+                    );
+                    painter.record(DrawOp::Gridline);
+                    continue;
+                };
+
+                // minor label
+                let minor_label_params = TextParams::new(&minor_label, 11. * ui_scale as f32);
+                let minor_text_layout = layout_text(painter.fcx, painter.lcx, &minor_label_params);
+                render_text(
+                    painter.scene,
+                    self.spaces.margin_text((
+                        -half_size.x + 15. * ui_scale,
+                        minor_line + minor_text_layout.height() as f64 / 2.,
+                    )),
+                    &minor_text_layout,
+                    &[Viewport::MINOR_LABEL_COLOR
+                        .with_alpha(minor_alpha * Viewport::label_alpha(minor_pos, ui_scale))
+                        .into()],
+                    true,
+                );
+                let minor_line_params = minor_line_params
+                    .padding(minor_text_layout.width() as f64 + 30. * ui_scale, 0.);
+                stroke_line(
+                    painter.scene,
+                    world_trans,
+                    camera,
+                    half_size,
+                    &minor_line_params,
+                );
+                painter.record(DrawOp::Gridline);
+            }
+        }
+    }
+
+    // motion trail and reference line, behind the bars
+    fn bands_layer(&self, painter: &mut Painter) {
+        let viewport = self.viewport;
+        if let Some(ghost_shift) = viewport.ghost_shift() {
+            let ghost_camera = viewport
+                .camera
+                .with_translation(viewport.shift_camera_position(ghost_shift, self.half_size))
+                .inverse();
+            for bar in &self.bars {
+                let alpha = Thing::alpha(bar.index, ghost_shift) * Viewport::GHOST_ALPHA;
+                let draw = BarDraw {
+                    color: bar.side.bar_color(),
+                    width: bar.width,
+                    alpha,
+                    style: self.settings.bar_style,
+                };
+                bar.thing.render_bar(
+                    self.bar_position(bar),
+                    draw,
+                    painter.scene,
+                    self.spaces.world_trans * ghost_camera,
+                );
+                painter.record(DrawOp::Ghost(bar.index));
+            }
+        }
+
+        if let Some((_, line)) = self.reference() {
+            let line_params = LineParams::horizontal(line)
+                .color(Viewport::REFERENCE_COLOR)
+                .width(self.ui_scale());
+            stroke_line(
+                painter.scene,
+                self.spaces.world_trans,
+                self.spaces.camera,
+                self.half_size,
+                &line_params,
+            );
+            painter.record(DrawOp::Reference);
+        }
+    }
+
+    // things and the pinned previous thing
+    fn bars_layer(&self, painter: &mut Painter) {
+        let viewport = self.viewport;
+        let ui_scale = self.ui_scale();
+        for bar in &self.bars {
+            let alpha = Thing::alpha(bar.index, viewport.shift) * viewport.focus_alpha(bar.index);
+            if self.tiny_height(bar).is_some() {
+                let (transform, tick, arrow, _) = self.tiny_marker(bar);
+                let color = bar.side.bar_color().with_alpha(alpha);
+                painter
+                    .scene
+                    .fill(Fill::NonZero, transform, color, None, &tick);
+                painter
+                    .scene
+                    .fill(Fill::NonZero, transform, color, None, &arrow);
+                painter.record(DrawOp::TinyMarker(bar.index));
+                continue;
+            }
+            let draw = BarDraw {
+                color: bar.side.bar_color(),
+                width: bar.width,
+                alpha,
+                style: self.settings.bar_style,
+            };
+            bar.thing.render_bar(
+                self.bar_position(bar),
+                draw,
+                painter.scene,
+                self.spaces.world_camera,
+            );
+            painter.record(DrawOp::Bar(bar.index));
+        }
+
+        if let Some(pin) = viewport.pinned_previous(self.things) {
+            let x = self.pin_x(pin.index);
+            let position = Vec2::new(x, Viewport::PIN_HEIGHT);
+            let draw = BarDraw {
+                color: Thing::BAR_COLOR,
+                width: Thing::BAR_WIDTH,
+                alpha: pin.alpha,
+                style: self.settings.bar_style,
+            };
+            self.things[pin.index].render_bar(
+                position,
+                draw,
+                painter.scene,
+                self.spaces.world_camera,
+            );
+
+            let bracket_x = x + Thing::BAR_HALF + 6. * ui_scale;
+            let tick = 4. * ui_scale;
+            let mut bracket = BezPath::new();
+            bracket.move_to((bracket_x - tick, 0.));
+            bracket.line_to((bracket_x, 0.));
+            bracket.line_to((bracket_x, Viewport::PIN_HEIGHT));
+            bracket.line_to((bracket_x - tick, Viewport::PIN_HEIGHT));
+            painter.scene.stroke(
+                &Stroke::new(ui_scale),
+                self.spaces.world_camera,
+                Thing::VALUE_COLOR.with_alpha(pin.alpha),
+                None,
+                &bracket,
+            );
+            painter.record(DrawOp::Pin(pin.index));
+        }
+    }
+
+    // area under axis line
+    fn footer_layer(&self, painter: &mut Painter) {
+        let size = self.size;
+        let rect = Rect::new(-size.width, 0., size.width, -size.height);
+        painter.scene.fill(
+            Fill::NonZero,
+            self.spaces.world_trans * ignore_x(self.spaces.camera),
+            self.settings.palette.background,
+            None,
+            &rect,
+        );
+        painter.record(DrawOp::Footer);
+    }
+
+    // axis line, exponent ruler and scale bar
+    fn axis_layer(&self, painter: &mut Painter) {
+        let Self {
+            viewport,
+            settings,
+            half_size,
+            ..
+        } = *self;
+        let ui_scale = self.ui_scale();
+        let x_line_params = LineParams::horizontal(0.)
+            .color(Thing::VALUE_COLOR)
+            .width(0.8 * ui_scale);
+        stroke_line(
+            painter.scene,
+            self.spaces.world_trans,
+            self.spaces.camera,
+            half_size,
+            &x_line_params,
+        );
+        painter.record(DrawOp::AxisLine);
+
+        if viewport.spacing == Spacing::Magnitude {
+            let ruler_y = Viewport::RULER_OFFSET * ui_scale - viewport.footer_height;
+            let tick = 4. * ui_scale;
+            let sign = viewport.direction.sign();
+            let ticks = viewport.ruler_ticks(self.things, self.secondary);
+            let mut ruler = BezPath::new();
+            if let (Some(&(first, _)), Some(&(last, _))) = (ticks.first(), ticks.last()) {
+                ruler.move_to((sign * (half_size.x + first), ruler_y));
+                ruler.line_to((sign * (half_size.x + last), ruler_y));
+            }
+            for &(offset, exponent) in &ticks {
+                let x = sign * (half_size.x + offset);
+                ruler.move_to((x, ruler_y));
+                ruler.line_to((x, ruler_y + tick));
+
+                let tick_label = Viewport::major_label(exponent as f64, settings.axis_labels);
+                let tick_params = TextParams::new(&tick_label, 10. * ui_scale as f32);
+                let tick_layout = layout_text(painter.fcx, painter.lcx, &tick_params);
+                render_text(
+                    painter.scene,
+                    self.spaces
+                        .text_at((x - tick_layout.width() as f64 / 2., ruler_y - 2. * ui_scale)),
+                    &tick_layout,
+                    &[Viewport::MINOR_LABEL_COLOR.into()],
+                    true,
+                );
+            }
+            painter.scene.stroke(
+                &Stroke::new(ui_scale),
+                self.spaces.world_camera,
+                Viewport::MINOR_LABEL_COLOR,
+                None,
+                &ruler,
+            );
+            painter.record(DrawOp::Ruler);
+        }
+
+        let scale_bar = settings
+            .scale_bar
+            .then(|| Viewport::scale_bar(viewport.render_scale(), Viewport::SCALE_BAR_BUDGET))
+            .flatten();
+        if let Some((unit_label, length)) = scale_bar {
+            let bracket_x = half_size.x - 20. * ui_scale;
+            let tick = 4. * ui_scale;
+            let mut bracket = BezPath::new();
+            bracket.move_to((bracket_x - tick, 0.));
+            bracket.line_to((bracket_x, 0.));
+            bracket.line_to((bracket_x, length));
+            bracket.line_to((bracket_x - tick, length));
+            painter.scene.stroke(
+                &Stroke::new(ui_scale),
+                self.spaces.world_trans * ignore_x(self.spaces.camera),
+                Viewport::MAJOR_COLOR,
+                None,
+                &bracket,
+            );
+
+            let unit_params = TextParams::new(&unit_label, 12. * ui_scale as f32);
+            let unit_layout = layout_text(painter.fcx, painter.lcx, &unit_params);
+            render_text(
+                painter.scene,
+                self.spaces.margin_text((
+                    bracket_x - tick - 4. * ui_scale - unit_layout.width() as f64,
+                    length / 2. + unit_layout.height() as f64 / 2.,
+                )),
+                &unit_layout,
+                &[Viewport::MAJOR_COLOR.into()],
+                true,
+            );
+            painter.record(DrawOp::ScaleBar);
+        }
+    }
+
+    // names, values, captions and titles
+    fn labels_layer(&self, painter: &mut Painter) {
+        let Self {
+            viewport,
+            settings,
+            half_size,
+            titles,
+            ..
+        } = *self;
+        let ui_scale = self.ui_scale();
+        if self.labels_visible {
+            for bar in &self.bars {
+                let position = self.bar_position(bar);
+                let alpha = Thing::alpha(bar.index, viewport.shift);
+                let bar_draw = BarDraw {
+                    color: bar.side.bar_color(),
+                    width: bar.width,
+                    alpha,
+                    style: settings.bar_style,
+                };
+                let (name_color, value_color) = bar.thing.label_colors(
+                    position,
+                    bar_draw,
+                    bar.side.value_color(),
+                    ui_scale,
+                    &settings.palette,
+                );
+                let name_scale = ui_scale * viewport.focus_text_scale(bar.index);
+                let name_draw = NameDraw {
+                    color: name_color,
+                    width: bar.width,
+                    alpha: alpha * viewport.focus_alpha(bar.index),
+                    row: bar.row,
+                    ui_scale: name_scale,
+                    style: settings.bar_style,
+                };
+                bar.thing.render_name(
+                    position,
+                    name_draw,
+                    painter.fcx,
+                    painter.lcx,
+                    painter.scene,
+                    self.spaces.text_camera,
+                );
+                painter.record(DrawOp::Name(bar.index));
+                let value_draw = ValueDraw {
+                    color: value_color,
+                    width: bar.width,
+                    alpha,
+                    row: bar.row,
+                    settings: *settings,
+                };
+                bar.thing.render_value(
+                    position,
+                    value_draw,
+                    painter.fcx,
+                    painter.lcx,
+                    painter.scene,
+                    self.spaces.text_camera,
+                );
+                painter.record(DrawOp::Value(bar.index));
+            }
+        }
+
+        // reference label
+        if let Some((index, line)) = self.reference() {
+            let reference_label = format!("reference: {}", self.things[index].name);
+            let reference_params = TextParams::new(&reference_label, 12. * ui_scale as f32);
+            let reference_layout = layout_text(painter.fcx, painter.lcx, &reference_params);
+            render_text(
+                painter.scene,
+                self.spaces.margin_text((
+                    half_size.x - reference_layout.width() as f64 - 15. * ui_scale,
+                    line + reference_layout.height() as f64 + 2. * ui_scale,
+                )),
+                &reference_layout,
+                &[Viewport::REFERENCE_COLOR.into()],
+                true,
+            );
+            painter.record(DrawOp::Caption);
+        }
+
+        // values in the margin
+        if settings.margin_values {
+            let gap = Viewport::MARGIN_VALUE_GAP * ui_scale;
+            for (bar, _, y) in viewport.margin_values(&self.bars, half_size, gap) {
+                let text = bar
+                    .thing
+                    .value
+                    .fmt_styled(bar.thing.display_unit, settings.format_style());
+                let params = TextParams::new(&text, 11. * ui_scale as f32);
+                let layout = layout_text(painter.fcx, painter.lcx, &params);
+                render_text(
+                    painter.scene,
+                    self.spaces.margin_text((
+                        -half_size.x + Viewport::MARGIN_VALUE_X * ui_scale,
+                        y + layout.height() as f64 / 2.,
+                    )),
+                    &layout,
+                    &[Thing::VALUE_COLOR.into()],
+                    true,
+                );
+                painter.record(DrawOp::MarginValue(bar.index));
+            }
+        }
+
+        // pinned previous thing
+        if let Some(pin) = viewport.pinned_previous(self.things) {
+            let bracket_x = self.pin_x(pin.index) + Thing::BAR_HALF + 6. * ui_scale;
+            let tick = 4. * ui_scale;
+            let pin_text = format!("{}\n{}", pin.label, pin.height_label);
+            let pin_params = TextParams::new(&pin_text, 12. * ui_scale as f32);
+            let pin_layout = layout_text(painter.fcx, painter.lcx, &pin_params);
+            render_text(
+                painter.scene,
+                self.spaces.text_at((
+                    bracket_x + tick,
+                    Viewport::PIN_HEIGHT / 2. + pin_layout.height() as f64 / 2.,
+                )),
+                &pin_layout,
+                &[Thing::VALUE_COLOR.with_alpha(pin.alpha).into()],
+                true,
+            );
+            painter.record(DrawOp::Caption);
+        }
+
+        // ratio between the outgoing and incoming things
+        if settings.explain_transitions
+            && let Some((from, to, progress)) = viewport.transition()
+            && let (Some(outgoing), Some(incoming)) = (self.things.get(from), self.things.get(to))
+            && let Some(note) = describe_jump(&outgoing.value, &incoming.value)
+        {
+            let note_params = TextParams::new(&note, 20. * ui_scale as f32).weight(600.);
+            let note_layout = layout_text(painter.fcx, painter.lcx, &note_params);
+            let middle = (self.pin_x(from) + self.pin_x(to)) / 2.;
+            let x = (self.spaces.world_camera * Point::new(middle, 0.)).x;
+            render_text(
+                painter.scene,
+                Affine::translate((
+                    x - note_layout.width() as f64 / 2.,
+                    self.size.height * Viewport::TRANSITION_NOTE_Y,
+                )),
+                &note_layout,
+                &[settings
+                    .palette
+                    .accent
+                    .with_alpha(Viewport::transition_alpha(progress))
+                    .into()],
+                true,
+            );
+            painter.record(DrawOp::Transition);
+        }
+
+        // narration of the focused thing, wrapped and clipped to the lower third
+        if let Some((index, alpha)) = viewport.reveal()
+            && let Some(thing) = self.things.get(index)
+            && !thing.reveal.is_empty()
+        {
+            let area = Viewport::reveal_area(self.size, settings.footer_height);
+            let reveal_params = TextParams::new(&thing.reveal, 22. * ui_scale as f32)
+                .max_advance(area.width() as f32)
+                .alignment(TextAlign::Center);
+            let reveal_layout = layout_text(painter.fcx, painter.lcx, &reveal_params);
+            painter.scene.push_clip_layer(Affine::IDENTITY, &area);
+            render_text(
+                painter.scene,
+                Affine::translate(area.origin().to_vec2()),
+                &reveal_layout,
+                &[Viewport::MAJOR_COLOR.with_alpha(alpha).into()],
+                true,
+            );
+            painter.scene.pop_layer();
+            painter.record(DrawOp::Reveal(index));
+        }
+
+        // what a tiny marker stands for, on hover or keyboard focus
+        for bar in &self.bars {
+            let Some(height) = self.tiny_height(bar) else {
+                continue;
+            };
+            let (.., bounds) = self.tiny_marker(bar);
+            let hovered = viewport.hover.is_some_and(|point| {
+                bounds
+                    .inflate(
+                        Viewport::MINOR_HOVER_DISTANCE,
+                        Viewport::MINOR_HOVER_DISTANCE,
+                    )
+                    .contains(point)
+            });
+            if !hovered && viewport.keyboard_focus != Some(bar.index) {
+                continue;
+            }
+            let tiny_label = Viewport::tiny_label(height);
+            let tiny_params = TextParams::new(&tiny_label, 12. * ui_scale as f32);
+            let tiny_layout = layout_text(painter.fcx, painter.lcx, &tiny_params);
+            render_text(
+                painter.scene,
+                Affine::translate((
+                    bounds.center().x - tiny_layout.width() as f64 / 2.,
+                    bounds.y0 - tiny_layout.height() as f64 - 4. * ui_scale,
+                )),
+                &tiny_layout,
+                &[Thing::VALUE_COLOR.into()],
+                true,
+            );
+            painter.record(DrawOp::TinyLabel(bar.index));
+        }
+
+        // axis title
+        let axis_title_params = TextParams::new(
+            Dataset::axis_title(titles.axis_title),
+            14. * ui_scale as f32,
+        )
+        .weight(600.);
+        let axis_title_layout = layout_text(painter.fcx, painter.lcx, &axis_title_params);
+        render_text(
+            painter.scene,
+            Affine::translate((15. * ui_scale, 15. * ui_scale)),
+            &axis_title_layout,
+            &[Viewport::MAJOR_COLOR.into()],
+            true,
+        );
+        painter.record(DrawOp::Title);
+
+        // chart title
+        if titles.chart_title && !titles.name.trim().is_empty() {
+            let title_params = TextParams::new(titles.name.trim(), 24. * ui_scale as f32)
+                .family(GenericFamily::Serif)
+                .weight(600.)
+                .max_advance(self.size.width as f32 * 0.8)
+                .alignment(TextAlign::Center);
+            let title_layout = layout_text(painter.fcx, painter.lcx, &title_params);
+            render_text(
+                painter.scene,
+                Affine::translate((
+                    half_size.x - title_layout.width() as f64 / 2.,
+                    15. * ui_scale,
+                )),
+                &title_layout,
+                &[Thing::NAME_COLOR.into()],
+                true,
+            );
+            painter.record(DrawOp::Title);
+        }
+
+        self.footer_text(painter);
+    }
+
+    // credit line on the left and live caption on the right of the footer
+    fn footer_text(&self, painter: &mut Painter) {
+        let Self {
+            viewport,
+            settings,
+            size,
+            titles,
+            ..
+        } = *self;
+        let ui_scale = self.ui_scale();
+        let margin = 12. * ui_scale;
+        let bottom = size.height - margin;
+
+        let credit = Dataset::credit(titles.project, titles.name, titles.author);
+        if settings.footer_credit && !credit.is_empty() {
+            let credit_params = TextParams::new(&credit, 13. * ui_scale as f32);
+            let credit_layout = layout_text(painter.fcx, painter.lcx, &credit_params);
+            render_text(
+                painter.scene,
+                Affine::translate((15. * ui_scale, bottom - credit_layout.height() as f64)),
+                &credit_layout,
+                &[settings.palette.muted_text.into()],
+                true,
+            );
+            painter.record(DrawOp::FooterCredit);
+        }
+
+        if !settings.footer_caption {
+            return;
+        }
+        let (current_alpha, previous_alpha) = viewport.caption.alphas();
+        let captions = [
+            (viewport.caption.previous, previous_alpha),
+            (viewport.caption.current, current_alpha),
+        ];
+        for (index, alpha) in captions {
+            let Some((index, thing)) =
+                index.and_then(|index| Some((index, self.things.get(index)?)))
+            else {
+                continue;
+            };
+            if alpha <= 0. {
+                continue;
+            }
+            let caption = format!(
+                "{}  {}",
+                thing.name,
+                thing.value_text(
+                    settings.time_display,
+                    settings.epoch,
+                    settings.format_style()
+                )
+            );
+            let caption_params = TextParams::new(&caption, 28. * ui_scale as f32)
+                .family(GenericFamily::Serif)
+                .weight(600.);
+            let caption_layout = layout_text(painter.fcx, painter.lcx, &caption_params);
+            render_text(
+                painter.scene,
+                Affine::translate((
+                    size.width - caption_layout.width() as f64 - 15. * ui_scale,
+                    bottom - caption_layout.height() as f64,
+                )),
+                &caption_layout,
+                &[settings.palette.text.with_alpha(alpha).into()],
+                true,
+            );
+            painter.record(DrawOp::FooterCaption(index));
+        }
+    }
+
+    // focus rings, crossfade, tooltip and context menu
+    fn focus_layer(&self, painter: &mut Painter) {
+        let Self {
+            viewport,
+            half_size,
+            size,
+            ..
+        } = *self;
+        let ui_scale = self.ui_scale();
+        for bar in &self.bars {
+            if viewport.focused_index() == Some(bar.index) {
+                let glow = viewport
+                    .bar_bounds(bar, half_size)
+                    .inflate(3. * ui_scale, 3. * ui_scale)
+                    .to_rounded_rect(3. * ui_scale);
+                painter.scene.stroke(
+                    &Stroke::new(1.5 * ui_scale),
+                    self.spaces.world_camera,
+                    Viewport::FOCUS_GLOW_COLOR.with_alpha(0.5 * viewport.focus_amount()),
+                    None,
+                    &glow,
+                );
+                painter.record(DrawOp::Glow(bar.index));
+            }
+            if viewport.keyboard_focus == Some(bar.index) && bar.side == Side::Primary {
+                let ring = viewport
+                    .bar_bounds(bar, half_size)
+                    .inflate(6. * ui_scale, 6. * ui_scale)
+                    .to_rounded_rect(4. * ui_scale);
+                painter.scene.stroke(
+                    &Stroke::new(2. * ui_scale).with_dashes(0., [6. * ui_scale, 4. * ui_scale]),
+                    self.spaces.world_camera,
+                    Viewport::FOCUS_RING_COLOR,
+                    None,
+                    &ring,
+                );
+                painter.record(DrawOp::Ring(bar.index));
+            }
+        }
+
+        // reduced motion crossfade
+        if viewport.crossfade > 0. {
+            painter.scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                Viewport::BACKGROUND_COLOR.with_alpha(viewport.crossfade_alpha()),
+                None,
+                &size.to_rect(),
+            );
+            painter.record(DrawOp::Crossfade);
+        }
+
+        // minor line magnitude
+        let minor = viewport
+            .hover
+            .filter(|_| self.settings.minor_lines && self.settings.minor_hover_labels)
+            .and_then(|point| Some((point, viewport.minor_line_at(point, size, ui_scale)?)));
+        if let Some((point, (exponent, line))) = minor {
+            let minor_label = TimeScale::from(ENumber::from_exp(exponent)).fmt_secs();
+            let minor_params = TextParams::new(&minor_label, 11. * ui_scale as f32);
+            let minor_layout = layout_text(painter.fcx, painter.lcx, &minor_params);
+            let padding = 4. * ui_scale;
+            let line_y = (viewport.world_camera(size) * Point::new(0., line)).y;
+            let origin = Point::new(
+                point.x + 12. * ui_scale,
+                line_y - minor_layout.height() as f64 / 2. - padding,
+            );
+            let plate = RoundedRect::from_origin_size(
+                origin,
+                (
+                    minor_layout.width() as f64 + 2. * padding,
+                    minor_layout.height() as f64 + 2. * padding,
+                ),
+                4. * ui_scale,
+            );
+            painter.scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                Viewport::FOOTER_AREA_COLOR,
+                None,
+                &plate,
+            );
+            render_text(
+                painter.scene,
+                Affine::translate(origin.to_vec2() + Vec2::new(padding, padding)),
+                &minor_layout,
+                &[Viewport::MINOR_LABEL_COLOR.into()],
+                true,
+            );
+            painter.record(DrawOp::MinorLabel);
+        }
+
+        // hover tooltip
+        let hovered = viewport
+            .hover
+            .filter(|_| viewport.tooltip_visible())
+            .and_then(|point| Some((point, viewport.hit_test(&self.bars, point, size)?)));
+        if let Some((point, index)) = hovered {
+            let tooltip = self.bars[index]
+                .thing
+                .tooltip_text(self.settings.format_style());
+            let tooltip_params = TextParams::new(&tooltip, 14. * ui_scale as f32);
+            let tooltip_layout = layout_text(painter.fcx, painter.lcx, &tooltip_params);
+            let padding = 8. * ui_scale;
+            let origin = point + Vec2::new(12., 12.) * ui_scale;
+            let plate = RoundedRect::from_origin_size(
+                origin,
+                (
+                    tooltip_layout.width() as f64 + 2. * padding,
+                    tooltip_layout.height() as f64 + 2. * padding,
+                ),
+                6. * ui_scale,
+            );
+            painter.scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                Viewport::FOOTER_AREA_COLOR,
+                None,
+                &plate,
+            );
+            painter.scene.stroke(
+                &Stroke::new(1.),
+                Affine::IDENTITY,
+                Viewport::MINOR_LINE_COLOR,
+                None,
+                &plate,
+            );
+            render_text(
+                painter.scene,
+                Affine::translate(origin.to_vec2() + Vec2::new(padding, padding)),
+                &tooltip_layout,
+                &[Viewport::MAJOR_COLOR.into()],
+                true,
+            );
+            painter.record(DrawOp::Tooltip(self.bars[index].index));
+        }
+
+        // context menu
+        if let Some(menu) = viewport.context_menu.filter(|_| self.pane == Side::Primary) {
+            let plate = menu.bounds(ui_scale).to_rounded_rect(4. * ui_scale);
+            painter.scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                Viewport::FOOTER_AREA_COLOR,
+                None,
+                &plate,
+            );
+            painter.scene.stroke(
+                &Stroke::new(1.),
+                Affine::IDENTITY,
+                Viewport::MINOR_LINE_COLOR,
+                None,
+                &plate,
+            );
+            for (i, item) in MenuItem::ALL.iter().enumerate() {
+                let rect = menu.item_rect(i, ui_scale);
+                if viewport.hover.is_some_and(|point| rect.contains(point)) {
+                    painter.scene.fill(
+                        Fill::NonZero,
+                        Affine::IDENTITY,
+                        Viewport::MINOR_LINE_COLOR,
+                        None,
+                        &rect,
+                    );
+                }
+                let item_label = item.to_string();
+                let item_params = TextParams::new(&item_label, 13. * ui_scale as f32);
+                let item_layout = layout_text(painter.fcx, painter.lcx, &item_params);
+                render_text(
+                    painter.scene,
+                    Affine::translate((
+                        rect.x0 + 8. * ui_scale,
+                        rect.center().y - item_layout.height() as f64 / 2.,
+                    )),
+                    &item_layout,
+                    &[Viewport::MAJOR_COLOR.into()],
+                    true,
+                );
+            }
+            painter.record(DrawOp::Menu);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+    use crate::animation::AnimStep;
+    use crate::input::pointer::PointerAction;
+    use crate::units::{DAY, YEAR};
+
+    fn ops(viewport: &mut Viewport, things: &[Thing], settings: &Settings) -> Vec<(Layer, DrawOp)> {
+        let (mut scene, mut fcx, mut lcx) =
+            (Scene::new(), FontContext::new(), LayoutContext::new());
+        let mut painter = Painter::recording(&mut scene, &mut fcx, &mut lcx);
+        viewport.apply_settings((things, None), settings);
+        viewport.fit(Size::new(800., 600.));
+        let frame = viewport.frame(
+            (things, None),
+            Titles {
+                project: "Cosmos",
+                name: "Timescales",
+                author: "A. Author",
+                axis_title: "",
+                chart_title: true,
+            },
+            settings,
+            Size::new(800., 600.),
+            Side::Primary,
+        );
+        frame.paint(&mut painter);
+        painter.ops().to_vec()
+    }
+
+    #[test]
+    fn test_labels_are_painted_after_every_bar_and_the_footer() {
+        let things = vec![
+            Thing::new("Blink", 0.3),
+            Thing::new("Day", DAY),
+            Thing::new("Year", YEAR),
+        ];
+        let settings = Settings {
+            scale_bar: true,
+            spacing: Spacing::Magnitude,
+            footer_credit: true,
+            footer_caption: true,
+            ..Settings::default()
+        };
+        let mut viewport = Viewport::init(&things);
+        viewport.animation.step = AnimStep::Pausing(1.);
+        viewport.keyboard_focus = Some(1);
+        viewport.motion_trail = true;
+        viewport.reference = Some(things[0].id);
+        viewport.caption.update(Some(1), 0., true);
+        let ops = ops(&mut viewport, &things, &settings);
+
+        let layers = ops
+            .iter()
+            .map(|(layer, _)| Layer::ORDER.iter().position(|order| order == layer))
+            .collect::<Vec<_>>();
+        assert!(layers.is_sorted());
+        let position = |op: DrawOp| ops.iter().position(|&(_, other)| other == op);
+        let last = |matches: fn(&DrawOp) -> bool| ops.iter().rposition(|(_, op)| matches(op));
+        let first = |matches: fn(&DrawOp) -> bool| ops.iter().position(|(_, op)| matches(op));
+
+        let last_bar = last(|op| matches!(op, DrawOp::Bar(_) | DrawOp::Ghost(_))).unwrap();
+        let footer = position(DrawOp::Footer).unwrap();
+        let first_label = first(|op| matches!(op, DrawOp::Name(_) | DrawOp::Value(_))).unwrap();
+        assert!(last_bar < footer && footer < first_label);
+        assert!(position(DrawOp::Reference).unwrap() < position(DrawOp::Bar(0)).unwrap());
+        assert!(position(DrawOp::Ruler).unwrap() < first_label);
+        assert!(position(DrawOp::ScaleBar).unwrap() < first_label);
+        for index in 0..things.len() {
+            assert!(position(DrawOp::Name(index)).is_some(), "name {index}");
+            assert!(position(DrawOp::Value(index)).is_some(), "value {index}");
+        }
+        assert!(ops.contains(&(Layer::Labels, DrawOp::FooterCredit)));
+        assert!(ops.contains(&(Layer::Labels, DrawOp::FooterCaption(1))));
+        assert!(!ops.contains(&(Layer::Labels, DrawOp::FooterCaption(0))));
+        let last_label = last(|op| matches!(op, DrawOp::Title | DrawOp::FooterCaption(_))).unwrap();
+        assert!(last_label < position(DrawOp::Ring(1)).unwrap());
+        assert_eq!(ops.last().map(|&(layer, _)| layer), Some(Layer::Focus));
+    }
+
+    #[test]
+    fn test_bars_far_from_the_shift_are_culled() {
+        let things = (0..200)
+            .map(|i| Thing::new(&format!("Thing {i}"), 1.5_f64.powi(i)))
+            .collect::<Vec<_>>();
+        let settings = Settings::default();
+        let mut viewport = Viewport::init(&things);
+        viewport.shift = 120.;
+        let painted = ops(&mut viewport, &things, &settings)
+            .iter()
+            .filter_map(|&(_, op)| match op {
+                DrawOp::Bar(index) | DrawOp::Name(index) | DrawOp::Value(index) => Some(index),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let visible = viewport.visible_slots(things.len(), settings.max_bars);
+        assert_eq!(visible.len(), settings.max_bars);
+        assert!(visible.contains(&120));
+        assert!(painted.iter().all(|index| visible.contains(index)));
+        assert!(!painted.contains(&0) && !painted.contains(&199));
+        assert!(painted.contains(&120));
+
+        let few = &things[..5];
+        let mut viewport = Viewport::init(few);
+        viewport.shift = 4.;
+        let few_ops = ops(&mut viewport, few, &settings);
+        for index in 0..few.len() {
+            assert!(few_ops.contains(&(Layer::Bars, DrawOp::Bar(index))));
+        }
+    }
+
+    #[test]
+    fn test_transition_note_only_while_shifting() {
+        let things = vec![Thing::new("Blink", 0.3), Thing::new("Hour", 3600.)];
+        let settings = Settings {
+            explain_transitions: true,
+            ..Settings::default()
+        };
+        let mut viewport = Viewport::init(&things);
+        viewport.prev_shift = 1.;
+        viewport.animation.step = AnimStep::Shifting(AnimStep::SHIFTING_TIME / 2.);
+        assert_eq!(viewport.transition(), Some((0, 1, 0.5)));
+        assert_eq!(Viewport::transition_alpha(0.5), 1.);
+        let note = (Layer::Labels, DrawOp::Transition);
+        assert!(ops(&mut viewport, &things, &settings).contains(&note));
+        assert!(!ops(&mut viewport, &things, &Settings::default()).contains(&note));
+
+        viewport.animation.step = AnimStep::Pausing(1.);
+        assert_eq!(viewport.transition(), None);
+        assert!(!ops(&mut viewport, &things, &settings).contains(&note));
+    }
+
+    #[test]
+    fn test_tiny_markers_replace_bars_below_the_threshold() {
+        let things = vec![Thing::new("Blink", 0.3), Thing::new("Hour", 3600.)];
+        let settings = Settings {
+            tiny_markers: true,
+            ..Settings::default()
+        };
+        let mut viewport = Viewport::init(&things);
+        viewport.scale = 1. - viewport.decades as f64 + Viewport::MAX_HEIGHT.log10();
+        viewport.visited.extend(things.iter().map(|thing| thing.id));
+        viewport.keyboard_focus = Some(0);
+        let tiny = ops(&mut viewport, &things, &settings);
+        assert!(tiny.contains(&(Layer::Bars, DrawOp::TinyMarker(0))));
+        assert!(!tiny.contains(&(Layer::Bars, DrawOp::Bar(0))));
+        assert!(tiny.contains(&(Layer::Bars, DrawOp::Bar(1))));
+        assert!(tiny.contains(&(Layer::Labels, DrawOp::TinyLabel(0))));
+
+        let below_blink = Settings {
+            tiny_threshold: 0.01,
+            ..settings
+        };
+        let below = ops(&mut viewport, &things, &below_blink);
+        assert!(below.contains(&(Layer::Bars, DrawOp::Bar(0))));
+        assert!(!below.contains(&(Layer::Labels, DrawOp::TinyLabel(0))));
+
+        let plain = ops(&mut viewport, &things, &Settings::default());
+        assert!(plain.contains(&(Layer::Bars, DrawOp::Bar(0))));
+    }
+
+    #[test]
+    fn test_margin_values_only_while_paused() {
+        let things = vec![Thing::new("Blink", 0.3), Thing::new("Hour", 3600.)];
+        let settings = Settings {
+            margin_values: true,
+            ..Settings::default()
+        };
+        let mut viewport = Viewport::init(&things);
+        let moving = ops(&mut viewport, &things, &settings);
+        assert!(
+            !moving
+                .iter()
+                .any(|(_, op)| matches!(op, DrawOp::MarginValue(_)))
+        );
+
+        viewport.animation.step = AnimStep::Pausing(AnimStep::PAUSING_TIME);
+        let paused = ops(&mut viewport, &things, &settings);
+        assert!(paused.contains(&(Layer::Labels, DrawOp::MarginValue(0))));
+        assert!(paused.contains(&(Layer::Labels, DrawOp::MarginValue(1))));
+        let plain = ops(&mut viewport, &things, &Settings::default());
+        assert!(
+            !plain
+                .iter()
+                .any(|(_, op)| matches!(op, DrawOp::MarginValue(_)))
+        );
+
+        // a longer secondary dataset labels its own bars past the primary's end
+        let secondary = vec![
+            Thing::new("Second", 1.),
+            Thing::new("Minute", 60.),
+            Thing::new("Day", 86400.),
+        ];
+        let (mut scene, mut fcx, mut lcx) =
+            (Scene::new(), FontContext::new(), LayoutContext::new());
+        let mut painter = Painter::recording(&mut scene, &mut fcx, &mut lcx);
+        viewport.apply_settings((&things, Some(&secondary)), &settings);
+        let frame = viewport.frame(
+            (&things, Some(&secondary)),
+            Titles::default(),
+            &settings,
+            Size::new(800., 600.),
+            Side::Primary,
+        );
+        let half_size = frame.half_size;
+        let values = frame.viewport.margin_values(&frame.bars, half_size, 0.);
+        let day = values.iter().find(|(bar, ..)| bar.index == 2).unwrap();
+        assert_eq!(day.0.side, Side::Secondary);
+        assert_eq!(day.0.thing.id, secondary[2].id);
+        frame.paint(&mut painter);
+        assert!(
+            painter
+                .ops()
+                .contains(&(Layer::Labels, DrawOp::MarginValue(2)))
+        );
+    }
+
+    #[test]
+    fn test_plain_painter_records_nothing() {
+        let things = vec![Thing::new("Blink", 0.3)];
+        let settings = Settings::default();
+        let mut viewport = Viewport::init(&things);
+        let (mut scene, mut fcx, mut lcx) =
+            (Scene::new(), FontContext::new(), LayoutContext::new());
+        let mut painter = Painter::new(&mut scene, &mut fcx, &mut lcx);
+        viewport.fit(Size::new(800., 600.));
+        let frame = viewport.frame(
+            (&things, None),
+            Titles::default(),
+            &settings,
+            Size::new(800., 600.),
+            Side::Primary,
+        );
+        frame.paint(&mut painter);
+        assert!(painter.ops().is_empty());
+        assert!(!ops(&mut viewport, &things, &settings).is_empty());
+    }
+
+    #[test]
+    fn test_pointer_over_a_minor_line_labels_it() {
+        let settings = Settings {
+            minor_hover_labels: true,
+            ..Settings::default()
+        };
+        let things = vec![Thing::new("Blink", 0.3), Thing::new("Week", 604800.)];
+        let mut state = State::new(things, settings);
+        let labeled = |state: &mut State| {
+            ops(&mut state.viewport, &state.things, &state.settings)
+                .iter()
+                .any(|(_, op)| *op == DrawOp::MinorLabel)
+        };
+        assert!(!labeled(&mut state));
+
+        let size = Size::new(800., 600.);
+        let world_camera = state.viewport.world_camera(size);
+        let y = state
+            .viewport
+            .minor_lines()
+            .iter()
+            .map(|&(_, line)| (world_camera * Point::new(0., line)).y)
+            .find(|y| (0.0..size.height).contains(y))
+            .unwrap();
+        let point = Point::new(120., y + 2.);
+        state.handle_input(PointerAction::Move { point, size }.input(false));
+        assert!(labeled(&mut state));
+
+        state.settings.minor_hover_labels = false;
+        assert!(!labeled(&mut state));
+        state.settings.minor_hover_labels = true;
+        state.handle_input(PointerAction::Leave.input(false));
+        assert!(!labeled(&mut state));
+    }
+}
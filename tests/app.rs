@@ -0,0 +1,54 @@
+use std::fs;
+
+use scale_comparison::{App, Thing};
+
+#[test]
+fn test_app_state_from_things() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.json");
+    let mut state = App::new()
+        .with_things(vec![Thing::new("Blink", 0.3), Thing::new("Week", 604800.)])
+        .with_data_file(&path)
+        .state();
+    assert_eq!(state.things().len(), 2);
+    assert_eq!(state.data_file(), Some(path.as_path()));
+    assert!(!path.exists());
+
+    let embedded = App::new().with_things(Vec::new()).state();
+    assert_eq!(embedded.data_file(), None);
+
+    state.save().unwrap();
+    let loaded = App::new().with_data_file(&path).state();
+    assert_eq!(loaded.things().len(), 2);
+    assert_eq!(loaded.things()[0].name, "Blink");
+    assert_eq!(loaded.things()[1].scale(), state.things()[1].scale());
+    assert_eq!(loaded.data_file(), Some(path.as_path()));
+}
+
+#[test]
+fn test_app_state_from_data_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("good.json");
+    let fixture = format!("{}/tests/fixtures/good.json", env!("CARGO_MANIFEST_DIR"));
+    fs::copy(fixture, &path).unwrap();
+    let state = App::new().with_data_file(&path).state();
+    assert_eq!(state.things().len(), 3);
+
+    let missing = dir.path().join("missing.json");
+    let state = App::new().with_data_file(&missing).state();
+    assert!(state.things().is_empty());
+    assert_eq!(state.data_file(), Some(missing.as_path()));
+}
+
+#[test]
+fn test_unreadable_data_file_is_never_overwritten() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.json");
+    let text = "[{\"name\": \"Blink\", \"value\": ";
+    fs::write(&path, text).unwrap();
+    let mut state = App::new().with_data_file(&path).state();
+    assert!(state.things().is_empty());
+    assert_eq!(state.data_file(), None);
+    assert!(state.save().is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), text);
+}
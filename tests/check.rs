@@ -0,0 +1,50 @@
+use scale_comparison::check::check_file;
+use scale_comparison::validation::IssueKind;
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn test_check_good_file() {
+    let report = check_file(fixture("good.json")).unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.import.things.len(), 3);
+    assert!(report.issues.is_empty());
+    assert!(report.to_string().ends_with("3 things, valid"));
+}
+
+#[test]
+fn test_check_bad_file() {
+    let report = check_file(fixture("bad.json")).unwrap();
+    assert!(!report.is_valid());
+    let kinds = report
+        .issues
+        .iter()
+        .map(|issue| (issue.index, issue.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            (1, IssueKind::OutOfOrder),
+            (2, IssueKind::NonPositive),
+            (3, IssueKind::ExceedsFormatting),
+            (3, IssueKind::DuplicateName),
+        ]
+    );
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_check_spreadsheet() {
+    let report = check_file(fixture("things.xlsx")).unwrap();
+    assert_eq!(report.import.things.len(), 5);
+    assert_eq!(report.import.errors.len(), 1);
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn test_check_unsupported_file() {
+    assert!(check_file(fixture("things.txt")).is_err());
+    assert!(check_file(fixture("missing.json")).is_err());
+}
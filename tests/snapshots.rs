@@ -0,0 +1,213 @@
+use std::fs;
+use std::path::PathBuf;
+
+use scale_comparison::dataset::Dataset;
+use scale_comparison::export::AnimationExport;
+use scale_comparison::import::from_csv;
+use scale_comparison::summary::summarize;
+use scale_comparison::thing::Thing;
+use scale_comparison::units::{
+    DAY, FormatStyle, HOUR, MINUTE, TimeDisplay, TimeScale, TimeUnit, YEAR,
+};
+use scale_comparison::viewport::{AxisLabels, MinorLabels, Viewport};
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.snap"));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    assert_eq!(
+        actual, expected,
+        "snapshot {name} changed, run with UPDATE_SNAPSHOTS=1 to accept"
+    );
+}
+
+fn assert_binary_snapshot(name: &str, actual: &[u8]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        actual == expected,
+        "snapshot {name} changed, run with UPDATE_SNAPSHOTS=1 to accept"
+    );
+}
+
+fn dataset() -> Vec<Thing> {
+    vec![
+        Thing::new("Planck time", (5.39, -44)),
+        Thing::new("Hydrogen-7 half-life", (2.3, -23)),
+        Thing::new("Blink", 0.3),
+        Thing::new("Marathon", 2. * HOUR + 35. * MINUTE),
+        Thing::new("Week", 7. * DAY),
+        Thing::new("Human lifespan", 73. * YEAR),
+        Thing::new("Dinosaur extinction", 66e6 * YEAR),
+        Thing::new("Age of the universe", 13.8e9 * YEAR),
+        Thing::new("Heat death", TimeScale::from_years((1., 100))),
+    ]
+}
+
+#[test]
+fn test_time_scale_display_snapshot() {
+    let values: Vec<TimeScale> = vec![
+        (1.23, -456).into(),
+        0.00086.into(),
+        MINUTE.into(),
+        (8. * MINUTE + 20.).into(),
+        HOUR.into(),
+        (HOUR + 32. * MINUTE).into(),
+        DAY.into(),
+        (30.4 * DAY).into(),
+        YEAR.into(),
+        (9.5 * YEAR).into(),
+        TimeScale::from_years(540e6),
+        TimeScale::from_years(2.5e9),
+        TimeScale::from_years(10e12),
+        TimeScale::from_years(1e161),
+        TimeScale::from_years((1., 32000)),
+    ];
+    let mut output = String::new();
+    for value in values {
+        output.push_str(&format!(
+            "{} | {} | {} | {}\n",
+            value.inner(),
+            value,
+            value.fmt_secs(),
+            value.fmt_in(TimeUnit::Year)
+        ));
+    }
+    assert_snapshot("time_scale_display", &output);
+}
+
+#[test]
+fn test_axis_labels_snapshot() {
+    let mut output = String::new();
+    for exponent in (-50..=50).step_by(10) {
+        let major = exponent as f64;
+        let minors = Viewport::minor_gridlines(major, true, MinorLabels::Values)
+            .into_iter()
+            .filter_map(|(_, label)| label)
+            .collect::<Vec<_>>();
+        output.push_str(&format!(
+            "{exponent} | {} | {}\n",
+            Viewport::major_label(major, AxisLabels::Time),
+            minors.join(", ")
+        ));
+    }
+    assert_snapshot("axis_labels", &output);
+}
+
+#[test]
+fn test_value_labels_snapshot() {
+    let mut output = String::new();
+    let style = FormatStyle::default();
+    for thing in dataset() {
+        output.push_str(&format!(
+            "{} | {} | {} | {}\n",
+            thing.name,
+            thing.value_text(TimeDisplay::Duration, None, style),
+            thing.value_text(TimeDisplay::Historical, None, style),
+            thing.value_text(TimeDisplay::Historical, Some(2000), style),
+        ));
+    }
+    output.push('\n');
+    output.push_str(&dataset()[1].tooltip_text(style));
+    output.push('\n');
+    assert_snapshot("value_labels", &output);
+}
+
+#[test]
+fn test_summary_snapshot() {
+    let things = dataset();
+    let output = [
+        summarize(&[]),
+        summarize(&things[2..3]),
+        summarize(&things[2..5]),
+        summarize(&things),
+    ]
+    .join("\n");
+    assert_snapshot("summary", &(output + "\n"));
+}
+
+#[test]
+fn test_csv_import_snapshot() {
+    let report = from_csv(
+        "name, value\n\
+         Week, 7 d\n\
+         Sun lifespan, 10 Gy\n\
+         proton decay, 1e34 y\n\
+         Gestation, 9 M\n\
+         Nap\n\
+         Eternity, forever\n",
+    );
+    let mut output = format!("{}\n", report.summary());
+    for thing in &report.things {
+        output.push_str(&format!("{} | {}\n", thing.name, thing.value));
+    }
+    for err in &report.errors {
+        output.push_str(&format!("{err}\n"));
+    }
+    assert_snapshot("csv_import", &output);
+}
+
+#[test]
+fn test_dataset_json_snapshot() {
+    let mut things = dataset();
+    for thing in &mut things {
+        thing.created = None;
+        thing.modified = None;
+    }
+    let dataset = Dataset {
+        project: "Scales".to_string(),
+        name: "Durations".to_string(),
+        author: "Dara".to_string(),
+        things,
+        ..Dataset::default()
+    };
+    let json = dataset.to_json().unwrap();
+    assert_snapshot("dataset_json", &(json.clone() + "\n"));
+    let reloaded = Dataset::from_json(&json).unwrap();
+    assert_eq!(reloaded.to_json().unwrap(), json);
+}
+
+#[test]
+fn test_png_frames_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let frames = AnimationExport::new(dataset()[2..5].to_vec())
+        .name("Golden")
+        .size(32., 18.)
+        .fps(2)
+        .speed(8.)
+        .frames_dir(dir.path())
+        .render()
+        .unwrap();
+    let mut files = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(files.len() as u64, frames);
+    assert_snapshot("png_frames", &(files.join("\n") + "\n"));
+    let first = fs::read(dir.path().join(&files[0])).unwrap();
+    let last = fs::read(dir.path().join(&files[files.len() - 1])).unwrap();
+    assert_binary_snapshot("png_first_frame.png", &first);
+    assert_binary_snapshot("png_last_frame.png", &last);
+}